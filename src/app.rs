@@ -1,36 +1,504 @@
-use crate::db::{Database, ModEntry};
-use crate::installer::ModInstaller;
-use crate::mod_io::ModIoClient;
-use crate::ui::render_ui;
+use crate::conflicts::{Conflict, ConflictReport};
+use crate::credentials;
+use crate::db::{Database, ModEntry, ProfileExport, ProfileStyle, DATABASE_FILE};
+use crate::download_failure::{self, FailureKind, MAX_RETRIES, RETRY_DELAYS};
+use crate::format;
+use crate::installer::{ArchiveEntry, InGameMod, ModInstaller, DOWNLOAD_CANCELLED, VERSION_GONE};
+use crate::keymap::{Keymap, ShortcutAction};
+use crate::launcher;
+use crate::lockfile::Lockfile;
+use crate::logging;
+use crate::mod_query::ModQuery;
+use crate::mod_url::ParsedModRef;
+use crate::modlist::{ModList, ModListComparison};
+use crate::pak::{self, PakStats};
+use crate::plan::{Plan, PlanAction};
+use crate::post_install::{self, PostInstall, PostInstallAction};
+use crate::protocol::{self, LinkAction};
+use crate::source_headers;
+use crate::startup_check::{self, StartupIssue};
+use crate::steam;
+use crate::webhook::WebhookEvent;
+use crate::mod_io::{CatalogSort, ModIoClient, ModIoDependency, ModIoFile, ModIoMod, ModIoTagOption, ModIoUser};
+use crate::textures::{TextureCache, DEFAULT_TEXTURE_BUDGET, LOW_MEMORY_TEXTURE_BUDGET};
+use crate::ui::{complete_mod_hub_check, finish_install, render_ui};
+use crate::worker::{DetailsPurpose, Task, TaskResult, Worker};
 use eframe::egui;
 use std::{
-    collections::{HashMap, HashSet},
-    path::Path,
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque},
+    path::{Path, PathBuf},
+    sync::{atomic::{AtomicBool, Ordering}, mpsc::Receiver, Arc},
 };
-use keyring::Entry;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+// Mods checked more recently than this are skipped by "Check now"
+pub const UPDATE_RECHECK_INTERVAL: i64 = 60 * 60;
+
+// Modded sessions with a mod enabled before asking the user to rate it
+pub const RATING_PROMPT_SESSIONS: u32 = 10;
+
+// Seconds between scheduled deep verification runs
+pub const DEEP_VERIFICATION_INTERVAL: i64 = 7 * 24 * 60 * 60;
+// Minimum time between two manual update checks
+pub const UPDATE_CHECK_COOLDOWN: i64 = 60;
+// Upper bound of mod.io requests made by a single update check
+pub const MAX_UPDATE_CHECKS_PER_RUN: usize = 30;
+// Finished downloads listed in the Downloads tab
+const DOWNLOAD_HISTORY_LEN: usize = 50;
+// Seconds between two samples of a download's speed
+const SPEED_SAMPLE_INTERVAL: f64 = 0.5;
 
 pub enum Tab {
     Browse,
     Installed,
+    Downloads,
     Settings,
 }
 
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum NotificationCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl NotificationCorner {
+    pub const ALL: [NotificationCorner; 4] = [
+        NotificationCorner::TopLeft,
+        NotificationCorner::TopRight,
+        NotificationCorner::BottomLeft,
+        NotificationCorner::BottomRight,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            NotificationCorner::TopLeft => "Top left",
+            NotificationCorner::TopRight => "Top right",
+            NotificationCorner::BottomLeft => "Bottom left",
+            NotificationCorner::BottomRight => "Bottom right",
+        }
+    }
+}
+
+// How much room each row of the mod list takes
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ListDensity {
+    Comfortable,
+    // One line per mod; the action buttons appear on hover
+    Compact,
+    // Columns that can be resized and sorted by clicking their header
+    Table,
+}
+
+impl ListDensity {
+    pub const ALL: [ListDensity; 3] = [ListDensity::Comfortable, ListDensity::Compact, ListDensity::Table];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ListDensity::Comfortable => "Comfortable",
+            ListDensity::Compact => "Compact",
+            ListDensity::Table => "Table",
+        }
+    }
+}
+
+// Columns of the table layout
+#[derive(Clone, Copy, PartialEq)]
+pub enum TableColumn {
+    Name,
+    Version,
+    Status,
+    Source,
+    Size,
+    LastUpdated,
+}
+
+impl TableColumn {
+    pub const ALL: [TableColumn; 6] = [
+        TableColumn::Name,
+        TableColumn::Version,
+        TableColumn::Status,
+        TableColumn::Source,
+        TableColumn::Size,
+        TableColumn::LastUpdated,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TableColumn::Name => "Name",
+            TableColumn::Version => "Version",
+            TableColumn::Status => "Status",
+            TableColumn::Source => "Source",
+            TableColumn::Size => "Size",
+            TableColumn::LastUpdated => "Last Updated",
+        }
+    }
+}
+
+// Orderings of the local mod list; mod.io figures come from the cached metadata
+#[derive(Clone, Copy, PartialEq)]
+pub enum ListSort {
+    Name,
+    RecentlyUpdated,
+    MostDownloaded,
+    MostSubscribed,
+    HighestRated,
+    InstallStatus,
+}
+
+impl ListSort {
+    pub const ALL: [ListSort; 6] = [
+        ListSort::Name,
+        ListSort::RecentlyUpdated,
+        ListSort::MostDownloaded,
+        ListSort::MostSubscribed,
+        ListSort::HighestRated,
+        ListSort::InstallStatus,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ListSort::Name => "Name",
+            ListSort::RecentlyUpdated => "Recently updated",
+            ListSort::MostDownloaded => "Most downloaded",
+            ListSort::MostSubscribed => "Most subscribers",
+            ListSort::HighestRated => "Highest rated",
+            ListSort::InstallStatus => "Install status",
+        }
+    }
+}
+
+// Dependencies missing for mods that are being installed
+pub struct DependencyPrompt {
+    pub mod_names: Vec<String>,
+    pub missing: Vec<ModIoDependency>,
+}
+
+// Custom headers being edited for the host of a direct-download link
+pub struct SourceEditor {
+    pub host: String,
+    pub headers: Vec<(String, String)>,
+}
+
+// Mods taken out of the game folder for an unmodded session, put back once the game exits
+pub struct VanillaSession {
+    pub mod_ids: Vec<String>,
+    // The game was seen running since the session started
+    pub game_seen: bool,
+    // Time of the last check, in egui input time
+    pub checked_at: f64,
+}
+
+// Logging in to mod.io with a code sent by email
+#[derive(Default)]
+pub struct EmailLogin {
+    pub email: String,
+    pub security_code: String,
+    pub code_sent: bool,
+    pub pending: bool,
+}
+
+// An install running in the background
+pub struct ActiveDownload {
+    // Release being fetched
+    pub version: String,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    // Stops the transfer; the partial file is kept for resuming
+    pub cancel: Arc<AtomicBool>,
+    // Smoothed bytes per second, and the time and byte count it was last sampled at
+    pub speed: Option<f64>,
+    last_sample: Option<(f64, u64)>,
+}
+
+impl ActiveDownload {
+    // Seconds left at the current speed, when the size is known
+    pub fn eta(&self) -> Option<f64> {
+        let speed = self.speed.filter(|speed| *speed > 0.0)?;
+        Some(self.total?.saturating_sub(self.downloaded) as f64 / speed)
+    }
+    
+    fn sample(&mut self, now: f64, downloaded: u64) {
+        let Some((sampled_at, sampled_bytes)) = self.last_sample else {
+            self.last_sample = Some((now, downloaded));
+            return;
+        };
+        let elapsed = now - sampled_at;
+        if elapsed < SPEED_SAMPLE_INTERVAL {
+            return;
+        }
+        let current = downloaded.saturating_sub(sampled_bytes) as f64 / elapsed;
+        self.speed = Some(match self.speed {
+            Some(speed) => speed * 0.7 + current * 0.3,
+            None => current,
+        });
+        self.last_sample = Some((now, downloaded));
+    }
+}
+
+pub enum DownloadOutcome {
+    Completed,
+    Failed(String),
+    Cancelled,
+}
+
+// A download that ended this session, for the Downloads tab
+pub struct FinishedDownload {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub version: String,
+    pub size: u64,
+    pub finished_at: i64,
+    pub outcome: DownloadOutcome,
+}
+
+// How a notification is styled; errors and warnings also stay up longer
+#[derive(Clone, Copy, PartialEq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+pub struct Notification {
+    pub message: String,
+    pub severity: Severity,
+    // Seconds left before the notification disappears
+    pub remaining: f32,
+}
+
+// Number of catalog entries requested per "Load more"
+pub const CATALOG_PAGE_SIZE: u32 = 50;
+
+// Seconds without typing before the search is sent to mod.io
+pub const CATALOG_SEARCH_DELAY: f64 = 0.4;
+
+// How often to look for finished background tasks while some are running
+const WORKER_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+// How often to check whether the game of a vanilla session is still running
+const VANILLA_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+// Seconds in the background after which regaining focus checks for updates
+const FOCUS_REFRESH_AFTER: f64 = 10.0 * 60.0;
+
+// Past these the game tends to stop loading mods without any error; 0 turns the warning off
+// How long the last "Update all" run can be reverted
+const UPDATE_SNAPSHOT_DAYS: i64 = 7;
+
+const DEFAULT_ENABLED_MOD_LIMIT: usize = 100;
+const DEFAULT_ENABLED_PAK_LIMIT: usize = 200;
+const DEFAULT_PARALLEL_DOWNLOADS: usize = 3;
+
+// Settings kept in config.json in the user's config directory
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub game_path: String,
+    pub auto_update_mods: bool,
+    pub refresh_on_focus: bool,
+    pub enable_mod_debugging: bool,
+    pub notification_duration: f32,
+    pub notification_corner: NotificationCorner,
+    pub notification_max_stacked: usize,
+    pub deep_verification: bool,
+    pub rating_prompts: bool,
+    pub rollback_grace_days: u32,
+    pub delete_downloads_on_uninstall: bool,
+    pub max_parallel_downloads: usize,
+    pub enabled_mod_limit: usize,
+    pub enabled_pak_limit: usize,
+    pub keymap: Keymap,
+    pub layout: PanelLayout,
+    pub guest_mode: bool,
+    pub list_density: ListDensity,
+    // Both kept through credentials; only read here to move them out of configs from older versions
+    #[serde(skip_serializing)]
+    pub mod_io_api_key: String,
+    #[serde(skip_serializing)]
+    pub webhook_url: String,
+    pub webhook_events: BTreeSet<WebhookEvent>,
+}
+
+// Widths of the resizable panels, remembered between runs
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PanelLayout {
+    pub side_panel_width: f32,
+    pub detail_panel_width: f32,
+}
+
+impl Default for PanelLayout {
+    fn default() -> Self {
+        Self {
+            side_panel_width: 200.0,
+            detail_panel_width: 320.0,
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            game_path: String::new(),
+            auto_update_mods: true,
+            refresh_on_focus: true,
+            enable_mod_debugging: false,
+            notification_duration: 5.0,
+            notification_corner: NotificationCorner::TopRight,
+            notification_max_stacked: 3,
+            deep_verification: false,
+            rating_prompts: true,
+            rollback_grace_days: 7,
+            delete_downloads_on_uninstall: true,
+            max_parallel_downloads: DEFAULT_PARALLEL_DOWNLOADS,
+            enabled_mod_limit: DEFAULT_ENABLED_MOD_LIMIT,
+            enabled_pak_limit: DEFAULT_ENABLED_PAK_LIMIT,
+            keymap: Keymap::default(),
+            layout: PanelLayout::default(),
+            guest_mode: false,
+            list_density: ListDensity::Comfortable,
+            mod_io_api_key: String::new(),
+            webhook_url: String::new(),
+            webhook_events: BTreeSet::new(),
+        }
+    }
+}
+
+impl Config {
+    fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("ue4-drg-modman")
+            .join("config.json")
+    }
+    
+    // Settings exported to share between machines
+    pub fn load_from(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        serde_json::from_str(&contents).map_err(|e| format!("Failed to parse {:?}: {}", path, e))
+    }
+    
+    // Missing or unreadable files fall back to the defaults
+    pub fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(Self::path()) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            warn!("Failed to parse config, using defaults: {}", e);
+            Self::default()
+        })
+    }
+    
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&Self::path())
+    }
+    
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let contents = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        std::fs::write(path, contents)
+            .map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+}
+
+#[derive(PartialEq)]
+pub enum BrowseSource {
+    Library,
+    Catalog,
+}
+
 pub enum ModAction {
     RequestDeleteConfirmation(String),
     CancelDeleteConfirmation(String),
     DeleteModVersion(String),
     UninstallMod(String),
     ToggleModEnabled(String, bool),
+    PreviewArchive(String),
+    SelectVariant(String, String),
+    PlayAudioPreview(String),
+    StopAudioPreview,
+    ShowDetails(String),
+    SelectVersion(String, String),
+    RollbackMod(String),
+    ArchiveMod(String),
+    RestoreMod(String),
+    UpdateMod(String),
+}
+
+// Why the manager turned a mod off without being asked to
+pub enum DisableReason {
+    MissingDependency(String),
+    DeployFailed(String),
+}
+
+impl DisableReason {
+    pub fn message(&self) -> String {
+        match self {
+            DisableReason::MissingDependency(dependency) => format!("{} is no longer enabled", dependency),
+            DisableReason::DeployFailed(error) => format!("Its files could not be deployed: {}", error),
+        }
+    }
+}
+
+// Progress of an "Update all" run
+#[derive(Default)]
+pub struct UpdateRun {
+    // Entries as they were before switching to the new version, by mod_id
+    pub old_entries: HashMap<String, ModEntry>,
+    pub updated: Vec<String>,
+    pub failed: Vec<String>,
+}
+
+// Side panel with the full mod.io metadata of one mod
+pub struct ModDetailPanel {
+    pub mod_id: String,
+    // None while loading, and for mods that don't come from mod.io
+    pub details: Option<Result<Box<ModIoMod>, String>>,
+    // File name and index statistics of each deployable pak of the selected version
+    pub paks: Vec<(String, Result<PakStats, String>)>,
+    // Notes as edited, saved when the field loses focus
+    pub notes: String,
+    // Tag being typed, added on Enter
+    pub new_tag: String,
+}
+
+// Contents of a downloaded archive shown before the mod is deployed
+pub struct ArchivePreview {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub archive_path: PathBuf,
+    pub entries: Vec<ArchiveEntry>,
+    pub excluded: HashSet<String>,
 }
 
 pub struct ModManager {
     pub mods: Vec<ModEntry>,
     pub selected_mods: HashSet<String>,
+    // Mod last clicked without Shift, where a Shift-click range starts
+    pub selection_anchor: Option<String>,
+    // mod_ids in the order the list showed them last frame
+    pub list_order: Vec<String>,
     pub search_query: String,
     pub show_installed_only: bool,
+    // Starred mods by mod_id, and whether the lists show only those
+    pub favorites: HashSet<String>,
+    pub favorites_only: bool,
     pub current_tab: Tab,
     pub db: Database,
     pub profiles: Vec<String>,
+    pub profile_styles: HashMap<String, ProfileStyle>,
+    pub post_install_actions: HashMap<String, PostInstall>,
+    // Last title sent to the window, to only update it on profile changes
+    pub window_title: String,
     pub new_profile_name: String,
     pub show_delete_confirmation: bool,
     pub profile_to_delete: String,
@@ -38,26 +506,181 @@ pub struct ModManager {
     pub file_path: String,
     pub mod_delete_confirmation_requested: HashMap<String, bool>,
     pub mod_io_oauth_key: String,
+    pub mod_io_key_delete_requested: bool,
     pub mod_io_client: ModIoClient,
     pub installer: ModInstaller,
     pub game_path: String,
     pub auto_update_mods: bool,
+    // Check for updates when the window comes back after being in the background for a while
+    pub refresh_on_focus: bool,
+    // Time the window lost focus, while it is in the background
+    pub unfocused_since: Option<f64>,
     pub enable_mod_debugging: bool,
     pub show_error_message: bool,
     pub error_message: String,
-    pub notification_message: String,
-    pub show_notification: bool,
-    pub notification_time: f32,
+    pub notifications: VecDeque<Notification>,
+    pub notification_duration: f32,
+    pub notification_corner: NotificationCorner,
+    pub list_density: ListDensity,
+    pub list_sort: ListSort,
+    // Table column the list is sorted by instead of list_sort, and whether descending
+    pub table_sort: Option<(TableColumn, bool)>,
+    // Last measured height of each list row, so only the rows on screen need to be built
+    pub row_heights: HashMap<String, f32>,
+    // User's mod.io API key, needed to log in with an emailed code
+    pub mod_io_api_key: String,
+    pub email_login: EmailLogin,
+    // Posted to when one of the chosen events happens
+    pub webhook_url: String,
+    pub webhook_events: BTreeSet<WebhookEvent>,
+    pub notification_max_stacked: usize,
+    pub archive_preview: Option<ArchivePreview>,
+    pub audio_previews: HashMap<String, Option<String>>,
+    #[cfg(feature = "audio-preview")]
+    pub audio_player: Option<crate::audio::AudioPlayer>,
+    pub mod_hub_prompt: Option<String>,
+    pub dependency_prompt: Option<DependencyPrompt>,
+    pub source_editor: Option<SourceEditor>,
+    pub last_update_check: Option<i64>,
+    // Mods found in the game's .modio folder, with whether to import each
+    pub ingame_import: Option<Vec<(InGameMod, bool)>>,
+    // Folder shared between machines by a sync client; empty disables syncing
+    pub sync_folder: String,
+    // Where archived mods' files are moved; empty deletes them instead
+    pub cold_storage_folder: String,
+    // mod_id of archived mods, with when they were archived and the folder their files were moved to
+    pub archived_mods: HashMap<String, (i64, String)>,
+    pub browse_source: BrowseSource,
+    pub catalog: Vec<ModIoMod>,
+    pub catalog_exhausted: bool,
+    // Only remembered for the current session
+    pub catalog_sort: CatalogSort,
+    // Search the catalog pages were loaded for
+    pub catalog_search: String,
+    // Input time of the last edit to the search field
+    pub search_edited_at: f64,
+    // Smaller texture budget for machines with little RAM
+    pub low_memory_mode: bool,
+    pub textures: TextureCache,
+    // Runs mod.io requests off the UI thread
+    pub worker: Worker,
+    pub oauth_check_pending: bool,
+    // Account of the validated OAuth token
+    pub mod_io_account: Option<ModIoUser>,
+    // Offset, order, search and tags of the catalog page being fetched
+    pub catalog_request: Option<(u32, CatalogSort, String, Vec<String>)>,
+    // Mods checked without a request and mods skipped, while the worker checks the rest
+    pub pending_update_check: Option<(usize, usize)>,
+    // Install running for each mod; there is never more than one per mod
+    pub downloads: HashMap<String, ActiveDownload>,
+    // Mods whose selected release changed while another release was downloading
+    pub queued_installs: Vec<String>,
+    // Installs waiting for a free download slot, in order, and those of them held back by the user
+    pub download_queue: Vec<String>,
+    pub paused_downloads: HashSet<String>,
+    // Newest first
+    pub download_history: VecDeque<FinishedDownload>,
+    // Shown while the game or storage folder is inside a cloud sync folder
+    pub cloud_sync_warning: Option<String>,
+    pub detail_panel: Option<ModDetailPanel>,
+    // Weekly hash check of every stored and deployed file
+    pub deep_verification: bool,
+    pub verification_running: bool,
+    // Problems found by the last integrity scan by mod_id, shown until dismissed
+    pub verification_report: Option<Vec<(String, Vec<String>)>>,
+    verification_requested: bool,
+    // Arguments of each later launch of the manager, which exited in favor of this window
+    pub incoming_launches: Option<Receiver<Vec<String>>>,
+    // What to do with each protocol link once its mod has been looked up
+    link_actions: HashMap<String, LinkAction>,
+    // Mods whose thumbnail was requested this session, to avoid asking twice
+    pub thumbnail_requests: HashSet<String>,
+    // Offer to rate mods on mod.io after they have been used for a while
+    pub rating_prompts: bool,
+    // Days the release a mod was updated from is kept for a rollback
+    pub rollback_grace_days: u32,
+    // Uninstalling also removes the downloaded release, unless something else still has it installed
+    pub delete_downloads_on_uninstall: bool,
+    // Downloads running at once; further installs wait in the download queue
+    pub max_parallel_downloads: usize,
+    // Enabled mods and paks above which a warning is shown
+    pub enabled_mod_limit: usize,
+    pub enabled_pak_limit: usize,
+    // Shown while the profile enables more than the limits, and the enabled set and limits it was counted for
+    pub load_limit_warning: Option<String>,
+    pub load_limit_key: String,
+    // Release each mod was updated from and when, per profile and mod_id
+    pub previous_versions: HashMap<(String, String), (String, i64)>,
+    // When the snapshot before each profile's last update run was taken
+    pub update_snapshots: HashMap<String, i64>,
+    // mod_id of the mod the user is asked to rate
+    pub rating_prompt: Option<String>,
+    // Mods whose release history was requested this session
+    pub version_requests: HashSet<String>,
+    // Installs that failed because the selected release is gone, shown at the version picker
+    pub version_errors: HashMap<String, String>,
+    pub keymap: Keymap,
+    pub layout: PanelLayout,
+    // Bumped when the layout is replaced, see set_layout
+    pub layout_generation: u32,
+    // Action waiting for the user to press its new shortcut
+    pub recording_shortcut: Option<ShortcutAction>,
+    pub update_run: Option<UpdateRun>,
+    // Read-only mode for streaming: hides tokens and paths and disables destructive actions
+    pub guest_mode: bool,
+    // Last mod.io metadata seen for each mod, kept in the database between runs
+    pub mod_metadata: HashMap<String, ModIoMod>,
+    // mod_ids of the mods subscribed to on mod.io
+    pub subscriptions: HashSet<String>,
+    pub subscription_import_pending: bool,
+    // Tag groups offered by mod.io, and the tags the lists are narrowed to
+    pub tag_options: Vec<ModIoTagOption>,
+    pub tag_filter: BTreeSet<String>,
+    // The user's own tags of each mod by mod_id, their colors, and the ones the lists are narrowed to
+    pub local_tags: HashMap<String, BTreeSet<String>>,
+    pub tag_colors: HashMap<String, [u8; 3]>,
+    pub local_tag_filter: BTreeSet<String>,
+    // Profile and enabled set the stored lockfile was generated from
+    pub lockfile_key: String,
+    // Lockfile being installed; finished downloads are checked against its hashes
+    pub lockfile_install: Option<Lockfile>,
+    // Installs that succeeded since downloads were last idle, for the post-install action
+    pub installs_succeeded: usize,
+    // Consecutive failed downloads per source, and automatic retries of each mod so far
+    pub source_failures: HashMap<String, u32>,
+    pub install_retries: HashMap<String, u32>,
+    // mod_ids waiting to be retried, with the time the retry is due
+    pub pending_retries: Vec<(String, f64)>,
+    pub vanilla_session: Option<VanillaSession>,
+    // Post-install command waiting for the user to confirm it
+    pub post_install_command_prompt: Option<String>,
+    // Assets replaced by more than one enabled mod, and the enabled set they were read for
+    pub asset_conflicts: Vec<Conflict>,
+    pub asset_conflicts_key: String,
+    pub show_conflicts: bool,
+    // Result of comparing the enabled mods with a shared list, and the file it was read from
+    pub mod_list_comparison: Option<(ModListComparison, String)>,
+    // mod.io link from the add field that is being looked up
+    pub resolving_link: Option<String>,
+    // Found when the manager started; shown in one panel until fixed or dismissed
+    pub startup_issues: Vec<StartupIssue>,
+}
+
+// Key the enabled state of a game install is stored under; FSD.exe and its folder are the same install
+pub fn game_target(game_path: &str) -> String {
+    if game_path.is_empty() {
+        return String::new();
+    }
+    ModInstaller::game_root(game_path).to_string_lossy().to_string()
 }
 
 impl ModManager {
-    fn find_game_path() -> String {
-        let possible_paths = [ //TODO: split it by platform
-            // Steam default path on Windows
-            "C:\\Program Files (x86)\\Steam\\steamapps\\common\\Deep Rock Galactic\\FSD.exe",
-            "C:\\Program Files\\Steam\\steamapps\\common\\Deep Rock Galactic\\FSD.exe",
-            // Steam default path on Linux (via Proton)
-            "~/.steam/steam/steamapps/common/Deep Rock Galactic/FSD.exe",
+    pub fn find_game_path() -> String {
+        if let Some(exe) = steam::find_game() {
+            return exe.to_string_lossy().to_string();
+        }
+        
+        let possible_paths = [
             // Microsoft Store / Game Pass path
             "C:\\Program Files\\WindowsApps\\CoffeeStainStudios.DeepRockGalactic",
             // Add more potential paths as needed
@@ -82,105 +705,3029 @@ impl ModManager {
         // Return empty string if no valid path found
         String::new()
     }
+        fn current_config(&self) -> Config {
+            Config {
+                game_path: self.game_path.clone(),
+                auto_update_mods: self.auto_update_mods,
+                refresh_on_focus: self.refresh_on_focus,
+                enable_mod_debugging: self.enable_mod_debugging,
+                notification_duration: self.notification_duration,
+                notification_corner: self.notification_corner,
+                notification_max_stacked: self.notification_max_stacked,
+                deep_verification: self.deep_verification,
+                rating_prompts: self.rating_prompts,
+                rollback_grace_days: self.rollback_grace_days,
+                delete_downloads_on_uninstall: self.delete_downloads_on_uninstall,
+                max_parallel_downloads: self.max_parallel_downloads,
+                enabled_mod_limit: self.enabled_mod_limit,
+                enabled_pak_limit: self.enabled_pak_limit,
+                keymap: self.keymap.clone(),
+                layout: self.layout,
+                guest_mode: self.guest_mode,
+                list_density: self.list_density,
+                mod_io_api_key: String::new(),
+                webhook_url: String::new(),
+                webhook_events: self.webhook_events.clone(),
+            }
+        }
         pub fn save_config(&mut self) {
-            // For now, just print a message
-            println!("Saving configuration with game path: {}", self.game_path);
-            
-            // In a real implementation, you would save to a config file
-            // For example:
-            // let config = Config {
-            //     game_path: self.game_path.clone(),
-            //     auto_update_mods: self.auto_update_mods,
-            //     enable_mod_debugging: self.enable_mod_debugging,
-            // };
-            // let config_path = dirs::config_dir()
-            //     .unwrap_or_else(|| std::path::PathBuf::from("."))
-            //     .join("ue4-drg-modman")
-            //     .join("config.json");
-            // std::fs::create_dir_all(config_path.parent().unwrap()).ok();
-            // std::fs::write(config_path, serde_json::to_string_pretty(&config).unwrap()).ok();
-        }
-        pub fn set_mod_io_oauth_key(&mut self, api_key: String) {
-            if api_key != self.mod_io_oauth_key {
-                self.mod_io_oauth_key = api_key;
-                
-                // Only initialize and call list_user_games if API key is not empty
-                if !self.mod_io_oauth_key.is_empty() {
-                    // Initialize ModIoClient if needed
-                    if self.mod_io_client.is_uninitialized() {
-                        self.mod_io_client = ModIoClient::new();
+            if let Err(e) = self.current_config().save() {
+                self.error_message = format!("Error saving settings: {}", e);
+                self.show_error_message = true;
+            }
+            
+            // The game path may have moved in or out of a sync folder
+            self.refresh_cloud_sync_warning();
+        }
+        pub fn refresh_cloud_sync_warning(&mut self) {
+            let mut locations = Vec::new();
+            if !self.game_path.is_empty()
+                && let Some(provider) = ModInstaller::cloud_sync_provider(Path::new(&self.game_path))
+            {
+                locations.push(format!("the game folder is inside {}", provider));
+            }
+            if let Some(provider) = ModInstaller::cloud_sync_provider(self.installer.app_data_dir()) {
+                locations.push(format!("the mod storage folder is inside {}", provider));
+            }
+            
+            // Sync clients briefly lock files they upload, so keep retrying there
+            self.installer.set_retry_on_lock(!locations.is_empty());
+            self.cloud_sync_warning = (!locations.is_empty()).then(|| format!(
+                "Warning: {}. Sync clients can lock pak files while mods are deployed; \
+                 pause syncing while changing mods or move the folder elsewhere. Locked files are retried automatically.",
+                locations.join(" and ")
+            ));
+        }
+        pub fn validate_mod_io_oauth_key(&mut self) {
+            // Initialize ModIoClient if needed
+            if self.mod_io_client.is_uninitialized() {
+                self.mod_io_client = ModIoClient::new();
+            }
+            
+            // Listing the user's games fails for an invalid key
+            self.oauth_check_pending = true;
+            self.worker.send(Task::ValidateOAuthToken(self.mod_io_oauth_key.clone()));
+        }
+        fn finish_mod_io_oauth_validation(&mut self, result: Result<ModIoUser, String>) {
+            self.oauth_check_pending = false;
+            match result {
+                Ok(account) => {
+                    self.mod_io_account = Some(account);
+                    if let Err(e) = self.db.set_state("mod_io_token_validated", "1") {
+                        warn!("Failed to record token validation: {}", e);
                     }
+                    self.startup_issues.retain(|issue| *issue != StartupIssue::UnvalidatedToken);
                     
-                    // List user games
-                    if let Err(e) = self.mod_io_client.list_user_games(&self.mod_io_oauth_key) {
-                        self.error_message = format!("Error listing Mod.io games: {}", e);
+                    // API key is valid, store it in the keyring
+                    if let Err(e) = credentials::set("mod_io_oauth_key", &self.mod_io_oauth_key) {
+                        self.error_message = format!("Error saving OAuth2 key: {}", e);
                         self.show_error_message = true;
+                    } else {
+                        // Use notification instead of error message
+                        self.notify(Severity::Success, "OAuth2 validated successfully and saved.".to_string());
                     }
+                },
+                Err(e) => {
+                    self.mod_io_account = None;
+                    if let Err(e) = self.db.delete_state("mod_io_token_validated") {
+                        warn!("Failed to clear token validation: {}", e);
+                    }
+                    self.error_message = format!("Error validating Mod.io OAuth2: {}", e);
+                    self.show_error_message = true;
                 }
             }
         }
-        pub fn show_notification(&mut self, message: String) {
-            self.notification_message = message;
-            self.show_notification = true;
-            self.notification_time = 5.0;
+        pub fn request_email_code(&mut self) {
+            let email = self.email_login.email.trim().to_string();
+            if email.is_empty() || self.mod_io_api_key.trim().is_empty() {
+                return;
+            }
+            // "mod_io_api_key" is the old name of the OAuth token
+            self.save_secret("mod_io_login_api_key", self.mod_io_api_key.trim().to_string());
+            self.email_login.pending = true;
+            self.worker.send(Task::RequestEmailCode {
+                api_key: self.mod_io_api_key.trim().to_string(),
+                email,
+            });
         }
-    }
-
-impl Default for ModManager {
-    fn default() -> Self {
-        // Initialize database
-        let app_data_dir = dirs::data_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("ue4-drg-modman");
-        
-        std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
-        
-        let db_path = app_data_dir.join("mods.db");
-        let db = Database::new(&db_path).expect("Failed to initialize database");
-        
-        let profiles = db.get_profiles().unwrap_or_default();
-        let mods = db.get_mods().unwrap_or_default();
-
-        // Try to load the Mod.io API key from the keyring
-        let mod_io_oauth_key = {
-            let keyring_entry = Entry::new("ue4-drg-modman", "mod_io_oauth_key").unwrap();
-            keyring_entry.get_password().unwrap_or_default()
-        };
-
-        Self {
-            mods,
-            selected_mods: HashSet::new(),
-            search_query: String::new(),
-            show_installed_only: false,
-            current_tab: Tab::Browse,
-            db,
-            profiles,
-            new_profile_name: String::new(),
-            show_delete_confirmation: false,
-            profile_to_delete: String::new(),
-            delete_confirmation_requested: false,
-            file_path: String::new(),
-            mod_delete_confirmation_requested: HashMap::new(),
-            mod_io_oauth_key,
-            mod_io_client: ModIoClient::uninitialized(),
-            installer: ModInstaller::new(app_data_dir),
-            game_path: Self::find_game_path(),
-            auto_update_mods: true,
-            enable_mod_debugging: false,
-            show_error_message: false,
-            error_message: String::new(),
-            notification_message: String::new(),
-            show_notification: false,
-            notification_time: 0.0,
+        pub fn exchange_email_code(&mut self) {
+            let security_code = self.email_login.security_code.trim().to_string();
+            if security_code.is_empty() {
+                return;
+            }
+            self.email_login.pending = true;
+            self.worker.send(Task::ExchangeEmailCode {
+                api_key: self.mod_io_api_key.trim().to_string(),
+                security_code,
+            });
         }
-    }
-}
-
-impl eframe::App for ModManager {
-    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
-        // Set dark theme
-        // ctx.set_visuals(egui::Visuals::dark());
-        
-        render_ui(self, ctx, frame);
+        fn finish_email_code_request(&mut self, result: Result<(), String>) {
+            self.email_login.pending = false;
+            match result {
+                Ok(()) => {
+                    self.email_login.code_sent = true;
+                    self.notify(Severity::Info, format!("mod.io sent a security code to {}.", self.email_login.email.trim()));
+                },
+                Err(e) => {
+                    self.error_message = format!("Error requesting a mod.io login code: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // The token goes through the usual validation, which also stores it in the keyring
+        fn finish_email_code_exchange(&mut self, result: Result<String, String>) {
+            self.email_login.pending = false;
+            match result {
+                Ok(token) => {
+                    self.email_login = EmailLogin::default();
+                    self.mod_io_oauth_key = token;
+                    self.mod_io_account = None;
+                    self.validate_mod_io_oauth_key();
+                },
+                Err(e) => {
+                    self.error_message = format!("Error logging in to mod.io: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // Forget the token everywhere, including the worker's client and its cached user ID
+        pub fn log_out_mod_io(&mut self) {
+            self.mod_io_oauth_key.clear();
+            self.mod_io_account = None;
+            self.worker.send(Task::SetOAuthToken(String::new()));
+            self.startup_issues.retain(|issue| *issue != StartupIssue::UnvalidatedToken);
+            if let Err(e) = self.db.delete_state("mod_io_token_validated") {
+                warn!("Failed to clear token validation: {}", e);
+            }
+            
+            if let Err(e) = credentials::delete("mod_io_oauth_key") {
+                self.error_message = format!("Error removing OAuth2 key: {}", e);
+                self.show_error_message = true;
+            }
+        }
+        pub fn can_check_for_updates(&self) -> bool {
+            if self.pending_update_check.is_some() {
+                return false;
+            }
+            let now = chrono::Utc::now().timestamp();
+            self.last_update_check
+                .map(|last| now - last >= UPDATE_CHECK_COOLDOWN)
+                .unwrap_or(true)
+        }
+        pub fn check_for_updates(&mut self) {
+            if !self.can_check_for_updates() {
+                self.notify(Severity::Warning, "Updates were checked less than a minute ago.".to_string());
+                return;
+            }
+            
+            let now = chrono::Utc::now().timestamp();
+            let mut checked = 0;
+            let mut skipped = 0;
+            let mut requests = Vec::new();
+            
+            for mod_entry in self.mods.iter().filter(|m| m.installed) {
+                // Skip entries that were checked recently
+                let recently_checked = mod_entry.last_checked
+                    .map(|last| now - last < UPDATE_RECHECK_INTERVAL)
+                    .unwrap_or(false);
+                if recently_checked {
+                    skipped += 1;
+                    continue;
+                }
+                
+                // Only mod.io entries can be queried; stop before exceeding the request budget
+                if let Some(modio_id) = mod_entry.modio_id() {
+                    if requests.len() >= MAX_UPDATE_CHECKS_PER_RUN {
+                        skipped += 1;
+                    } else {
+                        requests.push((mod_entry.mod_id.clone(), modio_id));
+                    }
+                    continue;
+                }
+                
+                if let Err(e) = self.db.set_last_checked(&mod_entry.mod_id, now) {
+                    warn!("Failed to record update check: {}", e);
+                }
+                checked += 1;
+            }
+            
+            self.last_update_check = Some(now);
+            if let Err(e) = self.db.set_state("last_update_check", &now.to_string()) {
+                warn!("Failed to record update check: {}", e);
+            }
+            
+            self.pending_update_check = Some((checked, skipped));
+            self.worker.send(Task::CheckUpdates(requests));
+        }
+        fn finish_update_check(&mut self, results: Vec<(String, Result<Box<ModIoMod>, String>)>) {
+            let Some((mut checked, skipped)) = self.pending_update_check.take() else {
+                return;
+            };
+            
+            let now = chrono::Utc::now().timestamp();
+            for (mod_id, result) in results {
+                let latest = match result {
+                    Ok(mod_io_mod) => {
+                        let latest = mod_io_mod.modfile.as_ref().map(ModIoFile::version_label);
+                        self.cache_metadata(&mod_id, &mod_io_mod);
+                        latest
+                    },
+                    Err(e) => {
+                        warn!("Failed to check {} for updates: {}", mod_id, e);
+                        continue;
+                    }
+                };
+                if let Some(latest) = latest
+                    && let Err(e) = self.db.set_latest_version(&mod_id, &latest)
+                {
+                    warn!("Failed to record latest version: {}", e);
+                }
+                if let Err(e) = self.db.set_last_checked(&mod_id, now) {
+                    warn!("Failed to record update check: {}", e);
+                }
+                checked += 1;
+            }
+            
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            let updates = self.mods.iter().filter(|m| m.update_available.is_some()).count();
+            if updates > 0 {
+                let names: Vec<&str> = self.mods
+                    .iter()
+                    .filter(|m| m.update_available.is_some())
+                    .map(|m| m.mod_name.as_str())
+                    .collect();
+                let message = format!("{} mod updates available: {}", updates, names.join(", "));
+                self.notify_webhook(WebhookEvent::UpdatesAvailable, message);
+            }
+            self.show_notification(format!(
+                "Checked {} mods, skipped {} recently checked. {} updates available.",
+                checked, skipped, updates
+            ));
+        }
+        pub fn scan_ingame_mods(&mut self) {
+            let found: Vec<(InGameMod, bool)> = ModInstaller::find_ingame_mods(&self.game_path)
+                .into_iter()
+                // Skip mods that are already in the library
+                .filter(|ingame| !self.mods.iter().any(|m| m.mod_id == format!("modio_{}", ingame.modio_id)))
+                .map(|ingame| (ingame, true))
+                .collect();
+            
+            if found.is_empty() {
+                self.ingame_import = None;
+                self.show_notification("No new mods found in the game's mod.io folder.".to_string());
+            } else {
+                self.ingame_import = Some(found);
+            }
+        }
+        pub fn import_ingame_mods(&mut self, ingame_mods: &[InGameMod]) {
+            let mut imported = 0;
+            for ingame in ingame_mods {
+                let mod_entry = ModEntry {
+                    mod_id: format!("modio_{}", ingame.modio_id),
+                    mod_name: ingame.name.clone(),
+                    mod_link: format!("https://mod.io/g/drg/m/{}", ingame.modio_id),
+                    download_folder: "downloads".to_string(),
+                    selected_version: "1.0.0".to_string(),
+                    versions: Vec::new(),
+                    // The game already has these deployed
+                    installed: true,
+                    enabled: true,
+                    variants: Vec::new(),
+                    selected_variant: String::new(),
+                    last_checked: None,
+                    disabled_reason: None,
+                    thumbnail_url: None,
+                    update_available: None,
+                    notes: String::new(),
+                };
+                
+                match self.db.add_mod(&mod_entry) {
+                    Ok(()) => imported += 1,
+                    Err(e) => warn!("Failed to import {}: {}", ingame.name, e),
+                }
+            }
+            
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            self.notify(Severity::Success, format!("Imported {} mods from the game.", imported));
+        }
+        pub fn sync_now(&mut self) {
+            if self.sync_folder.is_empty() {
+                return;
+            }
+            
+            match crate::sync::sync(&self.db, Path::new(&self.sync_folder)) {
+                Ok(report) => {
+                    self.profiles = self.db.get_profiles().unwrap_or_default();
+                    if let Ok(mods) = self.db.get_mods() {
+                        // Mods another machine removed from this profile leave the game too
+                        for removed in self.mods.iter().filter(|m| m.installed && !mods.iter().any(|n| n.mod_id == m.mod_id)) {
+                            if let Err(e) = self.installer.undeploy_mod(removed, &self.game_path) {
+                                warn!("Failed to remove {} from the game: {}", removed.mod_name, e);
+                            }
+                        }
+                        self.mods = mods;
+                    }
+                    // Merged mods that are enabled but missing here, or moved to another release
+                    if report.applied > 0 {
+                        let missing: Vec<String> = self.mods
+                            .iter()
+                            .filter(|m| m.enabled && !m.installed)
+                            .map(|m| m.mod_id.clone())
+                            .collect();
+                        for mod_id in &missing {
+                            self.start_install(mod_id);
+                        }
+                    }
+                    self.show_notification(format!(
+                        "Synced: {} changes exported, {} merged.",
+                        report.exported, report.applied
+                    ));
+                },
+                Err(e) => {
+                    self.error_message = format!("Error syncing: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        pub fn set_sync_folder(&mut self, folder: String) {
+            self.sync_folder = folder;
+            if let Err(e) = self.db.set_state("sync_folder", &self.sync_folder) {
+                warn!("Failed to save sync folder: {}", e);
+            }
+        }
+        pub fn set_cold_storage_folder(&mut self, folder: String) {
+            self.cold_storage_folder = folder;
+            if let Err(e) = self.db.set_state("cold_storage_folder", &self.cold_storage_folder) {
+                warn!("Failed to save cold storage folder: {}", e);
+            }
+        }
+        // Take the mod's files out of storage but keep it, its versions and metadata in the library
+        pub fn archive_mod(&mut self, mod_id: &str) {
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+                return;
+            };
+            if let Err(e) = self.installer.undeploy_everywhere(&mod_entry) {
+                self.error_message = format!("Error removing {} from the game: {}", mod_entry.mod_name, e);
+                self.show_error_message = true;
+                return;
+            }
+            
+            let location = if self.cold_storage_folder.is_empty() {
+                PathBuf::new()
+            } else {
+                Path::new(&self.cold_storage_folder).join(&mod_entry.mod_id)
+            };
+            let mut versions = mod_entry.versions.clone();
+            if !versions.contains(&mod_entry.selected_version) {
+                versions.push(mod_entry.selected_version.clone());
+            }
+            let download_dir = self.installer.app_data_dir().join(&mod_entry.download_folder);
+            for version in versions.iter().filter(|version| !version.is_empty()) {
+                let version_dir = download_dir.join(version);
+                // Other mods may store the same release in a shared folder
+                if !version_dir.is_dir() || self.db.version_in_use_by_others(&mod_entry.download_folder, version, mod_id).unwrap_or(true) {
+                    continue;
+                }
+                let result = if location.as_os_str().is_empty() {
+                    std::fs::remove_dir_all(&version_dir).map_err(|e| format!("Failed to remove {:?}: {}", version_dir, e))
+                } else {
+                    ModInstaller::move_dir(&version_dir, &location.join(version))
+                };
+                if let Err(e) = result {
+                    self.error_message = format!("Error archiving {}: {}", mod_entry.mod_name, e);
+                    self.show_error_message = true;
+                    return;
+                }
+            }
+            
+            let location = location.to_string_lossy().to_string();
+            if let Err(e) = self.db.archive_mod(mod_id, chrono::Utc::now().timestamp(), &location) {
+                self.error_message = format!("Error archiving {}: {}", mod_entry.mod_name, e);
+                self.show_error_message = true;
+                return;
+            }
+            self.archived_mods = self.db.get_archived_mods().unwrap_or_default();
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            self.notify(Severity::Success, if location.is_empty() {
+                format!("Archived {} and deleted its files.", mod_entry.mod_name)
+            } else {
+                format!("Archived {} to {}.", mod_entry.mod_name, location)
+            });
+        }
+        // Deployed files always go; the download only when nothing else has that release installed
+        pub fn uninstall_files(&self, mod_entry: &ModEntry) -> Result<(), String> {
+            let delete_download = self.delete_downloads_on_uninstall
+                && !self.db.version_installed_elsewhere(&mod_entry.download_folder, &mod_entry.selected_version, &mod_entry.mod_id)
+                    .unwrap_or(true);
+            self.installer.uninstall_mod(mod_entry, delete_download)
+        }
+        // Take every deployed file out of the game folder, leaving the library and profiles as they are
+        pub fn purge_game_folder(&mut self) {
+            match self.installer.purge_game(&self.mods, &self.game_path) {
+                Ok(removed) => {
+                    info!("Purged {} mod files from {}", removed, self.game_path);
+                    self.notify(Severity::Success, format!("Removed {} mod files from the game folder.", removed));
+                },
+                Err(e) => {
+                    self.error_message = format!("Error removing mods from the game folder: {}", e);
+                    self.show_error_message = true;
+                },
+            }
+        }
+        // Move the files back from cold storage, or download the selected release again
+        pub fn restore_mod(&mut self, mod_id: &str) {
+            let Some((_, location)) = self.archived_mods.get(mod_id).cloned() else {
+                return;
+            };
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+                return;
+            };
+            
+            let stored = Path::new(&location).join(&mod_entry.selected_version);
+            if !location.is_empty() && stored.is_dir() {
+                let download_dir = self.installer.app_data_dir().join(&mod_entry.download_folder);
+                let releases = std::fs::read_dir(&location)
+                    .map(|entries| entries.filter_map(|entry| entry.ok()).collect::<Vec<_>>())
+                    .unwrap_or_default();
+                for release in releases {
+                    let dest = download_dir.join(release.file_name());
+                    if dest.exists() {
+                        continue;
+                    }
+                    if let Err(e) = ModInstaller::move_dir(&release.path(), &dest) {
+                        self.error_message = format!("Error restoring {}: {}", mod_entry.mod_name, e);
+                        self.show_error_message = true;
+                        return;
+                    }
+                }
+                let _ = std::fs::remove_dir(&location);
+                if let Err(e) = self.db.update_mod_installed(mod_id, true) {
+                    warn!("Failed to mark {} installed: {}", mod_entry.mod_name, e);
+                }
+                self.notify(Severity::Success, format!("Restored {} from cold storage.", mod_entry.mod_name));
+            } else {
+                self.start_install(mod_id);
+            }
+            
+            if let Err(e) = self.db.unarchive_mod(mod_id) {
+                warn!("Failed to clear archive state of {}: {}", mod_entry.mod_name, e);
+            }
+            self.archived_mods.remove(mod_id);
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+        }
+        pub fn set_low_memory_mode(&mut self, enabled: bool) {
+            self.low_memory_mode = enabled;
+            self.textures.set_budget(texture_budget(enabled));
+            if let Err(e) = self.db.set_state("low_memory_mode", if enabled { "1" } else { "0" }) {
+                warn!("Failed to save low-memory mode: {}", e);
+            }
+        }
+        pub fn load_catalog_page(&mut self) {
+            let tags: Vec<String> = self.tag_filter.iter().cloned().collect();
+            let request = (self.catalog.len() as u32, self.catalog_sort, self.catalog_search.clone(), tags);
+            if self.catalog_request.as_ref() == Some(&request) {
+                return;
+            }
+            
+            self.catalog_request = Some(request.clone());
+            self.worker.send(Task::LoadCatalogPage {
+                offset: request.0,
+                limit: CATALOG_PAGE_SIZE,
+                sort: request.1,
+                search: request.2,
+                tags: request.3,
+            });
+        }
+        fn finish_catalog_page(
+            &mut self,
+            request: (u32, CatalogSort, String, Vec<String>),
+            result: Result<Vec<ModIoMod>, String>
+        ) {
+            // Ignore pages for an order, search, filter or position the catalog has since left
+            if self.catalog_request.as_ref() != Some(&request) {
+                return;
+            }
+            self.catalog_request = None;
+            
+            match result {
+                Ok(mods) => {
+                    // A short page means there is nothing left to load
+                    self.catalog_exhausted = (mods.len() as u32) < CATALOG_PAGE_SIZE;
+                    self.catalog.extend(mods);
+                },
+                Err(e) => {
+                    self.error_message = format!("Error loading the mod.io catalog: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        pub fn show_mod_details(&mut self, mod_id: &str) {
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id) else {
+                return;
+            };
+            
+            if let Some(modio_id) = mod_entry.modio_id() {
+                self.worker.send(Task::FetchModDetails {
+                    mod_id: mod_id.to_string(),
+                    modio_id,
+                    purpose: DetailsPurpose::DetailPanel,
+                });
+            }
+            // Only the pak indexes are read, so this stays quick even for large paks
+            let paks = self.installer.pak_paths(mod_entry)
+                .iter()
+                .map(|path| {
+                    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                    (name, pak::read_stats(path))
+                })
+                .collect();
+            let notes = mod_entry.notes.clone();
+            self.detail_panel = Some(ModDetailPanel {
+                mod_id: mod_id.to_string(),
+                details: None,
+                paks,
+                notes,
+                new_tag: String::new(),
+            });
+        }
+        // Download and unpack a mod in the background
+        pub fn start_install(&mut self, mod_id: &str) {
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+                return;
+            };
+            
+            // A repeated request for the same release follows the running task's progress
+            if let Some(download) = self.downloads.get(mod_id) {
+                if download.version == mod_entry.selected_version {
+                    info!("{} v{} is already downloading", mod_entry.mod_name, download.version);
+                } else if !self.queued_installs.iter().any(|queued| queued == mod_id) {
+                    // Both releases would update the same mod, so the other one waits its turn
+                    self.queued_installs.push(mod_id.to_string());
+                }
+                return;
+            }
+            if self.download_queue.iter().any(|queued| queued == mod_id) {
+                return;
+            }
+            // Started by hand, so a scheduled retry is no longer needed
+            self.pending_retries.retain(|(id, _)| id != mod_id);
+            
+            if let Some(modio_id) = mod_entry.modio_id() {
+                self.worker.send(Task::FetchDependencies {
+                    mod_id: mod_id.to_string(),
+                    modio_id,
+                });
+            }
+            if self.downloads.len() >= self.max_parallel_downloads.max(1) {
+                info!("Queued the download of {}", mod_entry.mod_name);
+                self.download_queue.push(mod_id.to_string());
+                return;
+            }
+            self.launch_download(mod_entry);
+        }
+        fn launch_download(&mut self, mod_entry: ModEntry) {
+            let cancel = Arc::new(AtomicBool::new(false));
+            self.downloads.insert(mod_entry.mod_id.clone(), ActiveDownload {
+                version: mod_entry.selected_version.clone(),
+                downloaded: 0,
+                total: None,
+                cancel: cancel.clone(),
+                speed: None,
+                last_sample: None,
+            });
+            self.worker.send(Task::InstallMod {
+                mod_entry: Box::new(mod_entry),
+                cancel,
+            });
+        }
+        // Fill the free download slots from the queue, skipping paused entries
+        pub fn start_next_downloads(&mut self) {
+            while self.downloads.len() < self.max_parallel_downloads.max(1) {
+                let Some(position) = self.download_queue
+                    .iter()
+                    .position(|mod_id| !self.paused_downloads.contains(mod_id))
+                else {
+                    return;
+                };
+                let mod_id = self.download_queue.remove(position);
+                if let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() {
+                    self.launch_download(mod_entry);
+                }
+            }
+        }
+        // Position among the queued downloads that will start, from 1
+        pub fn queue_position(&self, mod_id: &str) -> Option<usize> {
+            self.download_queue
+                .iter()
+                .filter(|queued| !self.paused_downloads.contains(*queued))
+                .position(|queued| queued == mod_id)
+                .map(|position| position + 1)
+        }
+        // A running download stops and goes back to the front of the queue, keeping its partial file
+        pub fn pause_download(&mut self, mod_id: &str) {
+            if let Some(download) = self.downloads.get(mod_id) {
+                download.cancel.store(true, Ordering::Relaxed);
+            } else if !self.download_queue.iter().any(|queued| queued == mod_id) {
+                return;
+            }
+            self.paused_downloads.insert(mod_id.to_string());
+        }
+        pub fn resume_download(&mut self, mod_id: &str) {
+            self.paused_downloads.remove(mod_id);
+            self.start_next_downloads();
+        }
+        pub fn cancel_download(&mut self, mod_id: &str) {
+            self.paused_downloads.remove(mod_id);
+            if let Some(download) = self.downloads.get(mod_id) {
+                download.cancel.store(true, Ordering::Relaxed);
+                return;
+            }
+            if let Some(position) = self.download_queue.iter().position(|queued| queued == mod_id) {
+                self.download_queue.remove(position);
+                let version = self.mods.iter()
+                    .find(|m| m.mod_id == mod_id)
+                    .map(|m| m.selected_version.clone())
+                    .unwrap_or_default();
+                self.record_download(mod_id, version, 0, DownloadOutcome::Cancelled);
+                self.abandon_update(mod_id);
+                self.finish_download_batch();
+            }
+        }
+        fn record_download(&mut self, mod_id: &str, version: String, size: u64, outcome: DownloadOutcome) {
+            let mod_name = self.mods.iter()
+                .find(|m| m.mod_id == mod_id)
+                .map_or(mod_id.to_string(), |m| m.mod_name.clone());
+            self.download_history.push_front(FinishedDownload {
+                mod_id: mod_id.to_string(),
+                mod_name,
+                version,
+                size,
+                finished_at: chrono::Utc::now().timestamp(),
+                outcome,
+            });
+            self.download_history.truncate(DOWNLOAD_HISTORY_LEN);
+        }
+        // A cancelled update stays on the release that is still on disk
+        fn abandon_update(&mut self, mod_id: &str) {
+            if let Some(old_entry) = self.take_update(mod_id) {
+                self.record_update_result(&old_entry, Err(DOWNLOAD_CANCELLED.to_string()));
+            }
+        }
+        // Run the post-install action once nothing is downloading or waiting any more
+        fn finish_download_batch(&mut self) {
+            if self.downloads.is_empty() && self.pending_retries.is_empty() && self.download_queue.is_empty() {
+                self.lockfile_install = None;
+                self.run_post_install_action();
+            }
+        }
+        // Remember what the mod requires and offer to install what is missing
+        fn finish_dependency_fetch(&mut self, mod_id: &str, result: Result<Vec<ModIoDependency>, String>) {
+            let dependencies = match result {
+                Ok(dependencies) => dependencies,
+                Err(e) => {
+                    warn!("Failed to fetch dependencies of {}: {}", mod_id, e);
+                    return;
+                }
+            };
+            
+            let rows: Vec<(String, String)> = dependencies
+                .iter()
+                .map(|dependency| (format!("modio_{}", dependency.mod_id), dependency.name.clone()))
+                .collect();
+            if let Err(e) = self.db.set_mod_dependencies(mod_id, &rows) {
+                warn!("Failed to save dependencies of {}: {}", mod_id, e);
+            }
+            
+            let missing: Vec<ModIoDependency> = dependencies
+                .into_iter()
+                .filter(|dependency| {
+                    let dependency_id = format!("modio_{}", dependency.mod_id);
+                    !self.downloads.contains_key(&dependency_id)
+                        && !self.mods.iter().any(|m| m.mod_id == dependency_id && m.installed && m.enabled)
+                })
+                .collect();
+            if missing.is_empty() {
+                return;
+            }
+            
+            let mod_name = self.mods
+                .iter()
+                .find(|m| m.mod_id == mod_id)
+                .map(|m| m.mod_name.clone())
+                .unwrap_or_else(|| mod_id.to_string());
+            // Several installs at once share a single prompt
+            let prompt = self.dependency_prompt.get_or_insert_with(|| DependencyPrompt {
+                mod_names: Vec::new(),
+                missing: Vec::new(),
+            });
+            prompt.mod_names.push(mod_name);
+            for dependency in missing {
+                if !prompt.missing.iter().any(|known| known.mod_id == dependency.mod_id) {
+                    prompt.missing.push(dependency);
+                }
+            }
+        }
+        // Anyone with the URL can post to the channel, so it's kept with the other secrets
+        pub fn save_webhook_url(&mut self) {
+            self.save_secret("webhook_url", self.webhook_url.trim().to_string());
+        }
+        // An empty value removes the stored secret
+        fn save_secret(&mut self, name: &str, secret: String) {
+            let result = if secret.is_empty() {
+                credentials::delete(name)
+            } else {
+                credentials::set(name, &secret)
+            };
+            if let Err(e) = result {
+                self.error_message = format!("Error saving {}: {}", name, e);
+                self.show_error_message = true;
+            }
+        }
+        // Post to the webhook if the user chose this kind of event
+        pub fn notify_webhook(&mut self, event: WebhookEvent, message: String) {
+            if self.webhook_url.trim().is_empty() || !self.webhook_events.contains(&event) {
+                return;
+            }
+            self.worker.send(Task::PostWebhook {
+                url: self.webhook_url.trim().to_string(),
+                content: message,
+            });
+        }
+        pub fn open_source_editor(&mut self, mod_link: &str) {
+            let Some(host) = source_headers::host_of(mod_link) else {
+                self.notify(Severity::Warning, "This mod has no web link to configure.".to_string());
+                return;
+            };
+            let headers = source_headers::load(&host);
+            self.source_editor = Some(SourceEditor { host, headers });
+        }
+        pub fn save_source_editor(&mut self) {
+            let Some(editor) = self.source_editor.take() else {
+                return;
+            };
+            match source_headers::save(&editor.host, &editor.headers) {
+                Ok(()) => self.notify(Severity::Success, format!("Saved download headers for {}.", editor.host)),
+                Err(e) => {
+                    self.error_message = format!("Error saving headers: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // Add, enable and download the dependencies of the open prompt
+        pub fn install_dependencies(&mut self) {
+            let Some(prompt) = self.dependency_prompt.take() else {
+                return;
+            };
+            
+            for dependency in &prompt.missing {
+                let mod_id = format!("modio_{}", dependency.mod_id);
+                if !self.mods.iter().any(|m| m.mod_id == mod_id) {
+                    let slug = if dependency.name_id.is_empty() { dependency.mod_id.to_string() } else { dependency.name_id.clone() };
+                    // The download needs a release mod.io actually has
+                    let version = dependency.modfile
+                        .as_ref()
+                        .map(ModIoFile::version_label)
+                        .unwrap_or_else(|| "1.0.0".to_string());
+                    let mod_entry = ModEntry {
+                        mod_id: mod_id.clone(),
+                        mod_name: dependency.name.clone(),
+                        mod_link: format!("https://mod.io/g/drg/m/{}", slug),
+                        download_folder: "downloads".to_string(),
+                        selected_version: version.clone(),
+                        versions: vec![version],
+                        installed: false,
+                        enabled: false,
+                        variants: Vec::new(),
+                        selected_variant: String::new(),
+                        last_checked: None,
+                        disabled_reason: None,
+                        thumbnail_url: None,
+                        update_available: None,
+                        notes: String::new(),
+                    };
+                    if let Err(e) = self.db.add_mod(&mod_entry) {
+                        warn!("Failed to add dependency {}: {}", dependency.name, e);
+                        continue;
+                    }
+                }
+                
+                // Enabled up front so it gets deployed as soon as the download finishes
+                if let Err(e) = self.db.update_mod_enabled(&mod_id, true) {
+                    warn!("Failed to enable dependency {}: {}", dependency.name, e);
+                }
+            }
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            
+            for dependency in &prompt.missing {
+                let mod_id = format!("modio_{}", dependency.mod_id);
+                let installed = self.mods.iter().any(|m| m.mod_id == mod_id && m.installed);
+                if !installed {
+                    self.start_install(&mod_id);
+                } else if let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id)
+                    && let Err(e) = self.installer.deploy_mod(mod_entry, &self.game_path)
+                {
+                    warn!("Failed to deploy dependency {}: {}", dependency.name, e);
+                }
+            }
+            self.show_notification(format!("Installing {} dependencies...", prompt.missing.len()));
+        }
+        // Start a release that was selected while another one of the same mod downloaded
+        fn start_queued_install(&mut self, mod_id: &str, finished_version: &str) {
+            let Some(position) = self.queued_installs.iter().position(|queued| queued == mod_id) else {
+                return;
+            };
+            self.queued_installs.remove(position);
+            
+            let still_wanted = self.mods
+                .iter()
+                .any(|m| m.mod_id == mod_id && m.selected_version != finished_version);
+            if still_wanted {
+                self.start_install(mod_id);
+            }
+        }
+        // Overall fraction of all running downloads, if every size is known
+        pub fn download_progress(&self) -> Option<f32> {
+            let mut downloaded = 0;
+            let mut total = 0;
+            for download in self.downloads.values() {
+                downloaded += download.downloaded;
+                total += download.total?;
+            }
+            (total > 0).then(|| downloaded as f32 / total as f32)
+        }
+        pub fn is_catalog_loading(&self) -> bool {
+            self.catalog_request.is_some()
+        }
+        // Handle finished background tasks; called every frame
+        pub fn poll_worker(&mut self, ctx: &egui::Context) {
+            let mut received = false;
+            while let Some(result) = self.worker.try_recv() {
+                received = true;
+                match result {
+                    TaskResult::OAuthTokenValidated(result) => self.finish_mod_io_oauth_validation(result),
+                    TaskResult::EmailCodeRequested(result) => self.finish_email_code_request(result),
+                    TaskResult::EmailCodeExchanged(result) => self.finish_email_code_exchange(result),
+                    TaskResult::CatalogPage { offset, sort, search, tags, result } => {
+                        self.finish_catalog_page((offset, sort, search, tags), result);
+                    },
+                    TaskResult::TagOptions(Ok(options)) => self.tag_options = options,
+                    TaskResult::TagOptions(Err(e)) => warn!("Failed to load mod.io tags: {}", e),
+                    TaskResult::ModLinkResolved { link, result } => self.finish_mod_link(&link, result),
+                    TaskResult::UpdatesChecked(results) => self.finish_update_check(results),
+                    TaskResult::ModDetails { mod_id, purpose: DetailsPurpose::DetailPanel, result } => {
+                        if let Ok(details) = &result {
+                            self.record_thumbnail(&mod_id, &details.logo.thumb_320x180);
+                            self.cache_metadata(&mod_id, details);
+                        }
+                        // The panel may have been closed or switched to another mod
+                        if let Some(panel) = self.detail_panel.as_mut()
+                            && panel.mod_id == mod_id
+                        {
+                            panel.details = Some(result);
+                        }
+                    },
+                    TaskResult::ModDetails { mod_id, purpose: DetailsPurpose::ModHubCheck, result } => {
+                        let description = match result {
+                            Ok(details) => {
+                                let description = format!("{}\n{}", details.summary, details.description);
+                                self.cache_metadata(&mod_id, &details);
+                                description
+                            },
+                            Err(e) => {
+                                warn!("Failed to fetch details for {}: {}", mod_id, e);
+                                String::new()
+                            }
+                        };
+                        complete_mod_hub_check(self, &mod_id, &description);
+                    },
+                    TaskResult::DownloadProgress { mod_id, downloaded, total } => {
+                        if let Some(download) = self.downloads.get_mut(&mod_id) {
+                            download.sample(ctx.input(|input| input.time), downloaded);
+                            download.downloaded = downloaded;
+                            download.total = total;
+                        }
+                    },
+                    TaskResult::Installed { mod_id, result } => {
+                        let download = self.downloads.remove(&mod_id);
+                        let cancelled = result.is_err()
+                            && download.as_ref().is_some_and(|download| download.cancel.load(Ordering::Relaxed));
+                        let size = download.as_ref().map_or(0, |download| download.total.unwrap_or(download.downloaded));
+                        let finished_version = download.map(|download| download.version).unwrap_or_default();
+                        if cancelled {
+                            if self.paused_downloads.contains(&mod_id) {
+                                self.download_queue.insert(0, mod_id);
+                            } else {
+                                info!("Cancelled the download of {}", mod_id);
+                                self.record_download(&mod_id, finished_version, size, DownloadOutcome::Cancelled);
+                                self.abandon_update(&mod_id);
+                            }
+                            self.start_next_downloads();
+                            self.finish_download_batch();
+                            continue;
+                        }
+                        // Paused too late to stop it, so it isn't waiting in the queue
+                        self.paused_downloads.remove(&mod_id);
+                        if let Err(e) = &result
+                            && self.schedule_install_retry(&mod_id, e, ctx.input(|input| input.time))
+                        {
+                            continue;
+                        }
+                        let installed = result.is_ok();
+                        // Point at the version picker, with the release list fetched again
+                        match &result {
+                            Err(e) if e.contains(VERSION_GONE) => {
+                                self.version_requests.remove(&mod_id);
+                                self.version_errors.insert(mod_id.clone(), e.clone());
+                            },
+                            _ => {
+                                self.version_errors.remove(&mod_id);
+                            },
+                        }
+                        let result = match result {
+                            Ok(path) => {
+                                self.clear_install_failures(&mod_id);
+                                Ok(path)
+                            },
+                            Err(e) => Err(self.describe_install_failure(&mod_id, e)),
+                        };
+                        let outcome = match &result {
+                            Ok(()) => DownloadOutcome::Completed,
+                            Err(e) => DownloadOutcome::Failed(e.clone()),
+                        };
+                        self.record_download(&mod_id, finished_version.clone(), size, outcome);
+                        if let Err(e) = &result {
+                            let mod_name = self.mods
+                                .iter()
+                                .find(|m| m.mod_id == mod_id)
+                                .map_or(mod_id.as_str(), |m| m.mod_name.as_str());
+                            let message = format!("Installing {} failed: {}", mod_name, e);
+                            self.notify_webhook(WebhookEvent::InstallFailed, message);
+                        }
+                        finish_install(self, &mod_id, result);
+                        if installed {
+                            self.check_lockfile_install(&mod_id);
+                            self.installs_succeeded += 1;
+                        }
+                        self.start_queued_install(&mod_id, &finished_version);
+                        self.start_next_downloads();
+                        self.finish_download_batch();
+                    },
+                    TaskResult::VerificationFinished(problems) => self.finish_deep_verification(problems),
+                    // A result for an enabled set that has changed since is dropped
+                    TaskResult::AssetConflictsDetected { key, conflicts } => {
+                        if key == self.asset_conflicts_key {
+                            self.asset_conflicts = conflicts;
+                        }
+                    },
+                    TaskResult::VersionsFetched { mod_id, result } => self.finish_version_fetch(&mod_id, result),
+                    TaskResult::Subscriptions(result) => self.finish_subscription_import(result),
+                    TaskResult::DependenciesFetched { mod_id, result } => self.finish_dependency_fetch(&mod_id, result),
+                    TaskResult::SubscriptionChanged { mod_id, subscribed, result } => {
+                        self.finish_subscription_change(&mod_id, subscribed, result);
+                    },
+                    TaskResult::ModRated { mod_id, result } => match result {
+                        Ok(()) => self.notify(Severity::Success, "Thanks for rating the mod!".to_string()),
+                        Err(e) => warn!("Failed to rate {}: {}", mod_id, e),
+                    },
+                    TaskResult::ThumbnailFetched { mod_id, result } => match result {
+                        // A replaced file is decoded again the next time it's shown
+                        Ok(true) => {
+                            let path = self.installer.app_data_dir().join("thumbnails").join(&mod_id);
+                            self.textures.forget(&path);
+                        },
+                        Ok(false) => {},
+                        // Failed downloads are not retried until the next start
+                        Err(e) => warn!("Failed to download thumbnail for {}: {}", mod_id, e),
+                    },
+                }
+            }
+            
+            if received {
+                ctx.request_repaint();
+            } else if self.worker.is_busy() {
+                // Keep polling while requests are in flight
+                ctx.request_repaint_after(WORKER_POLL_INTERVAL);
+            }
+        }
+        // Thumbnail from the disk cache. It's downloaded in the background on first use and
+        // revalidated once per session, so a changed logo replaces the cached one
+        pub fn thumbnail(&mut self, ctx: &egui::Context, mod_entry: &ModEntry) -> Option<egui::TextureHandle> {
+            let url = mod_entry.thumbnail_url.as_ref()?;
+            let path = self.installer.app_data_dir().join("thumbnails").join(&mod_entry.mod_id);
+            if self.thumbnail_requests.insert(mod_entry.mod_id.clone()) {
+                self.worker.send(Task::FetchThumbnail {
+                    mod_id: mod_entry.mod_id.clone(),
+                    url: url.clone(),
+                    path: path.clone(),
+                });
+            }
+            
+            if path.exists() {
+                return self.textures.get(ctx, &path);
+            }
+            None
+        }
+        // Avatar of the logged-in mod.io account, cached like mod thumbnails
+        pub fn mod_io_avatar(&mut self, ctx: &egui::Context) -> Option<egui::TextureHandle> {
+            let account = self.mod_io_account.as_ref()?;
+            let url = account.avatar.as_ref().map(|avatar| &avatar.thumb_100x100).filter(|url| !url.is_empty())?;
+            let key = format!("avatar_{}", account.id);
+            let path = self.installer.app_data_dir().join("thumbnails").join(&key);
+            if self.thumbnail_requests.insert(key.clone()) {
+                self.worker.send(Task::FetchThumbnail {
+                    mod_id: key,
+                    url: url.clone(),
+                    path: path.clone(),
+                });
+            }
+            
+            if path.exists() {
+                return self.textures.get(ctx, &path);
+            }
+            None
+        }
+        // The game isn't started from here, so each start of the manager with a mod.io mod
+        // enabled and working counts as a modded session for it
+        fn count_modded_session(&mut self) {
+            if !self.rating_prompts || self.mod_io_oauth_key.is_empty() {
+                return;
+            }
+            
+            for mod_entry in &self.mods {
+                if mod_entry.installed
+                    && mod_entry.enabled
+                    && mod_entry.disabled_reason.is_none()
+                    && mod_entry.modio_id().is_some()
+                    && let Err(e) = self.db.record_modded_session(&mod_entry.mod_id)
+                {
+                    warn!("Failed to record modded session: {}", e);
+                }
+            }
+            
+            // One prompt per start is enough
+            let candidates = self.db.get_rating_candidates(RATING_PROMPT_SESSIONS).unwrap_or_default();
+            self.rating_prompt = candidates
+                .into_iter()
+                .find(|mod_id| self.mods.iter().any(|m| &m.mod_id == mod_id && m.enabled));
+        }
+        // Answer the rating prompt; `rate` sends a positive rating, `never_ask` stops asking about the mod
+        pub fn close_rating_prompt(&mut self, rate: bool, never_ask: bool) {
+            let Some(mod_id) = self.rating_prompt.take() else {
+                return;
+            };
+            
+            if rate && let Some(modio_id) = self.mods.iter().find(|m| m.mod_id == mod_id).and_then(|m| m.modio_id()) {
+                self.worker.send(Task::RateMod { mod_id: mod_id.clone(), modio_id });
+            }
+            if let Err(e) = self.db.close_rating_prompt(&mod_id, rate || never_ask) {
+                warn!("Failed to record rating prompt: {}", e);
+            }
+        }
+        pub fn set_guest_mode(&mut self, enabled: bool) {
+            self.guest_mode = enabled;
+            // Nothing half-deleted should stay armed across the switch
+            self.delete_confirmation_requested = false;
+            self.mod_delete_confirmation_requested.clear();
+            self.mod_io_key_delete_requested = false;
+            self.save_config();
+        }
+        // Text as shown on screen; in guest mode the user's folders are masked
+        pub fn redact(&self, text: &str) -> String {
+            if !self.guest_mode {
+                return text.to_string();
+            }
+            
+            let mut folders = vec![
+                self.installer.app_data_dir().to_string_lossy().to_string(),
+                self.game_path.clone(),
+                self.sync_folder.clone(),
+            ];
+            if let Some(home) = dirs::home_dir() {
+                folders.push(home.to_string_lossy().to_string());
+            }
+            // Longest first so a folder inside the home directory is masked as a whole
+            folders.retain(|folder| !folder.is_empty());
+            folders.sort_by_key(|folder| std::cmp::Reverse(folder.len()));
+            
+            let mut redacted = text.to_string();
+            for folder in folders {
+                redacted = redacted.replace(&folder, "<hidden>");
+            }
+            redacted
+        }
+        pub fn outdated_mod_count(&self) -> usize {
+            self.mods.iter().filter(|m| m.installed && m.update_available.is_some()).count()
+        }
+        // Download the newest release of every out-of-date mod; finish_install re-deploys them
+        pub fn update_all(&mut self) {
+            let mod_ids: Vec<String> = self.mods.iter().map(|m| m.mod_id.clone()).collect();
+            if !self.update_mods(&mod_ids) {
+                self.show_notification("All mods are up to date.".to_string());
+            }
+        }
+        // Same as update_all for the given mods only; false when none of them is out of date
+        pub fn update_mods(&mut self, mod_ids: &[String]) -> bool {
+            let outdated: Vec<ModEntry> = self.mods.iter()
+                .filter(|m| mod_ids.contains(&m.mod_id))
+                .filter(|m| m.installed && m.update_available.is_some() && !self.downloads.contains_key(&m.mod_id))
+                .cloned()
+                .collect();
+            if outdated.is_empty() {
+                return false;
+            }
+            if self.update_run.is_none() {
+                self.take_update_snapshot();
+            }
+            
+            let run = self.update_run.get_or_insert_with(UpdateRun::default);
+            let mut started = Vec::new();
+            for old_entry in outdated {
+                let Some(latest) = &old_entry.update_available else {
+                    continue;
+                };
+                if let Err(e) = self.db.set_selected_version(&old_entry.mod_id, latest) {
+                    warn!("Failed to select version {} of {}: {}", latest, old_entry.mod_name, e);
+                    continue;
+                }
+                started.push(old_entry.mod_id.clone());
+                run.old_entries.insert(old_entry.mod_id.clone(), old_entry);
+            }
+            
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            // Mods already in the run keep their running downloads
+            for mod_id in started {
+                self.start_install(&mod_id);
+            }
+            true
+        }
+        // Called by finish_install for mods in an update run; returns the entry before the update
+        pub fn take_update(&mut self, mod_id: &str) -> Option<ModEntry> {
+            self.update_run.as_mut()?.old_entries.remove(mod_id)
+        }
+        pub fn record_update_result(&mut self, old_entry: &ModEntry, result: Result<(), String>) {
+            let Some(run) = self.update_run.as_mut() else {
+                return;
+            };
+            match result {
+                Ok(()) => run.updated.push(old_entry.mod_name.clone()),
+                Err(e) => {
+                    run.failed.push(format!("{} ({})", old_entry.mod_name, e));
+                    // Stay on the version that is still on disk
+                    if let Err(e) = self.db.set_selected_version(&old_entry.mod_id, &old_entry.selected_version) {
+                        warn!("Failed to restore version of {}: {}", old_entry.mod_name, e);
+                    }
+                }
+            }
+            
+            if !run.old_entries.is_empty() {
+                return;
+            }
+            let Some(run) = self.update_run.take() else {
+                return;
+            };
+            let mut message = format!("Updated {} mods.", run.updated.len());
+            let severity = if run.failed.is_empty() {
+                Severity::Success
+            } else {
+                message.push_str(&format!(" Failed: {}", run.failed.join(", ")));
+                Severity::Error
+            };
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            self.notify(severity, message);
+        }
+        // Versions and enabled set of the profile before an update run starts
+        fn take_update_snapshot(&mut self) {
+            let profile = self.db.get_current_profile().to_string();
+            let taken_at = chrono::Utc::now().timestamp();
+            let result = Lockfile::build(&self.installer, &self.mods, &profile)
+                .to_json()
+                .and_then(|json| self.db.set_update_snapshot(&profile, &json, taken_at).map_err(|e| e.to_string()));
+            match result {
+                Ok(()) => {
+                    self.update_snapshots.insert(profile, taken_at);
+                },
+                Err(e) => warn!("Failed to snapshot the profile before updating: {}", e),
+            }
+        }
+        // Time of the current profile's update snapshot, while it can still be reverted
+        pub fn update_snapshot_time(&self) -> Option<i64> {
+            let taken_at = *self.update_snapshots.get(self.db.get_current_profile())?;
+            (chrono::Utc::now().timestamp() < taken_at + UPDATE_SNAPSHOT_DAYS * 24 * 60 * 60).then_some(taken_at)
+        }
+        // Put the profile back to the versions and enabled set it had before the last update run
+        pub fn revert_last_update(&mut self) {
+            let profile = self.db.get_current_profile().to_string();
+            let lockfile = match self.db.get_update_snapshot(&profile) {
+                Ok(Some(json)) => Lockfile::from_json(&json),
+                Ok(None) => Err("No update run to revert".to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+            let lockfile = match lockfile {
+                Ok(lockfile) => lockfile,
+                Err(e) => {
+                    self.error_message = format!("Error reverting the update run: {}", e);
+                    self.show_error_message = true;
+                    return;
+                }
+            };
+            
+            let taken_at = self.update_snapshots.get(&profile).copied().unwrap_or_default();
+            self.apply_lockfile(lockfile, format!("Reverted the update run of {}", format::date(taken_at)));
+            if let Err(e) = self.db.clear_update_snapshot(&profile) {
+                warn!("Failed to remove the update snapshot of {}: {}", profile, e);
+            }
+            self.update_snapshots.remove(&profile);
+        }
+        pub fn prune_update_snapshots(&mut self) {
+            let snapshots = match self.db.get_update_snapshot_times() {
+                Ok(snapshots) => snapshots,
+                Err(e) => {
+                    warn!("Failed to load update snapshots: {}", e);
+                    return;
+                }
+            };
+            let cutoff = chrono::Utc::now().timestamp() - UPDATE_SNAPSHOT_DAYS * 24 * 60 * 60;
+            self.update_snapshots = snapshots.into_iter()
+                .filter(|(profile, taken_at)| {
+                    if *taken_at > cutoff {
+                        return true;
+                    }
+                    if let Err(e) = self.db.clear_update_snapshot(profile) {
+                        warn!("Failed to remove the update snapshot of {}: {}", profile, e);
+                    }
+                    false
+                })
+                .collect();
+        }
+        // Keep the release a mod was updated from until the grace period ends
+        pub fn record_previous_version(&mut self, old_entry: &ModEntry) {
+            if !old_entry.installed || old_entry.selected_version.is_empty() {
+                return;
+            }
+            if let Err(e) = self.db.set_previous_version(&old_entry.mod_id, &old_entry.selected_version, chrono::Utc::now().timestamp()) {
+                warn!("Failed to remember the previous version of {}: {}", old_entry.mod_name, e);
+            }
+            self.previous_versions = self.db.get_previous_versions().unwrap_or_default();
+        }
+        // Release the mod can be rolled back to, while it is within the grace period and still on disk
+        pub fn rollback_version(&self, mod_entry: &ModEntry) -> Option<&str> {
+            let key = (self.db.get_current_profile().to_string(), mod_entry.mod_id.clone());
+            let (version, replaced_at) = self.previous_versions.get(&key)?;
+            let expires_at = replaced_at + i64::from(self.rollback_grace_days) * 24 * 60 * 60;
+            if version == &mod_entry.selected_version || chrono::Utc::now().timestamp() >= expires_at {
+                return None;
+            }
+            let mut previous = mod_entry.clone();
+            previous.selected_version = version.clone();
+            self.installer.version_dir(&previous).is_dir().then_some(version.as_str())
+        }
+        // Deletes releases whose grace period ended, unless a profile still selects them
+        pub fn prune_previous_versions(&mut self) {
+            let previous_versions = match self.db.get_previous_versions() {
+                Ok(previous_versions) => previous_versions,
+                Err(e) => {
+                    warn!("Failed to load previous versions: {}", e);
+                    return;
+                }
+            };
+            let folders: HashMap<String, String> = self.db.get_global_mods()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(mod_id, _, _, download_folder)| (mod_id, download_folder))
+                .collect();
+            let cutoff = chrono::Utc::now().timestamp() - i64::from(self.rollback_grace_days) * 24 * 60 * 60;
+            
+            for ((profile, mod_id), (version, replaced_at)) in &previous_versions {
+                if *replaced_at > cutoff {
+                    continue;
+                }
+                if let Err(e) = self.db.clear_previous_version(profile, mod_id) {
+                    warn!("Failed to forget the previous version of {}: {}", mod_id, e);
+                    continue;
+                }
+                let Some(download_folder) = folders.get(mod_id) else {
+                    continue;
+                };
+                // Other mods and profiles may share the folder
+                if self.db.version_in_use(download_folder, version).unwrap_or(true) {
+                    continue;
+                }
+                let version_dir = self.installer.app_data_dir().join(download_folder).join(version);
+                if version_dir.is_dir() {
+                    match std::fs::remove_dir_all(&version_dir) {
+                        Ok(()) => info!("Removed version {} of {} after the rollback period", version, mod_id),
+                        Err(e) => warn!("Failed to remove {}: {}", version_dir.display(), e),
+                    }
+                }
+            }
+            self.previous_versions = self.db.get_previous_versions().unwrap_or_default();
+        }
+        // Everything in the config except the game path, which differs between machines
+        pub fn export_settings(&self, path: &Path) -> Result<(), String> {
+            let mut config = self.current_config();
+            config.game_path.clear();
+            config.save_to(path)
+        }
+        pub fn import_settings(&mut self, path: &Path) -> Result<(), String> {
+            let config = Config::load_from(path)?;
+            self.auto_update_mods = config.auto_update_mods;
+            self.refresh_on_focus = config.refresh_on_focus;
+            self.enable_mod_debugging = config.enable_mod_debugging;
+            logging::set_debug(self.enable_mod_debugging);
+            self.notification_duration = config.notification_duration;
+            self.notification_corner = config.notification_corner;
+            self.notification_max_stacked = config.notification_max_stacked;
+            self.deep_verification = config.deep_verification;
+            self.rating_prompts = config.rating_prompts;
+            self.rollback_grace_days = config.rollback_grace_days;
+            self.delete_downloads_on_uninstall = config.delete_downloads_on_uninstall;
+            self.max_parallel_downloads = config.max_parallel_downloads;
+            self.enabled_mod_limit = config.enabled_mod_limit;
+            self.enabled_pak_limit = config.enabled_pak_limit;
+            self.keymap = config.keymap;
+            self.list_density = config.list_density;
+            self.webhook_events = config.webhook_events;
+            self.set_layout(config.layout);
+            self.save_config();
+            Ok(())
+        }
+        pub fn reset_keymap_and_layout(&mut self) {
+            self.keymap = Keymap::default();
+            self.set_layout(PanelLayout::default());
+            self.save_config();
+        }
+        fn set_layout(&mut self, layout: PanelLayout) {
+            self.layout = layout;
+            // egui keeps panel sizes by ID, so new IDs make the panels pick up the widths
+            self.layout_generation += 1;
+        }
+        // Run the actions whose shortcut was pressed this frame
+        pub fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+            // Typing into a field or binding a new shortcut must not trigger actions
+            if self.recording_shortcut.is_some() || ctx.wants_keyboard_input() {
+                return;
+            }
+            
+            for (action, shortcut) in self.keymap.by_specificity() {
+                if !ctx.input_mut(|input| input.consume_shortcut(&shortcut)) {
+                    continue;
+                }
+                match action {
+                    ShortcutAction::ShowBrowse => self.current_tab = Tab::Browse,
+                    ShortcutAction::ShowInstalled => self.current_tab = Tab::Installed,
+                    ShortcutAction::ShowDownloads => self.current_tab = Tab::Downloads,
+                    ShortcutAction::ShowSettings => self.current_tab = Tab::Settings,
+                    ShortcutAction::Refresh => {
+                        if let Ok(mods) = self.db.get_mods() {
+                            self.mods = mods;
+                        }
+                    },
+                    ShortcutAction::CheckUpdates => {
+                        if self.can_check_for_updates() {
+                            self.check_for_updates();
+                        }
+                    },
+                    ShortcutAction::InstallSelected => {
+                        for mod_id in self.selected_mods.clone() {
+                            self.start_install(&mod_id);
+                        }
+                    },
+                }
+            }
+        }
+        // Ask mod.io for the releases of a mod once per session
+        pub fn request_versions(&mut self, mod_entry: &ModEntry) {
+            let Some(modio_id) = mod_entry.modio_id() else {
+                return;
+            };
+            if self.version_requests.insert(mod_entry.mod_id.clone()) {
+                self.worker.send(Task::FetchVersions { mod_id: mod_entry.mod_id.clone(), modio_id });
+            }
+        }
+        fn finish_version_fetch(&mut self, mod_id: &str, result: Result<Vec<String>, String>) {
+            let versions = match result {
+                Ok(versions) if !versions.is_empty() => versions,
+                Ok(_) => return,
+                Err(e) => {
+                    warn!("Failed to fetch versions of {}: {}", mod_id, e);
+                    return;
+                }
+            };
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id) else {
+                return;
+            };
+            
+            // Installed files stay in their version folder, so keep an installed selection
+            let keep_selection = mod_entry.installed || versions.contains(&mod_entry.selected_version);
+            let mut stored = versions.clone();
+            if mod_entry.installed && !versions.contains(&mod_entry.selected_version) {
+                stored.push(mod_entry.selected_version.clone());
+            }
+            let result = self.db.set_mod_versions(mod_id, &stored)
+                .and_then(|()| if keep_selection {
+                    Ok(())
+                } else {
+                    self.db.set_selected_version(mod_id, &versions[0])
+                });
+            if let Err(e) = result {
+                warn!("Failed to save versions of {}: {}", mod_id, e);
+            }
+            
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+        }
+        pub fn import_subscriptions(&mut self) {
+            if self.subscription_import_pending {
+                return;
+            }
+            self.subscription_import_pending = true;
+            self.worker.send(Task::ImportSubscriptions);
+        }
+        // Add every subscribed mod missing from the library
+        fn finish_subscription_import(&mut self, result: Result<Vec<ModIoMod>, String>) {
+            self.subscription_import_pending = false;
+            let subscribed = match result {
+                Ok(subscribed) => subscribed,
+                Err(e) => {
+                    self.error_message = format!("Error importing subscriptions: {}", e);
+                    self.show_error_message = true;
+                    return;
+                }
+            };
+            
+            let mut added = 0;
+            for mod_io_mod in &subscribed {
+                let mod_entry = self.mod_io_client.convert_to_mod_entry(mod_io_mod);
+                if !self.mods.iter().any(|m| m.mod_id == mod_entry.mod_id) {
+                    if let Err(e) = self.db.add_mod(&mod_entry) {
+                        warn!("Failed to add {}: {}", mod_entry.mod_name, e);
+                        continue;
+                    }
+                    added += 1;
+                }
+                if let Err(e) = self.db.set_subscribed(&mod_entry.mod_id, true) {
+                    warn!("Failed to record subscription: {}", e);
+                }
+                self.subscriptions.insert(mod_entry.mod_id.clone());
+                self.cache_metadata(&mod_entry.mod_id, mod_io_mod);
+            }
+            
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            self.show_notification(format!(
+                "Found {} subscriptions, added {} new mods to the library.",
+                subscribed.len(), added
+            ));
+        }
+        pub fn set_subscribed(&mut self, mod_id: &str, subscribed: bool) {
+            let Some(modio_id) = self.mods.iter().find(|m| m.mod_id == mod_id).and_then(|m| m.modio_id()) else {
+                return;
+            };
+            self.worker.send(Task::SetSubscribed { mod_id: mod_id.to_string(), modio_id, subscribed });
+        }
+        fn finish_subscription_change(&mut self, mod_id: &str, subscribed: bool, result: Result<(), String>) {
+            if let Err(e) = result {
+                self.error_message = format!("Error updating the mod.io subscription: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            
+            if let Err(e) = self.db.set_subscribed(mod_id, subscribed) {
+                warn!("Failed to record subscription: {}", e);
+            }
+            if subscribed {
+                self.subscriptions.insert(mod_id.to_string());
+            } else {
+                self.subscriptions.remove(mod_id);
+            }
+        }
+        // Keep metadata for the Installed tab statistics
+        fn cache_metadata(&mut self, mod_id: &str, mod_io_mod: &ModIoMod) {
+            match serde_json::to_string(mod_io_mod) {
+                Ok(json) => {
+                    if let Err(e) = self.db.set_mod_metadata(mod_id, &json) {
+                        warn!("Failed to cache metadata of {}: {}", mod_id, e);
+                    }
+                },
+                Err(e) => warn!("Failed to serialize metadata of {}: {}", mod_id, e),
+            }
+            self.mod_metadata.insert(mod_id.to_string(), mod_io_mod.clone());
+        }
+        // Remember the thumbnail of mods added before thumbnails were stored
+        fn record_thumbnail(&mut self, mod_id: &str, url: &str) {
+            let Some(mod_entry) = self.mods.iter_mut().find(|m| m.mod_id == mod_id) else {
+                return;
+            };
+            if url.is_empty() || mod_entry.thumbnail_url.as_deref() == Some(url) {
+                return;
+            }
+            
+            mod_entry.thumbnail_url = Some(url.to_string());
+            if let Err(e) = self.db.set_thumbnail_url(mod_id, url) {
+                warn!("Failed to record thumbnail: {}", e);
+            }
+        }
+        // Start the deep verification once a week when enabled; called every frame
+        pub fn run_scheduled_verification(&mut self) {
+            if !self.deep_verification || self.verification_running {
+                return;
+            }
+            
+            let now = chrono::Utc::now().timestamp();
+            let last_run: Option<i64> = self.db.get_state("last_deep_verification")
+                .ok()
+                .flatten()
+                .and_then(|value| value.parse().ok());
+            if last_run.is_some_and(|last| now - last < DEEP_VERIFICATION_INTERVAL) {
+                return;
+            }
+            
+            if let Err(e) = self.db.set_state("last_deep_verification", &now.to_string()) {
+                warn!("Failed to record deep verification: {}", e);
+            }
+            self.start_verification();
+        }
+        // Scan every installed mod now and show the report when done
+        pub fn verify_mods(&mut self) {
+            if self.verification_running {
+                return;
+            }
+            self.verification_requested = true;
+            self.start_verification();
+        }
+        fn start_verification(&mut self) {
+            let installed: Vec<ModEntry> = self.mods.iter().filter(|m| m.installed).cloned().collect();
+            self.verification_running = true;
+            self.worker.send(Task::VerifyMods {
+                mods: installed,
+                game_path: self.game_path.clone(),
+            });
+        }
+        fn finish_deep_verification(&mut self, problems: Vec<(String, Vec<String>)>) {
+            self.verification_running = false;
+            
+            let message = if problems.is_empty() {
+                "Deep verification found no problems".to_string()
+            } else {
+                let details: Vec<String> = problems.iter()
+                    .map(|(mod_id, mod_problems)| {
+                        let mod_name = self.mods.iter()
+                            .find(|m| &m.mod_id == mod_id)
+                            .map_or(mod_id.as_str(), |m| m.mod_name.as_str());
+                        format!("{}: {}", mod_name, mod_problems.join(", "))
+                    })
+                    .collect();
+                format!("Deep verification found problems in {} mods. {}", problems.len(), details.join("; "))
+            };
+            if let Err(e) = self.db.add_history("verification", &message) {
+                warn!("Failed to record verification result: {}", e);
+            }
+            
+            // Scheduled runs stay quiet when everything checks out
+            let requested = std::mem::take(&mut self.verification_requested);
+            if !problems.is_empty() && !requested {
+                self.notify(Severity::Warning, format!(
+                    "Deep verification found problems in {} mods.",
+                    problems.len()
+                ));
+            }
+            if !problems.is_empty() || requested {
+                self.verification_report = Some(problems);
+            }
+        }
+        // Download the selected release again, which replaces the stored and deployed files
+        pub fn repair_mod(&mut self, mod_id: &str) {
+            if let Some(report) = self.verification_report.as_mut() {
+                report.retain(|(id, _)| id != mod_id);
+            }
+            self.start_install(mod_id);
+        }
+        pub fn set_catalog_sort(&mut self, sort: CatalogSort) {
+            if sort == self.catalog_sort {
+                return;
+            }
+            
+            // Restart paging with the new order
+            self.catalog_sort = sort;
+            self.catalog.clear();
+            self.catalog_exhausted = false;
+            self.load_catalog_page();
+        }
+        // Restart paging with the search box contents
+        pub fn set_catalog_search(&mut self, search: &str) {
+            if search == self.catalog_search {
+                return;
+            }
+            
+            self.catalog_search = search.to_string();
+            self.catalog.clear();
+            self.catalog_exhausted = false;
+            self.load_catalog_page();
+        }
+        // Filter and order of the mod list as currently set up
+        pub fn mod_query(&self) -> ModQuery<'_> {
+            ModQuery {
+                tab: &self.current_tab,
+                search: &self.search_query,
+                installed_only: self.show_installed_only,
+                favorites: &self.favorites,
+                favorites_only: self.favorites_only,
+                tags: &self.tag_filter,
+                local_tags: &self.local_tags,
+                local_tag_filter: &self.local_tag_filter,
+                sort: self.list_sort,
+                table_sort: self.table_sort,
+                metadata: &self.mod_metadata,
+                downloading: self.downloads.keys().map(String::as_str).collect(),
+                archived: self.archived_mods.keys().map(String::as_str).collect(),
+            }
+        }
+        // Shift selects the range from the last clicked mod, Ctrl toggles one mod and a plain click selects only it
+        pub fn click_selection(&mut self, mod_id: &str, modifiers: egui::Modifiers) {
+            let anchor = self.selection_anchor.as_ref()
+                .and_then(|anchor| self.list_order.iter().position(|id| id == anchor));
+            let clicked = self.list_order.iter().position(|id| id == mod_id);
+            if modifiers.shift
+                && let (Some(anchor), Some(clicked)) = (anchor, clicked)
+            {
+                if !modifiers.command {
+                    self.selected_mods.clear();
+                }
+                let range = anchor.min(clicked)..=anchor.max(clicked);
+                self.selected_mods.extend(self.list_order[range].iter().cloned());
+                return;
+            }
+            
+            if modifiers.command {
+                if !self.selected_mods.remove(mod_id) {
+                    self.selected_mods.insert(mod_id.to_string());
+                }
+            } else {
+                self.selected_mods.clear();
+                self.selected_mods.insert(mod_id.to_string());
+            }
+            self.selection_anchor = Some(mod_id.to_string());
+        }
+        pub fn select_all_shown(&mut self) {
+            self.selected_mods.extend(self.list_order.iter().cloned());
+        }
+        // Mods hidden by the filters keep their selection
+        pub fn invert_selection_shown(&mut self) {
+            for mod_id in &self.list_order {
+                if !self.selected_mods.remove(mod_id) {
+                    self.selected_mods.insert(mod_id.clone());
+                }
+            }
+        }
+        // Clicking the sorted column's header again reverses it
+        pub fn sort_table_by(&mut self, column: TableColumn) {
+            self.table_sort = match self.table_sort {
+                Some((sorted, descending)) if sorted == column => Some((column, !descending)),
+                _ => Some((column, false)),
+            };
+        }
+        pub fn set_favorite(&mut self, mod_id: &str, favorite: bool) {
+            if let Err(e) = self.db.set_favorite(mod_id, favorite) {
+                self.error_message = format!("Error saving favorite: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            if favorite {
+                self.favorites.insert(mod_id.to_string());
+            } else {
+                self.favorites.remove(mod_id);
+            }
+        }
+        // Every tag the user has given a mod, in name order
+        pub fn all_local_tags(&self) -> BTreeSet<String> {
+            self.local_tags.values().flatten().cloned().collect()
+        }
+        // Tags without a chosen color get one from a fixed palette, picked by name
+        pub fn tag_color(&self, tag: &str) -> [u8; 3] {
+            const PALETTE: [[u8; 3]; 8] = [
+                [70, 130, 180],
+                [60, 150, 90],
+                [190, 120, 40],
+                [160, 70, 160],
+                [180, 60, 60],
+                [50, 150, 150],
+                [130, 130, 60],
+                [100, 100, 180],
+            ];
+            self.tag_colors.get(tag).copied().unwrap_or_else(|| {
+                let index = tag.bytes().fold(0usize, |hash, byte| hash.wrapping_mul(31).wrapping_add(byte.into()));
+                PALETTE[index % PALETTE.len()]
+            })
+        }
+        pub fn add_local_tag(&mut self, mod_id: &str, tag: &str) {
+            let tag = tag.trim();
+            if tag.is_empty() {
+                return;
+            }
+            if let Err(e) = self.db.add_mod_tag(mod_id, tag) {
+                self.error_message = format!("Error adding tag: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            self.local_tags.entry(mod_id.to_string()).or_default().insert(tag.to_string());
+        }
+        pub fn remove_local_tag(&mut self, mod_id: &str, tag: &str) {
+            if let Err(e) = self.db.remove_mod_tag(mod_id, tag) {
+                self.error_message = format!("Error removing tag: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            if let Some(tags) = self.local_tags.get_mut(mod_id) {
+                tags.remove(tag);
+            }
+            // A filter on a tag no mod has any more would hide everything
+            if !self.local_tags.values().any(|tags| tags.contains(tag)) {
+                self.local_tag_filter.remove(tag);
+            }
+        }
+        pub fn set_tag_color(&mut self, tag: &str, color: [u8; 3]) {
+            if let Err(e) = self.db.set_tag_color(tag, color) {
+                self.error_message = format!("Error saving tag color: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            self.tag_colors.insert(tag.to_string(), color);
+        }
+        // Local tags only narrow the library lists, so the catalog is left alone
+        pub fn toggle_local_tag_filter(&mut self, tag: &str) {
+            if !self.local_tag_filter.remove(tag) {
+                self.local_tag_filter.insert(tag.to_string());
+            }
+        }
+        pub fn toggle_tag_filter(&mut self, tag: &str) {
+            let mut tags = self.tag_filter.clone();
+            if !tags.remove(tag) {
+                tags.insert(tag.to_string());
+            }
+            self.set_tag_filter(tags);
+        }
+        pub fn set_tag_filter(&mut self, tags: BTreeSet<String>) {
+            self.tag_filter = tags;
+            
+            // The catalog is filtered by mod.io, so it starts over
+            self.catalog.clear();
+            self.catalog_exhausted = false;
+            if self.browse_source == BrowseSource::Catalog {
+                self.load_catalog_page();
+            }
+        }
+        pub fn add_catalog_mod(&mut self, index: usize) {
+            if let Some(mod_io_mod) = self.catalog.get(index).cloned() {
+                self.add_mod_io_mod(&mod_io_mod);
+            }
+        }
+        fn add_mod_io_mod(&mut self, mod_io_mod: &ModIoMod) -> bool {
+            let mod_entry = self.mod_io_client.convert_to_mod_entry(mod_io_mod);
+            match self.db.add_mod(&mod_entry) {
+                Ok(()) => {
+                    self.cache_metadata(&mod_entry.mod_id, mod_io_mod);
+                    if let Ok(mods) = self.db.get_mods() {
+                        self.mods = mods;
+                    }
+                    self.notify(Severity::Success, format!("Added {} to the library.", mod_entry.mod_name));
+                    true
+                },
+                Err(e) => {
+                    self.error_message = format!("Error adding mod: {}", e);
+                    self.show_error_message = true;
+                    false
+                }
+            }
+        }
+        // Pasted mod.io links are looked up first, so the entry gets the real ID, name and metadata
+        pub fn add_mod_io_link(&mut self, reference: ParsedModRef) {
+            let link = self.file_path.trim().to_string();
+            self.resolving_link = Some(link.clone());
+            self.worker.send(Task::ResolveModLink { link, reference });
+        }
+        fn finish_mod_link(&mut self, link: &str, result: Result<Box<ModIoMod>, String>) {
+            if self.resolving_link.as_deref() == Some(link) {
+                self.resolving_link = None;
+            }
+            let action = self.link_actions.remove(link);
+            let mod_io_mod = match result {
+                Ok(mod_io_mod) => mod_io_mod,
+                Err(e) => {
+                    self.error_message = format!("Error looking up {} on mod.io: {}", link, e);
+                    self.show_error_message = true;
+                    return;
+                }
+            };
+            
+            let mod_id = format!("modio_{}", mod_io_mod.id);
+            let in_library = self.mods.iter().any(|m| m.mod_id == mod_id);
+            if !in_library && !self.add_mod_io_mod(&mod_io_mod) {
+                return;
+            }
+            if self.file_path.trim() == link {
+                self.file_path.clear();
+            }
+            match action {
+                Some(action) => self.run_link_action(&mod_id, action),
+                None if !in_library => complete_mod_hub_check(self, &mod_id, &mod_io_mod.description),
+                None => {},
+            }
+        }
+        // drgmodman:// and modio:// links, from the desktop or forwarded by a later launch
+        pub fn open_protocol_link(&mut self, link: &str) {
+            let Some(parsed) = protocol::parse(link) else {
+                self.error_message = format!("Not a link to a mod: {}", link);
+                self.show_error_message = true;
+                return;
+            };
+            info!("Opening {}", link);
+            self.open_mod_ref(link, parsed.reference, parsed.action);
+        }
+        fn open_mod_ref(&mut self, link: &str, reference: ParsedModRef, action: LinkAction) {
+            // Mods already in the library don't need a lookup
+            if let ParsedModRef::Id(modio_id) = reference
+                && let Some(mod_id) = self.mods.iter().find(|m| m.modio_id() == Some(modio_id)).map(|m| m.mod_id.clone())
+            {
+                self.run_link_action(&mod_id, action);
+                return;
+            }
+            self.link_actions.insert(link.to_string(), action);
+            self.worker.send(Task::ResolveModLink {
+                link: link.to_string(),
+                reference,
+            });
+        }
+        // Command line arguments of the window: protocol links, mod.io links, slugs and IDs, or mod files
+        pub fn open_argument(&mut self, argument: &str) {
+            let argument = argument.trim();
+            if protocol::is_protocol_link(argument) {
+                self.open_protocol_link(argument);
+                return;
+            }
+            if !Path::new(argument).exists()
+                && let Some(reference) = ParsedModRef::parse_input(argument)
+            {
+                self.open_mod_ref(argument, reference, LinkAction::Open);
+                return;
+            }
+            
+            // Local files and other URLs go through the add field, so nothing is added unasked
+            self.file_path = argument.to_string();
+            self.current_tab = Tab::Browse;
+            self.notify(Severity::Info, format!("Press [+] to add {}.", argument));
+        }
+        fn run_link_action(&mut self, mod_id: &str, action: LinkAction) {
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+                return;
+            };
+            
+            if action == LinkAction::Install {
+                if !mod_entry.installed {
+                    // Enabled up front so it gets deployed as soon as the download finishes
+                    if let Err(e) = self.db.update_mod_enabled(mod_id, true) {
+                        self.error_message = format!("Error enabling {}: {}", mod_entry.mod_name, e);
+                        self.show_error_message = true;
+                        return;
+                    }
+                    if let Ok(mods) = self.db.get_mods() {
+                        self.mods = mods;
+                    }
+                    self.start_install(mod_id);
+                    self.notify(Severity::Info, format!("Downloading {}...", mod_entry.mod_name));
+                    return;
+                }
+                self.notify(Severity::Info, format!("{} is already installed.", mod_entry.mod_name));
+            }
+            self.current_tab = Tab::Installed;
+            self.show_mod_details(mod_id);
+        }
+        // Another launch exited in favor of this window, so come to the front and take over its arguments
+        fn receive_launches(&mut self, ctx: &egui::Context) {
+            let Some(incoming) = &self.incoming_launches else {
+                return;
+            };
+            let launches: Vec<Vec<String>> = incoming.try_iter().collect();
+            if launches.is_empty() {
+                return;
+            }
+            
+            ctx.send_viewport_cmd(egui::ViewportCommand::Minimized(false));
+            ctx.send_viewport_cmd(egui::ViewportCommand::Focus);
+            for argument in launches.into_iter().flatten() {
+                self.open_argument(&argument);
+            }
+        }
+        pub fn save_mod_notes(&mut self, mod_id: &str, notes: &str) {
+            let notes = notes.trim();
+            if let Err(e) = self.db.set_mod_notes(mod_id, notes) {
+                self.error_message = format!("Error saving notes: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            if let Some(mod_entry) = self.mods.iter_mut().find(|m| m.mod_id == mod_id) {
+                mod_entry.notes = notes.to_string();
+            }
+        }
+        pub fn profile_style(&self, profile: &str) -> ProfileStyle {
+            self.profile_styles.get(profile).cloned().unwrap_or_default()
+        }
+        // Profile name prefixed with its icon
+        pub fn profile_label(&self, profile: &str) -> String {
+            let style = self.profile_style(profile);
+            if style.icon.is_empty() {
+                profile.to_string()
+            } else {
+                format!("{} {}", style.icon, profile)
+            }
+        }
+        pub fn set_profile_style(&mut self, style: ProfileStyle) {
+            let profile = self.db.get_current_profile().to_string();
+            if let Err(e) = self.db.set_profile_style(&profile, &style) {
+                self.error_message = format!("Error saving profile style: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            self.profile_styles.insert(profile, style);
+        }
+        pub fn post_install(&self, profile: &str) -> PostInstall {
+            self.post_install_actions.get(profile).cloned().unwrap_or_default()
+        }
+        pub fn set_post_install(&mut self, post_install: PostInstall) {
+            let profile = self.db.get_current_profile().to_string();
+            if let Err(e) = self.db.set_post_install(&profile, &post_install) {
+                self.error_message = format!("Error saving post-install action: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            self.post_install_actions.insert(profile, post_install);
+        }
+        // Once every download has finished, run the current profile's action if any install succeeded
+        fn run_post_install_action(&mut self) {
+            let installed = std::mem::take(&mut self.installs_succeeded);
+            if installed == 0 {
+                return;
+            }
+            
+            let post_install = self.post_install(self.db.get_current_profile());
+            let game_root = ModInstaller::game_root(&self.game_path);
+            let result = match post_install.action {
+                PostInstallAction::Nothing => Ok(()),
+                PostInstallAction::Notify => {
+                    self.notify(Severity::Success, format!("Finished installing {} mods.", installed));
+                    Ok(())
+                },
+                PostInstallAction::LaunchGame => launcher::launch_game(&self.game_path),
+                PostInstallAction::OpenGameFolder => launcher::open_folder(&game_root),
+                // Commands can do anything, so each run is confirmed
+                PostInstallAction::RunCommand => {
+                    if !post_install.command.trim().is_empty() {
+                        self.post_install_command_prompt = Some(post_install.command);
+                    }
+                    Ok(())
+                },
+            };
+            if let Err(e) = result {
+                self.error_message = format!("Error running post-install action: {}", e);
+                self.show_error_message = true;
+            }
+        }
+        // Bring the game folder in line with the current profile, then start the game
+        pub fn launch_game(&mut self) {
+            let plan = Plan::build(&self.installer, &self.mods, self.db.get_current_profile(), &self.game_path);
+            let mut failed = Vec::new();
+            for change in &plan.changes {
+                let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == change.mod_id) else {
+                    continue;
+                };
+                let result = match change.action {
+                    PlanAction::Deploy | PlanAction::Repair => self.installer.deploy_mod(mod_entry, &self.game_path),
+                    PlanAction::Remove => self.installer.undeploy_mod(mod_entry, &self.game_path),
+                    // Downloads take a while, so the game starts without them
+                    PlanAction::Install => {
+                        info!("{} is enabled but not downloaded yet", mod_entry.mod_name);
+                        Ok(())
+                    },
+                };
+                if let Err(e) = result {
+                    failed.push(format!("{}: {}", mod_entry.mod_name, e));
+                }
+            }
+            if !failed.is_empty() {
+                self.error_message = format!("The game was not started, some mods could not be deployed:\n{}", failed.join("\n"));
+                self.show_error_message = true;
+                return;
+            }
+            
+            match launcher::launch_game(&self.game_path) {
+                Ok(()) => {
+                    let enabled = self.mods.iter().filter(|m| m.enabled && m.installed).count();
+                    self.show_notification(format!("Launching DRG with {} mods.", enabled));
+                },
+                Err(e) => {
+                    self.error_message = format!("Error launching the game: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // Take this manager's paks out of the game folder and start the game without them
+        pub fn launch_vanilla(&mut self) {
+            if self.vanilla_session.is_some() {
+                return;
+            }
+            
+            let deployed: Vec<ModEntry> = self.mods
+                .iter()
+                .filter(|m| !self.installer.deployed_files(m, &self.game_path).is_empty())
+                .cloned()
+                .collect();
+            let mut removed = Vec::new();
+            for mod_entry in &deployed {
+                if let Err(e) = self.installer.undeploy_mod(mod_entry, &self.game_path) {
+                    self.vanilla_session = Some(VanillaSession { mod_ids: removed, game_seen: false, checked_at: 0.0 });
+                    self.restore_vanilla_session();
+                    self.error_message = format!("The game was not started, {} could not be removed: {}", mod_entry.mod_name, e);
+                    self.show_error_message = true;
+                    return;
+                }
+                removed.push(mod_entry.mod_id.clone());
+            }
+            
+            // Kept in the database so a restart of the manager still puts them back
+            if let Ok(json) = serde_json::to_string(&removed)
+                && let Err(e) = self.db.set_state("vanilla_session", &json)
+            {
+                warn!("Failed to record vanilla session: {}", e);
+            }
+            self.vanilla_session = Some(VanillaSession { mod_ids: removed, game_seen: false, checked_at: 0.0 });
+            
+            if let Err(e) = launcher::launch_game(&self.game_path) {
+                self.restore_vanilla_session();
+                self.error_message = format!("Error launching the game: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            self.show_notification(format!(
+                "Launching DRG without mods. {} mods are put back once the game exits.",
+                deployed.len()
+            ));
+        }
+        pub fn restore_vanilla_session(&mut self) {
+            let Some(session) = self.vanilla_session.take() else {
+                return;
+            };
+            
+            let mut failed = Vec::new();
+            let mut restored = 0;
+            for mod_id in &session.mod_ids {
+                // Mods disabled or uninstalled during the session stay out of the game folder
+                let Some(mod_entry) = self.mods.iter().find(|m| &m.mod_id == mod_id && m.installed && m.enabled) else {
+                    continue;
+                };
+                match self.installer.deploy_mod(mod_entry, &self.game_path) {
+                    Ok(()) => restored += 1,
+                    Err(e) => failed.push(format!("{}: {}", mod_entry.mod_name, e)),
+                }
+            }
+            if let Err(e) = self.db.delete_state("vanilla_session") {
+                warn!("Failed to clear vanilla session: {}", e);
+            }
+            
+            if failed.is_empty() {
+                self.notify(Severity::Success, format!("Put {} mods back into the game folder.", restored));
+            } else {
+                self.error_message = format!("Some mods could not be put back:\n{}", failed.join("\n"));
+                self.show_error_message = true;
+            }
+        }
+        // Restore the mods once the game of a vanilla session has been seen running and exited
+        fn watch_vanilla_session(&mut self, ctx: &egui::Context) {
+            let now = ctx.input(|input| input.time);
+            let Some(session) = self.vanilla_session.as_mut() else {
+                return;
+            };
+            ctx.request_repaint_after(VANILLA_WATCH_INTERVAL);
+            if now - session.checked_at < VANILLA_WATCH_INTERVAL.as_secs_f64() {
+                return;
+            }
+            session.checked_at = now;
+            
+            if launcher::game_running() {
+                session.game_seen = true;
+            } else if session.game_seen {
+                self.restore_vanilla_session();
+            }
+        }
+        // Per-mod recheck intervals and the request budget of check_for_updates keep this cheap
+        fn check_updates_on_focus(&mut self, ctx: &egui::Context) {
+            let now = ctx.input(|input| input.time);
+            match ctx.input(|input| input.viewport().focused) {
+                Some(false) => {
+                    self.unfocused_since.get_or_insert(now);
+                },
+                Some(true) => {
+                    let Some(unfocused_since) = self.unfocused_since.take() else {
+                        return;
+                    };
+                    if self.refresh_on_focus
+                        && now - unfocused_since >= FOCUS_REFRESH_AFTER
+                        && self.can_check_for_updates()
+                    {
+                        info!("Checking for updates after {:.0} minutes in the background", (now - unfocused_since) / 60.0);
+                        self.check_for_updates();
+                    }
+                },
+                None => {},
+            }
+        }
+        // Queues another attempt after a transient failure. Returns false once the failure should be reported
+        fn schedule_install_retry(&mut self, mod_id: &str, error: &str, now: f64) -> bool {
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id) else {
+                return false;
+            };
+            let failures = self.source_failures.entry(download_failure::source_of(mod_entry)).or_default();
+            *failures += 1;
+            let delay = RETRY_DELAYS[(*failures as usize - 1).min(RETRY_DELAYS.len() - 1)];
+            
+            let retries = self.install_retries.get(mod_id).copied().unwrap_or(0);
+            if !FailureKind::classify(error).is_transient() || retries >= MAX_RETRIES {
+                return false;
+            }
+            self.install_retries.insert(mod_id.to_string(), retries + 1);
+            self.pending_retries.push((mod_id.to_string(), now + delay));
+            warn!("Installing {} failed, retry {} of {} in {}s: {}", mod_id, retries + 1, MAX_RETRIES, delay, error);
+            true
+        }
+        fn clear_install_failures(&mut self, mod_id: &str) {
+            self.install_retries.remove(mod_id);
+            if let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id) {
+                self.source_failures.remove(&download_failure::source_of(mod_entry));
+            }
+        }
+        // The error with the number of attempts made, what kind of failure it looks like and how to fix it
+        fn describe_install_failure(&mut self, mod_id: &str, error: String) -> String {
+            let attempts = self.install_retries.remove(mod_id).unwrap_or(0) + 1;
+            let kind = FailureKind::classify(&error);
+            let mut message = error;
+            if attempts > 1 {
+                message.push_str(&format!(" (after {} attempts)", attempts));
+            }
+            if let Some(suggestion) = kind.suggestion() {
+                message.push_str(&format!("\n{}: {}", kind.label(), suggestion));
+            }
+            message
+        }
+        pub fn retry_due_in(&self, mod_id: &str, now: f64) -> Option<f64> {
+            self.pending_retries
+                .iter()
+                .find(|(id, _)| id == mod_id)
+                .map(|(_, due)| (due - now).max(0.0))
+        }
+        fn run_due_retries(&mut self, ctx: &egui::Context) {
+            if self.pending_retries.is_empty() {
+                return;
+            }
+            let now = ctx.input(|input| input.time);
+            let (due, waiting): (Vec<_>, Vec<_>) = std::mem::take(&mut self.pending_retries)
+                .into_iter()
+                .partition(|(_, due)| *due <= now);
+            self.pending_retries = waiting;
+            
+            // Dependencies were already fetched by the first attempt
+            for (mod_id, _) in due {
+                let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+                    continue;
+                };
+                info!("Retrying install of {}", mod_entry.mod_name);
+                if self.downloads.len() >= self.max_parallel_downloads.max(1) {
+                    self.download_queue.insert(0, mod_id);
+                } else {
+                    self.launch_download(mod_entry);
+                }
+            }
+            // Nothing was restarted, e.g. the mod was deleted meanwhile, so the batch ends here
+            self.finish_download_batch();
+            // Once a second, for the countdown shown in the mod's row
+            if !self.pending_retries.is_empty() {
+                ctx.request_repaint_after(std::time::Duration::from_secs(1));
+            }
+        }
+        pub fn check_startup_issues(&mut self) {
+            let token_validated = self.db.get_state("mod_io_token_validated").ok().flatten().is_some();
+            self.startup_issues = startup_check::find_issues(
+                &self.mod_io_oauth_key,
+                token_validated,
+                &self.game_path,
+                self.installer.app_data_dir(),
+                &self.sync_folder,
+            );
+        }
+        // Look for the game again after the saved path went missing
+        pub fn detect_game_path(&mut self) {
+            let found = Self::find_game_path();
+            if found.is_empty() {
+                self.notify(Severity::Warning, "Deep Rock Galactic was not found, set the game path in Settings.".to_string());
+                return;
+            }
+            self.game_path = found;
+            self.game_path_changed();
+            self.startup_issues.retain(|issue| !matches!(issue, StartupIssue::GamePathMissing(_)));
+        }
+        // Each game install keeps its own enabled mods, so they are reloaded for the new one
+        pub fn game_path_changed(&mut self) {
+            self.save_config();
+            self.db.set_game_target(game_target(&self.game_path));
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+        }
+        pub fn confirm_post_install_command(&mut self) {
+            let Some(command) = self.post_install_command_prompt.take() else {
+                return;
+            };
+            if let Err(e) = post_install::run_command(&command, &ModInstaller::game_root(&self.game_path)) {
+                self.error_message = format!("Error running post-install command: {}", e);
+                self.show_error_message = true;
+            }
+        }
+        pub fn export_conflict_report(&mut self, path: &Path) {
+            let report = ConflictReport::detect(&self.installer, &self.mods, self.db.get_current_profile());
+            match report.export(path) {
+                Ok(()) => self.notify(Severity::Success, format!(
+                    "Exported {} conflicts to {}.",
+                    report.conflicts.len(),
+                    path.display()
+                )),
+                Err(e) => {
+                    self.error_message = format!("Error exporting conflict report: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // Changes whenever the profile or its enabled mods, versions or variants do
+        fn enabled_set_key(&self) -> String {
+            let enabled: Vec<String> = self.mods
+                .iter()
+                .filter(|m| m.enabled)
+                .map(|m| format!("{}@{}/{}/{}", m.mod_id, m.selected_version, m.selected_variant, m.installed))
+                .collect();
+            format!("{}:{}", self.db.get_current_profile(), enabled.join(","))
+        }
+        // Store a new lockfile whenever the enabled set of the profile changes
+        fn refresh_lockfile(&mut self) {
+            let profile = self.db.get_current_profile().to_string();
+            let key = self.enabled_set_key();
+            if key == self.lockfile_key {
+                return;
+            }
+            self.lockfile_key = key;
+            
+            let result = Lockfile::build(&self.installer, &self.mods, &profile)
+                .to_json()
+                .and_then(|json| {
+                    // Leave the stored copy and its timestamp alone when nothing changed
+                    if self.db.get_lockfile(&profile).ok().flatten().as_ref() == Some(&json) {
+                        return Ok(());
+                    }
+                    self.db.set_lockfile(&profile, &json).map_err(|e| e.to_string())
+                });
+            if let Err(e) = result {
+                warn!("Failed to save lockfile: {}", e);
+            }
+        }
+        // Pak indexes are only read again when the enabled set changes
+        fn refresh_asset_conflicts(&mut self) {
+            let key = self.enabled_set_key();
+            if key == self.asset_conflicts_key {
+                return;
+            }
+            self.asset_conflicts_key = key.clone();
+            let mods = self.mods.iter().filter(|m| m.installed && m.enabled).cloned().collect();
+            self.worker.send(Task::DetectAssetConflicts { key, mods });
+        }
+        // Paks are only counted again when the enabled set or the limits change
+        fn refresh_load_limit_warning(&mut self) {
+            let key = format!("{}|{}|{}", self.enabled_set_key(), self.enabled_mod_limit, self.enabled_pak_limit);
+            if key == self.load_limit_key {
+                return;
+            }
+            self.load_limit_key = key;
+            
+            let enabled: Vec<&ModEntry> = self.mods.iter().filter(|m| m.enabled && m.installed).collect();
+            let paks: usize = enabled.iter().map(|m| self.installer.pak_paths(m).len()).sum();
+            let mut exceeded = Vec::new();
+            if self.enabled_mod_limit > 0 && enabled.len() > self.enabled_mod_limit {
+                exceeded.push(format!("{} mods enabled (limit {})", enabled.len(), self.enabled_mod_limit));
+            }
+            if self.enabled_pak_limit > 0 && paks > self.enabled_pak_limit {
+                exceeded.push(format!("{} paks enabled (limit {})", paks, self.enabled_pak_limit));
+            }
+            self.load_limit_warning = (!exceeded.is_empty()).then(|| format!(
+                "⚠ {}. The game may skip mods without an error; disable mods you don't need, or use another profile for them.",
+                exceeded.join(", ")
+            ));
+        }
+        // Other mods replacing assets of this one, with the number of shared assets
+        pub fn conflicting_mods(&self, mod_id: &str) -> Vec<(String, usize)> {
+            let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+            for conflict in &self.asset_conflicts {
+                if !conflict.mods.iter().any(|m| m.mod_id == mod_id) {
+                    continue;
+                }
+                for other in conflict.mods.iter().filter(|m| m.mod_id != mod_id) {
+                    *counts.entry(other.mod_name.clone()).or_default() += 1;
+                }
+            }
+            counts.into_iter().collect()
+        }
+        pub fn export_lockfile(&mut self, path: &Path) {
+            let profile = self.db.get_current_profile().to_string();
+            let json = match self.db.get_lockfile(&profile) {
+                Ok(Some(json)) => Ok(json),
+                _ => Lockfile::build(&self.installer, &self.mods, &profile).to_json(),
+            };
+            let result = json.and_then(|json| {
+                std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+            });
+            match result {
+                Ok(()) => self.notify(Severity::Success, format!("Exported the lockfile to {}.", path.display())),
+                Err(e) => {
+                    self.error_message = format!("Error exporting lockfile: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // `name`, or `name (2)`, `name (3)`... if a profile already has it
+        fn unused_profile_name(&self, name: &str) -> String {
+            let mut profile = name.to_string();
+            let mut suffix = 2;
+            while self.profiles.contains(&profile) {
+                profile = format!("{} ({})", name, suffix);
+                suffix += 1;
+            }
+            profile
+        }
+        // Copy the current profile and switch to the copy
+        pub fn duplicate_profile(&mut self) {
+            let source = self.db.get_current_profile().to_string();
+            let profile = self.unused_profile_name(&format!("{} copy", source));
+            if let Err(e) = self.db.clone_profile(&source, &profile) {
+                self.error_message = format!("Error duplicating profile: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            
+            self.profiles = self.db.get_profiles().unwrap_or_default();
+            self.profile_styles = self.db.get_profile_styles().unwrap_or_default();
+            self.post_install_actions = self.db.get_post_install_actions().unwrap_or_default();
+            self.db.set_current_profile(profile.clone());
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            self.notify(Severity::Success, format!("Duplicated profile {} as {}.", source, profile));
+        }
+        pub fn export_profile(&mut self, path: &Path) {
+            let profile = self.db.get_current_profile().to_string();
+            let result = self.db.export_profile(&profile)
+                .map_err(|e| e.to_string())
+                .and_then(|export| export.to_json())
+                .and_then(|json| {
+                    std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+                });
+            match result {
+                Ok(()) => self.notify(Severity::Success, format!("Exported profile {} to {}.", profile, path.display())),
+                Err(e) => {
+                    self.error_message = format!("Error exporting profile: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // Add a shared profile next to the existing ones and download its enabled mods
+        pub fn import_profile(&mut self, path: &Path) {
+            let export = match std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                .and_then(|json| ProfileExport::from_json(&json))
+            {
+                Ok(export) => export,
+                Err(e) => {
+                    self.error_message = format!("Error importing profile: {}", e);
+                    self.show_error_message = true;
+                    return;
+                }
+            };
+            
+            // Never merge into an existing profile of the same name
+            let name = export.profile.trim();
+            let profile = self.unused_profile_name(if name.is_empty() { "Imported" } else { name });
+            
+            if let Err(e) = self.db.import_profile(&export, &profile) {
+                self.error_message = format!("Error importing profile: {}", e);
+                self.show_error_message = true;
+                return;
+            }
+            self.profiles = self.db.get_profiles().unwrap_or_default();
+            self.db.set_current_profile(profile.clone());
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            
+            // Releases already downloaded for another profile are reused
+            let mut downloads = Vec::new();
+            for mod_entry in self.mods.iter().filter(|m| export.mods.iter().any(|e| e.mod_id == m.mod_id)) {
+                if self.installer.load_manifest(mod_entry).is_some() {
+                    if let Err(e) = self.db.update_mod_installed(&mod_entry.mod_id, true) {
+                        warn!("Failed to mark {} installed: {}", mod_entry.mod_name, e);
+                    }
+                } else if mod_entry.enabled {
+                    downloads.push(mod_entry.mod_id.clone());
+                }
+            }
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            
+            if let Err(e) = self.db.add_history("profile", &format!("Imported profile {} with {} mods", profile, export.mods.len())) {
+                warn!("Failed to record profile import: {}", e);
+            }
+            self.notify(Severity::Success, format!("Imported profile {} with {} mods.", profile, export.mods.len()));
+            for mod_id in &downloads {
+                self.start_install(mod_id);
+            }
+        }
+        pub fn export_mod_list(&mut self, path: &Path) {
+            let list = ModList::build(&self.mods);
+            let skipped = self.mods.iter().filter(|m| m.enabled && m.modio_id().is_none()).count();
+            let result = list.to_json().and_then(|json| {
+                std::fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+            });
+            match result {
+                Ok(()) if skipped > 0 => self.notify(Severity::Warning, format!(
+                    "Exported {} mods to {}. {} mods not from mod.io were left out.",
+                    list.mods.len(),
+                    path.display(),
+                    skipped
+                )),
+                Ok(()) => self.notify(Severity::Success, format!("Exported {} mods to {}.", list.mods.len(), path.display())),
+                Err(e) => {
+                    self.error_message = format!("Error exporting mod list: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // What differs from a shared list, e.g. before joining a friend's lobby; nothing is changed
+        pub fn compare_mod_list(&mut self, path: &Path) {
+            match ModList::load(path) {
+                Ok(theirs) => {
+                    let comparison = ModListComparison::build(&ModList::build(&self.mods), &theirs);
+                    let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                    self.mod_list_comparison = Some((comparison, file_name));
+                },
+                Err(e) => {
+                    self.error_message = format!("Error comparing mod lists: {}", e);
+                    self.show_error_message = true;
+                }
+            }
+        }
+        // Enable exactly the mods of a shared list, e.g. to match a lobby host, downloading what is missing
+        pub fn install_mod_list(&mut self, path: &Path) {
+            let list = match std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                .and_then(|text| ModList::parse(&text))
+            {
+                Ok(list) => list,
+                Err(e) => {
+                    self.error_message = format!("Error importing mod list: {}", e);
+                    self.show_error_message = true;
+                    return;
+                }
+            };
+            
+            // Everything currently enabled leaves the game folder first
+            for mod_entry in self.mods.iter().filter(|m| m.enabled) {
+                if let Err(e) = self.installer.undeploy_mod(mod_entry, &self.game_path) {
+                    warn!("Failed to remove files of {}: {}", mod_entry.mod_name, e);
+                }
+                if let Err(e) = self.db.update_mod_enabled(&mod_entry.mod_id, false) {
+                    warn!("Failed to disable {}: {}", mod_entry.mod_name, e);
+                }
+            }
+            
+            let mut downloads = Vec::new();
+            for listed in &list.mods {
+                let mod_id = format!("modio_{}", listed.id);
+                let known = self.mods.iter().find(|m| m.mod_id == mod_id).cloned();
+                let version = match (&known, listed.version.is_empty()) {
+                    (_, false) => listed.version.clone(),
+                    (Some(known), true) => known.selected_version.clone(),
+                    (None, true) => "1.0.0".to_string(),
+                };
+                
+                let Some(mod_entry) = known.or_else(|| {
+                    let mod_entry = ModEntry {
+                        mod_id: mod_id.clone(),
+                        mod_name: if listed.name.is_empty() { format!("mod.io {}", listed.id) } else { listed.name.clone() },
+                        mod_link: format!("https://mod.io/g/drg/m/{}", listed.id),
+                        download_folder: "downloads".to_string(),
+                        selected_version: version.clone(),
+                        versions: vec![version.clone()],
+                        installed: false,
+                        enabled: false,
+                        variants: Vec::new(),
+                        selected_variant: String::new(),
+                        last_checked: None,
+                        disabled_reason: None,
+                        thumbnail_url: None,
+                        update_available: None,
+                        notes: String::new(),
+                    };
+                    match self.db.add_mod(&mod_entry) {
+                        Ok(()) => Some(mod_entry),
+                        Err(e) => {
+                            warn!("Failed to add {}: {}", mod_entry.mod_name, e);
+                            None
+                        }
+                    }
+                }) else {
+                    continue;
+                };
+                
+                let up_to_date = mod_entry.installed
+                    && mod_entry.selected_version == version
+                    && self.installer.load_manifest(&mod_entry).is_some();
+                let result = self.db.set_selected_version(&mod_id, &version)
+                    .and_then(|()| self.db.set_disabled_reason(&mod_id, None))
+                    .and_then(|()| self.db.update_mod_status(&mod_id, up_to_date, true));
+                if let Err(e) = result {
+                    warn!("Failed to enable {}: {}", mod_entry.mod_name, e);
+                    continue;
+                }
+                if up_to_date {
+                    if let Err(e) = self.installer.deploy_mod(&mod_entry, &self.game_path) {
+                        warn!("Failed to deploy {}: {}", mod_entry.mod_name, e);
+                    }
+                } else {
+                    downloads.push(mod_id);
+                }
+            }
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            
+            let message = format!("Enabled {} mods from the mod list {}", list.mods.len(), path.display());
+            if let Err(e) = self.db.add_history("modlist", &message) {
+                warn!("Failed to record mod list import: {}", e);
+            }
+            self.show_notification(format!("{}; downloading {}.", message, downloads.len()));
+            for mod_id in &downloads {
+                self.start_install(mod_id);
+            }
+        }
+        // Switch the profile to exactly the mods, versions and variants of a lockfile
+        pub fn install_from_lockfile(&mut self, path: &Path) {
+            let lockfile = match Lockfile::load(path) {
+                Ok(lockfile) => lockfile,
+                Err(e) => {
+                    self.error_message = format!("Error installing from lockfile: {}", e);
+                    self.show_error_message = true;
+                    return;
+                }
+            };
+            
+            let message = format!("Installed {} mods from the lockfile of profile {}", lockfile.mods.len(), lockfile.profile);
+            self.apply_lockfile(lockfile, message);
+        }
+        // Releases that are still on disk are deployed again, the others downloaded
+        fn apply_lockfile(&mut self, lockfile: Lockfile, message: String) {
+            // Everything currently enabled leaves the game folder first
+            for mod_entry in self.mods.iter().filter(|m| m.enabled) {
+                if let Err(e) = self.installer.undeploy_mod(mod_entry, &self.game_path) {
+                    warn!("Failed to remove files of {}: {}", mod_entry.mod_name, e);
+                }
+                if let Err(e) = self.db.update_mod_enabled(&mod_entry.mod_id, false) {
+                    warn!("Failed to disable {}: {}", mod_entry.mod_name, e);
+                }
+            }
+            
+            for locked in &lockfile.mods {
+                if !self.mods.iter().any(|m| m.mod_id == locked.mod_id) {
+                    let mod_entry = ModEntry {
+                        mod_id: locked.mod_id.clone(),
+                        mod_name: locked.mod_name.clone(),
+                        mod_link: locked.mod_link.clone(),
+                        download_folder: "downloads".to_string(),
+                        selected_version: locked.version.clone(),
+                        versions: vec![locked.version.clone()],
+                        installed: false,
+                        enabled: false,
+                        variants: Vec::new(),
+                        selected_variant: locked.variant.clone(),
+                        last_checked: None,
+                        disabled_reason: None,
+                        thumbnail_url: None,
+                        update_available: None,
+                        notes: String::new(),
+                    };
+                    if let Err(e) = self.db.add_mod(&mod_entry) {
+                        warn!("Failed to add {}: {}", locked.mod_name, e);
+                        continue;
+                    }
+                }
+                
+                let result = self.db.set_selected_version(&locked.mod_id, &locked.version)
+                    .and_then(|()| self.db.set_selected_variant(&locked.mod_id, &locked.variant))
+                    .and_then(|()| self.db.set_disabled_reason(&locked.mod_id, None));
+                if let Err(e) = result {
+                    warn!("Failed to pin {}: {}", locked.mod_name, e);
+                }
+            }
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            
+            // Releases already on disk with the locked contents are deployed, the rest downloaded
+            let mut downloads = Vec::new();
+            for locked in &lockfile.mods {
+                let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == locked.mod_id) else {
+                    continue;
+                };
+                let up_to_date = self.installer.load_manifest(mod_entry).is_some()
+                    && locked.mismatches(&self.installer, mod_entry).is_empty();
+                
+                if let Err(e) = self.db.update_mod_status(&locked.mod_id, up_to_date, true) {
+                    warn!("Failed to enable {}: {}", locked.mod_name, e);
+                    continue;
+                }
+                if !up_to_date {
+                    downloads.push(locked.mod_id.clone());
+                } else if let Err(e) = self.installer.deploy_mod(mod_entry, &self.game_path) {
+                    warn!("Failed to deploy {}: {}", locked.mod_name, e);
+                }
+            }
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            
+            if let Err(e) = self.db.add_history("lockfile", &message) {
+                warn!("Failed to record lockfile install: {}", e);
+            }
+            self.notify_webhook(WebhookEvent::ProfileApplied, message);
+            for mod_id in &downloads {
+                self.start_install(mod_id);
+            }
+            self.lockfile_install = (!downloads.is_empty()).then_some(lockfile);
+        }
+        // Warn when a download from a lockfile doesn't match the pinned files
+        pub fn check_lockfile_install(&mut self, mod_id: &str) {
+            let Some(lockfile) = &self.lockfile_install else {
+                return;
+            };
+            let (Some(locked), Some(mod_entry)) = (lockfile.find(mod_id), self.mods.iter().find(|m| m.mod_id == mod_id)) else {
+                return;
+            };
+            
+            let mismatches = locked.mismatches(&self.installer, mod_entry);
+            if !mismatches.is_empty() {
+                self.error_message = format!(
+                    "{} doesn't match the lockfile:\n{}",
+                    mod_entry.mod_name,
+                    mismatches.join("\n")
+                );
+                self.show_error_message = true;
+            }
+        }
+        pub fn auto_disable_mod(&mut self, mod_id: &str, reason: DisableReason) {
+            let Some(mod_entry) = self.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+                return;
+            };
+            
+            if let Err(e) = self.installer.undeploy_mod(&mod_entry, &self.game_path) {
+                warn!("Failed to remove files of {}: {}", mod_entry.mod_name, e);
+            }
+            let message = reason.message();
+            let result = self.db.update_mod_enabled(mod_id, false)
+                .and_then(|()| self.db.set_disabled_reason(mod_id, Some(&message)));
+            if let Err(e) = result {
+                warn!("Failed to disable {}: {}", mod_entry.mod_name, e);
+                return;
+            }
+            
+            if let Ok(mods) = self.db.get_mods() {
+                self.mods = mods;
+            }
+            self.notify(Severity::Warning, format!("{} was disabled: {}", mod_entry.mod_name, message));
+        }
+        pub fn show_notification(&mut self, message: String) {
+            self.notify(Severity::Info, message);
+        }
+        pub fn notify(&mut self, severity: Severity, message: String) {
+            let remaining = match severity {
+                Severity::Info | Severity::Success => self.notification_duration,
+                Severity::Warning | Severity::Error => self.notification_duration * 2.0,
+            };
+            self.notifications.push_back(Notification {
+                message,
+                severity,
+                remaining,
+            });
+            
+            // Drop the oldest notifications once the stack is full
+            while self.notifications.len() > self.notification_max_stacked.max(1) {
+                self.notifications.pop_front();
+            }
+        }
+    }
+
+impl Default for ModManager {
+    fn default() -> Self {
+        // Initialize database
+        let app_data_dir = app_data_dir();
+        
+        std::fs::create_dir_all(&app_data_dir).expect("Failed to create app data directory");
+        
+        let db_path = app_data_dir.join(DATABASE_FILE);
+        let mut db = Database::new(&db_path).expect("Failed to initialize database");
+        let config = Config::load();
+        let game_path = if config.game_path.is_empty() {
+            Self::find_game_path()
+        } else {
+            config.game_path.clone()
+        };
+        db.set_game_target(game_target(&game_path));
+        
+        let profiles = db.get_profiles().unwrap_or_default();
+        let profile_styles = db.get_profile_styles().unwrap_or_default();
+        let post_install_actions = db.get_post_install_actions().unwrap_or_default();
+        // A vanilla session left over from the last run ends as soon as the game isn't running
+        let vanilla_session = db.get_state("vanilla_session").ok().flatten()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .map(|mod_ids| VanillaSession { mod_ids, game_seen: true, checked_at: 0.0 });
+        let mods = db.get_mods().unwrap_or_default();
+        let last_update_check = db.get_state("last_update_check")
+            .ok()
+            .flatten()
+            .and_then(|value| value.parse().ok());
+        let sync_folder = db.get_state("sync_folder").ok().flatten().unwrap_or_default();
+        let cold_storage_folder = db.get_state("cold_storage_folder").ok().flatten().unwrap_or_default();
+        let archived_mods = db.get_archived_mods().unwrap_or_default();
+        let subscriptions = db.get_subscriptions().unwrap_or_default();
+        let favorites = db.get_favorites().unwrap_or_default();
+        let mod_metadata = db.get_mod_metadata()
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(mod_id, json)| Some((mod_id, serde_json::from_str(&json).ok()?)))
+            .collect();
+        let local_tags = db.get_mod_tags().unwrap_or_default();
+        let tag_colors = db.get_tag_colors().unwrap_or_default();
+        // Default to low-memory mode on the Steam Deck
+        let low_memory_mode = match db.get_state("low_memory_mode").ok().flatten() {
+            Some(value) => value == "1",
+            None => std::env::var_os("SteamDeck").is_some(),
+        };
+
+        // Try to load the Mod.io API key from the keyring
+        let mod_io_oauth_key = credentials::get("mod_io_oauth_key").unwrap_or_else(|| {
+            // Validated tokens used to be saved under another name
+            let token = credentials::get("mod_io_api_key").unwrap_or_default();
+            if !token.is_empty() && credentials::set("mod_io_oauth_key", &token).is_ok() {
+                let _ = credentials::delete("mod_io_api_key");
+            }
+            token
+        });
+
+        let legacy_secrets = !config.mod_io_api_key.is_empty() || !config.webhook_url.is_empty();
+        let mod_io_api_key = load_secret("mod_io_login_api_key", &config.mod_io_api_key);
+        let webhook_url = load_secret("webhook_url", &config.webhook_url);
+
+        let mut manager = Self {
+            mods,
+            selected_mods: HashSet::new(),
+            selection_anchor: None,
+            list_order: Vec::new(),
+            search_query: String::new(),
+            show_installed_only: false,
+            favorites,
+            favorites_only: false,
+            current_tab: Tab::Browse,
+            db,
+            profiles,
+            profile_styles,
+            post_install_actions,
+            window_title: String::new(),
+            new_profile_name: String::new(),
+            show_delete_confirmation: false,
+            profile_to_delete: String::new(),
+            delete_confirmation_requested: false,
+            file_path: String::new(),
+            mod_delete_confirmation_requested: HashMap::new(),
+            mod_io_oauth_key,
+            mod_io_key_delete_requested: false,
+            mod_io_client: ModIoClient::uninitialized(),
+            installer: ModInstaller::new(app_data_dir.clone()),
+            game_path,
+            auto_update_mods: config.auto_update_mods,
+            refresh_on_focus: config.refresh_on_focus,
+            unfocused_since: None,
+            enable_mod_debugging: config.enable_mod_debugging,
+            show_error_message: false,
+            error_message: String::new(),
+            notifications: VecDeque::new(),
+            notification_duration: config.notification_duration,
+            notification_corner: config.notification_corner,
+            list_density: config.list_density,
+            list_sort: ListSort::Name,
+            table_sort: None,
+            row_heights: HashMap::new(),
+            mod_io_api_key,
+            email_login: EmailLogin::default(),
+            webhook_url,
+            webhook_events: config.webhook_events,
+            notification_max_stacked: config.notification_max_stacked,
+            archive_preview: None,
+            audio_previews: HashMap::new(),
+            #[cfg(feature = "audio-preview")]
+            audio_player: None,
+            mod_hub_prompt: None,
+            dependency_prompt: None,
+            source_editor: None,
+            last_update_check,
+            ingame_import: None,
+            sync_folder,
+            cold_storage_folder,
+            archived_mods,
+            browse_source: BrowseSource::Library,
+            catalog: Vec::new(),
+            catalog_exhausted: false,
+            catalog_sort: CatalogSort::Trending,
+            catalog_search: String::new(),
+            search_edited_at: 0.0,
+            low_memory_mode,
+            textures: TextureCache::new(texture_budget(low_memory_mode)),
+            worker: Worker::spawn(app_data_dir),
+            downloads: HashMap::new(),
+            queued_installs: Vec::new(),
+            download_queue: Vec::new(),
+            paused_downloads: HashSet::new(),
+            download_history: VecDeque::new(),
+            cloud_sync_warning: None,
+            detail_panel: None,
+            oauth_check_pending: false,
+            mod_io_account: None,
+            catalog_request: None,
+            pending_update_check: None,
+            deep_verification: config.deep_verification,
+            verification_running: false,
+            verification_report: None,
+            verification_requested: false,
+            incoming_launches: None,
+            link_actions: HashMap::new(),
+            thumbnail_requests: HashSet::new(),
+            rating_prompts: config.rating_prompts,
+            rollback_grace_days: config.rollback_grace_days,
+            delete_downloads_on_uninstall: config.delete_downloads_on_uninstall,
+            max_parallel_downloads: config.max_parallel_downloads,
+            enabled_mod_limit: config.enabled_mod_limit,
+            enabled_pak_limit: config.enabled_pak_limit,
+            load_limit_warning: None,
+            load_limit_key: String::new(),
+            previous_versions: HashMap::new(),
+            update_snapshots: HashMap::new(),
+            rating_prompt: None,
+            version_requests: HashSet::new(),
+            version_errors: HashMap::new(),
+            keymap: config.keymap,
+            layout: config.layout,
+            layout_generation: 0,
+            recording_shortcut: None,
+            update_run: None,
+            guest_mode: config.guest_mode,
+            mod_metadata,
+            tag_options: Vec::new(),
+            tag_filter: BTreeSet::new(),
+            local_tags,
+            tag_colors,
+            local_tag_filter: BTreeSet::new(),
+            lockfile_key: String::new(),
+            lockfile_install: None,
+            installs_succeeded: 0,
+            source_failures: HashMap::new(),
+            install_retries: HashMap::new(),
+            pending_retries: Vec::new(),
+            vanilla_session,
+            post_install_command_prompt: None,
+            asset_conflicts: Vec::new(),
+            asset_conflicts_key: String::new(),
+            show_conflicts: false,
+            mod_list_comparison: None,
+            resolving_link: None,
+            startup_issues: Vec::new(),
+            subscriptions,
+            subscription_import_pending: false,
+        };
+        manager.worker.send(Task::SetOAuthToken(manager.mod_io_oauth_key.clone()));
+        // Rewrite the config without the secrets it used to hold
+        if legacy_secrets {
+            manager.save_config();
+        }
+        manager.refresh_cloud_sync_warning();
+        manager.check_startup_issues();
+        manager.prune_previous_versions();
+        manager.prune_update_snapshots();
+        
+        // Offer to mirror what the game already manages on first run
+        if db_first_run(&manager.db) {
+            let found: Vec<(InGameMod, bool)> = ModInstaller::find_ingame_mods(&manager.game_path)
+                .into_iter()
+                .map(|ingame| (ingame, true))
+                .collect();
+            if !found.is_empty() {
+                manager.ingame_import = Some(found);
+            }
+            if let Err(e) = manager.db.set_state("ingame_import_offered", "1") {
+                warn!("Failed to record first run: {}", e);
+            }
+        }
+        
+        // Pick up changes made on other machines, unless the sync folder is already known to be gone
+        if !manager.startup_issues.iter().any(|issue| matches!(issue, StartupIssue::SyncFolderMissing(_))) {
+            manager.sync_now();
+        }
+        manager.count_modded_session();
+        manager.worker.send(Task::FetchTagOptions);
+        
+        if manager.auto_update_mods && manager.can_check_for_updates() {
+            manager.check_for_updates();
+        }
+        
+        manager
+    }
+}
+
+pub fn app_data_dir() -> PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("ue4-drg-modman")
+}
+
+fn texture_budget(low_memory_mode: bool) -> usize {
+    if low_memory_mode {
+        LOW_MEMORY_TEXTURE_BUDGET
+    } else {
+        DEFAULT_TEXTURE_BUDGET
+    }
+}
+
+// A secret from credentials, or the value an older config held, moved into credentials
+fn load_secret(name: &str, legacy: &str) -> String {
+    if let Some(secret) = credentials::get(name) {
+        return secret;
+    }
+    if !legacy.is_empty()
+        && let Err(e) = credentials::set(name, legacy)
+    {
+        warn!("Failed to move {} out of the config: {}", name, e);
+    }
+    legacy.to_string()
+}
+
+fn db_first_run(db: &Database) -> bool {
+    db.get_state("ingame_import_offered").ok().flatten().is_none()
+}
+
+impl eframe::App for ModManager {
+    fn update(&mut self, ctx: &egui::Context, frame: &mut eframe::Frame) {
+        // Set dark theme
+        // ctx.set_visuals(egui::Visuals::dark());
+        
+        self.poll_worker(ctx);
+        self.receive_launches(ctx);
+        self.handle_shortcuts(ctx);
+        self.run_scheduled_verification();
+        self.watch_vanilla_session(ctx);
+        self.check_updates_on_focus(ctx);
+        self.run_due_retries(ctx);
+        self.refresh_lockfile();
+        self.refresh_asset_conflicts();
+        self.refresh_load_limit_warning();
+        render_ui(self, ctx, frame);
+    }
+    
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        // Catch settings edited without a save, e.g. a slider still being dragged
+        self.save_config();
     }
 }
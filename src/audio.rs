@@ -0,0 +1,37 @@
+use rodio::{Decoder, OutputStream, Sink};
+use std::io::Cursor;
+
+// Plays the sound preview bundled with an audio mod
+pub struct AudioPlayer {
+    // The output stream must stay alive for as long as the sink plays
+    _stream: OutputStream,
+    sink: Sink,
+    pub mod_id: String,
+}
+
+impl AudioPlayer {
+    pub fn play(mod_id: &str, data: Vec<u8>) -> Result<Self, String> {
+        let (stream, handle) = OutputStream::try_default()
+            .map_err(|e| format!("Failed to open audio output: {}", e))?;
+        let sink = Sink::try_new(&handle)
+            .map_err(|e| format!("Failed to create audio sink: {}", e))?;
+        let source = Decoder::new(Cursor::new(data))
+            .map_err(|e| format!("Failed to decode audio preview: {}", e))?;
+        
+        sink.append(source);
+        
+        Ok(Self {
+            _stream: stream,
+            sink,
+            mod_id: mod_id.to_string(),
+        })
+    }
+    
+    pub fn is_playing(&self) -> bool {
+        !self.sink.empty()
+    }
+    
+    pub fn stop(&self) {
+        self.sink.stop();
+    }
+}
@@ -0,0 +1,401 @@
+use crate::app::{app_data_dir, game_target, Config, ModManager};
+use crate::credentials;
+use crate::db::{Database, ModEntry, DATABASE_FILE};
+use crate::installer::ModInstaller;
+use crate::mod_io::{ModIoClient, ModIoFile, ModIoMod};
+use crate::mod_url::ParsedModRef;
+use crate::modlist::{ModList, ModListComparison};
+use crate::plan::{Plan, PlanAction};
+use clap::{Parser, Subcommand};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+// Without a subcommand the window opens as usual
+#[derive(Parser)]
+#[command(version, about = "Mod manager for Deep Rock Galactic", args_conflicts_with_subcommands = true)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// drgmodman:// links, mod.io links, slugs or IDs, or mod files to open in the window.
+    /// When the window is already open they are handed to it
+    #[arg(value_name = "LINK")]
+    pub arguments: Vec<String>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Add a mod.io mod to the current profile, download it and deploy it
+    Install {
+        /// mod.io link or numeric mod ID
+        url: String,
+    },
+    /// List the mods of the current profile
+    List,
+    /// Enable a mod, downloading it first if needed
+    Enable {
+        /// Mod ID as shown by `list`, mod.io ID or mod name
+        id: String,
+    },
+    /// Disable a mod and remove its files from the game
+    Disable {
+        /// Mod ID as shown by `list`, mod.io ID or mod name
+        id: String,
+    },
+    /// List profiles or switch to another one
+    Profile {
+        #[command(subcommand)]
+        command: ProfileCommand,
+    },
+    /// Install the newest release of mod.io mods
+    Update {
+        /// Update every installed mod.io mod
+        #[arg(long, conflicts_with = "ids")]
+        all: bool,
+        /// Mods to update
+        ids: Vec<String>,
+    },
+    /// Show what deploying the current profile would change, without changing anything
+    Plan {
+        #[arg(long)]
+        json: bool,
+    },
+    /// Compare the enabled mods with a mod list someone shared
+    Compare {
+        /// Exported mod list, or text with one mod.io ID or link per line
+        path: PathBuf,
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileCommand {
+    List,
+    Switch {
+        name: String,
+    },
+}
+
+// Same database, downloads, game path and mod.io login as the window
+struct Session {
+    db: Database,
+    installer: ModInstaller,
+    client: ModIoClient,
+    game_path: String,
+    logged_in: bool,
+}
+
+impl Session {
+    fn open() -> Result<Self, String> {
+        let app_data_dir = app_data_dir();
+        std::fs::create_dir_all(&app_data_dir)
+            .map_err(|e| format!("Failed to create {:?}: {}", app_data_dir, e))?;
+        let mut db = Database::new(&app_data_dir.join(DATABASE_FILE))
+            .map_err(|e| format!("Failed to open database: {}", e))?;
+
+        let config = Config::load();
+        let game_path = if config.game_path.is_empty() {
+            ModManager::find_game_path()
+        } else {
+            config.game_path
+        };
+        db.set_game_target(game_target(&game_path));
+
+        let token = credentials::get("mod_io_oauth_key").unwrap_or_default();
+        let mut installer = ModInstaller::new(app_data_dir);
+        installer.set_mod_io_token(&token);
+        let mut client = ModIoClient::new();
+        client.set_oauth_token(&token);
+
+        Ok(Self {
+            db,
+            installer,
+            client,
+            game_path,
+            logged_in: !token.is_empty(),
+        })
+    }
+
+    fn mods(&self) -> Result<Vec<ModEntry>, String> {
+        self.db.get_mods().map_err(|e| format!("Failed to load mods: {}", e))
+    }
+
+    // Matches the manager's mod ID, the mod.io ID or the name, ignoring case
+    fn find_mod(&self, id: &str) -> Result<ModEntry, String> {
+        let mods = self.mods()?;
+        mods.iter()
+            .find(|m| m.mod_id == id)
+            .or_else(|| mods.iter().find(|m| m.modio_id().is_some_and(|modio_id| modio_id.to_string() == id)))
+            .or_else(|| mods.iter().find(|m| m.mod_name.eq_ignore_ascii_case(id)))
+            .cloned()
+            .ok_or_else(|| format!("No mod {} in profile {}", id, self.db.get_current_profile()))
+    }
+
+    fn require_login(&self) -> Result<(), String> {
+        if self.logged_in {
+            Ok(())
+        } else {
+            Err("mod.io needs a login; add your token in the manager's Settings first".to_string())
+        }
+    }
+
+    // Download the selected release; deploying it is up to the caller
+    fn download(&self, mod_entry: &ModEntry) -> Result<(), String> {
+        println!("Downloading {} v{}", mod_entry.mod_name, mod_entry.selected_version);
+        self.installer.install_mod(mod_entry, &|_, _| {})?;
+        self.db.update_mod_installed(&mod_entry.mod_id, true)
+            .map_err(|e| format!("Failed to mark {} as installed: {}", mod_entry.mod_name, e))
+    }
+
+    fn deploy(&self, mod_entry: &ModEntry) -> Result<(), String> {
+        self.installer.deploy_mod(mod_entry, &self.game_path)?;
+        println!("Deployed {}", mod_entry.mod_name);
+        Ok(())
+    }
+
+    fn install(&self, url: &str) -> Result<(), String> {
+        self.require_login()?;
+        let parsed = match url.trim().parse() {
+            Ok(modio_id) => ParsedModRef::Id(modio_id),
+            Err(_) => ParsedModRef::parse(url).ok_or_else(|| format!("Not a mod.io link or ID: {}", url))?,
+        };
+        let mod_io_mod = match &parsed {
+            ParsedModRef::Id(modio_id) => self.client.get_mod_by_id(*modio_id),
+            ParsedModRef::NameId(name_id) => self.client.get_mod_by_name_id(name_id),
+        }
+        .map_err(|e| format!("Failed to look up {} on mod.io: {}", url, e))?;
+
+        let mut mod_entry = self.client.convert_to_mod_entry(&mod_io_mod);
+        mod_entry.enabled = true;
+        self.db.add_mod(&mod_entry)
+            .and_then(|()| self.db.update_mod_enabled(&mod_entry.mod_id, true))
+            .map_err(|e| format!("Failed to add {}: {}", mod_entry.mod_name, e))?;
+
+        // A mod already in the profile keeps its chosen release
+        let mod_entry = self.find_mod(&mod_entry.mod_id)?;
+        if !mod_entry.installed {
+            self.download(&mod_entry)?;
+        }
+        self.deploy(&mod_entry)
+    }
+
+    fn list(&self) -> Result<(), String> {
+        let mods = self.mods()?;
+        println!("Profile: {}", self.db.get_current_profile());
+        if mods.is_empty() {
+            println!("No mods.");
+            return Ok(());
+        }
+
+        let id_width = mods.iter().map(|m| m.mod_id.len()).max().unwrap_or(0).max(2);
+        let name_width = mods.iter().map(|m| m.mod_name.len()).max().unwrap_or(0).max(4);
+        println!("{:<id_width$} {:<name_width$} {:<12} STATUS", "ID", "NAME", "VERSION");
+        for mod_entry in &mods {
+            let mut status = match (mod_entry.enabled, mod_entry.installed) {
+                (_, false) => "not downloaded".to_string(),
+                (true, true) => "enabled".to_string(),
+                (false, true) => "disabled".to_string(),
+            };
+            if let Some(latest) = &mod_entry.update_available {
+                status.push_str(&format!(", update to {}", latest));
+            }
+            println!(
+                "{:<id_width$} {:<name_width$} {:<12} {}",
+                mod_entry.mod_id, mod_entry.mod_name, mod_entry.selected_version, status
+            );
+        }
+        Ok(())
+    }
+
+    fn set_enabled(&self, id: &str, enabled: bool) -> Result<(), String> {
+        let mut mod_entry = self.find_mod(id)?;
+        mod_entry.enabled = enabled;
+        if !enabled {
+            self.installer.undeploy_mod(&mod_entry, &self.game_path)?;
+        } else {
+            if !mod_entry.installed {
+                if mod_entry.modio_id().is_some() {
+                    self.require_login()?;
+                }
+                self.download(&mod_entry)?;
+            }
+            self.deploy(&mod_entry)?;
+        }
+
+        self.db.update_mod_enabled(&mod_entry.mod_id, enabled)
+            .and_then(|()| self.db.set_disabled_reason(&mod_entry.mod_id, None))
+            .map_err(|e| format!("Failed to save {}: {}", mod_entry.mod_name, e))?;
+        println!("{} {}", if enabled { "Enabled" } else { "Disabled" }, mod_entry.mod_name);
+        Ok(())
+    }
+
+    fn profile(&mut self, command: ProfileCommand) -> Result<(), String> {
+        let profiles = self.db.get_profiles().map_err(|e| format!("Failed to load profiles: {}", e))?;
+        match command {
+            ProfileCommand::List => {
+                for profile in profiles {
+                    let marker = if profile == self.db.get_current_profile() { "*" } else { " " };
+                    println!("{} {}", marker, profile);
+                }
+            },
+            ProfileCommand::Switch { name } => {
+                if !profiles.contains(&name) {
+                    return Err(format!("No profile {}; existing profiles: {}", name, profiles.join(", ")));
+                }
+                self.db.set_current_profile(name.clone());
+                // The game folder follows the profile, as Launch DRG does in the window
+                self.apply_plan()?;
+                println!("Switched to profile {}", name);
+            },
+        }
+        Ok(())
+    }
+
+    // Deploy enabled mods and remove disabled ones; mods never downloaded are left to `update` or `enable`
+    fn apply_plan(&self) -> Result<(), String> {
+        let mods = self.mods()?;
+        let plan = Plan::build(&self.installer, &mods, self.db.get_current_profile(), &self.game_path);
+        let mut failed = Vec::new();
+        for change in plan.changes {
+            let Some(mod_entry) = mods.iter().find(|m| m.mod_id == change.mod_id) else {
+                continue;
+            };
+            let result = match change.action {
+                PlanAction::Install => {
+                    println!("{} is not downloaded yet, run `enable {}`", mod_entry.mod_name, mod_entry.mod_id);
+                    Ok(())
+                },
+                PlanAction::Deploy | PlanAction::Repair => self.deploy(mod_entry),
+                PlanAction::Remove => self.installer.undeploy_mod(mod_entry, &self.game_path)
+                    .map(|()| println!("Removed {}", mod_entry.mod_name)),
+            };
+            if let Err(e) = result {
+                failed.push(format!("{}: {}", mod_entry.mod_name, e));
+            }
+        }
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(failed.join("\n"))
+        }
+    }
+
+    fn update(&self, all: bool, ids: &[String]) -> Result<(), String> {
+        if !all && ids.is_empty() {
+            return Err("Name the mods to update, or pass --all".to_string());
+        }
+        self.require_login()?;
+        let mods = if all {
+            self.mods()?.into_iter().filter(|m| m.installed && m.modio_id().is_some()).collect()
+        } else {
+            ids.iter().map(|id| self.find_mod(id)).collect::<Result<Vec<_>, _>>()?
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        let mut updated = 0;
+        let mut failed = Vec::new();
+        for old_entry in mods {
+            let Some(modio_id) = old_entry.modio_id() else {
+                println!("Skipping {}, only mod.io mods can be updated", old_entry.mod_name);
+                continue;
+            };
+            let latest = match self.client.get_mod_by_id(modio_id) {
+                Ok(ModIoMod { modfile: Some(modfile), .. }) => ModIoFile::version_label(&modfile),
+                Ok(_) => {
+                    failed.push(format!("{} has no files on mod.io", old_entry.mod_name));
+                    continue;
+                },
+                Err(e) => {
+                    failed.push(format!("{}: {}", old_entry.mod_name, e));
+                    continue;
+                },
+            };
+            if let Err(e) = self.db.set_latest_version(&old_entry.mod_id, &latest)
+                .and_then(|()| self.db.set_last_checked(&old_entry.mod_id, now))
+            {
+                warn!("Failed to record update check: {}", e);
+            }
+            if old_entry.installed && latest == old_entry.selected_version {
+                continue;
+            }
+
+            let mut new_entry = old_entry.clone();
+            new_entry.selected_version = latest.clone();
+            let result = self.db.set_selected_version(&old_entry.mod_id, &latest)
+                .map_err(|e| e.to_string())
+                .and_then(|()| self.download(&new_entry));
+            match result {
+                Ok(()) => {
+                    // The previous release's paks are tracked in its own manifest
+                    if old_entry.installed && let Err(e) = self.installer.undeploy_everywhere(&old_entry) {
+                        warn!("Failed to remove the previous version of {}: {}", old_entry.mod_name, e);
+                    }
+                    // Kept on disk so the update can be rolled back in the app
+                    if old_entry.installed && let Err(e) = self.db.set_previous_version(&old_entry.mod_id, &old_entry.selected_version, now) {
+                        warn!("Failed to remember the previous version of {}: {}", old_entry.mod_name, e);
+                    }
+                    if new_entry.enabled && let Err(e) = self.deploy(&new_entry) {
+                        failed.push(format!("{}: {}", new_entry.mod_name, e));
+                        continue;
+                    }
+                    println!("Updated {} to {}", old_entry.mod_name, latest);
+                    updated += 1;
+                },
+                Err(e) => {
+                    // Stay on the version that is still on disk
+                    if let Err(e) = self.db.set_selected_version(&old_entry.mod_id, &old_entry.selected_version) {
+                        warn!("Failed to restore version of {}: {}", old_entry.mod_name, e);
+                    }
+                    failed.push(format!("{}: {}", old_entry.mod_name, e));
+                },
+            }
+        }
+
+        println!("Updated {} mods.", updated);
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Failed:\n{}", failed.join("\n")))
+        }
+    }
+
+    fn plan(&self, json: bool) -> Result<(), String> {
+        let plan = Plan::build(&self.installer, &self.mods()?, self.db.get_current_profile(), &self.game_path);
+        if json {
+            let output = serde_json::to_string_pretty(&plan)
+                .map_err(|e| format!("Failed to serialize plan: {}", e))?;
+            println!("{}", output);
+        } else {
+            print!("{}", plan.to_table());
+        }
+        Ok(())
+    }
+
+    fn compare(&self, path: &Path, json: bool) -> Result<(), String> {
+        let theirs = ModList::load(path)?;
+        let comparison = ModListComparison::build(&ModList::build(&self.mods()?), &theirs);
+        if json {
+            let output = serde_json::to_string_pretty(&comparison)
+                .map_err(|e| format!("Failed to serialize comparison: {}", e))?;
+            println!("{}", output);
+        } else {
+            println!("Profile: {}", self.db.get_current_profile());
+            print!("{}", comparison.to_text());
+        }
+        Ok(())
+    }
+}
+
+pub fn run(command: Command) -> Result<(), String> {
+    let mut session = Session::open()?;
+    match command {
+        Command::Install { url } => session.install(&url),
+        Command::List => session.list(),
+        Command::Enable { id } => session.set_enabled(&id, true),
+        Command::Disable { id } => session.set_enabled(&id, false),
+        Command::Profile { command } => session.profile(command),
+        Command::Update { all, ids } => session.update(all, &ids),
+        Command::Plan { json } => session.plan(json),
+        Command::Compare { path, json } => session.compare(&path, json),
+    }
+}
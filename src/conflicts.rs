@@ -0,0 +1,164 @@
+use crate::db::ModEntry;
+use crate::installer::ModInstaller;
+use crate::pak;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tracing::warn;
+
+// A mod involved in a conflict, numbered from lowest to highest priority
+#[derive(Serialize)]
+pub struct ConflictingMod {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub priority: usize,
+}
+
+// A game file written by more than one enabled mod
+#[derive(Serialize)]
+pub struct Conflict {
+    pub path: String,
+    // Ordered from lowest to highest priority
+    pub mods: Vec<ConflictingMod>,
+    // mod_id of the mod whose file is used, when the order decides it
+    pub winner: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ConflictReport {
+    pub profile: String,
+    pub generated_at: String,
+    pub conflicts: Vec<Conflict>,
+    // Assets inside the paks that more than one enabled mod replaces
+    pub asset_conflicts: Vec<Conflict>,
+}
+
+impl ConflictReport {
+    // Enabled mods deploying to the same path overwrite each other; the mod deployed last wins
+    pub fn detect(installer: &ModInstaller, mods: &[ModEntry], profile: &str) -> Self {
+        let mut writers: BTreeMap<String, Vec<(Option<i64>, &ModEntry)>> = BTreeMap::new();
+        for mod_entry in mods.iter().filter(|m| m.installed && m.enabled) {
+            let deployed_at = installer.deployed_at(mod_entry);
+            for target in installer.deploy_targets(mod_entry) {
+                writers.entry(target.to_lowercase()).or_default().push((deployed_at, mod_entry));
+            }
+        }
+        
+        Self {
+            profile: profile.to_string(),
+            generated_at: chrono::Utc::now().to_rfc3339(),
+            conflicts: Self::overlapping(writers),
+            asset_conflicts: Self::detect_assets(installer, mods),
+        }
+    }
+    
+    // Enabled mods whose paks contain the same asset; the game loads only one of them. The engine
+    // mounts paks named *_P.pak above the others, and doesn't define an order among equal ones
+    pub fn detect_assets(installer: &ModInstaller, mods: &[ModEntry]) -> Vec<Conflict> {
+        let mut writers: BTreeMap<String, Vec<(Option<i64>, &ModEntry)>> = BTreeMap::new();
+        for mod_entry in mods.iter().filter(|m| m.installed && m.enabled) {
+            // A mod's own paks may repeat an asset, which is not a conflict
+            let mut assets: BTreeMap<String, i64> = BTreeMap::new();
+            for pak_path in installer.pak_paths(mod_entry) {
+                let priority = i64::from(Self::is_patch_pak(&pak_path));
+                match pak::read_asset_paths(&pak_path) {
+                    Ok(paths) => {
+                        for path in paths {
+                            let entry = assets.entry(path.to_lowercase()).or_default();
+                            *entry = (*entry).max(priority);
+                        }
+                    },
+                    Err(e) => warn!("Failed to read the index of {:?}: {}", pak_path, e),
+                }
+            }
+            for (asset, priority) in assets {
+                writers.entry(asset).or_default().push((Some(priority), mod_entry));
+            }
+        }
+        Self::overlapping(writers)
+    }
+    
+    fn is_patch_pak(path: &Path) -> bool {
+        path.file_stem()
+            .map(|stem| stem.to_string_lossy().to_lowercase().ends_with("_p"))
+            .unwrap_or(false)
+    }
+    
+    // Sorted by the given priority; the last mod wins only when its priority is above every other
+    fn overlapping(writers: BTreeMap<String, Vec<(Option<i64>, &ModEntry)>>) -> Vec<Conflict> {
+        writers
+            .into_iter()
+            .filter(|(_, mods)| mods.len() > 1)
+            .map(|(path, mut mods)| {
+                mods.sort_by_key(|(priority, _)| *priority);
+                let winner = match mods.as_slice() {
+                    [.., (Some(runner_up), _), (Some(top), winner)] if top > runner_up => Some(winner.mod_id.clone()),
+                    _ => None,
+                };
+                Conflict {
+                    path,
+                    winner,
+                    mods: mods
+                        .into_iter()
+                        .enumerate()
+                        .map(|(priority, (_, mod_entry))| ConflictingMod {
+                            mod_id: mod_entry.mod_id.clone(),
+                            mod_name: mod_entry.mod_name.clone(),
+                            priority,
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+    
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize conflict report: {}", e))
+    }
+    
+    // Plain enough to paste into a Discord message
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "## Mod conflicts in profile \"{}\"\n\nGenerated {}\n\n",
+            self.profile, self.generated_at
+        );
+        if self.conflicts.is_empty() && self.asset_conflicts.is_empty() {
+            markdown.push_str("No conflicts detected.\n");
+            return markdown;
+        }
+        
+        Self::push_conflicts(&mut markdown, &self.conflicts);
+        if !self.asset_conflicts.is_empty() {
+            markdown.push_str("## Assets replaced by more than one mod\n\n");
+            Self::push_conflicts(&mut markdown, &self.asset_conflicts);
+        }
+        markdown
+    }
+    
+    fn push_conflicts(markdown: &mut String, conflicts: &[Conflict]) {
+        for conflict in conflicts {
+            markdown.push_str(&format!("### `{}`\n", conflict.path));
+            for conflicting in conflict.mods.iter().rev() {
+                let winner = if conflict.winner.as_ref() == Some(&conflicting.mod_id) { " (wins)" } else { "" };
+                markdown.push_str(&format!(
+                    "- {}. {} (`{}`){}\n",
+                    conflicting.priority + 1,
+                    conflicting.mod_name,
+                    conflicting.mod_id,
+                    winner
+                ));
+            }
+            markdown.push('\n');
+        }
+    }
+    
+    // The format follows the file extension, markdown unless it is .json
+    pub fn export(&self, path: &std::path::Path) -> Result<(), String> {
+        let is_json = path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|ext| ext.eq_ignore_ascii_case("json"))
+            .unwrap_or(false);
+        let contents = if is_json { self.to_json()? } else { self.to_markdown() };
+        std::fs::write(path, contents).map_err(|e| format!("Failed to write {:?}: {}", path, e))
+    }
+}
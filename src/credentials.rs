@@ -0,0 +1,249 @@
+use crate::app::app_data_dir;
+use chacha20poly1305::aead::{Aead, KeyInit};
+#[cfg(not(windows))]
+use chacha20poly1305::aead::{AeadCore, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use keyring::Entry;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use tracing::warn;
+
+// Secrets like the mod.io token and download headers go to the system keyring. Some Linux setups
+// run no secret service, so when the keyring fails they are kept in credentials.bin in the app data
+// folder instead. On Windows the file is protected with DPAPI, tied to the user's login; elsewhere
+// it is encrypted with a key derived from the machine ID and user name. That keeps them out of
+// backups and copies to other machines, not away from other programs of the same user
+
+const SERVICE: &str = "ue4-drg-modman";
+const NONCE_LEN: usize = 12;
+
+pub fn get(name: &str) -> Option<String> {
+    match Entry::new(SERVICE, name).and_then(|entry| entry.get_password()) {
+        Ok(secret) => Some(secret),
+        Err(keyring::Error::NoEntry) => load_file().remove(name),
+        Err(e) => {
+            warn!("System keyring unavailable, reading {} from the credential file: {}", name, e);
+            load_file().remove(name)
+        }
+    }
+}
+
+pub fn set(name: &str, secret: &str) -> Result<(), String> {
+    match Entry::new(SERVICE, name).and_then(|entry| entry.set_password(secret)) {
+        // An older copy in the file would otherwise outlive the keyring one
+        Ok(()) => remove_from_file(name),
+        Err(e) => {
+            warn!("System keyring unavailable, storing {} in the credential file: {}", name, e);
+            let mut secrets = load_file_for_update()?;
+            secrets.insert(name.to_string(), secret.to_string());
+            save_file(&secrets)
+        }
+    }
+}
+
+pub fn delete(name: &str) -> Result<(), String> {
+    match Entry::new(SERVICE, name).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {},
+        Err(e) => warn!("Failed to remove {} from the system keyring: {}", name, e),
+    }
+    remove_from_file(name)
+}
+
+fn file_path() -> PathBuf {
+    app_data_dir().join("credentials.bin")
+}
+
+// Whether any secret is kept in the fallback file rather than the system keyring
+pub fn uses_fallback_file() -> bool {
+    file_path().exists()
+}
+
+fn remove_from_file(name: &str) -> Result<(), String> {
+    if !file_path().exists() {
+        return Ok(());
+    }
+    let mut secrets = load_file_for_update()?;
+    if secrets.remove(name).is_some() {
+        save_file(&secrets)?;
+    }
+    Ok(())
+}
+
+// Stable for this user on this machine
+fn cipher() -> ChaCha20Poly1305 {
+    let machine_id = ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| std::fs::read_to_string(path).ok())
+        .or_else(|| std::env::var("COMPUTERNAME").ok())
+        .unwrap_or_default();
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+
+    let mut hasher = Sha256::new();
+    hasher.update(SERVICE.as_bytes());
+    hasher.update(machine_id.trim().as_bytes());
+    hasher.update(user.as_bytes());
+    ChaCha20Poly1305::new(Key::from_slice(&hasher.finalize()))
+}
+
+// The file holds the encrypted JSON map of name to secret
+fn load_file() -> BTreeMap<String, String> {
+    let Ok(contents) = std::fs::read(file_path()) else {
+        return BTreeMap::new();
+    };
+
+    match unseal(&contents) {
+        Some(json) => serde_json::from_slice(&json).unwrap_or_default(),
+        None => {
+            warn!("The credential file can't be decrypted, e.g. because it was copied from another machine");
+            BTreeMap::new()
+        }
+    }
+}
+
+// The secrets to change and write back. A file that can't be decrypted, e.g. after the machine ID
+// changed, is moved aside to credentials.bin.bak instead of being overwritten
+fn load_file_for_update() -> Result<BTreeMap<String, String>, String> {
+    let path = file_path();
+    let contents = match std::fs::read(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("Failed to read the credential file: {}", e)),
+    };
+    if let Some(secrets) = unseal(&contents).and_then(|json| serde_json::from_slice(&json).ok()) {
+        return Ok(secrets);
+    }
+
+    let backup = path.with_extension("bin.bak");
+    std::fs::rename(&path, &backup)
+        .map_err(|e| format!("The credential file can't be decrypted and moving it aside failed: {}", e))?;
+    warn!("The credential file can't be decrypted, moved it to {}", backup.display());
+    Ok(BTreeMap::new())
+}
+
+// The nonce followed by the ciphertext
+#[cfg(not(windows))]
+fn seal(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher()
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| "Failed to encrypt the credentials".to_string())?;
+    let mut contents = nonce.to_vec();
+    contents.extend(ciphertext);
+    Ok(contents)
+}
+
+#[cfg(windows)]
+fn seal(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+    dpapi::protect(plaintext)
+}
+
+#[cfg(not(windows))]
+fn unseal(contents: &[u8]) -> Option<Vec<u8>> {
+    unseal_with_key(contents)
+}
+
+// Files written before DPAPI was used still open with the derived key
+#[cfg(windows)]
+fn unseal(contents: &[u8]) -> Option<Vec<u8>> {
+    dpapi::unprotect(contents).or_else(|| unseal_with_key(contents))
+}
+
+fn unseal_with_key(contents: &[u8]) -> Option<Vec<u8>> {
+    if contents.len() < NONCE_LEN {
+        return None;
+    }
+    let (nonce, ciphertext) = contents.split_at(NONCE_LEN);
+    cipher().decrypt(Nonce::from_slice(nonce), ciphertext).ok()
+}
+
+fn save_file(secrets: &BTreeMap<String, String>) -> Result<(), String> {
+    let path = file_path();
+    if secrets.is_empty() {
+        return match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(format!("Failed to remove the credential file: {}", e)),
+            _ => Ok(()),
+        };
+    }
+
+    let json = serde_json::to_vec(secrets).map_err(|e| e.to_string())?;
+    let contents = seal(&json)?;
+
+    // Write next to the file first so a crash never leaves it half written
+    let partial = path.with_extension("part");
+    std::fs::create_dir_all(app_data_dir())
+        .and_then(|()| std::fs::write(&partial, &contents))
+        .and_then(|()| restrict_to_owner(&partial))
+        .and_then(|()| std::fs::rename(&partial, &path))
+        .map_err(|e| format!("Failed to write the credential file: {}", e))
+}
+
+#[cfg(unix)]
+pub fn restrict_to_owner(path: &std::path::Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+}
+
+#[cfg(not(unix))]
+pub fn restrict_to_owner(_path: &std::path::Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+// CryptProtectData with the user's credentials; only the same user on the same machine can decrypt
+#[cfg(windows)]
+mod dpapi {
+    use windows_sys::Win32::Foundation::LocalFree;
+    use windows_sys::Win32::Security::Cryptography::{
+        CryptProtectData, CryptUnprotectData, CRYPTPROTECT_UI_FORBIDDEN, CRYPT_INTEGER_BLOB,
+    };
+
+    fn blob(data: &[u8]) -> CRYPT_INTEGER_BLOB {
+        CRYPT_INTEGER_BLOB { cbData: data.len() as u32, pbData: data.as_ptr() as *mut u8 }
+    }
+
+    // Copy the output out of the buffer Windows allocated for it, then free that
+    fn take(output: CRYPT_INTEGER_BLOB) -> Vec<u8> {
+        let data = unsafe { std::slice::from_raw_parts(output.pbData, output.cbData as usize) }.to_vec();
+        unsafe { LocalFree(output.pbData.cast()) };
+        data
+    }
+
+    pub fn protect(plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let input = blob(plaintext);
+        let mut output = CRYPT_INTEGER_BLOB { cbData: 0, pbData: std::ptr::null_mut() };
+        let ok = unsafe {
+            CryptProtectData(
+                &input,
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        };
+        if ok == 0 {
+            return Err(format!("Failed to encrypt the credentials: {}", std::io::Error::last_os_error()));
+        }
+        Ok(take(output))
+    }
+
+    pub fn unprotect(contents: &[u8]) -> Option<Vec<u8>> {
+        let input = blob(contents);
+        let mut output = CRYPT_INTEGER_BLOB { cbData: 0, pbData: std::ptr::null_mut() };
+        let ok = unsafe {
+            CryptUnprotectData(
+                &input,
+                std::ptr::null_mut(),
+                std::ptr::null(),
+                std::ptr::null(),
+                std::ptr::null(),
+                CRYPTPROTECT_UI_FORBIDDEN,
+                &mut output,
+            )
+        };
+        (ok != 0).then(|| take(output))
+    }
+}
@@ -1,5 +1,9 @@
+use crate::post_install::{PostInstall, PostInstallAction};
 use rusqlite::{Connection, Result, params};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::path::Path;
+use tracing::{info, warn};
 
 #[derive(Clone)]
 pub struct ModEntry {
@@ -8,48 +12,560 @@ pub struct ModEntry {
     pub mod_link: String,
     pub download_folder: String,
     pub selected_version: String,
+    // Known releases, newest first
+    pub versions: Vec<String>,
     pub installed: bool,
     pub enabled: bool,
+    pub variants: Vec<String>,
+    pub selected_variant: String,
+    pub last_checked: Option<i64>,
+    // Why the manager disabled this mod on its own, if it did
+    pub disabled_reason: Option<String>,
+    // mod.io logo thumbnail, shown next to the mod
+    pub thumbnail_url: Option<String>,
+    // Latest release when it differs from the selected version
+    pub update_available: Option<String>,
+    // The user's own notes, e.g. why it's installed or which settings go with it
+    pub notes: String,
+}
+
+impl ModEntry {
+    // Numeric mod.io ID for entries that came from mod.io
+    pub fn modio_id(&self) -> Option<u32> {
+        self.mod_id.strip_prefix("modio_").and_then(|id| id.parse().ok())
+    }
+}
+
+// Color and icon that set a profile apart in the UI
+#[derive(Clone, PartialEq)]
+pub struct ProfileStyle {
+    pub color: [u8; 3],
+    pub icon: String,
+}
+
+impl Default for ProfileStyle {
+    fn default() -> Self {
+        Self {
+            color: [200, 200, 200],
+            icon: String::new(),
+        }
+    }
+}
+
+// Profile and tag colors are stored as #rrggbb
+fn color_hex(color: [u8; 3]) -> String {
+    format!("#{:02x}{:02x}{:02x}", color[0], color[1], color[2])
+}
+
+fn parse_color(hex: &str) -> Option<[u8; 3]> {
+    let hex = hex.strip_prefix('#')?;
+    if hex.len() != 6 {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+// File name of the database inside the app data directory
+pub const DATABASE_FILE: &str = "mods.db";
+
+// Schema changes in the order they were introduced. Append new ones at the end and never
+// edit a released migration, since existing databases have already run it
+const MIGRATIONS: &[fn(&Connection) -> Result<()>] = &[
+    create_initial_schema,
+    move_profile_tables,
+    create_post_install_table,
+    create_target_table,
+    create_previous_versions_table,
+    create_archive_table,
+    create_update_snapshot_table,
+    create_mod_files_table,
+    create_download_hashes_table,
+    add_mod_notes_column,
+    create_mod_tags_table,
+    create_favorites_table,
+    add_changed_at_column,
+    create_sync_tombstones_table,
+];
+
+// Tables of databases created before migrations were tracked; existing ones are kept as they are
+fn create_initial_schema(conn: &Connection) -> Result<()> {
+    // Create profiles table if it doesn't exist
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS profiles (
+            name TEXT PRIMARY KEY
+        )",
+        [],
+    )?;
+    
+    // Create table for the color and icon of each profile
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS profile_styles (
+            profile TEXT PRIMARY KEY,
+            color TEXT NOT NULL,
+            icon TEXT NOT NULL,
+            FOREIGN KEY(profile) REFERENCES profiles(name)
+        )",
+        [],
+    )?;
+    
+    // Create global mods table if it doesn't exist
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mods_global (
+            mod_id TEXT PRIMARY KEY,
+            mod_name TEXT NOT NULL,
+            mod_link TEXT NOT NULL,
+            download_folder TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create versions table to store all available versions
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_versions (
+            mod_id TEXT,
+            version TEXT,
+            PRIMARY KEY (mod_id, version),
+            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
+        )",
+        [],
+    )?;
+    
+    // Create variants table for mods that ship alternative paks
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_variants (
+            mod_id TEXT,
+            variant TEXT,
+            PRIMARY KEY (mod_id, variant),
+            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
+        )",
+        [],
+    )?;
+    
+    // Create table for the variant chosen in each profile
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS profile_variants (
+            profile TEXT,
+            mod_id TEXT,
+            variant TEXT NOT NULL,
+            PRIMARY KEY (profile, mod_id),
+            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
+        )",
+        [],
+    )?;
+    
+    // Create table for the last time each mod was checked for updates
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS update_checks (
+            mod_id TEXT PRIMARY KEY,
+            checked_at INTEGER NOT NULL,
+            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
+        )",
+        [],
+    )?;
+    
+    // Create table for the reasons mods were disabled automatically in each profile
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS disable_reasons (
+            profile TEXT,
+            mod_id TEXT,
+            reason TEXT NOT NULL,
+            PRIMARY KEY (profile, mod_id),
+            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
+        )",
+        [],
+    )?;
+    
+    // Create table for the thumbnail URL of each mod
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_thumbnails (
+            mod_id TEXT PRIMARY KEY,
+            url TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create table for the mods the user is subscribed to on mod.io
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS subscriptions (
+            mod_id TEXT PRIMARY KEY
+        )",
+        [],
+    )?;
+    
+    // Create table caching the mod.io metadata of each mod as JSON
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_metadata (
+            mod_id TEXT PRIMARY KEY,
+            json TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create table for the latest release seen by the update checker
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS latest_versions (
+            mod_id TEXT PRIMARY KEY,
+            version TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create table counting modded sessions per mod for the rating prompt
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS rating_prompts (
+            mod_id TEXT PRIMARY KEY,
+            sessions INTEGER NOT NULL DEFAULT 0,
+            dismissed INTEGER NOT NULL DEFAULT 0
+        )",
+        [],
+    )?;
+    
+    // Create table for a log of notable events such as verification results
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS history (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            timestamp INTEGER NOT NULL,
+            category TEXT NOT NULL,
+            message TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create table for the mods each mod.io mod requires
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_dependencies (
+            mod_id TEXT NOT NULL,
+            dependency_id TEXT NOT NULL,
+            dependency_name TEXT NOT NULL,
+            PRIMARY KEY (mod_id, dependency_id)
+        )",
+        [],
+    )?;
+    
+    // Create table for the latest lockfile of each profile
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS lockfiles (
+            profile TEXT PRIMARY KEY,
+            json TEXT NOT NULL,
+            updated_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create key/value table for application-wide state
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS app_state (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    // Create tables used by the sync layer to track local changes and merged journals
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_state (
+            profile TEXT,
+            mod_id TEXT,
+            fingerprint TEXT NOT NULL,
+            updated_at INTEGER NOT NULL,
+            PRIMARY KEY (profile, mod_id)
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_applied (
+            journal TEXT PRIMARY KEY
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Older versions kept a mods_<profile> table per profile; move their rows into profile_mods
+fn move_profile_tables(conn: &Connection) -> Result<()> {
+    // Create table for the state of each mod in each profile
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS profile_mods (
+            profile TEXT NOT NULL,
+            mod_id TEXT NOT NULL,
+            selected_version TEXT NOT NULL,
+            installed INTEGER NOT NULL,
+            enabled INTEGER NOT NULL,
+            PRIMARY KEY (profile, mod_id),
+            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
+        )",
+        [],
+    )?;
+    
+    
+    let tables: Vec<String> = conn
+        .prepare("SELECT name FROM sqlite_master WHERE type = 'table' AND name LIKE 'mods\\_%' ESCAPE '\\' AND name != 'mods_global'")?
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+    if tables.is_empty() {
+        return Ok(());
+    }
+    
+    for table in &tables {
+        let profile = &table["mods_".len()..];
+        // Quoted, since profile names may contain spaces or quotes
+        let quoted = format!("\"{}\"", table.replace('"', "\"\""));
+        
+        conn.execute("INSERT OR IGNORE INTO profiles (name) VALUES (?1)", params![profile])?;
+        conn.execute(
+            &format!(
+                "INSERT OR IGNORE INTO profile_mods (profile, mod_id, selected_version, installed, enabled)
+                 SELECT ?1, mod_id, selected_version, installed, enabled FROM {}",
+                quoted
+            ),
+            params![profile],
+        )?;
+        conn.execute(&format!("DROP TABLE {}", quoted), [])?;
+    }
+    
+    conn.execute(
+        "INSERT INTO history (timestamp, category, message) VALUES (?1, ?2, ?3)",
+        params![
+            chrono::Utc::now().timestamp(),
+            "migration",
+            format!("Moved {} profile tables into profile_mods", tables.len())
+        ],
+    )?;
+    Ok(())
+}
+
+// A profile's mod list as a portable JSON document, for sharing with other players
+#[derive(Serialize, Deserialize)]
+pub struct ProfileExport {
+    pub profile: String,
+    // In load order, which is the order the mods were added to the profile
+    pub mods: Vec<ExportedMod>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ExportedMod {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub mod_link: String,
+    pub version: String,
+    #[serde(default)]
+    pub variant: String,
+    pub enabled: bool,
+}
+
+impl ProfileExport {
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize profile: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid profile file: {}", e))
+    }
+}
+
+// Action each profile runs after a batch of installs or updates
+fn create_post_install_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS profile_post_install (
+            profile TEXT PRIMARY KEY,
+            action TEXT NOT NULL,
+            command TEXT NOT NULL,
+            FOREIGN KEY(profile) REFERENCES profiles(name)
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Enabled state of a profile's mods in each game install; profile_mods keeps the state
+// last set anywhere, which is what an install starts from
+fn create_target_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS profile_targets (
+            profile TEXT NOT NULL,
+            game_path TEXT NOT NULL,
+            mod_id TEXT NOT NULL,
+            enabled INTEGER NOT NULL,
+            PRIMARY KEY (profile, game_path, mod_id)
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Release each mod was updated from, kept so the update can be rolled back
+fn create_previous_versions_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS previous_versions (
+            profile TEXT NOT NULL,
+            mod_id TEXT NOT NULL,
+            version TEXT NOT NULL,
+            replaced_at INTEGER NOT NULL,
+            PRIMARY KEY (profile, mod_id)
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Mods whose files were taken out of storage; location is the cold storage folder they were moved to, empty if deleted
+fn create_archive_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS archived_mods (
+            mod_id TEXT PRIMARY KEY,
+            archived_at INTEGER NOT NULL,
+            location TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Lockfile of each profile taken right before its last "Update all" run
+fn create_update_snapshot_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS update_snapshots (
+            profile TEXT PRIMARY KEY,
+            json TEXT NOT NULL,
+            taken_at INTEGER NOT NULL
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Files each mod copied into a game install; written by the installer through mod_files::ModFiles
+fn create_mod_files_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_files (
+            mod_id TEXT NOT NULL,
+            path TEXT NOT NULL,
+            hash TEXT,
+            deployed_at INTEGER NOT NULL,
+            PRIMARY KEY (mod_id, path)
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// MD5 of each downloaded release that matched its mod.io filehash, for later integrity scans
+fn create_download_hashes_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS download_hashes (
+            mod_id TEXT NOT NULL,
+            version TEXT NOT NULL,
+            file_name TEXT NOT NULL,
+            md5 TEXT NOT NULL,
+            verified_at INTEGER NOT NULL,
+            PRIMARY KEY (mod_id, version)
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Free text per mod, shared by all profiles
+fn add_mod_notes_column(conn: &Connection) -> Result<()> {
+    conn.execute("ALTER TABLE mods_global ADD COLUMN notes TEXT NOT NULL DEFAULT ''", [])?;
+    
+    Ok(())
+}
+
+// The user's own tags, shared by all profiles, and the color each one is shown in
+fn create_mod_tags_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS mod_tags (
+            mod_id TEXT NOT NULL,
+            tag TEXT NOT NULL,
+            PRIMARY KEY (mod_id, tag)
+        )",
+        [],
+    )?;
+    
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS tag_colors (
+            tag TEXT PRIMARY KEY,
+            color TEXT NOT NULL
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Starred mods, shared by all profiles
+fn create_favorites_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS favorites (
+            mod_id TEXT PRIMARY KEY
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// When a row's selected version or enabled state last changed, in milliseconds, so sync can
+// tell which machine wrote last. Rows synced before this start from their last synced time
+fn add_changed_at_column(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "ALTER TABLE profile_mods ADD COLUMN changed_at INTEGER NOT NULL DEFAULT 0",
+        [],
+    )?;
+    conn.execute(
+        "UPDATE profile_mods SET changed_at = COALESCE(
+            (SELECT updated_at FROM sync_state
+             WHERE sync_state.profile = profile_mods.profile AND sync_state.mod_id = profile_mods.mod_id),
+            0)",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+// Profiles and mods removed on this machine, so a sync removes them elsewhere instead of
+// bringing them back. An empty mod_id stands for the whole profile
+fn create_sync_tombstones_table(conn: &Connection) -> Result<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS sync_tombstones (
+            profile TEXT NOT NULL,
+            mod_id TEXT NOT NULL,
+            removed_at INTEGER NOT NULL,
+            PRIMARY KEY (profile, mod_id)
+        )",
+        [],
+    )?;
+    
+    Ok(())
+}
+
+fn now_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
 }
 
 pub struct Database {
     conn: Connection,
     current_profile: String,
+    // Game install whose enabled state is read and written; empty when none is set
+    game_target: String,
 }
 
 impl Database {
     pub fn new(db_path: &Path) -> Result<Self> {
         let conn = Connection::open(db_path)?;
         
-        // Create profiles table if it doesn't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS profiles (
-                name TEXT PRIMARY KEY
-            )",
-            [],
-        )?;
-        
-        // Create global mods table if it doesn't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS mods_global (
-                mod_id TEXT PRIMARY KEY,
-                mod_name TEXT NOT NULL,
-                mod_link TEXT NOT NULL,
-                download_folder TEXT NOT NULL
-            )",
-            [],
-        )?;
-        
-        // Create versions table to store all available versions
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS mod_versions (
-                mod_id TEXT,
-                version TEXT,
-                PRIMARY KEY (mod_id, version),
-                FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
-            )",
-            [],
-        )?;
+        Self::migrate(&conn)?;
         
         // Check if Default profile exists, create if not
         let count: i64 = conn.query_row(
@@ -65,74 +581,154 @@ impl Database {
             )?;
         }
         
-        // Create table for Default profile if it doesn't exist
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS mods_Default (
-                mod_id TEXT PRIMARY KEY,
-                selected_version TEXT NOT NULL,
-                installed INTEGER NOT NULL,
-                enabled INTEGER NOT NULL,
-                FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
-            )",
+        // Start in the profile used last, from the window or the command line
+        let current_profile = conn.query_row(
+            "SELECT p.name FROM app_state s JOIN profiles p ON p.name = s.value WHERE s.key = 'current_profile'",
             [],
-        )?;
-        
-        // Get all profiles and ensure they have tables
-        // Create a scope for the statement to ensure it's dropped before we move conn
-        {
-            let mut stmt = conn.prepare("SELECT name FROM profiles")?;
-            let profile_names = stmt.query_map([], |row| {
-                row.get::<_, String>(0)
-            })?
-            .collect::<Result<Vec<String>>>()?;
-            
-            for profile_name in profile_names {
-                if profile_name != "Default" {
-                    let table_name = format!("mods_{}", profile_name);
-                    let query = format!(
-                        "CREATE TABLE IF NOT EXISTS {} (
-                            mod_id TEXT PRIMARY KEY,
-                            selected_version TEXT NOT NULL,
-                            installed INTEGER NOT NULL,
-                            enabled INTEGER NOT NULL,
-                            FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
-                        )",
-                        table_name
-                    );
-                    
-                    conn.execute(&query, [])?;
-                }
-            }
-        }
+            |row| row.get(0),
+        ).unwrap_or_else(|_| "Default".to_string());
         
         Ok(Self {
             conn,
-            current_profile: "Default".to_string(),
+            current_profile,
+            game_target: String::new(),
         })
     }
 
+    // Bring the schema up to date; user_version counts the migrations already applied
+    fn migrate(conn: &Connection) -> Result<()> {
+        let applied: usize = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if applied > MIGRATIONS.len() {
+            warn!(
+                "Database schema version {} is newer than this version of the manager knows ({})",
+                applied,
+                MIGRATIONS.len()
+            );
+            return Ok(());
+        }
+        
+        for (version, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+            // Each migration and its version bump commit together, so a failed one is retried
+            let transaction = conn.unchecked_transaction()?;
+            migration(&transaction)?;
+            transaction.pragma_update(None, "user_version", version + 1)?;
+            transaction.commit()?;
+            info!("Applied database migration {}", version + 1);
+        }
+        Ok(())
+    }
+
     pub fn create_profile(&self, profile_name: &str) -> Result<()> {
         self.conn.execute(
             "INSERT INTO profiles (name) VALUES (?1)",
             params![profile_name],
         )?;
         
-        // Create table for this profile
-        let table_name = format!("mods_{}", profile_name);
-        let query = format!(
-            "CREATE TABLE IF NOT EXISTS {} (
-                mod_id TEXT PRIMARY KEY,
-                selected_version TEXT NOT NULL,
-                installed INTEGER NOT NULL,
-                enabled INTEGER NOT NULL,
-                FOREIGN KEY(mod_id) REFERENCES mods_global(mod_id)
-            )",
-            table_name
-        );
+        Ok(())
+    }
+
+    pub fn export_profile(&self, profile_name: &str) -> Result<ProfileExport> {
+        let mut stmt = self.conn.prepare(
+            "SELECT p.mod_id, g.mod_name, g.mod_link, p.selected_version, COALESCE(v.variant, ''), p.enabled
+             FROM profile_mods p
+             JOIN mods_global g ON g.mod_id = p.mod_id
+             LEFT JOIN profile_variants v ON v.profile = p.profile AND v.mod_id = p.mod_id
+             WHERE p.profile = ?1
+             ORDER BY p.rowid"
+        )?;
+        let mods = stmt.query_map(params![profile_name], |row| {
+            Ok(ExportedMod {
+                mod_id: row.get(0)?,
+                mod_name: row.get(1)?,
+                mod_link: row.get(2)?,
+                version: row.get(3)?,
+                variant: row.get(4)?,
+                enabled: row.get(5)?,
+            })
+        })?
+        .collect::<Result<Vec<ExportedMod>>>()?;
+        
+        Ok(ProfileExport {
+            profile: profile_name.to_string(),
+            mods,
+        })
+    }
+
+    // Create a profile from an export; nothing is downloaded yet, so no mod counts as installed
+    pub fn import_profile(&self, export: &ProfileExport, profile_name: &str) -> Result<()> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute("INSERT INTO profiles (name) VALUES (?1)", params![profile_name])?;
+        
+        for exported in &export.mods {
+            // Mods already known here keep their local name and link
+            transaction.execute(
+                "INSERT OR IGNORE INTO mods_global (mod_id, mod_name, mod_link, download_folder)
+                 VALUES (?1, ?2, ?3, 'downloads')",
+                params![exported.mod_id, exported.mod_name, exported.mod_link],
+            )?;
+            transaction.execute(
+                "INSERT OR IGNORE INTO mod_versions (mod_id, version) VALUES (?1, ?2)",
+                params![exported.mod_id, exported.version],
+            )?;
+            transaction.execute(
+                "INSERT OR IGNORE INTO profile_mods (profile, mod_id, selected_version, installed, enabled, changed_at)
+                 VALUES (?1, ?2, ?3, 0, ?4, ?5)",
+                params![profile_name, exported.mod_id, exported.version, exported.enabled, now_millis()],
+            )?;
+            if !exported.variant.is_empty() {
+                transaction.execute(
+                    "INSERT OR REPLACE INTO profile_variants (profile, mod_id, variant) VALUES (?1, ?2, ?3)",
+                    params![profile_name, exported.mod_id, exported.variant],
+                )?;
+            }
+        }
         
-        self.conn.execute(&query, [])?;
+        transaction.commit()
+    }
+
+    // New profile with the same mods, versions, variants, style and settings as `source`
+    pub fn clone_profile(&self, source: &str, destination: &str) -> Result<()> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute("INSERT INTO profiles (name) VALUES (?1)", params![destination])?;
         
-        Ok(())
+        // ORDER BY keeps the load order, which follows insertion
+        transaction.execute(
+            "INSERT INTO profile_mods (profile, mod_id, selected_version, installed, enabled, changed_at)
+             SELECT ?2, mod_id, selected_version, installed, enabled, ?3 FROM profile_mods WHERE profile = ?1 ORDER BY rowid",
+            params![source, destination, now_millis()],
+        )?;
+        transaction.execute(
+            "INSERT INTO profile_variants (profile, mod_id, variant)
+             SELECT ?2, mod_id, variant FROM profile_variants WHERE profile = ?1",
+            params![source, destination],
+        )?;
+        transaction.execute(
+            "INSERT INTO disable_reasons (profile, mod_id, reason)
+             SELECT ?2, mod_id, reason FROM disable_reasons WHERE profile = ?1",
+            params![source, destination],
+        )?;
+        transaction.execute(
+            "INSERT INTO profile_styles (profile, color, icon)
+             SELECT ?2, color, icon FROM profile_styles WHERE profile = ?1",
+            params![source, destination],
+        )?;
+        transaction.execute(
+            "INSERT INTO profile_post_install (profile, action, command)
+             SELECT ?2, action, command FROM profile_post_install WHERE profile = ?1",
+            params![source, destination],
+        )?;
+        transaction.execute(
+            "INSERT INTO profile_targets (profile, game_path, mod_id, enabled)
+             SELECT ?2, game_path, mod_id, enabled FROM profile_targets WHERE profile = ?1",
+            params![source, destination],
+        )?;
+        transaction.execute(
+            "INSERT INTO previous_versions (profile, mod_id, version, replaced_at)
+             SELECT ?2, mod_id, version, replaced_at FROM previous_versions WHERE profile = ?1",
+            params![source, destination],
+        )?;
+        
+        transaction.commit()
     }
 
     pub fn delete_profile(&self, profile_name: &str) -> Result<()> {
@@ -141,18 +737,72 @@ impl Database {
             return Err(rusqlite::Error::InvalidParameterName("Cannot delete Default profile".to_string()));
         }
         
-        // Delete the profile from profiles table
-        self.conn.execute(
+        // Everything kept for the profile goes at once, or not at all
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
             "DELETE FROM profiles WHERE name = ?1",
             params![profile_name],
         )?;
         
-        // Drop the mods table for this profile
-        let table_name = format!("mods_{}", profile_name);
-        let query = format!("DROP TABLE IF EXISTS {}", table_name);
-        self.conn.execute(&query, [])?;
+        transaction.execute(
+            "DELETE FROM profile_mods WHERE profile = ?1",
+            params![profile_name],
+        )?;
         
-        Ok(())
+        // Forget the variants chosen in this profile
+        transaction.execute(
+            "DELETE FROM profile_variants WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM disable_reasons WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM profile_styles WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM lockfiles WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM profile_post_install WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM profile_targets WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM previous_versions WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        transaction.execute(
+            "DELETE FROM update_snapshots WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        // A profile created later under this name exports all of its rows again
+        transaction.execute(
+            "DELETE FROM sync_state WHERE profile = ?1",
+            params![profile_name],
+        )?;
+        
+        // Recorded so a sync deletes it on other machines too
+        transaction.execute(
+            "INSERT OR REPLACE INTO sync_tombstones (profile, mod_id, removed_at) VALUES (?1, '', ?2)",
+            params![profile_name, now_millis()],
+        )?;
+        
+        transaction.commit()
     }
 
     pub fn get_profiles(&self) -> Result<Vec<String>> {
@@ -166,7 +816,62 @@ impl Database {
         Ok(profiles)
     }
 
+    pub fn get_profile_styles(&self) -> Result<HashMap<String, ProfileStyle>> {
+        let mut stmt = self.conn.prepare("SELECT profile, color, icon FROM profile_styles")?;
+        let styles = stmt.query_map([], |row| {
+            let profile: String = row.get(0)?;
+            let color: String = row.get(1)?;
+            let icon: String = row.get(2)?;
+            Ok((profile, color, icon))
+        })?
+        .collect::<Result<Vec<(String, String, String)>>>()?;
+        
+        Ok(styles
+            .into_iter()
+            .map(|(profile, color, icon)| {
+                let color = parse_color(&color).unwrap_or(ProfileStyle::default().color);
+                (profile, ProfileStyle { color, icon })
+            })
+            .collect())
+    }
+
+    pub fn set_profile_style(&self, profile: &str, style: &ProfileStyle) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO profile_styles (profile, color, icon) VALUES (?1, ?2, ?3)",
+            params![profile, color_hex(style.color), style.icon],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn get_post_install_actions(&self) -> Result<HashMap<String, PostInstall>> {
+        let mut stmt = self.conn.prepare("SELECT profile, action, command FROM profile_post_install")?;
+        let actions = stmt.query_map([], |row| {
+            let profile: String = row.get(0)?;
+            let action: String = row.get(1)?;
+            Ok((profile, PostInstall {
+                action: PostInstallAction::from_key(&action),
+                command: row.get(2)?,
+            }))
+        })?
+        .collect::<Result<_>>()?;
+        
+        Ok(actions)
+    }
+
+    pub fn set_post_install(&self, profile: &str, post_install: &PostInstall) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO profile_post_install (profile, action, command) VALUES (?1, ?2, ?3)",
+            params![profile, post_install.action.key(), post_install.command],
+        )?;
+        
+        Ok(())
+    }
+
     pub fn set_current_profile(&mut self, profile: String) {
+        if let Err(e) = self.set_state("current_profile", &profile) {
+            warn!("Failed to remember the current profile: {}", e);
+        }
         self.current_profile = profile;
     }
 
@@ -174,10 +879,14 @@ impl Database {
         &self.current_profile
     }
 
+    pub fn set_game_target(&mut self, game_target: String) {
+        self.game_target = game_target;
+    }
+
     pub fn get_mods(&self) -> Result<Vec<ModEntry>> {
         // First, get all mods from global table
         let mut stmt = self.conn.prepare(
-            "SELECT mod_id, mod_name, mod_link, download_folder 
+            "SELECT mod_id, mod_name, mod_link, download_folder, notes 
              FROM mods_global"
         )?;
         
@@ -187,19 +896,16 @@ impl Database {
                 row.get::<_, String>(1)?, // mod_name
                 row.get::<_, String>(2)?, // mod_link
                 row.get::<_, String>(3)?, // download_folder
+                row.get::<_, String>(4)?, // notes
             ))
         })?
-        .collect::<Result<Vec<(String, String, String, String)>>>()?;
+        .collect::<Result<Vec<(String, String, String, String, String)>>>()?;
         
         // Now get the installed/enabled status and selected version from the current profile
-        let table_name = format!("mods_{}", self.current_profile);
-        let query = format!(
-            "SELECT mod_id, selected_version, installed, enabled FROM {}",
-            table_name
-        );
-        
-        let mut stmt = self.conn.prepare(&query)?;
-        let profile_mods = stmt.query_map([], |row| {
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, selected_version, installed, enabled FROM profile_mods WHERE profile = ?1"
+        )?;
+        let profile_mods = stmt.query_map(params![self.current_profile], |row| {
             let mod_id: String = row.get(0)?;
             let selected_version: String = row.get(1)?;
             let installed: bool = row.get(2)?;
@@ -208,28 +914,115 @@ impl Database {
         })?
         .collect::<Result<Vec<(String, String, bool, bool)>>>()?;
         
+        // What was enabled in the current game install, where it differs from the last state set anywhere
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, enabled FROM profile_targets WHERE profile = ?1 AND game_path = ?2"
+        )?;
+        let target_enabled: HashMap<String, bool> = stmt
+            .query_map(params![self.current_profile, self.game_target], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_>>()?;
+        
         // Create maps for profile data
-        let profile_data: std::collections::HashMap<String, (String, bool, bool)> = profile_mods
+        let profile_data: HashMap<String, (String, bool, bool)> = profile_mods
             .into_iter()
-            .map(|(id, ver, installed, enabled)| (id, (ver, installed, enabled)))
+            .map(|(id, ver, installed, enabled)| {
+                let enabled = target_enabled.get(&id).copied().unwrap_or(enabled);
+                (id, (ver, installed, enabled))
+            })
             .collect();
         
+        // Get all known variants and the ones selected in the current profile
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, variant FROM mod_variants ORDER BY variant"
+        )?;
+        let mut variants: HashMap<String, Vec<String>> = HashMap::new();
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (mod_id, variant) = row?;
+            variants.entry(mod_id).or_default().push(variant);
+        }
+        
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, variant FROM profile_variants WHERE profile = ?1"
+        )?;
+        let selected_variants: HashMap<String, String> = stmt
+            .query_map(params![self.current_profile], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<_>>()?;
+        
+        let mut stmt = self.conn.prepare("SELECT mod_id, checked_at FROM update_checks")?;
+        let update_checks: HashMap<String, i64> = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)))?
+            .collect::<Result<_>>()?;
+        
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, reason FROM disable_reasons WHERE profile = ?1"
+        )?;
+        let disable_reasons: HashMap<String, String> = stmt
+            .query_map(params![self.current_profile], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<_>>()?;
+        
+        let mut stmt = self.conn.prepare("SELECT mod_id, version FROM mod_versions ORDER BY rowid")?;
+        let mut versions: HashMap<String, Vec<String>> = HashMap::new();
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (mod_id, version) = row?;
+            versions.entry(mod_id).or_default().push(version);
+        }
+        
+        let mut stmt = self.conn.prepare("SELECT mod_id, version FROM latest_versions")?;
+        let latest_versions: HashMap<String, String> = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<_>>()?;
+        
+        let mut stmt = self.conn.prepare("SELECT mod_id, url FROM mod_thumbnails")?;
+        let thumbnails: HashMap<String, String> = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<_>>()?;
+        
         // Combine the data
         let mut result = Vec::new();
-        for (mod_id, mod_name, mod_link, download_folder) in global_mods {
+        for (mod_id, mod_name, mod_link, download_folder, notes) in global_mods {
             let (selected_version, installed, enabled) = profile_data
                 .get(&mod_id)
                 .cloned()
                 .unwrap_or(("1.0.0".to_string(), false, false));
+            let mod_variants = variants.remove(&mod_id).unwrap_or_default();
+            let selected_variant = selected_variants
+                .get(&mod_id)
+                .cloned()
+                .or_else(|| mod_variants.first().cloned())
+                .unwrap_or_default();
+            let last_checked = update_checks.get(&mod_id).copied();
+            let disabled_reason = disable_reasons.get(&mod_id).cloned();
+            let thumbnail_url = thumbnails.get(&mod_id).cloned();
+            let mod_versions = versions.remove(&mod_id).unwrap_or_default();
+            let update_available = latest_versions
+                .get(&mod_id)
+                .filter(|latest| installed && **latest != selected_version)
+                .cloned();
             
             result.push(ModEntry {
                 mod_id,
                 mod_name,
                 mod_link,
                 download_folder,
+                versions: mod_versions,
                 selected_version,
                 installed,
                 enabled,
+                variants: mod_variants,
+                selected_variant,
+                last_checked,
+                disabled_reason,
+                thumbnail_url,
+                update_available,
+                notes,
             });
         }
         
@@ -237,11 +1030,15 @@ impl Database {
     }
 
     pub fn add_mod(&self, mod_entry: &ModEntry) -> Result<()> {
-        // First, add or update the mod in the global table
+        // First, add or update the mod in the global table, keeping its notes
         self.conn.execute(
-            "INSERT OR REPLACE INTO mods_global 
+            "INSERT INTO mods_global 
              (mod_id, mod_name, mod_link, download_folder)
-             VALUES (?1, ?2, ?3, ?4)",
+             VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(mod_id) DO UPDATE SET
+                mod_name = excluded.mod_name,
+                mod_link = excluded.mod_link,
+                download_folder = excluded.download_folder",
             params![
                 mod_entry.mod_id,
                 mod_entry.mod_name,
@@ -261,22 +1058,22 @@ impl Database {
             ],
         )?;
         
-        // Then, add an entry in the current profile table if it doesn't exist
-        let table_name = format!("mods_{}", self.current_profile);
-        let query = format!(
-            "INSERT OR IGNORE INTO {} 
-             (mod_id, selected_version, installed, enabled)
-             VALUES (?1, ?2, ?3, ?4)",
-            table_name
-        );
+        if let Some(url) = &mod_entry.thumbnail_url {
+            self.set_thumbnail_url(&mod_entry.mod_id, url)?;
+        }
         
+        // Then, add an entry in the current profile if it doesn't exist
         self.conn.execute(
-            &query,
+            "INSERT OR IGNORE INTO profile_mods 
+             (profile, mod_id, selected_version, installed, enabled, changed_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
             params![
+                self.current_profile,
                 mod_entry.mod_id,
                 mod_entry.selected_version,
                 mod_entry.installed,
-                mod_entry.enabled
+                mod_entry.enabled,
+                now_millis()
             ],
         )?;
         
@@ -284,41 +1081,830 @@ impl Database {
     }
 
     pub fn update_mod_status(&self, mod_id: &str, installed: bool, enabled: bool) -> Result<()> {
-        // Update both statuses in the current profile table
-        let table_name = format!("mods_{}", self.current_profile);
-        let query = format!(
-            "UPDATE {} SET installed = ?1, enabled = ?2 WHERE mod_id = ?3",
-            table_name
-        );
-        
-        self.conn.execute(&query, params![installed, enabled, mod_id])?;
+        // Update both statuses in the current profile
+        self.conn.execute(
+            "UPDATE profile_mods SET installed = ?1, enabled = ?2,
+             changed_at = CASE WHEN enabled != ?2 THEN ?5 ELSE changed_at END
+             WHERE profile = ?3 AND mod_id = ?4",
+            params![installed, enabled, self.current_profile, mod_id, now_millis()],
+        )?;
         
-        Ok(())
+        self.set_target_enabled(mod_id, enabled)
     }
 
     pub fn update_mod_installed(&self, mod_id: &str, installed: bool) -> Result<()> {
         // Update just the installed status
-        let table_name = format!("mods_{}", self.current_profile);
-        let query = format!(
-            "UPDATE {} SET installed = ?1 WHERE mod_id = ?2",
-            table_name
-        );
-        
-        self.conn.execute(&query, params![installed, mod_id])?;
+        self.conn.execute(
+            "UPDATE profile_mods SET installed = ?1 WHERE profile = ?2 AND mod_id = ?3",
+            params![installed, self.current_profile, mod_id],
+        )?;
         
         Ok(())
     }
 
     pub fn update_mod_enabled(&self, mod_id: &str, enabled: bool) -> Result<()> {
         // Update just the enabled status
-        let table_name = format!("mods_{}", self.current_profile);
-        let query = format!(
-            "UPDATE {} SET enabled = ?1 WHERE mod_id = ?2",
-            table_name
-        );
+        self.conn.execute(
+            "UPDATE profile_mods SET enabled = ?1,
+             changed_at = CASE WHEN enabled != ?1 THEN ?4 ELSE changed_at END
+             WHERE profile = ?2 AND mod_id = ?3",
+            params![enabled, self.current_profile, mod_id, now_millis()],
+        )?;
+        
+        self.set_target_enabled(mod_id, enabled)
+    }
+
+    fn set_target_enabled(&self, mod_id: &str, enabled: bool) -> Result<()> {
+        if self.game_target.is_empty() {
+            return Ok(());
+        }
+        self.conn.execute(
+            "INSERT OR REPLACE INTO profile_targets (profile, game_path, mod_id, enabled)
+             SELECT profile, ?2, mod_id, ?4 FROM profile_mods WHERE profile = ?1 AND mod_id = ?3",
+            params![self.current_profile, self.game_target, mod_id, enabled],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn set_mod_variants(&self, mod_id: &str, variants: &[String]) -> Result<()> {
+        // Replace the known variants of this mod
+        self.conn.execute(
+            "DELETE FROM mod_variants WHERE mod_id = ?1",
+            params![mod_id],
+        )?;
+        
+        for variant in variants {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO mod_variants (mod_id, variant) VALUES (?1, ?2)",
+                params![mod_id, variant],
+            )?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn set_mod_versions(&self, mod_id: &str, versions: &[String]) -> Result<()> {
+        // Replace the known releases, keeping their order
+        self.conn.execute(
+            "DELETE FROM mod_versions WHERE mod_id = ?1",
+            params![mod_id],
+        )?;
+        
+        for version in versions {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO mod_versions (mod_id, version) VALUES (?1, ?2)",
+                params![mod_id, version],
+            )?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn set_selected_version(&self, mod_id: &str, version: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE profile_mods SET selected_version = ?1,
+             changed_at = CASE WHEN selected_version != ?1 THEN ?4 ELSE changed_at END
+             WHERE profile = ?2 AND mod_id = ?3",
+            params![version, self.current_profile, mod_id, now_millis()],
+        )?;
         
-        self.conn.execute(&query, params![enabled, mod_id])?;
+        Ok(())
+    }
+
+    // Remembers the release the current profile's mod was just updated from
+    pub fn set_previous_version(&self, mod_id: &str, version: &str, replaced_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO previous_versions (profile, mod_id, version, replaced_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![self.current_profile, mod_id, version, replaced_at],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn clear_previous_version(&self, profile: &str, mod_id: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM previous_versions WHERE profile = ?1 AND mod_id = ?2",
+            params![profile, mod_id],
+        )?;
         
         Ok(())
     }
+
+    // Previous release and when it was replaced, per profile and mod
+    pub fn get_previous_versions(&self) -> Result<HashMap<(String, String), (String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT profile, mod_id, version, replaced_at FROM previous_versions"
+        )?;
+        let versions = stmt.query_map([], |row| {
+            Ok(((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?)))
+        })?
+        .collect::<Result<_>>()?;
+        
+        Ok(versions)
+    }
+
+    // Whether any profile still selects the release stored in this folder
+    pub fn version_in_use(&self, download_folder: &str, version: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM profile_mods
+             JOIN mods_global ON mods_global.mod_id = profile_mods.mod_id
+             WHERE mods_global.download_folder = ?1 AND profile_mods.selected_version = ?2",
+            params![download_folder, version],
+            |row| row.get(0),
+        )?;
+        
+        Ok(count > 0)
+    }
+
+    // Whether another mod's selected release is stored in this folder
+    pub fn version_in_use_by_others(&self, download_folder: &str, version: &str, mod_id: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM profile_mods
+             JOIN mods_global ON mods_global.mod_id = profile_mods.mod_id
+             WHERE mods_global.download_folder = ?1 AND profile_mods.selected_version = ?2 AND profile_mods.mod_id != ?3",
+            params![download_folder, version, mod_id],
+            |row| row.get(0),
+        )?;
+        
+        Ok(count > 0)
+    }
+
+    // Whether another mod, or this mod in another profile, has the release stored in this folder installed
+    pub fn version_installed_elsewhere(&self, download_folder: &str, version: &str, mod_id: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM profile_mods
+             JOIN mods_global ON mods_global.mod_id = profile_mods.mod_id
+             WHERE mods_global.download_folder = ?1 AND profile_mods.selected_version = ?2 AND profile_mods.installed = 1
+             AND NOT (profile_mods.mod_id = ?3 AND profile_mods.profile = ?4)",
+            params![download_folder, version, mod_id, self.current_profile],
+            |row| row.get(0),
+        )?;
+        
+        Ok(count > 0)
+    }
+
+    // The files are gone in every profile, so the mod is no longer installed or enabled in any of them
+    pub fn archive_mod(&self, mod_id: &str, archived_at: i64, location: &str) -> Result<()> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO archived_mods (mod_id, archived_at, location) VALUES (?1, ?2, ?3)",
+            params![mod_id, archived_at, location],
+        )?;
+        transaction.execute(
+            "UPDATE profile_mods SET installed = 0, enabled = 0,
+             changed_at = CASE WHEN enabled != 0 THEN ?2 ELSE changed_at END
+             WHERE mod_id = ?1",
+            params![mod_id, now_millis()],
+        )?;
+        transaction.execute(
+            "UPDATE profile_targets SET enabled = 0 WHERE mod_id = ?1",
+            params![mod_id],
+        )?;
+        
+        transaction.commit()
+    }
+
+    pub fn unarchive_mod(&self, mod_id: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM archived_mods WHERE mod_id = ?1",
+            params![mod_id],
+        )?;
+        
+        Ok(())
+    }
+
+    // When each archived mod was archived and where its files went
+    pub fn get_archived_mods(&self) -> Result<HashMap<String, (i64, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, archived_at, location FROM archived_mods"
+        )?;
+        let archived = stmt.query_map([], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+        })?
+        .collect::<Result<_>>()?;
+        
+        Ok(archived)
+    }
+
+    pub fn set_selected_variant(&self, mod_id: &str, variant: &str) -> Result<()> {
+        // Only one variant can be active per mod in the current profile
+        self.conn.execute(
+            "INSERT OR REPLACE INTO profile_variants (profile, mod_id, variant) VALUES (?1, ?2, ?3)",
+            params![self.current_profile, mod_id, variant],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn set_disabled_reason(&self, mod_id: &str, reason: Option<&str>) -> Result<()> {
+        // No reason means the current state was chosen by the user
+        match reason {
+            Some(reason) => self.conn.execute(
+                "INSERT OR REPLACE INTO disable_reasons (profile, mod_id, reason) VALUES (?1, ?2, ?3)",
+                params![self.current_profile, mod_id, reason],
+            )?,
+            None => self.conn.execute(
+                "DELETE FROM disable_reasons WHERE profile = ?1 AND mod_id = ?2",
+                params![self.current_profile, mod_id],
+            )?,
+        };
+        
+        Ok(())
+    }
+
+    pub fn set_last_checked(&self, mod_id: &str, checked_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO update_checks (mod_id, checked_at) VALUES (?1, ?2)",
+            params![mod_id, checked_at],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn get_subscriptions(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare("SELECT mod_id FROM subscriptions")?;
+        let subscriptions = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<_>>()?;
+        
+        Ok(subscriptions)
+    }
+
+    pub fn set_subscribed(&self, mod_id: &str, subscribed: bool) -> Result<()> {
+        if subscribed {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO subscriptions (mod_id) VALUES (?1)",
+                params![mod_id],
+            )?;
+        } else {
+            self.conn.execute(
+                "DELETE FROM subscriptions WHERE mod_id = ?1",
+                params![mod_id],
+            )?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn get_favorites(&self) -> Result<HashSet<String>> {
+        let mut stmt = self.conn.prepare("SELECT mod_id FROM favorites")?;
+        let favorites = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .collect::<Result<_>>()?;
+        
+        Ok(favorites)
+    }
+
+    pub fn set_favorite(&self, mod_id: &str, favorite: bool) -> Result<()> {
+        if favorite {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO favorites (mod_id) VALUES (?1)",
+                params![mod_id],
+            )?;
+        } else {
+            self.conn.execute(
+                "DELETE FROM favorites WHERE mod_id = ?1",
+                params![mod_id],
+            )?;
+        }
+        
+        Ok(())
+    }
+
+    pub fn set_mod_metadata(&self, mod_id: &str, json: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO mod_metadata (mod_id, json) VALUES (?1, ?2)",
+            params![mod_id, json],
+        )?;
+        
+        Ok(())
+    }
+
+    // Raw JSON by mod_id; parsing is left to the caller
+    pub fn get_mod_metadata(&self) -> Result<HashMap<String, String>> {
+        let mut stmt = self.conn.prepare("SELECT mod_id, json FROM mod_metadata")?;
+        let metadata = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?
+            .collect::<Result<_>>()?;
+        
+        Ok(metadata)
+    }
+
+    pub fn set_latest_version(&self, mod_id: &str, version: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO latest_versions (mod_id, version) VALUES (?1, ?2)",
+            params![mod_id, version],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn set_mod_notes(&self, mod_id: &str, notes: &str) -> Result<()> {
+        self.conn.execute(
+            "UPDATE mods_global SET notes = ?2 WHERE mod_id = ?1",
+            params![mod_id, notes],
+        )?;
+        
+        Ok(())
+    }
+
+    // Tags of each mod, by mod_id
+    pub fn get_mod_tags(&self) -> Result<HashMap<String, BTreeSet<String>>> {
+        let mut stmt = self.conn.prepare("SELECT mod_id, tag FROM mod_tags")?;
+        let mut tags: HashMap<String, BTreeSet<String>> = HashMap::new();
+        for row in stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })? {
+            let (mod_id, tag) = row?;
+            tags.entry(mod_id).or_default().insert(tag);
+        }
+        
+        Ok(tags)
+    }
+
+    pub fn add_mod_tag(&self, mod_id: &str, tag: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO mod_tags (mod_id, tag) VALUES (?1, ?2)",
+            params![mod_id, tag],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn remove_mod_tag(&self, mod_id: &str, tag: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM mod_tags WHERE mod_id = ?1 AND tag = ?2",
+            params![mod_id, tag],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn get_tag_colors(&self) -> Result<HashMap<String, [u8; 3]>> {
+        let mut stmt = self.conn.prepare("SELECT tag, color FROM tag_colors")?;
+        let colors = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<(String, String)>>>()?;
+        
+        Ok(colors
+            .into_iter()
+            .filter_map(|(tag, color)| Some((tag, parse_color(&color)?)))
+            .collect())
+    }
+
+    pub fn set_tag_color(&self, tag: &str, color: [u8; 3]) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO tag_colors (tag, color) VALUES (?1, ?2)",
+            params![tag, color_hex(color)],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn set_thumbnail_url(&self, mod_id: &str, url: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO mod_thumbnails (mod_id, url) VALUES (?1, ?2)",
+            params![mod_id, url],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn record_modded_session(&self, mod_id: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO rating_prompts (mod_id, sessions) VALUES (?1, 1)
+             ON CONFLICT(mod_id) DO UPDATE SET sessions = sessions + 1",
+            params![mod_id],
+        )?;
+        
+        Ok(())
+    }
+
+    // Mods used for at least `min_sessions` sessions that may still be asked about
+    pub fn get_rating_candidates(&self, min_sessions: u32) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id FROM rating_prompts WHERE dismissed = 0 AND sessions >= ?1 ORDER BY sessions DESC"
+        )?;
+        let mod_ids = stmt
+            .query_map(params![min_sessions], |row| row.get::<_, String>(0))?
+            .collect::<Result<_>>()?;
+        
+        Ok(mod_ids)
+    }
+
+    // Never ask about the mod again, or with `dismissed` false, ask again after more sessions
+    pub fn close_rating_prompt(&self, mod_id: &str, dismissed: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE rating_prompts SET sessions = 0, dismissed = ?2 WHERE mod_id = ?1",
+            params![mod_id, dismissed],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn add_history(&self, category: &str, message: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO history (timestamp, category, message) VALUES (?1, ?2, ?3)",
+            params![chrono::Utc::now().timestamp(), category, message],
+        )?;
+        
+        Ok(())
+    }
+
+    // Replaces what was known about the mod's dependencies
+    pub fn set_mod_dependencies(&self, mod_id: &str, dependencies: &[(String, String)]) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM mod_dependencies WHERE mod_id = ?1",
+            params![mod_id],
+        )?;
+        for (dependency_id, dependency_name) in dependencies {
+            self.conn.execute(
+                "INSERT OR REPLACE INTO mod_dependencies (mod_id, dependency_id, dependency_name) VALUES (?1, ?2, ?3)",
+                params![mod_id, dependency_id, dependency_name],
+            )?;
+        }
+        
+        Ok(())
+    }
+
+    // (dependency mod_id, name) pairs
+    pub fn get_mod_dependencies(&self, mod_id: &str) -> Result<Vec<(String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT dependency_id, dependency_name FROM mod_dependencies WHERE mod_id = ?1 ORDER BY rowid"
+        )?;
+        let dependencies = stmt
+            .query_map(params![mod_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_>>()?;
+        
+        Ok(dependencies)
+    }
+
+    pub fn get_lockfile(&self, profile: &str) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare("SELECT json FROM lockfiles WHERE profile = ?1")?;
+        let mut rows = stmt.query(params![profile])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_lockfile(&self, profile: &str, json: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO lockfiles (profile, json, updated_at) VALUES (?1, ?2, ?3)",
+            params![profile, json, chrono::Utc::now().timestamp()],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn set_update_snapshot(&self, profile: &str, json: &str, taken_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO update_snapshots (profile, json, taken_at) VALUES (?1, ?2, ?3)",
+            params![profile, json, taken_at],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn get_update_snapshot(&self, profile: &str) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare("SELECT json FROM update_snapshots WHERE profile = ?1")?;
+        let mut rows = stmt.query(params![profile])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn clear_update_snapshot(&self, profile: &str) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM update_snapshots WHERE profile = ?1",
+            params![profile],
+        )?;
+        
+        Ok(())
+    }
+
+    // When each profile's update snapshot was taken
+    pub fn get_update_snapshot_times(&self) -> Result<HashMap<String, i64>> {
+        let mut stmt = self.conn.prepare("SELECT profile, taken_at FROM update_snapshots")?;
+        let times = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_>>()?;
+        
+        Ok(times)
+    }
+
+    pub fn get_state(&self, key: &str) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare("SELECT value FROM app_state WHERE key = ?1")?;
+        let mut rows = stmt.query(params![key])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_state(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO app_state (key, value) VALUES (?1, ?2)",
+            params![key, value],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn delete_state(&self, key: &str) -> Result<()> {
+        self.conn.execute("DELETE FROM app_state WHERE key = ?1", params![key])?;
+        
+        Ok(())
+    }
+
+    pub fn get_global_mods(&self) -> Result<Vec<(String, String, String, String)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, mod_name, mod_link, download_folder FROM mods_global"
+        )?;
+        let mods = stmt.query_map([], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+        
+        Ok(mods)
+    }
+
+    pub fn ensure_global_mod(&self, mod_id: &str, mod_name: &str, mod_link: &str, download_folder: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO mods_global (mod_id, mod_name, mod_link, download_folder)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![mod_id, mod_name, mod_link, download_folder],
+        )?;
+        
+        Ok(())
+    }
+
+    // Synced state stored for a profile: mod_id, selected_version, enabled, changed_at
+    pub fn get_profile_rows(&self, profile: &str) -> Result<Vec<(String, String, bool, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT mod_id, selected_version, enabled, changed_at FROM profile_mods WHERE profile = ?1"
+        )?;
+        let rows = stmt.query_map(params![profile], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+        
+        Ok(rows)
+    }
+
+    // Installed stays local: a row keeps it while its release is unchanged and starts uninstalled otherwise
+    pub fn set_profile_row(&self, profile: &str, mod_id: &str, selected_version: &str, enabled: bool, changed_at: i64) -> Result<()> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
+            "INSERT INTO profile_mods 
+             (profile, mod_id, selected_version, installed, enabled, changed_at)
+             VALUES (?1, ?2, ?3, 0, ?4, ?5)
+             ON CONFLICT(profile, mod_id) DO UPDATE SET
+             installed = CASE WHEN selected_version = excluded.selected_version THEN installed ELSE 0 END,
+             selected_version = excluded.selected_version,
+             enabled = excluded.enabled,
+             changed_at = excluded.changed_at",
+            params![profile, mod_id, selected_version, enabled, changed_at],
+        )?;
+        // Per-install states override the profile row in get_mods, so they follow the synced state too
+        transaction.execute(
+            "UPDATE profile_targets SET enabled = ?3 WHERE profile = ?1 AND mod_id = ?2",
+            params![profile, mod_id, enabled],
+        )?;
+        transaction.commit()?;
+        
+        Ok(())
+    }
+
+    pub fn get_sync_states(&self) -> Result<HashMap<(String, String), (String, i64)>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT profile, mod_id, fingerprint, updated_at FROM sync_state"
+        )?;
+        let states = stmt.query_map([], |row| {
+            Ok(((row.get(0)?, row.get(1)?), (row.get(2)?, row.get(3)?)))
+        })?
+        .collect::<Result<_>>()?;
+        
+        Ok(states)
+    }
+
+    pub fn set_sync_state(&self, profile: &str, mod_id: &str, fingerprint: &str, updated_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sync_state (profile, mod_id, fingerprint, updated_at)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![profile, mod_id, fingerprint, updated_at],
+        )?;
+        
+        Ok(())
+    }
+
+    pub fn get_sync_tombstones(&self) -> Result<HashMap<(String, String), i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT profile, mod_id, removed_at FROM sync_tombstones"
+        )?;
+        let tombstones = stmt.query_map([], |row| {
+            Ok(((row.get(0)?, row.get(1)?), row.get(2)?))
+        })?
+        .collect::<Result<_>>()?;
+        
+        Ok(tombstones)
+    }
+
+    pub fn set_sync_tombstone(&self, profile: &str, mod_id: &str, removed_at: i64) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO sync_tombstones (profile, mod_id, removed_at) VALUES (?1, ?2, ?3)",
+            params![profile, mod_id, removed_at],
+        )?;
+        
+        Ok(())
+    }
+
+    // Drop a mod from a profile because another machine removed it
+    pub fn remove_profile_row(&self, profile: &str, mod_id: &str, removed_at: i64) -> Result<()> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction.execute(
+            "DELETE FROM profile_mods WHERE profile = ?1 AND mod_id = ?2",
+            params![profile, mod_id],
+        )?;
+        transaction.execute(
+            "DELETE FROM profile_targets WHERE profile = ?1 AND mod_id = ?2",
+            params![profile, mod_id],
+        )?;
+        transaction.execute(
+            "INSERT OR REPLACE INTO sync_tombstones (profile, mod_id, removed_at) VALUES (?1, ?2, ?3)",
+            params![profile, mod_id, removed_at],
+        )?;
+        transaction.commit()
+    }
+
+    pub fn is_journal_applied(&self, journal: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sync_applied WHERE journal = ?1",
+            params![journal],
+            |row| row.get(0),
+        )?;
+        
+        Ok(count > 0)
+    }
+
+    pub fn mark_journal_applied(&self, journal: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR IGNORE INTO sync_applied (journal) VALUES (?1)",
+            params![journal],
+        )?;
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mod_id: &str) -> ModEntry {
+        ModEntry {
+            mod_id: mod_id.to_string(),
+            mod_name: mod_id.to_string(),
+            mod_link: String::new(),
+            download_folder: "downloads".to_string(),
+            selected_version: "1.0".to_string(),
+            versions: vec!["1.0".to_string()],
+            installed: false,
+            enabled: true,
+            variants: Vec::new(),
+            selected_variant: String::new(),
+            last_checked: None,
+            disabled_reason: None,
+            thumbnail_url: None,
+            update_available: None,
+            notes: String::new(),
+        }
+    }
+
+    fn memory_db() -> Database {
+        Database::new(Path::new(":memory:")).unwrap()
+    }
+
+    // Tables as the first release created them, before migrations were tracked
+    fn baseline_db() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE profiles (name TEXT PRIMARY KEY);
+             CREATE TABLE mods_global (
+                 mod_id TEXT PRIMARY KEY,
+                 mod_name TEXT NOT NULL,
+                 mod_link TEXT NOT NULL,
+                 download_folder TEXT NOT NULL
+             );
+             CREATE TABLE mod_versions (
+                 mod_id TEXT,
+                 version TEXT,
+                 PRIMARY KEY (mod_id, version)
+             );
+             CREATE TABLE mods_Default (
+                 mod_id TEXT PRIMARY KEY,
+                 selected_version TEXT NOT NULL,
+                 installed INTEGER NOT NULL,
+                 enabled INTEGER NOT NULL
+             );
+             CREATE TABLE \"mods_My Profile\" (
+                 mod_id TEXT PRIMARY KEY,
+                 selected_version TEXT NOT NULL,
+                 installed INTEGER NOT NULL,
+                 enabled INTEGER NOT NULL
+             );
+             INSERT INTO profiles (name) VALUES ('Default'), ('My Profile');
+             INSERT INTO mods_global VALUES ('modio_1', 'Mod', 'https://mod.io/g/drg/m/mod', 'downloads');
+             INSERT INTO mod_versions VALUES ('modio_1', '1.0'), ('modio_1', '2.0');
+             INSERT INTO mods_Default VALUES ('modio_1', '1.0', 1, 1);
+             INSERT INTO \"mods_My Profile\" VALUES ('modio_1', '2.0', 0, 0);",
+        ).unwrap();
+        conn
+    }
+
+    fn profile_rows(conn: &Connection) -> Vec<(String, String, String, bool, bool)> {
+        conn.prepare("SELECT profile, mod_id, selected_version, installed, enabled FROM profile_mods ORDER BY profile")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)))
+            .unwrap()
+            .collect::<Result<_>>()
+            .unwrap()
+    }
+
+    fn user_version(conn: &Connection) -> usize {
+        conn.query_row("PRAGMA user_version", [], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn baseline_schema_migrates_with_its_data() {
+        let conn = baseline_db();
+        Database::migrate(&conn).unwrap();
+        
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        assert_eq!(profile_rows(&conn), [
+            ("Default".to_string(), "modio_1".to_string(), "1.0".to_string(), true, true),
+            ("My Profile".to_string(), "modio_1".to_string(), "2.0".to_string(), false, false),
+        ]);
+        let old_tables: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name IN ('mods_Default', 'mods_My Profile')",
+            [],
+            |row| row.get(0),
+        ).unwrap();
+        assert_eq!(old_tables, 0);
+        let versions: i64 = conn.query_row("SELECT COUNT(*) FROM mod_versions", [], |row| row.get(0)).unwrap();
+        assert_eq!(versions, 2);
+    }
+
+    #[test]
+    fn migrating_twice_changes_nothing() {
+        let conn = baseline_db();
+        Database::migrate(&conn).unwrap();
+        let rows = profile_rows(&conn);
+        let history: i64 = conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0)).unwrap();
+        
+        Database::migrate(&conn).unwrap();
+        
+        assert_eq!(user_version(&conn), MIGRATIONS.len());
+        assert_eq!(profile_rows(&conn), rows);
+        let history_after: i64 = conn.query_row("SELECT COUNT(*) FROM history", [], |row| row.get(0)).unwrap();
+        assert_eq!(history_after, history);
+    }
+
+    #[test]
+    fn deleting_a_profile_clears_its_sync_and_install_rows() {
+        let mut db = memory_db();
+        db.add_mod(&entry("modio_1")).unwrap();
+        db.clone_profile("Default", "Work").unwrap();
+        db.set_current_profile("Work".to_string());
+        db.set_game_target("C:/Games/DRG".to_string());
+        db.update_mod_enabled("modio_1", true).unwrap();
+        db.set_sync_state("Work", "modio_1", "1.0|true", 1).unwrap();
+        db.set_current_profile("Default".to_string());
+        
+        db.delete_profile("Work").unwrap();
+        
+        let count = |table: &str| -> i64 {
+            db.conn.query_row(&format!("SELECT COUNT(*) FROM {} WHERE profile = 'Work'", table), [], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(count("profile_mods"), 0);
+        assert_eq!(count("profile_targets"), 0);
+        assert_eq!(count("sync_state"), 0);
+        assert!(db.get_sync_tombstones().unwrap().contains_key(&("Work".to_string(), String::new())));
+    }
+
+    #[test]
+    fn synced_row_overrides_per_install_state() {
+        let mut db = memory_db();
+        db.add_mod(&entry("modio_1")).unwrap();
+        db.set_game_target("C:/Games/DRG".to_string());
+        db.update_mod_enabled("modio_1", true).unwrap();
+        
+        db.set_profile_row("Default", "modio_1", "1.0", false, 1).unwrap();
+        
+        let mods = db.get_mods().unwrap();
+        assert!(!mods[0].enabled);
+    }
 }
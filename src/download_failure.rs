@@ -0,0 +1,101 @@
+use crate::db::ModEntry;
+use crate::source_headers;
+
+// Seconds before an automatic retry, growing with the consecutive failures of the mod's source
+pub const RETRY_DELAYS: [f64; 3] = [10.0, 60.0, 300.0];
+
+// Automatic retries of one mod before its failure is reported
+pub const MAX_RETRIES: u32 = 3;
+
+// Why a download failed, judged from the error message and its causes
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FailureKind {
+    Dns,
+    Tls,
+    NotFound,
+    Denied,
+    Disk,
+    WebPage,
+    // Timeouts, dropped connections, rate limits and server errors
+    Network,
+    Other,
+}
+
+impl FailureKind {
+    pub fn classify(error: &str) -> Self {
+        let error = error.to_lowercase();
+        let mentions = |needles: &[&str]| needles.iter().any(|needle| error.contains(needle));
+
+        if mentions(&["no space left", "not enough space", "os error 28", "os error 112", "permission denied",
+            "access is denied", "read-only file system", "os error 13", "os error 30"])
+        {
+            FailureKind::Disk
+        } else if mentions(&["dns error", "failed to lookup address", "name or service not known",
+            "no such host is known", "nodename nor servname", "temporary failure in name resolution"])
+        {
+            FailureKind::Dns
+        } else if mentions(&["certificate", "tls", "ssl", "handshake"]) {
+            FailureKind::Tls
+        } else if mentions(&["points to a web page"]) {
+            FailureKind::WebPage
+        } else if mentions(&["has no files on mod.io"]) {
+            FailureKind::NotFound
+        } else {
+            match http_status(&error) {
+                Some(404 | 410) => FailureKind::NotFound,
+                Some(401 | 403) => FailureKind::Denied,
+                Some(429 | 500..=599) => FailureKind::Network,
+                Some(_) => FailureKind::Other,
+                None if mentions(&["timed out", "connection", "error sending request", "error decoding response body",
+                    "unexpected eof", "broken pipe"]) => FailureKind::Network,
+                None => FailureKind::Other,
+            }
+        }
+    }
+
+    // Failures that may go away by themselves are retried
+    pub fn is_transient(&self) -> bool {
+        matches!(self, FailureKind::Dns | FailureKind::Network)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FailureKind::Dns => "Host not found",
+            FailureKind::Tls => "Secure connection failed",
+            FailureKind::NotFound => "File not found",
+            FailureKind::Denied => "Access denied",
+            FailureKind::Disk => "Could not write files",
+            FailureKind::WebPage => "Not a download link",
+            FailureKind::Network => "Connection problem",
+            FailureKind::Other => "Unknown problem",
+        }
+    }
+
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self {
+            FailureKind::Dns => Some("Check your internet connection, DNS settings or VPN, and that the link's host still exists."),
+            FailureKind::Tls => Some("Check that the system clock is right and that no antivirus or proxy intercepts HTTPS traffic."),
+            FailureKind::NotFound => Some("The mod or release may have been removed. Look for a new link or pick another version."),
+            FailureKind::Denied => Some("Check the mod.io token in Settings, or the download headers set for this host."),
+            FailureKind::Disk => Some("Free up disk space and make sure the manager's data folder is writable."),
+            FailureKind::WebPage => Some("Use the direct link to the file instead of the page it is shown on."),
+            FailureKind::Network => Some("The server may be overloaded or your connection unstable. Try again later."),
+            FailureKind::Other => None,
+        }
+    }
+}
+
+// Where a mod is downloaded from; failures are tracked per source
+pub fn source_of(mod_entry: &ModEntry) -> String {
+    if mod_entry.modio_id().is_some() {
+        "mod.io".to_string()
+    } else {
+        source_headers::host_of(&mod_entry.mod_link).unwrap_or_else(|| "local files".to_string())
+    }
+}
+
+// Status code of messages like "HTTP 404 Not Found"
+fn http_status(error: &str) -> Option<u16> {
+    let (_, rest) = error.split_once("http ")?;
+    rest.get(..3)?.parse().ok()
+}
@@ -0,0 +1,122 @@
+use std::sync::OnceLock;
+
+// Separators numbers are written with in the user's locale
+struct NumberStyle {
+    group: char,
+    decimal: char,
+}
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+static NUMBER_STYLE: OnceLock<NumberStyle> = OnceLock::new();
+
+fn number_style() -> &'static NumberStyle {
+    NUMBER_STYLE.get_or_init(|| style_for_locale(&locale()))
+}
+
+// LC_ALL overrides LC_NUMERIC, which overrides LANG, as in the C library
+fn locale() -> String {
+    ["LC_ALL", "LC_NUMERIC", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty() && value != "C" && value != "POSIX")
+        .unwrap_or_default()
+}
+
+fn style_for_locale(locale: &str) -> NumberStyle {
+    // "de_CH.UTF-8" and "de-CH" alike
+    let locale = locale.split(['.', '@']).next().unwrap_or_default().replace('-', "_").to_lowercase();
+    let (language, region) = locale.split_once('_').unwrap_or((&locale, ""));
+    let (group, decimal) = match (language, region) {
+        ("de" | "it", "ch") => ('\'', '.'),
+        ("de" | "nl" | "it" | "es" | "pt" | "id" | "tr" | "da" | "ro" | "el", _) => ('.', ','),
+        ("fr" | "ru" | "pl" | "cs" | "sk" | "sv" | "fi" | "nb" | "no" | "uk" | "hu" | "bg", _) => ('\u{a0}', ','),
+        _ => (',', '.'),
+    };
+    NumberStyle { group, decimal }
+}
+
+// 1234567 as "1,234,567", grouped the way the locale does
+pub fn number(value: u64) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::new();
+    for (index, digit) in digits.chars().enumerate() {
+        if index > 0 && (digits.len() - index).is_multiple_of(3) {
+            grouped.push(number_style().group);
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+// A fraction with the locale's decimal separator and grouped whole part
+pub fn decimal(value: f64, places: usize) -> String {
+    let formatted = format!("{:.*}", places, value.abs());
+    let (whole, fraction) = formatted.split_once('.').unwrap_or((&formatted, ""));
+    // No "-0.0" for values that round to zero
+    let sign = if value < 0.0 && formatted.chars().any(|c| ('1'..='9').contains(&c)) { "-" } else { "" };
+    let whole = number(whole.parse().unwrap_or(0));
+    if fraction.is_empty() {
+        format!("{}{}", sign, whole)
+    } else {
+        format!("{}{}{}{}", sign, whole, number_style().decimal, fraction)
+    }
+}
+
+pub fn percent(ratio: f64) -> String {
+    format!("{}%", decimal(ratio * 100.0, 0))
+}
+
+// Byte counts in binary units, e.g. "12.5 MiB"
+pub fn size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", number(bytes), UNITS[unit])
+    } else {
+        format!("{} {}", decimal(size, 1), UNITS[unit])
+    }
+}
+
+// Remaining time like "2m 05s", for download estimates
+pub fn duration(seconds: u64) -> String {
+    match seconds {
+        0..60 => format!("{}s", seconds),
+        60..3600 => format!("{}m {:02}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {:02}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+// Calendar date of a Unix timestamp, in the local time zone
+pub fn date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+// "3 days ago"; older than a year is shown as the date itself
+pub fn relative_date(timestamp: i64) -> String {
+    let elapsed = (chrono::Utc::now().timestamp() - timestamp).max(0);
+    let count = |amount: i64, unit: &str| {
+        if amount == 1 {
+            format!("1 {} ago", unit)
+        } else {
+            format!("{} {}s ago", number(amount as u64), unit)
+        }
+    };
+    match elapsed {
+        0..60 => "just now".to_string(),
+        60..3600 => count(elapsed / 60, "minute"),
+        3600..SECONDS_PER_DAY => count(elapsed / 3600, "hour"),
+        _ if elapsed < 2 * SECONDS_PER_DAY => "yesterday".to_string(),
+        _ if elapsed < 14 * SECONDS_PER_DAY => count(elapsed / SECONDS_PER_DAY, "day"),
+        _ if elapsed < 60 * SECONDS_PER_DAY => count(elapsed / (7 * SECONDS_PER_DAY), "week"),
+        _ if elapsed < 365 * SECONDS_PER_DAY => count(elapsed / (30 * SECONDS_PER_DAY), "month"),
+        _ => date(timestamp),
+    }
+}
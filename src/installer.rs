@@ -1,17 +1,513 @@
-use crate::db::ModEntry;
+use crate::db::{ModEntry, DATABASE_FILE};
+use crate::mod_files::{DeployedFile, ModFiles};
+use crate::mod_io::ModIoClient;
+use crate::source_headers;
+use md5::Md5;
+use reqwest::blocking::{Client, Response};
+use reqwest::StatusCode;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::{debug, info, warn};
+
+// Bytes copied between two progress reports
+const PROGRESS_STEP: u64 = 256 * 1024;
+
+// Times a download that broke off is resumed from a freshly resolved link
+const MAX_LINK_REFRESHES: u32 = 3;
+
+// Redirects followed for a direct-download link before giving up
+const MAX_REDIRECTS: usize = 10;
+
+// Error of an install whose download was stopped through the cancel flag
+pub const DOWNLOAD_CANCELLED: &str = "Download cancelled";
+
+// Part of the error of a mod.io install whose selected release was removed
+pub const VERSION_GONE: &str = "is not on mod.io anymore";
+
+// Called with the bytes downloaded so far and the total size, when known
+pub type ProgressCallback<'a> = &'a dyn Fn(u64, Option<u64>);
+
+// Attempts at a file operation while a sync client holds the file
+const LOCK_RETRY_ATTEMPTS: u32 = 5;
+const LOCK_RETRY_DELAY: std::time::Duration = std::time::Duration::from_millis(500);
+
+// Folder names used by cloud sync clients that lock files while uploading
+const CLOUD_SYNC_FOLDERS: [(&str, &str); 6] = [
+    ("onedrive", "OneDrive"),
+    ("dropbox", "Dropbox"),
+    ("google drive", "Google Drive"),
+    ("googledrive", "Google Drive"),
+    ("icloud drive", "iCloud Drive"),
+    ("iclouddrive", "iCloud Drive"),
+];
+
+// File names audio mods use to ship a sample of the replaced sounds
+const AUDIO_PREVIEW_NAMES: [&str; 3] = ["preview.ogg", "preview.wav", "preview.mp3"];
+
+// A single file inside a mod archive
+#[derive(Clone)]
+pub struct ArchiveEntry {
+    pub path: String,
+    pub size: u64,
+    pub compressed_size: u64,
+}
+
+// How a server answered a request for the rest of a partial download
+#[derive(Debug, PartialEq)]
+enum RangeReply {
+    // The bytes right after the ones on disk
+    Continues,
+    // A range starting elsewhere
+    OtherRange,
+    // The whole file, the range ignored
+    WholeFile,
+    // The range refused, e.g. because the partial file is already complete
+    NotSatisfiable,
+    Failed,
+}
+
+// A mod the game's built-in mod.io integration already manages
+#[derive(Clone)]
+pub struct InGameMod {
+    pub modio_id: u32,
+    pub name: String,
+    pub path: PathBuf,
+}
+
+// Per-version manifest stored next to the downloaded files
+#[derive(Default, Serialize, Deserialize)]
+pub struct InstallManifest {
+    pub mod_id: String,
+    pub version: String,
+    pub archive: String,
+    pub excluded_files: Vec<String>,
+    // Deployed files, their deployment time and hashes as older versions recorded them;
+    // moved to the mod_files table the first time the mod's files are looked up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deployed_files: Vec<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deployed_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub deployed_hashes: BTreeMap<PathBuf, String>,
+    // SHA-256 of the downloaded and extracted files, relative to the version directory
+    #[serde(default)]
+    pub stored_hashes: BTreeMap<String, String>,
+    // Archive paths of the paks unpacked into the extracted folder
+    #[serde(default)]
+    pub extracted_paks: Vec<String>,
+}
+
+// Backend able to list and unpack one archive format
+pub trait ArchiveExtractor {
+    // Lowercase file extensions handled by this backend
+    fn extensions(&self) -> &'static [&'static str];
+    
+    fn list(&self, archive_path: &Path) -> Result<Vec<ArchiveEntry>, String>;
+    
+    // Unpack every file below dest and return their archive paths
+    fn extract(&self, archive_path: &Path, dest: &Path) -> Result<Vec<String>, String>;
+    
+    fn read_file(&self, archive_path: &Path, name: &str) -> Result<Vec<u8>, String>;
+}
+
+// All supported archive formats
+fn extractors() -> [Box<dyn ArchiveExtractor>; 3] {
+    [Box::new(ZipExtractor), Box::new(SevenZipExtractor), Box::new(RarExtractor)]
+}
+
+pub fn extractor_for(path: &Path) -> Option<Box<dyn ArchiveExtractor>> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    extractors()
+        .into_iter()
+        .find(|extractor| extractor.extensions().contains(&ext.as_str()))
+}
+
+// Archive path with '/' separators, or None if it would escape the destination
+fn enclosed_path(name: &str) -> Option<PathBuf> {
+    let mut path = PathBuf::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => continue,
+            ".." => return None,
+            _ if part.contains(':') => return None,
+            _ => path.push(part),
+        }
+    }
+    if path.as_os_str().is_empty() { None } else { Some(path) }
+}
+
+fn normalize_name(name: &str) -> String {
+    name.replace('\\', "/")
+}
+
+fn create_parent(out_path: &Path) -> Result<(), String> {
+    if let Some(parent) = out_path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    Ok(())
+}
+
+pub struct ZipExtractor;
+
+impl ZipExtractor {
+    fn open(archive_path: &Path) -> Result<zip::ZipArchive<std::fs::File>, String> {
+        let file = std::fs::File::open(archive_path)
+            .map_err(|e| format!("Failed to open archive: {}", e))?;
+        zip::ZipArchive::new(file)
+            .map_err(|e| format!("Failed to read archive: {}", e))
+    }
+}
+
+impl ArchiveExtractor for ZipExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["zip"]
+    }
+    
+    fn list(&self, archive_path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+        let mut archive = Self::open(archive_path)?;
+        let mut entries = Vec::new();
+        for i in 0..archive.len() {
+            let file = archive.by_index(i)
+                .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+            if file.is_dir() {
+                continue;
+            }
+            entries.push(ArchiveEntry {
+                path: normalize_name(file.name()),
+                size: file.size(),
+                compressed_size: file.compressed_size(),
+            });
+        }
+        Ok(entries)
+    }
+    
+    fn extract(&self, archive_path: &Path, dest: &Path) -> Result<Vec<String>, String> {
+        let mut archive = Self::open(archive_path)?;
+        let mut extracted = Vec::new();
+        for i in 0..archive.len() {
+            let mut entry = archive.by_index(i)
+                .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+            if entry.is_dir() {
+                continue;
+            }
+            
+            // Skip entries that would escape the extraction directory
+            let Some(relative_path) = entry.enclosed_name() else {
+                warn!("Skipping unsafe archive entry: {}", entry.name());
+                continue;
+            };
+            let out_path = dest.join(relative_path);
+            create_parent(&out_path)?;
+            let mut out_file = std::fs::File::create(&out_path)
+                .map_err(|e| format!("Failed to create {:?}: {}", out_path, e))?;
+            std::io::copy(&mut entry, &mut out_file)
+                .map_err(|e| format!("Failed to extract {}: {}", entry.name(), e))?;
+            extracted.push(normalize_name(entry.name()));
+        }
+        Ok(extracted)
+    }
+    
+    fn read_file(&self, archive_path: &Path, name: &str) -> Result<Vec<u8>, String> {
+        let mut archive = Self::open(archive_path)?;
+        let mut entry = archive.by_name(name)
+            .map_err(|e| format!("Failed to find {} in archive: {}", name, e))?;
+        let mut data = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut data)
+            .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        Ok(data)
+    }
+}
+
+pub struct SevenZipExtractor;
+
+impl SevenZipExtractor {
+    fn open(archive_path: &Path) -> Result<sevenz_rust::SevenZReader<std::fs::File>, String> {
+        sevenz_rust::SevenZReader::open(archive_path, sevenz_rust::Password::empty())
+            .map_err(|e| format!("Failed to read archive: {}", e))
+    }
+}
+
+impl ArchiveExtractor for SevenZipExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["7z"]
+    }
+    
+    fn list(&self, archive_path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+        let reader = Self::open(archive_path)?;
+        Ok(reader.archive().files
+            .iter()
+            .filter(|file| !file.is_directory())
+            .map(|file| ArchiveEntry {
+                path: normalize_name(file.name()),
+                size: file.size(),
+                // Solid archives compress files together, so this is often zero
+                compressed_size: file.compressed_size,
+            })
+            .collect())
+    }
+    
+    fn extract(&self, archive_path: &Path, dest: &Path) -> Result<Vec<String>, String> {
+        let mut reader = Self::open(archive_path)?;
+        let mut extracted = Vec::new();
+        let mut failure = None;
+        reader.for_each_entries(|entry, data| {
+            if entry.is_directory() {
+                return Ok(true);
+            }
+            let Some(relative_path) = enclosed_path(entry.name()) else {
+                warn!("Skipping unsafe archive entry: {}", entry.name());
+                std::io::copy(data, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+                return Ok(true);
+            };
+            let out_path = dest.join(relative_path);
+            let result = create_parent(&out_path).and_then(|_| {
+                let mut out_file = std::fs::File::create(&out_path)
+                    .map_err(|e| format!("Failed to create {:?}: {}", out_path, e))?;
+                std::io::copy(data, &mut out_file)
+                    .map_err(|e| format!("Failed to extract {}: {}", entry.name(), e))
+            });
+            match result {
+                Ok(_) => {
+                    extracted.push(normalize_name(entry.name()));
+                    Ok(true)
+                }
+                Err(e) => {
+                    failure = Some(e);
+                    Ok(false)
+                }
+            }
+        }).map_err(|e| format!("Failed to extract archive: {}", e))?;
+        
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(extracted),
+        }
+    }
+    
+    fn read_file(&self, archive_path: &Path, name: &str) -> Result<Vec<u8>, String> {
+        let mut reader = Self::open(archive_path)?;
+        let mut found = None;
+        reader.for_each_entries(|entry, data| {
+            if normalize_name(entry.name()) != name {
+                std::io::copy(data, &mut std::io::sink()).map_err(sevenz_rust::Error::io)?;
+                return Ok(true);
+            }
+            let mut contents = Vec::new();
+            data.read_to_end(&mut contents).map_err(sevenz_rust::Error::io)?;
+            found = Some(contents);
+            Ok(false)
+        }).map_err(|e| format!("Failed to read {}: {}", name, e))?;
+        
+        found.ok_or_else(|| format!("Failed to find {} in archive", name))
+    }
+}
+
+pub struct RarExtractor;
+
+impl ArchiveExtractor for RarExtractor {
+    fn extensions(&self) -> &'static [&'static str] {
+        &["rar"]
+    }
+    
+    fn list(&self, archive_path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+        let archive = unrar::Archive::new(archive_path)
+            .open_for_listing()
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        
+        let mut entries = Vec::new();
+        for header in archive {
+            let header = header.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+            if !header.is_file() {
+                continue;
+            }
+            entries.push(ArchiveEntry {
+                path: normalize_name(&header.filename.to_string_lossy()),
+                size: header.unpacked_size,
+                // unrar does not expose per-file packed sizes
+                compressed_size: header.unpacked_size,
+            });
+        }
+        Ok(entries)
+    }
+    
+    fn extract(&self, archive_path: &Path, dest: &Path) -> Result<Vec<String>, String> {
+        let mut archive = unrar::Archive::new(archive_path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        
+        let mut extracted = Vec::new();
+        while let Some(header) = archive.read_header()
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?
+        {
+            let name = normalize_name(&header.entry().filename.to_string_lossy());
+            let relative_path = enclosed_path(&name);
+            archive = match relative_path {
+                Some(relative_path) if header.entry().is_file() => {
+                    let out_path = dest.join(relative_path);
+                    create_parent(&out_path)?;
+                    let next = header.extract_to(&out_path)
+                        .map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+                    extracted.push(name);
+                    next
+                }
+                relative_path => {
+                    if relative_path.is_none() {
+                        warn!("Skipping unsafe archive entry: {}", name);
+                    }
+                    header.skip()
+                        .map_err(|e| format!("Failed to read archive entry: {}", e))?
+                }
+            };
+        }
+        Ok(extracted)
+    }
+    
+    fn read_file(&self, archive_path: &Path, name: &str) -> Result<Vec<u8>, String> {
+        let mut archive = unrar::Archive::new(archive_path)
+            .open_for_processing()
+            .map_err(|e| format!("Failed to read archive: {}", e))?;
+        
+        while let Some(header) = archive.read_header()
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?
+        {
+            if normalize_name(&header.entry().filename.to_string_lossy()) == name {
+                let (data, _) = header.read()
+                    .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+                return Ok(data);
+            }
+            archive = header.skip()
+                .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        }
+        Err(format!("Failed to find {} in archive", name))
+    }
+}
 
 pub struct ModInstaller {
     app_data_dir: PathBuf,
+    // Doesn't follow redirects, so saved source headers only go to the host they were set for
+    client: Client,
+    // Retry file operations that fail because another process has the file open
+    retry_on_lock: bool,
+    // Fetches the files of mod.io entries
+    mod_io: ModIoClient,
+    // Set from another thread to stop a running download
+    cancel: Arc<AtomicBool>,
+}
+
+// reqwest only names the failed request at the top level; the actual reason, e.g. a DNS or
+// certificate error, is further down the chain of sources
+pub fn error_with_causes(error: &dyn std::error::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_message = cause.to_string();
+        if !message.contains(&cause_message) {
+            message.push_str(": ");
+            message.push_str(&cause_message);
+        }
+        source = cause.source();
+    }
+    message
 }
 
 impl ModInstaller {
     pub fn new(app_data_dir: PathBuf) -> Self {
-        Self { app_data_dir }
+        // No overall timeout: large sound packs can take minutes to download
+        let client = Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(30))
+            .timeout(None)
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        
+        Self {
+            app_data_dir,
+            client,
+            retry_on_lock: false,
+            mod_io: ModIoClient::new(),
+            cancel: Arc::new(AtomicBool::new(false)),
+        }
+    }
+    
+    pub fn set_cancel_flag(&mut self, cancel: Arc<AtomicBool>) {
+        self.cancel = cancel;
+    }
+    
+    // mod.io only serves files to authenticated users
+    pub fn set_mod_io_token(&mut self, token: &str) {
+        self.mod_io.set_oauth_token(token);
+    }
+    
+    pub fn app_data_dir(&self) -> &Path {
+        &self.app_data_dir
+    }
+    
+    pub fn set_retry_on_lock(&mut self, retry_on_lock: bool) {
+        self.retry_on_lock = retry_on_lock;
     }
     
-    pub fn install_mod(&self, mod_entry: &ModEntry) -> Result<(), String> {
-        println!("Installing mod: {}", mod_entry.mod_name);
+    // Name of the sync client managing the folder, if any
+    pub fn cloud_sync_provider(path: &Path) -> Option<&'static str> {
+        // OneDrive may be relocated, but it always advertises its root
+        for var in ["OneDrive", "OneDriveConsumer", "OneDriveCommercial"] {
+            if let Some(root) = std::env::var_os(var)
+                && !root.is_empty()
+                && path.starts_with(&root)
+            {
+                return Some("OneDrive");
+            }
+        }
+        
+        path.components().find_map(|component| {
+            let name = component.as_os_str().to_string_lossy().to_lowercase();
+            CLOUD_SYNC_FOLDERS
+                .iter()
+                // Business accounts are named e.g. "OneDrive - Contoso"
+                .find(|(folder, _)| name == *folder || name.starts_with(&format!("{} - ", folder)))
+                .map(|(_, provider)| *provider)
+        })
+    }
+    
+    // Sharing and lock violations, and the access denied Windows reports for a file that antivirus or
+    // the game has open. Elsewhere files aren't locked that way and a permission error is a real one
+    #[cfg(windows)]
+    fn is_lock_error(error: &std::io::Error) -> bool {
+        matches!(error.raw_os_error(), Some(32) | Some(33))
+            || error.kind() == std::io::ErrorKind::PermissionDenied
+    }
+    
+    #[cfg(not(windows))]
+    fn is_lock_error(_error: &std::io::Error) -> bool {
+        false
+    }
+    
+    fn with_lock_retry<T>(&self, mut operation: impl FnMut() -> std::io::Result<T>) -> std::io::Result<T> {
+        let mut attempt = 1;
+        loop {
+            match operation() {
+                Err(e) if self.retry_on_lock && attempt < LOCK_RETRY_ATTEMPTS && Self::is_lock_error(&e) => {
+                    warn!("File is locked, retrying ({}/{}): {}", attempt, LOCK_RETRY_ATTEMPTS, e);
+                    std::thread::sleep(LOCK_RETRY_DELAY * attempt);
+                    attempt += 1;
+                },
+                result => return result,
+            }
+        }
+    }
+    
+    pub fn version_dir(&self, mod_entry: &ModEntry) -> PathBuf {
+        self.app_data_dir
+            .join(&mod_entry.download_folder)
+            .join(&mod_entry.selected_version)
+    }
+
+    pub fn install_mod(&self, mod_entry: &ModEntry, progress: ProgressCallback) -> Result<(), String> {
+        info!("Installing mod: {}", mod_entry.mod_name);
         
         // Create the download directory if it doesn't exist
         let download_dir = self.app_data_dir.join(&mod_entry.download_folder);
@@ -27,24 +523,582 @@ impl ModInstaller {
         let is_url = mod_entry.mod_link.starts_with("http://") || 
                      mod_entry.mod_link.starts_with("https://");
         
-        if is_url {
+        if let Some(modio_id) = mod_entry.modio_id() {
+            // mod.io entries link to the web page, so go through the API
+            self.download_from_mod_io(mod_entry, modio_id, &version_dir, progress)?;
+        } else if is_url {
             // Handle URL download
-            self.download_from_url(mod_entry, &version_dir)
+            self.download_from_url(mod_entry, &version_dir, progress)?;
         } else {
             // Handle local file
-            self.copy_local_file(mod_entry, &version_dir)
+            self.copy_local_file(mod_entry, &version_dir, progress)?;
         }
+        
+        // Most mods ship as archives containing the paks
+        let mut manifest = self.manifest_or_default(mod_entry);
+        if let Some(archive_path) = self.find_archive(mod_entry) {
+            manifest.extracted_paks = self.extract_archive(mod_entry, &archive_path)?;
+        }
+        
+        // Baseline for later integrity checks
+        manifest.stored_hashes = self.hash_stored_files(mod_entry);
+        self.save_manifest(mod_entry, &manifest)
     }
     
-    fn download_from_url(&self, mod_entry: &ModEntry, version_dir: &Path) -> Result<(), String> {
-        // TODO: Implement URL download
-        println!("Would download from URL: {}", mod_entry.mod_link);
+    fn hash_file(path: &Path) -> std::io::Result<String> {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Sha256::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+    
+    // Hashes of every file of the selected version except the manifest itself
+    fn hash_stored_files(&self, mod_entry: &ModEntry) -> BTreeMap<String, String> {
+        let version_dir = self.version_dir(mod_entry);
+        let mut hashes = BTreeMap::new();
+        let mut pending = vec![version_dir.clone()];
+        while let Some(dir) = pending.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+                if path.is_dir() {
+                    pending.push(path);
+                    continue;
+                }
+                let Ok(relative) = path.strip_prefix(&version_dir) else {
+                    continue;
+                };
+                let relative = normalize_name(&relative.to_string_lossy());
+                if relative == "manifest.json" {
+                    continue;
+                }
+                match Self::hash_file(&path) {
+                    Ok(hash) => {
+                        hashes.insert(relative, hash);
+                    },
+                    Err(e) => warn!("Failed to hash {:?}: {}", path, e),
+                }
+            }
+        }
+        hashes
+    }
+    
+    // Re-hash the stored and deployed files and describe any that went missing or changed.
+    // Files without a recorded hash, e.g. from older installs, get one now.
+    pub fn verify_mod(&self, mod_entry: &ModEntry, game_path: &str) -> Result<Vec<String>, String> {
+        let Some(mut manifest) = self.load_manifest(mod_entry) else {
+            return Ok(Vec::new());
+        };
+        
+        let mut problems = Vec::new();
+        let current = self.hash_stored_files(mod_entry);
+        for (path, expected) in &manifest.stored_hashes {
+            match current.get(path) {
+                None => problems.push(format!("stored file {} is missing", path)),
+                Some(hash) if hash != expected => problems.push(format!("stored file {} was modified", path)),
+                Some(_) => {},
+            }
+        }
+        for (path, hash) in current {
+            manifest.stored_hashes.entry(path).or_insert(hash);
+        }
+        
+        self.save_manifest(mod_entry, &manifest)?;
+        
+        let (mod_files, files) = self.indexed_files(mod_entry)?;
+        if let Some((file_name, expected)) = mod_files.download_hash(&mod_entry.mod_id, &mod_entry.selected_version)? {
+            let archive = self.version_dir(mod_entry).join(&file_name);
+            match Self::md5_file(&archive) {
+                Ok(md5) if md5 != expected => problems.push(format!("downloaded archive {} is corrupted", file_name)),
+                Ok(_) => {},
+                Err(_) if !archive.exists() => problems.push(format!("downloaded archive {} is missing", file_name)),
+                Err(e) => problems.push(format!("downloaded archive {} can't be read: {}", file_name, e)),
+            }
+        }
+        for file in Self::files_in_game(&files, game_path) {
+            if !file.path.exists() {
+                problems.push(format!("deployed file {} is missing", file.path.display()));
+                continue;
+            }
+            let hash = Self::hash_file(&file.path)
+                .map_err(|e| format!("Failed to hash {:?}: {}", file.path, e))?;
+            match &file.hash {
+                Some(expected) if *expected != hash => {
+                    problems.push(format!("deployed file {} was modified", file.path.display()));
+                },
+                Some(_) => {},
+                None => mod_files.set_hash(&mod_entry.mod_id, &file.path, &hash)?,
+            }
+        }
+        Ok(problems)
+    }
+    
+    // Deployed files that went missing or no longer match their recorded hash, without changing anything
+    pub fn deployment_problems(&self, mod_entry: &ModEntry, game_path: &str) -> Vec<String> {
+        let files = match self.indexed_files(mod_entry) {
+            Ok((_, files)) => files,
+            Err(e) => {
+                warn!("{}", e);
+                return Vec::new();
+            }
+        };
         
-        // For now, just pretend it worked
+        Self::files_in_game(&files, game_path)
+            .into_iter()
+            .filter_map(|file| {
+                if !file.path.exists() {
+                    return Some(format!("{} is missing", file.path.display()));
+                }
+                let expected = file.hash.as_ref()?;
+                match Self::hash_file(&file.path) {
+                    Ok(hash) if hash == *expected => None,
+                    Ok(_) => Some(format!("{} was modified", file.path.display())),
+                    Err(e) => Some(format!("{} can't be read: {}", file.path.display(), e)),
+                }
+            })
+            .collect()
+    }
+    
+    fn extracted_dir(&self, mod_entry: &ModEntry) -> PathBuf {
+        self.version_dir(mod_entry).join("extracted")
+    }
+    
+    // Unpack the archive next to it and return the archive paths of the contained paks
+    fn extract_archive(&self, mod_entry: &ModEntry, archive_path: &Path) -> Result<Vec<String>, String> {
+        let extract_dir = self.extracted_dir(mod_entry);
+        if extract_dir.exists() {
+            std::fs::remove_dir_all(&extract_dir)
+                .map_err(|e| format!("Failed to clear extracted files: {}", e))?;
+        }
+        std::fs::create_dir_all(&extract_dir)
+            .map_err(|e| format!("Failed to create extraction directory: {}", e))?;
+        
+        let extractor = extractor_for(archive_path)
+            .ok_or_else(|| format!("Unsupported archive: {:?}", archive_path))?;
+        let paks: Vec<String> = extractor.extract(archive_path, &extract_dir)?
+            .into_iter()
+            .filter(|name| name.to_lowercase().ends_with(".pak"))
+            .collect();
+        
+        info!("Extracted {} paks from {:?}", paks.len(), archive_path);
+        Ok(paks)
+    }
+    
+    // Copy in chunks so the caller can follow the progress; `copied` keeps counting across resumes
+    fn copy_with_progress(
+        reader: &mut dyn std::io::Read,
+        file: &mut std::fs::File,
+        copied: &mut u64,
+        total: Option<u64>,
+        progress: ProgressCallback,
+        cancel: &AtomicBool
+    ) -> std::io::Result<()> {
+        let mut buffer = vec![0; 64 * 1024];
+        let mut reported = *copied;
+        
+        progress(*copied, total);
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(std::io::Error::new(std::io::ErrorKind::Interrupted, DOWNLOAD_CANCELLED));
+            }
+            let read = reader.read(&mut buffer)?;
+            if read == 0 {
+                break;
+            }
+            std::io::Write::write_all(file, &buffer[..read])?;
+            *copied += read as u64;
+            if *copied - reported >= PROGRESS_STEP {
+                progress(*copied, total);
+                reported = *copied;
+            }
+        }
+        progress(*copied, total);
         Ok(())
     }
     
-    fn copy_local_file(&self, mod_entry: &ModEntry, version_dir: &Path) -> Result<(), String> {
+    // Where a download is written until it is complete
+    fn partial_path(dest_path: &Path) -> PathBuf {
+        let mut file_name = dest_path.file_name().unwrap_or_default().to_os_string();
+        file_name.push(".part");
+        dest_path.with_file_name(file_name)
+    }
+    
+    // Bytes an earlier attempt left in the partial file, which the first request asks to skip
+    fn partial_len(dest_path: &Path) -> u64 {
+        std::fs::metadata(Self::partial_path(dest_path)).map(|metadata| metadata.len()).unwrap_or(0)
+    }
+    
+    // Final path of a partial download in this folder, for links that only name the file in the response
+    fn partial_download(dir: &Path) -> Option<PathBuf> {
+        std::fs::read_dir(dir).ok()?
+            .filter_map(|entry| entry.ok())
+            .find_map(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.strip_suffix(".part").map(|dest_name| dir.join(dest_name))
+            })
+    }
+    
+    // Start offset and full size from the Content-Range of a 206, e.g. "bytes 100-999/1000"
+    fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+        let (range, size) = value.strip_prefix("bytes ")?.split_once('/')?;
+        let start = range.split_once('-')?.0.trim().parse().ok()?;
+        Some((start, size.trim().parse().ok()))
+    }
+    
+    fn content_range(response: &Response) -> Option<(u64, Option<u64>)> {
+        Self::parse_content_range(response.headers().get(reqwest::header::CONTENT_RANGE)?.to_str().ok()?)
+    }
+    
+    // How a server answered a request for the bytes after the `copied` already on disk
+    fn range_reply(status: StatusCode, content_range: Option<&str>, copied: u64) -> RangeReply {
+        if status == StatusCode::PARTIAL_CONTENT {
+            return match content_range.and_then(Self::parse_content_range) {
+                Some((start, _)) if start == copied => RangeReply::Continues,
+                _ => RangeReply::OtherRange,
+            };
+        }
+        if status == StatusCode::RANGE_NOT_SATISFIABLE {
+            RangeReply::NotSatisfiable
+        } else if status.is_success() {
+            RangeReply::WholeFile
+        } else {
+            RangeReply::Failed
+        }
+    }
+    
+    fn reply_to_range(response: &Response, copied: u64) -> RangeReply {
+        let content_range = response.headers()
+            .get(reqwest::header::CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok());
+        Self::range_reply(response.status(), content_range, copied)
+    }
+    
+    // Ask for the rest of a partial file; when it is already complete the server refuses the range
+    fn open_download(offset: u64, request: &dyn Fn(u64) -> Result<Response, String>) -> Result<Response, String> {
+        let response = request(offset)?;
+        if offset > 0 && Self::reply_to_range(&response, offset) == RangeReply::NotSatisfiable {
+            return request(0);
+        }
+        Ok(response)
+    }
+    
+    // Stream a download to disk. Signed links (mod.io binary URLs among them) expire, so when the
+    // connection breaks off the source is resolved again and the download resumes where it stopped.
+    // The data goes to a .part file that is kept on failure, so a later attempt picks up from there too.
+    // `response` must have been requested from the partial file's length on
+    fn download_resumable(
+        mut response: Response,
+        dest_path: &Path,
+        mut total: Option<u64>,
+        progress: ProgressCallback,
+        cancel: &AtomicBool,
+        resolve: &dyn Fn(u64) -> Result<Response, String>
+    ) -> Result<(), String> {
+        let part_path = Self::partial_path(dest_path);
+        let mut copied = Self::partial_len(dest_path);
+        if copied > 0 {
+            match Self::reply_to_range(&response, copied) {
+                RangeReply::Continues => info!("Resuming download of {:?} at {} bytes", dest_path, copied),
+                // No range support, or the partial file doesn't fit the source any more
+                reply => {
+                    copied = 0;
+                    if reply == RangeReply::OtherRange {
+                        response = resolve(0)?;
+                        if !response.status().is_success() {
+                            return Err(format!("HTTP {}", response.status()));
+                        }
+                    }
+                },
+            }
+        }
+        // A 206 only counts the bytes it sends
+        if let Some((_, Some(size))) = Self::content_range(&response) {
+            total = Some(size);
+        }
+        let mut file = if copied > 0 {
+            std::fs::OpenOptions::new().append(true).open(&part_path)
+        } else {
+            std::fs::File::create(&part_path)
+        }
+        .map_err(|e| e.to_string())?;
+        let mut refreshes = 0;
+        
+        loop {
+            let mut error = match Self::copy_with_progress(&mut response, &mut file, &mut copied, total, progress, cancel) {
+                Ok(()) => {
+                    drop(file);
+                    return std::fs::rename(&part_path, dest_path)
+                        .map_err(|e| format!("Failed to move the finished download into place: {}", e));
+                },
+                // The partial file stays, so starting the install again resumes it
+                Err(_) if cancel.load(Ordering::Relaxed) => return Err(DOWNLOAD_CANCELLED.to_string()),
+                Err(e) => e.to_string(),
+            };
+            
+            // Keep resolving while the link is expired or the connection fails
+            loop {
+                if refreshes == MAX_LINK_REFRESHES {
+                    return Err(error);
+                }
+                refreshes += 1;
+                warn!("Download interrupted after {} bytes ({}), resolving the link again", copied, error);
+                
+                let resumed = match resolve(copied) {
+                    Ok(resumed) => resumed,
+                    Err(e) => {
+                        error = e;
+                        continue;
+                    },
+                };
+                match Self::reply_to_range(&resumed, copied) {
+                    RangeReply::Continues => {
+                        response = resumed;
+                        break;
+                    },
+                    RangeReply::OtherRange => {
+                        // Not where the file stopped, so the next attempt fetches all of it
+                        file.set_len(0)
+                            .and_then(|()| std::io::Seek::rewind(&mut file))
+                            .map_err(|e| e.to_string())?;
+                        copied = 0;
+                        error = "the server sent a different range than requested".to_string();
+                    },
+                    RangeReply::WholeFile => {
+                        // The server ignored the range and sends the whole file again
+                        file.set_len(0)
+                            .and_then(|()| std::io::Seek::rewind(&mut file))
+                            .map_err(|e| e.to_string())?;
+                        copied = 0;
+                        response = resumed;
+                        break;
+                    },
+                    _ if matches!(resumed.status(), StatusCode::FORBIDDEN | StatusCode::GONE) => {
+                        error = format!("download link expired (HTTP {})", resumed.status());
+                    },
+                    _ => return Err(format!("HTTP {}", resumed.status())),
+                }
+            }
+        }
+    }
+    
+    fn download_from_mod_io(
+        &self,
+        mod_entry: &ModEntry,
+        modio_id: u32,
+        version_dir: &Path,
+        progress: ProgressCallback
+    ) -> Result<(), String> {
+        let files = self.mod_io.get_mod_files(modio_id)
+            .map_err(|e| format!("Failed to list mod files: {}", error_with_causes(&*e)))?;
+        
+        // Another release would end up in the selected version's folder, so a missing one is an error
+        if files.is_empty() {
+            return Err(format!("{} has no files on mod.io", mod_entry.mod_name));
+        }
+        let file = files.iter()
+            .find(|file| file.version_label() == mod_entry.selected_version)
+            .ok_or_else(|| format!(
+                "Version {} of {} {}, pick another version",
+                mod_entry.selected_version, mod_entry.mod_name, VERSION_GONE
+            ))?;
+        
+        // Every resolve fetches the file entry again, which carries a fresh binary URL
+        let resolve = |offset| {
+            self.mod_io.download_modfile(modio_id, file.id, offset).map_err(|e| error_with_causes(&*e))
+        };
+        let file_name = Self::safe_file_name(&file.filename);
+        let dest_path = version_dir.join(&file_name);
+        
+        let response = Self::open_download(Self::partial_len(&dest_path), &resolve)
+            .map_err(|e| format!("Failed to download mod: {}", e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Failed to download mod: HTTP {}", status));
+        }
+        
+        let total = Some(file.filesize);
+        Self::download_resumable(response, &dest_path, total, progress, &self.cancel, &resolve)
+            .map_err(|e| format!("Failed to download mod: {}", e))?;
+        
+        debug!("Downloaded mod file to: {:?}", dest_path);
+        
+        // A truncated or tampered archive must not get extracted and deployed
+        let Some(expected) = file.filehash.as_ref().map(|filehash| filehash.md5.to_lowercase()) else {
+            warn!("mod.io lists no hash for {}, skipping verification", file.filename);
+            return Ok(());
+        };
+        let md5 = Self::md5_file(&dest_path)
+            .map_err(|e| format!("Failed to hash {:?}: {}", dest_path, e))?;
+        if md5 != expected {
+            let _ = std::fs::remove_file(&dest_path);
+            return Err(format!(
+                "Downloaded file {} is corrupted: its MD5 is {} but mod.io lists {}. Try installing again",
+                file.filename, md5, expected
+            ));
+        }
+        self.mod_files()?.record_download_hash(&mod_entry.mod_id, &mod_entry.selected_version, &file_name, &md5)
+    }
+    
+    fn md5_file(path: &Path) -> std::io::Result<String> {
+        let mut file = std::fs::File::open(path)?;
+        let mut hasher = Md5::new();
+        std::io::copy(&mut file, &mut hasher)?;
+        Ok(format!("{:x}", hasher.finalize()))
+    }
+    
+    fn download_from_url(&self, mod_entry: &ModEntry, version_dir: &Path, progress: ProgressCallback) -> Result<(), String> {
+        debug!("Downloading from URL: {}", mod_entry.mod_link);
+        
+        // Requesting the original link again follows its redirects to a freshly signed one
+        let resolve = |offset| self.send_direct(&mod_entry.mod_link, offset);
+        
+        let partial = Self::partial_download(version_dir);
+        let offset = partial.as_deref().map_or(0, Self::partial_len);
+        let response = Self::open_download(offset, &resolve)
+            .map_err(|e| format!("Failed to download mod: {}", e))?;
+        
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!("Failed to download mod: HTTP {}", status));
+        }
+        
+        // A web page means the link isn't a direct download
+        let is_html = response.headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.starts_with("text/html"))
+            .unwrap_or(false);
+        if is_html {
+            return Err(format!("URL points to a web page, not a mod file: {}", mod_entry.mod_link));
+        }
+        
+        // A resumed response may not name the file again, so the partial file's name is kept
+        let dest_path = match partial {
+            Some(partial) if Self::reply_to_range(&response, offset) == RangeReply::Continues => partial,
+            partial => {
+                let dest_path = version_dir.join(Self::response_file_name(&response));
+                if let Some(stale) = partial.filter(|stale| *stale != dest_path) {
+                    let _ = std::fs::remove_file(Self::partial_path(&stale));
+                }
+                dest_path
+            },
+        };
+        
+        // Stream the body to disk instead of buffering it in memory
+        let total = response.content_length();
+        Self::download_resumable(response, &dest_path, total, progress, &self.cancel, &resolve)
+            .map_err(|e| format!("Failed to download mod: {}", e))?;
+        
+        debug!("Downloaded mod file to: {:?}", dest_path);
+        Ok(())
+    }
+    
+    // Some hosts want an auth header or cookie. Redirects are followed here, sending those
+    // headers only while the request stays on the link's own host
+    fn send_direct(&self, link: &str, offset: u64) -> Result<Response, String> {
+        let headers = source_headers::for_url(link);
+        let original = reqwest::Url::parse(link).map_err(|e| format!("Invalid URL {}: {}", link, e))?;
+        let mut url = original.clone();
+        let mut send_headers = true;
+        
+        for _ in 0..=MAX_REDIRECTS {
+            let mut request = self.client.get(url.clone());
+            send_headers = send_headers && Self::keeps_source_headers(&original, &url);
+            if send_headers {
+                for (name, value) in &headers {
+                    request = request.header(name.as_str(), value.as_str());
+                }
+            }
+            if offset > 0 {
+                request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+            }
+            let response = request.send().map_err(|e| error_with_causes(&e))?;
+            
+            let location = response.headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|value| value.to_str().ok());
+            match location {
+                Some(location) if response.status().is_redirection() => {
+                    url = url.join(location).map_err(|e| format!("Invalid redirect to {}: {}", location, e))?;
+                    debug!("Redirected to {}", url);
+                },
+                _ => return Ok(response),
+            }
+        }
+        Err(format!("Too many redirects for {}", link))
+    }
+    
+    // Saved headers only go to the origin of the link they were saved for. Once a redirect leaves it
+    // they are dropped for good, even if a later hop comes back
+    fn keeps_source_headers(original: &reqwest::Url, url: &reqwest::Url) -> bool {
+        url.origin() == original.origin()
+    }
+    
+    // File name from Content-Disposition, falling back to the final URL after redirects
+    fn response_file_name(response: &Response) -> String {
+        let from_header = response.headers()
+            .get(reqwest::header::CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(Self::parse_content_disposition);
+        
+        let from_url = || {
+            response.url()
+                .path_segments()
+                .and_then(|mut segments| segments.next_back())
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.to_string())
+        };
+        
+        let name = from_header.or_else(from_url).unwrap_or_else(|| "download".to_string());
+        Self::safe_file_name(&name)
+    }
+    
+    // Never let the server choose a path outside the version directory
+    fn safe_file_name(name: &str) -> String {
+        Path::new(&name.replace('\\', "/"))
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .filter(|name| name != "..")
+            .unwrap_or_else(|| "download".to_string())
+    }
+    
+    fn parse_content_disposition(header: &str) -> Option<String> {
+        let mut plain_name = None;
+        for part in header.split(';').map(|part| part.trim()) {
+            if let Some(value) = part.strip_prefix("filename*=") {
+                // RFC 5987 form: charset'language'percent-encoded-name
+                let encoded = value.rsplit('\'').next().unwrap_or(value);
+                return Some(Self::percent_decode(encoded.trim_matches('"')));
+            } else if let Some(value) = part.strip_prefix("filename=") {
+                plain_name = Some(value.trim_matches('"').to_string());
+            }
+        }
+        plain_name.filter(|name| !name.is_empty())
+    }
+    
+    fn percent_decode(value: &str) -> String {
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'%'
+                && i + 2 < bytes.len()
+                && let Some(byte) = std::str::from_utf8(&bytes[i + 1..i + 3])
+                    .ok()
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                decoded.push(byte);
+                i += 3;
+            } else {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+        String::from_utf8_lossy(&decoded).to_string()
+    }
+    
+    fn copy_local_file(&self, mod_entry: &ModEntry, version_dir: &Path, progress: ProgressCallback) -> Result<(), String> {
         let source_path = std::path::Path::new(&mod_entry.mod_link);
         if !source_path.exists() {
             return Err(format!("Source file does not exist: {}", mod_entry.mod_link));
@@ -55,10 +1109,643 @@ impl ModInstaller {
         
         let dest_path = version_dir.join(file_name);
         
-        std::fs::copy(source_path, &dest_path)
+        let mut source = std::fs::File::open(source_path)
+            .map_err(|e| format!("Failed to open mod file: {}", e))?;
+        let total = source.metadata().ok().map(|metadata| metadata.len());
+        std::fs::File::create(&dest_path)
+            .and_then(|mut file| Self::copy_with_progress(&mut source, &mut file, &mut 0, total, progress, &self.cancel))
             .map_err(|e| format!("Failed to copy mod file: {}", e))?;
         
-        println!("Copied mod file to: {:?}", dest_path);
+        debug!("Copied mod file to: {:?}", dest_path);
         Ok(())
     }
-}
\ No newline at end of file
+    
+    // Find the first archive among the downloaded files of the selected version
+    pub fn find_archive(&self, mod_entry: &ModEntry) -> Option<PathBuf> {
+        let entries = std::fs::read_dir(self.version_dir(mod_entry)).ok()?;
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|path| Self::is_archive(path))
+    }
+    
+    pub fn is_archive(path: &Path) -> bool {
+        extractor_for(path).is_some()
+    }
+    
+    // List the files contained in an archive without extracting it
+    pub fn list_archive(archive_path: &Path) -> Result<Vec<ArchiveEntry>, String> {
+        let extractor = extractor_for(archive_path)
+            .ok_or_else(|| format!("Unsupported archive: {:?}", archive_path))?;
+        let mut entries = extractor.list(archive_path)?;
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(entries)
+    }
+    
+    pub fn load_manifest(&self, mod_entry: &ModEntry) -> Option<InstallManifest> {
+        let manifest_path = self.version_dir(mod_entry).join("manifest.json");
+        let contents = std::fs::read_to_string(manifest_path).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+    
+    // Existing manifest of the selected version, or a fresh one
+    pub fn manifest_or_default(&self, mod_entry: &ModEntry) -> InstallManifest {
+        self.load_manifest(mod_entry).unwrap_or_else(|| InstallManifest {
+            mod_id: mod_entry.mod_id.clone(),
+            version: mod_entry.selected_version.clone(),
+            archive: self.find_archive(mod_entry)
+                .and_then(|path| path.file_name().map(|name| name.to_string_lossy().to_string()))
+                .unwrap_or_default(),
+            excluded_files: Vec::new(),
+            deployed_files: Vec::new(),
+            deployed_at: None,
+            deployed_hashes: BTreeMap::new(),
+            stored_hashes: BTreeMap::new(),
+            extracted_paks: Vec::new(),
+        })
+    }
+    
+    pub fn save_manifest(&self, mod_entry: &ModEntry, manifest: &InstallManifest) -> Result<(), String> {
+        let version_dir = self.version_dir(mod_entry);
+        std::fs::create_dir_all(&version_dir)
+            .map_err(|e| format!("Failed to create version directory: {}", e))?;
+        
+        let contents = serde_json::to_string_pretty(manifest)
+            .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+        std::fs::write(version_dir.join("manifest.json"), contents)
+            .map_err(|e| format!("Failed to write manifest: {}", e))?;
+        
+        debug!("Saved manifest for {} v{}", manifest.mod_id, manifest.version);
+        Ok(())
+    }
+    
+    // Alternative paks shipped with a mod; more than one means the user must pick a variant
+    pub fn detect_variants(&self, mod_entry: &ModEntry) -> Vec<String> {
+        let is_pak = |path: &str| path.to_lowercase().ends_with(".pak");
+        
+        let mut variants: Vec<String> = if let Some(archive_path) = self.find_archive(mod_entry) {
+            Self::list_archive(&archive_path)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|entry| entry.path)
+                .filter(|path| is_pak(path))
+                .collect()
+        } else {
+            std::fs::read_dir(self.version_dir(mod_entry))
+                .map(|entries| {
+                    entries
+                        .filter_map(|entry| entry.ok())
+                        .map(|entry| entry.file_name().to_string_lossy().to_string())
+                        .filter(|name| is_pak(name))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        
+        variants.sort();
+        variants
+    }
+    
+    // Exclude every variant except the chosen one so only it gets deployed
+    pub fn apply_variant(&self, mod_entry: &ModEntry, variant: &str) -> Result<(), String> {
+        let mut manifest = self.manifest_or_default(mod_entry);
+        
+        manifest.excluded_files.retain(|file| !mod_entry.variants.contains(file));
+        manifest.excluded_files.extend(
+            mod_entry.variants
+                .iter()
+                .filter(|other| other.as_str() != variant)
+                .cloned()
+        );
+        manifest.excluded_files.sort();
+        
+        info!("Switching {} to variant {}", mod_entry.mod_name, variant);
+        self.save_manifest(mod_entry, &manifest)
+    }
+    
+    fn is_audio_preview(path: &str) -> bool {
+        let file_name = path.rsplit('/').next().unwrap_or(path).to_lowercase();
+        AUDIO_PREVIEW_NAMES.contains(&file_name.as_str())
+    }
+    
+    // Locate a bundled sound preview, either inside the archive or among the downloaded files
+    pub fn find_audio_preview(&self, mod_entry: &ModEntry) -> Option<String> {
+        if let Some(archive_path) = self.find_archive(mod_entry) {
+            return Self::list_archive(&archive_path)
+                .ok()?
+                .into_iter()
+                .map(|entry| entry.path)
+                .find(|path| Self::is_audio_preview(path));
+        }
+        
+        std::fs::read_dir(self.version_dir(mod_entry))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .find(|name| Self::is_audio_preview(name))
+    }
+    
+    #[cfg(feature = "audio-preview")]
+    pub fn read_audio_preview(&self, mod_entry: &ModEntry, preview: &str) -> Result<Vec<u8>, String> {
+        let Some(archive_path) = self.find_archive(mod_entry) else {
+            return std::fs::read(self.version_dir(mod_entry).join(preview))
+                .map_err(|e| format!("Failed to read audio preview: {}", e));
+        };
+        
+        let extractor = extractor_for(&archive_path)
+            .ok_or_else(|| format!("Unsupported archive: {:?}", archive_path))?;
+        extractor.read_file(&archive_path, preview)
+    }
+    
+    // Concatenated readme/description text files shipped with the mod
+    pub fn read_bundled_text(&self, mod_entry: &ModEntry) -> String {
+        let is_text = |path: &str| {
+            let path = path.to_lowercase();
+            path.ends_with(".txt") || path.ends_with(".md")
+        };
+        
+        let mut text = String::new();
+        if let Some(archive_path) = self.find_archive(mod_entry) {
+            let Some(extractor) = extractor_for(&archive_path) else {
+                return text;
+            };
+            let Ok(entries) = extractor.list(&archive_path) else {
+                return text;
+            };
+            for entry in entries.iter().filter(|entry| is_text(&entry.path)) {
+                if let Ok(data) = extractor.read_file(&archive_path, &entry.path) {
+                    text.push_str(&String::from_utf8_lossy(&data));
+                    text.push('\n');
+                }
+            }
+        } else if let Ok(entries) = std::fs::read_dir(self.version_dir(mod_entry)) {
+            for path in entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+                if is_text(&path.to_string_lossy()) && let Ok(contents) = std::fs::read_to_string(&path) {
+                    text.push_str(&contents);
+                    text.push('\n');
+                }
+            }
+        }
+        text
+    }
+    
+    // Folders where DRG's mod.io integration keeps its mods, relative to the game root
+    fn modio_dirs(game_path: &str) -> Vec<PathBuf> {
+        let game_root = Self::game_root(game_path);
+        
+        vec![
+            game_root.join(".modio"),
+            game_root.join("FSD").join(".modio"),
+            game_root.join("FSD").join("Mods").join(".modio"),
+        ]
+    }
+    
+    pub fn find_ingame_mods(game_path: &str) -> Vec<InGameMod> {
+        if game_path.is_empty() {
+            return Vec::new();
+        }
+        
+        let mut mods: Vec<InGameMod> = Vec::new();
+        for modio_dir in Self::modio_dirs(game_path) {
+            // Mod names are only known from the plugin's state file
+            let names: std::collections::HashMap<u32, String> = std::fs::read_to_string(
+                modio_dir.join("metadata").join("state.json")
+            )
+                .ok()
+                .and_then(|contents| serde_json::from_str::<serde_json::Value>(&contents).ok())
+                .and_then(|state| state.get("Mods").and_then(|m| m.as_array()).cloned())
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|entry| {
+                    let id = entry.get("ID")?.as_u64()? as u32;
+                    let name = entry.get("Profile")?.get("name")?.as_str()?.to_string();
+                    Some((id, name))
+                })
+                .collect();
+            
+            let Ok(entries) = std::fs::read_dir(modio_dir.join("mods")) else {
+                continue;
+            };
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let path = entry.path();
+                let Some(modio_id) = entry.file_name().to_str().and_then(|name| name.parse::<u32>().ok()) else {
+                    continue;
+                };
+                if !path.is_dir() || mods.iter().any(|m| m.modio_id == modio_id) {
+                    continue;
+                }
+                
+                mods.push(InGameMod {
+                    modio_id,
+                    name: names.get(&modio_id).cloned().unwrap_or_else(|| format!("mod.io mod {}", modio_id)),
+                    path,
+                });
+            }
+        }
+        
+        mods.sort_by(|a, b| a.name.cmp(&b.name));
+        mods
+    }
+    
+    // Rename when possible; cold storage is often another drive, where only copying works
+    pub fn move_dir(from: &Path, to: &Path) -> Result<(), String> {
+        if let Some(parent) = to.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| format!("Failed to create {:?}: {}", parent, e))?;
+        }
+        if std::fs::rename(from, to).is_ok() {
+            return Ok(());
+        }
+        
+        Self::copy_dir(from, to)?;
+        std::fs::remove_dir_all(from).map_err(|e| format!("Failed to remove {:?}: {}", from, e))
+    }
+    
+    fn copy_dir(from: &Path, to: &Path) -> Result<(), String> {
+        std::fs::create_dir_all(to).map_err(|e| format!("Failed to create {:?}: {}", to, e))?;
+        let entries = std::fs::read_dir(from).map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+        for entry in entries {
+            let entry = entry.map_err(|e| format!("Failed to read {:?}: {}", from, e))?;
+            let path = entry.path();
+            let dest = to.join(entry.file_name());
+            if path.is_dir() {
+                Self::copy_dir(&path, &dest)?;
+            } else {
+                std::fs::copy(&path, &dest).map_err(|e| format!("Failed to copy {:?}: {}", path, e))?;
+            }
+        }
+        Ok(())
+    }
+    
+    // Game root from the configured path, which usually points at FSD.exe
+    pub fn game_root(game_path: &str) -> PathBuf {
+        let game_path = Path::new(game_path);
+        if game_path.is_file() {
+            game_path.parent().unwrap_or(game_path).to_path_buf()
+        } else {
+            game_path.to_path_buf()
+        }
+    }
+    
+    pub fn paks_dir(game_path: &str) -> Option<PathBuf> {
+        if game_path.is_empty() {
+            return None;
+        }
+        
+        let paks_dir = Self::game_root(game_path).join("FSD").join("Content").join("Paks");
+        paks_dir.is_dir().then_some(paks_dir)
+    }
+    
+    // Folder the game loads loose mod paks from
+    pub fn mods_dir(game_path: &str) -> Option<PathBuf> {
+        Self::paks_dir(game_path).map(|paks_dir| paks_dir.join("~mods"))
+    }
+    
+    // Where a mod's paks go: mod.io mods live in an ID-named folder under FSD/Mods
+    // like the game's own mod.io integration expects, everything else in ~mods
+    pub fn deploy_dir(mod_entry: &ModEntry, game_path: &str) -> Option<PathBuf> {
+        match mod_entry.modio_id() {
+            Some(modio_id) => {
+                let fsd_dir = Self::game_root(game_path).join("FSD");
+                (!game_path.is_empty() && fsd_dir.is_dir())
+                    .then(|| fsd_dir.join("Mods").join(modio_id.to_string()))
+            },
+            None => Self::mods_dir(game_path),
+        }
+    }
+    
+    // Paks of the selected version that get deployed, where they are stored
+    pub fn pak_paths(&self, mod_entry: &ModEntry) -> Vec<PathBuf> {
+        let manifest = self.manifest_or_default(mod_entry);
+        self.deployable_paks(mod_entry, &manifest)
+    }
+    
+    // Paks of the selected version that the user didn't exclude
+    fn deployable_paks(&self, mod_entry: &ModEntry, manifest: &InstallManifest) -> Vec<PathBuf> {
+        // Paks unpacked from an archive are addressed by their archive path
+        if !manifest.extracted_paks.is_empty() {
+            let extract_dir = self.extracted_dir(mod_entry);
+            return manifest.extracted_paks
+                .iter()
+                .filter(|pak| !manifest.excluded_files.contains(pak))
+                .map(|pak| extract_dir.join(pak))
+                .collect();
+        }
+        
+        let Ok(entries) = std::fs::read_dir(self.version_dir(mod_entry)) else {
+            return Vec::new();
+        };
+        
+        let mut paks: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                name.to_lowercase().ends_with(".pak") && !manifest.excluded_files.contains(&name)
+            })
+            .collect();
+        paks.sort();
+        paks
+    }
+    
+    // Copy the mod's paks into the game's ~mods folder
+    pub fn deploy_mod(&self, mod_entry: &ModEntry, game_path: &str) -> Result<(), String> {
+        let mods_dir = Self::deploy_dir(mod_entry, game_path)
+            .ok_or_else(|| "Game folder not found, set the game path in Settings".to_string())?;
+        std::fs::create_dir_all(&mods_dir)
+            .map_err(|e| format!("Failed to create mods folder: {}", e))?;
+        
+        // Paks outside mod.io's per-mod folders all land in ~mods, so two mods can ship the same file name
+        let mod_files = self.mod_files()?;
+        for pak in self.pak_paths(mod_entry) {
+            let Some(file_name) = pak.file_name() else {
+                continue;
+            };
+            let dest_path = mods_dir.join(file_name);
+            if !dest_path.exists() {
+                continue;
+            }
+            if let Some(owner) = mod_files.other_owner(&mod_entry.mod_id, &dest_path)? {
+                return Err(format!(
+                    "{} would overwrite {}, which {} deployed; remove or disable that mod first",
+                    mod_entry.mod_name,
+                    file_name.to_string_lossy(),
+                    owner
+                ));
+            }
+        }
+        
+        // Start from a clean state so switching variants leaves no stale paks behind
+        self.undeploy_mod(mod_entry, game_path)?;
+        
+        let manifest = self.manifest_or_default(mod_entry);
+        let paks = self.deployable_paks(mod_entry, &manifest);
+        if paks.is_empty() {
+            return Err(format!("No .pak files to deploy for {}", mod_entry.mod_name));
+        }
+        
+        let deployed_at = chrono::Utc::now().timestamp();
+        for pak in paks {
+            let Some(file_name) = pak.file_name() else {
+                continue;
+            };
+            let dest_path = mods_dir.join(file_name);
+            self.with_lock_retry(|| std::fs::copy(&pak, &dest_path))
+                .map_err(|e| format!("Failed to deploy {}: {}", file_name.to_string_lossy(), e))?;
+            debug!("Deployed {:?}", dest_path);
+            let hash = Self::hash_file(&dest_path)
+                .map_err(|e| warn!("Failed to hash {:?}: {}", dest_path, e))
+                .ok();
+            mod_files.record(&mod_entry.mod_id, &DeployedFile { path: dest_path, hash, deployed_at })?;
+        }
+        
+        self.save_manifest(mod_entry, &manifest)
+    }
+    
+    // Game-relative paths the mod's paks are copied to, e.g. FSD/Content/Paks/~mods/foo.pak
+    pub fn deploy_targets(&self, mod_entry: &ModEntry) -> Vec<String> {
+        let folder = match mod_entry.modio_id() {
+            Some(modio_id) => format!("FSD/Mods/{}", modio_id),
+            None => "FSD/Content/Paks/~mods".to_string(),
+        };
+        
+        let manifest = self.manifest_or_default(mod_entry);
+        self.deployable_paks(mod_entry, &manifest)
+            .iter()
+            .filter_map(|pak| pak.file_name())
+            .map(|name| format!("{}/{}", folder, name.to_string_lossy()))
+            .collect()
+    }
+    
+    fn mod_files(&self) -> Result<ModFiles, String> {
+        ModFiles::open(&self.app_data_dir.join(DATABASE_FILE))
+    }
+    
+    // The mod's entries in the file index, after taking over any its manifest still lists
+    fn indexed_files(&self, mod_entry: &ModEntry) -> Result<(ModFiles, Vec<DeployedFile>), String> {
+        let mod_files = self.mod_files()?;
+        if let Some(mut manifest) = self.load_manifest(mod_entry)
+            && !manifest.deployed_files.is_empty()
+        {
+            let deployed_at = manifest.deployed_at.take().unwrap_or_default();
+            for path in std::mem::take(&mut manifest.deployed_files) {
+                let hash = manifest.deployed_hashes.remove(&path);
+                mod_files.record(&mod_entry.mod_id, &DeployedFile { path, hash, deployed_at })?;
+            }
+            manifest.deployed_hashes.clear();
+            self.save_manifest(mod_entry, &manifest)?;
+        }
+        let files = mod_files.for_mod(&mod_entry.mod_id)?;
+        Ok((mod_files, files))
+    }
+    
+    // Files this mod has deployed into the given game install
+    pub fn deployed_files(&self, mod_entry: &ModEntry, game_path: &str) -> Vec<PathBuf> {
+        match self.indexed_files(mod_entry) {
+            Ok((_, files)) => Self::files_in_game(&files, game_path)
+                .into_iter()
+                .map(|file| file.path.clone())
+                .collect(),
+            Err(e) => {
+                warn!("{}", e);
+                Vec::new()
+            }
+        }
+    }
+    
+    // Unix time of the mod's last deployment; later deployments overwrite shared files
+    pub fn deployed_at(&self, mod_entry: &ModEntry) -> Option<i64> {
+        let (_, files) = self.indexed_files(mod_entry).ok()?;
+        files.iter().map(|file| file.deployed_at).max()
+    }
+    
+    // Game installs are told apart by the paths, so each keeps its own deployment
+    fn files_in_game<'a>(files: &'a [DeployedFile], game_path: &str) -> Vec<&'a DeployedFile> {
+        if game_path.is_empty() {
+            return Vec::new();
+        }
+        let game_root = Self::game_root(game_path);
+        files
+            .iter()
+            .filter(|file| file.path.starts_with(&game_root))
+            .collect()
+    }
+    
+    // Remove the files this mod copied into the given game install; other installs keep theirs
+    pub fn undeploy_mod(&self, mod_entry: &ModEntry, game_path: &str) -> Result<(), String> {
+        if game_path.is_empty() {
+            return Ok(());
+        }
+        let game_root = Self::game_root(game_path);
+        self.remove_deployed(mod_entry, |path| path.starts_with(&game_root))
+    }
+    
+    // Remove every file this mod copied into any game install, e.g. before its files are deleted
+    pub fn undeploy_everywhere(&self, mod_entry: &ModEntry) -> Result<(), String> {
+        self.remove_deployed(mod_entry, |_| true)
+    }
+    
+    // Take the mod out of every game install and, with `delete_download`, remove the downloaded release as well
+    pub fn uninstall_mod(&self, mod_entry: &ModEntry, delete_download: bool) -> Result<(), String> {
+        self.undeploy_everywhere(mod_entry)?;
+        if !delete_download || mod_entry.selected_version.is_empty() {
+            return Ok(());
+        }
+        
+        // The archive, extracted files and manifest all live in the version folder
+        let version_dir = self.version_dir(mod_entry);
+        if version_dir.is_dir() {
+            self.with_lock_retry(|| std::fs::remove_dir_all(&version_dir))
+                .map_err(|e| format!("Failed to remove {:?}: {}", version_dir, e))?;
+            debug!("Removed {:?}", version_dir);
+        }
+        Ok(())
+    }
+    
+    // Remove every file any mod deployed into the given game install, as recorded in the file index.
+    // Files the manager didn't put there are left alone. Returns how many files were removed
+    pub fn purge_game(&self, mods: &[ModEntry], game_path: &str) -> Result<usize, String> {
+        if game_path.is_empty() {
+            return Ok(0);
+        }
+        // Manifests from before the index still list their files
+        for mod_entry in mods {
+            self.indexed_files(mod_entry)?;
+        }
+        
+        let game_root = Self::game_root(game_path);
+        let fsd_mods_dir = game_root.join("FSD").join("Mods");
+        let mod_files = self.mod_files()?;
+        let mut removed = 0;
+        for (mod_id, path) in mod_files.all()? {
+            if !path.starts_with(&game_root) {
+                continue;
+            }
+            if path.exists() {
+                self.with_lock_retry(|| std::fs::remove_file(&path))
+                    .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+                debug!("Removed {:?}", path);
+                removed += 1;
+            }
+            mod_files.remove(&mod_id, &path)?;
+            
+            // ID-named folders of mod.io mods go once they are empty
+            if let Some(parent) = path.parent()
+                && parent.parent() == Some(fsd_mods_dir.as_path())
+            {
+                let _ = std::fs::remove_dir(parent);
+            }
+        }
+        Ok(removed)
+    }
+    
+    fn remove_deployed(&self, mod_entry: &ModEntry, matches: impl Fn(&Path) -> bool) -> Result<(), String> {
+        let (mod_files, files) = self.indexed_files(mod_entry)?;
+        for file in files.iter().filter(|file| matches(&file.path)) {
+            let path = &file.path;
+            if path.exists() {
+                self.with_lock_retry(|| std::fs::remove_file(path))
+                    .map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+                debug!("Removed {:?}", path);
+            }
+            mod_files.remove(&mod_entry.mod_id, path)?;
+            
+            // Drop the ID-named folder of mod.io mods once it is empty
+            if mod_entry.modio_id().is_some()
+                && let Some(parent) = path.parent()
+            {
+                let _ = std::fs::remove_dir(parent);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn url(url: &str) -> reqwest::Url {
+        reqwest::Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn source_headers_stay_on_the_original_origin() {
+        let original = url("https://files.example.com/mods/a.zip");
+        assert!(ModInstaller::keeps_source_headers(&original, &url("https://files.example.com/cdn/a.zip")));
+        assert!(ModInstaller::keeps_source_headers(&original, &url("https://FILES.example.com/a.zip")));
+        assert!(!ModInstaller::keeps_source_headers(&original, &url("https://cdn.other.net/a.zip")));
+        assert!(!ModInstaller::keeps_source_headers(&original, &url("https://example.com/a.zip")));
+        // Not over plain HTTP or to another port either
+        assert!(!ModInstaller::keeps_source_headers(&original, &url("http://files.example.com/a.zip")));
+        assert!(!ModInstaller::keeps_source_headers(&original, &url("https://files.example.com:8443/a.zip")));
+    }
+
+    #[test]
+    fn content_disposition_names() {
+        assert_eq!(
+            ModInstaller::parse_content_disposition("attachment; filename=\"Mod v1.2.zip\""),
+            Some("Mod v1.2.zip".to_string())
+        );
+        assert_eq!(ModInstaller::parse_content_disposition("attachment; filename=mod.pak"), Some("mod.pak".to_string()));
+        // The encoded form wins over the plain one
+        assert_eq!(
+            ModInstaller::parse_content_disposition("attachment; filename=\"fallback.zip\"; filename*=UTF-8''%C3%9Cber%20Mod.zip"),
+            Some("Über Mod.zip".to_string())
+        );
+        assert_eq!(ModInstaller::parse_content_disposition("attachment; filename=\"\""), None);
+        assert_eq!(ModInstaller::parse_content_disposition("inline"), None);
+    }
+
+    #[test]
+    fn percent_decoding() {
+        assert_eq!(ModInstaller::percent_decode("a%20b%2Fc"), "a b/c");
+        assert_eq!(ModInstaller::percent_decode("%E2%9C%93"), "✓");
+        // Broken escapes are kept as they are
+        assert_eq!(ModInstaller::percent_decode("100%"), "100%");
+        assert_eq!(ModInstaller::percent_decode("%zz%4"), "%zz%4");
+    }
+
+    #[test]
+    fn file_names_stay_in_the_version_folder() {
+        assert_eq!(ModInstaller::safe_file_name("mod.zip"), "mod.zip");
+        assert_eq!(ModInstaller::safe_file_name("../../evil.pak"), "evil.pak");
+        assert_eq!(ModInstaller::safe_file_name("..\\..\\evil.pak"), "evil.pak");
+        assert_eq!(ModInstaller::safe_file_name("/etc/passwd"), "passwd");
+        assert_eq!(ModInstaller::safe_file_name("C:\\Windows\\evil.dll"), "evil.dll");
+        assert_eq!(ModInstaller::safe_file_name(".."), "download");
+        assert_eq!(ModInstaller::safe_file_name(""), "download");
+    }
+
+    #[test]
+    fn content_range_start_and_size() {
+        assert_eq!(ModInstaller::parse_content_range("bytes 100-999/1000"), Some((100, Some(1000))));
+        assert_eq!(ModInstaller::parse_content_range("bytes 100-999/*"), Some((100, None)));
+        assert_eq!(ModInstaller::parse_content_range("items 0-1/2"), None);
+    }
+
+    #[test]
+    fn range_continuing_the_partial_file() {
+        let reply = ModInstaller::range_reply(StatusCode::PARTIAL_CONTENT, Some("bytes 500-999/1000"), 500);
+        assert_eq!(reply, RangeReply::Continues);
+    }
+
+    #[test]
+    fn full_response_to_a_range_request() {
+        assert_eq!(ModInstaller::range_reply(StatusCode::OK, None, 500), RangeReply::WholeFile);
+    }
+
+    #[test]
+    fn range_with_a_different_start() {
+        let reply = ModInstaller::range_reply(StatusCode::PARTIAL_CONTENT, Some("bytes 0-999/1000"), 500);
+        assert_eq!(reply, RangeReply::OtherRange);
+        // A 206 without a readable Content-Range can't be appended either
+        assert_eq!(ModInstaller::range_reply(StatusCode::PARTIAL_CONTENT, None, 500), RangeReply::OtherRange);
+    }
+
+    #[test]
+    fn range_past_a_complete_partial_file() {
+        let reply = ModInstaller::range_reply(StatusCode::RANGE_NOT_SATISFIABLE, Some("bytes */1000"), 1000);
+        assert_eq!(reply, RangeReply::NotSatisfiable);
+        assert_eq!(ModInstaller::range_reply(StatusCode::NOT_FOUND, None, 1000), RangeReply::Failed);
+    }
+}
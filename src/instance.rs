@@ -0,0 +1,149 @@
+use crate::app::app_data_dir;
+use crate::credentials::restrict_to_owner;
+use chacha20poly1305::aead::OsRng;
+use chacha20poly1305::aead::rand_core::RngCore;
+use eframe::egui;
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Write};
+use std::net::{Ipv4Addr, SocketAddr, TcpListener, TcpStream};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::OnceLock;
+use std::time::Duration;
+use tracing::{info, warn};
+
+// Only one window or command may use the database at a time. The holder keeps a lock on instance.lock
+// and listens on a local port, kept in instance.port, so a later launch hands over its arguments
+// and exits instead of opening a second window. The file also holds a random token only this user
+// can read, and a message must start with it, so other local users and programs can't drive the window
+
+// Starts the first line of every message, followed by the token
+const HEADER: &str = "ue4-drg-modman";
+
+// Bounds on a message, so a connection that never finishes or floods doesn't stall the listener
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+const MAX_MESSAGE_BYTES: u64 = 64 * 1024;
+
+// Held until the process exits
+static LOCK: OnceLock<File> = OnceLock::new();
+
+fn port_path() -> PathBuf {
+    app_data_dir().join("instance.port")
+}
+
+// False when another window or command already holds the lock
+pub fn claim() -> bool {
+    let path = app_data_dir().join("instance.lock");
+    let file = match std::fs::create_dir_all(app_data_dir())
+        .and_then(|()| OpenOptions::new().create(true).truncate(false).write(true).open(&path))
+    {
+        Ok(file) => file,
+        Err(e) => {
+            warn!("Failed to open {}, not checking for another window: {}", path.display(), e);
+            return true;
+        }
+    };
+
+    match file.try_lock() {
+        Ok(()) => {
+            let _ = LOCK.set(file);
+            true
+        },
+        Err(TryLockError::WouldBlock) => false,
+        Err(TryLockError::Error(e)) => {
+            warn!("Failed to lock {}, not checking for another window: {}", path.display(), e);
+            true
+        }
+    }
+}
+
+fn new_token() -> String {
+    let mut bytes = [0u8; 32];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+// The port on the first line of instance.port and the token on the second
+fn read_port_file() -> Option<(u16, String)> {
+    let contents = std::fs::read_to_string(port_path()).ok()?;
+    let mut lines = contents.lines();
+    let port = lines.next()?.trim().parse().ok()?;
+    let token = lines.next()?.trim().to_string();
+    Some((port, token))
+}
+
+// Restricted before the token goes in, and moved in place once complete
+fn write_port_file(port: u16, token: &str) -> std::io::Result<()> {
+    let path = port_path();
+    let partial = path.with_extension("part");
+    let mut file = File::create(&partial)?;
+    restrict_to_owner(&partial)?;
+    file.write_all(format!("{}\n{}\n", port, token).as_bytes())?;
+    drop(file);
+    std::fs::rename(&partial, &path)
+}
+
+// Hand the arguments of this launch to the running window; false when it can't be reached
+pub fn forward(args: &[String]) -> bool {
+    let Some((port, token)) = read_port_file() else {
+        return false;
+    };
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let Ok(mut stream) = TcpStream::connect_timeout(&address, Duration::from_secs(1)) else {
+        return false;
+    };
+
+    let first_line = format!("{} {}", HEADER, token);
+    let message = std::iter::once(first_line).chain(args.iter().cloned()).collect::<Vec<_>>().join("\n");
+    match stream.write_all(message.as_bytes()) {
+        Ok(()) => true,
+        Err(e) => {
+            warn!("Failed to reach the running manager: {}", e);
+            false
+        }
+    }
+}
+
+// The arguments of each later launch, possibly none, delivered while the window is open
+pub fn listen(ctx: egui::Context) -> Option<Receiver<Vec<String>>> {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, 0)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Failed to listen for other launches: {}", e);
+            return None;
+        }
+    };
+    let port = listener.local_addr().ok()?.port();
+    let token = new_token();
+    if let Err(e) = write_port_file(port, &token) {
+        warn!("Failed to write {}: {}", port_path().display(), e);
+        return None;
+    }
+    let expected = format!("{} {}", HEADER, token);
+
+    let (sender, receiver) = channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+                warn!("Failed to set a timeout on another launch's connection: {}", e);
+                continue;
+            }
+            let mut message = String::new();
+            if stream.take(MAX_MESSAGE_BYTES).read_to_string(&mut message).is_err() {
+                continue;
+            }
+            let mut lines = message.lines();
+            if lines.next() != Some(expected.as_str()) {
+                warn!("Ignored a connection without the instance token");
+                continue;
+            }
+            let args: Vec<String> = lines.filter(|line| !line.trim().is_empty()).map(str::to_string).collect();
+            info!("Another launch handed over {:?}", args);
+            if sender.send(args).is_err() {
+                return;
+            }
+            ctx.request_repaint();
+        }
+    });
+    Some(receiver)
+}
@@ -0,0 +1,119 @@
+use eframe::egui::{Key, KeyboardShortcut, Modifiers};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Things that can be bound to a keyboard shortcut
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ShortcutAction {
+    ShowBrowse,
+    ShowInstalled,
+    ShowSettings,
+    ShowDownloads,
+    Refresh,
+    CheckUpdates,
+    InstallSelected,
+}
+
+impl ShortcutAction {
+    pub const ALL: [ShortcutAction; 7] = [
+        ShortcutAction::ShowBrowse,
+        ShortcutAction::ShowInstalled,
+        ShortcutAction::ShowSettings,
+        ShortcutAction::ShowDownloads,
+        ShortcutAction::Refresh,
+        ShortcutAction::CheckUpdates,
+        ShortcutAction::InstallSelected,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShortcutAction::ShowBrowse => "Browse tab",
+            ShortcutAction::ShowInstalled => "Installed tab",
+            ShortcutAction::ShowSettings => "Settings tab",
+            ShortcutAction::ShowDownloads => "Downloads tab",
+            ShortcutAction::Refresh => "Refresh mod list",
+            ShortcutAction::CheckUpdates => "Check for updates",
+            ShortcutAction::InstallSelected => "Install selected mods",
+        }
+    }
+
+    pub fn default_shortcut(&self) -> KeyboardShortcut {
+        match self {
+            ShortcutAction::ShowBrowse => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num1),
+            ShortcutAction::ShowInstalled => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num2),
+            ShortcutAction::ShowSettings => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num3),
+            ShortcutAction::ShowDownloads => KeyboardShortcut::new(Modifiers::COMMAND, Key::Num4),
+            ShortcutAction::Refresh => KeyboardShortcut::new(Modifiers::NONE, Key::F5),
+            ShortcutAction::CheckUpdates => KeyboardShortcut::new(Modifiers::COMMAND, Key::U),
+            ShortcutAction::InstallSelected => KeyboardShortcut::new(Modifiers::COMMAND, Key::I),
+        }
+    }
+}
+
+// Shortcuts the user changed, stored as text like "Ctrl+Shift+U"; other actions use the defaults
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    pub bindings: BTreeMap<ShortcutAction, String>,
+}
+
+impl Keymap {
+    pub fn shortcut(&self, action: ShortcutAction) -> KeyboardShortcut {
+        self.bindings
+            .get(&action)
+            .and_then(|text| parse_shortcut(text))
+            .unwrap_or_else(|| action.default_shortcut())
+    }
+
+    pub fn set_shortcut(&mut self, action: ShortcutAction, shortcut: KeyboardShortcut) {
+        if shortcut == action.default_shortcut() {
+            self.bindings.remove(&action);
+        } else {
+            self.bindings.insert(action, format_shortcut(&shortcut));
+        }
+    }
+
+    // Actions ordered so shortcuts with more modifiers are matched first;
+    // egui ignores extra Shift and Alt, so Ctrl+U would otherwise swallow Ctrl+Shift+U
+    pub fn by_specificity(&self) -> Vec<(ShortcutAction, KeyboardShortcut)> {
+        let mut shortcuts: Vec<_> = ShortcutAction::ALL
+            .iter()
+            .map(|action| (*action, self.shortcut(*action)))
+            .collect();
+        shortcuts.sort_by_key(|(_, shortcut)| {
+            let modifiers = shortcut.modifiers;
+            std::cmp::Reverse(modifiers.command as u8 + modifiers.shift as u8 + modifiers.alt as u8)
+        });
+        shortcuts
+    }
+}
+
+// "Ctrl" stands for Cmd on macOS
+pub fn format_shortcut(shortcut: &KeyboardShortcut) -> String {
+    let mut parts = Vec::new();
+    if shortcut.modifiers.command || shortcut.modifiers.ctrl {
+        parts.push("Ctrl");
+    }
+    if shortcut.modifiers.shift {
+        parts.push("Shift");
+    }
+    if shortcut.modifiers.alt {
+        parts.push("Alt");
+    }
+    parts.push(shortcut.logical_key.name());
+    parts.join("+")
+}
+
+pub fn parse_shortcut(text: &str) -> Option<KeyboardShortcut> {
+    let mut modifiers = Modifiers::NONE;
+    let mut key = None;
+    for part in text.split('+').map(str::trim) {
+        match part.to_lowercase().as_str() {
+            "ctrl" | "cmd" => modifiers.command = true,
+            "shift" => modifiers.shift = true,
+            "alt" => modifiers.alt = true,
+            _ => key = Some(Key::from_name(part)?),
+        }
+    }
+    Some(KeyboardShortcut::new(modifiers, key?))
+}
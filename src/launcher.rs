@@ -0,0 +1,83 @@
+use crate::installer::ModInstaller;
+use std::path::Path;
+use std::process::Command;
+use tracing::info;
+
+// Deep Rock Galactic's Steam app ID
+pub const STEAM_APP_ID: u32 = 548430;
+
+// Name of the game's process, the same under Proton
+const GAME_PROCESS: &str = "FSD-Win64-Shipping";
+
+// FSD.exe on Windows; elsewhere the game runs through Proton, which only Steam can start
+pub fn launch_game(game_path: &str) -> Result<(), String> {
+    if game_path.is_empty() {
+        return Err("Set the game path in Settings first".to_string());
+    }
+    
+    let exe = ModInstaller::game_root(game_path).join("FSD.exe");
+    if cfg!(windows) && exe.is_file() {
+        let child = Command::new(&exe)
+            .current_dir(exe.parent().unwrap_or(Path::new(".")))
+            .spawn()
+            .map_err(|e| format!("Failed to start {}: {}", exe.display(), e))?;
+        info!("Started {} (pid {})", exe.display(), child.id());
+        return Ok(());
+    }
+    open(&format!("steam://run/{}", STEAM_APP_ID))
+}
+
+// Used to tell when a session started from here has ended; always false where processes can't be listed
+pub fn game_running() -> bool {
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        // CREATE_NO_WINDOW, so no console flashes up on every check
+        Command::new("tasklist")
+            .args(["/NH", "/FI", &format!("IMAGENAME eq {}.exe", GAME_PROCESS)])
+            .creation_flags(0x0800_0000)
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(GAME_PROCESS))
+    }
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_dir("/proc").is_ok_and(|entries| entries.flatten().any(|entry| {
+            std::fs::read(entry.path().join("cmdline"))
+                .is_ok_and(|cmdline| String::from_utf8_lossy(&cmdline).contains(GAME_PROCESS))
+        }))
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        false
+    }
+}
+
+pub fn open_folder(folder: &Path) -> Result<(), String> {
+    if !folder.is_dir() {
+        return Err(format!("{} is not a folder", folder.display()));
+    }
+    open(&folder.to_string_lossy())
+}
+
+pub fn open_file(file: &Path) -> Result<(), String> {
+    if !file.is_file() {
+        return Err(format!("{} is not a file", file.display()));
+    }
+    open(&file.to_string_lossy())
+}
+
+// Hand a URL or path to the desktop's default handler
+fn open(target: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut opener = {
+        let mut opener = Command::new("cmd");
+        opener.args(["/C", "start", ""]);
+        opener
+    };
+    #[cfg(target_os = "macos")]
+    let mut opener = Command::new("open");
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let mut opener = Command::new("xdg-open");
+
+    opener.arg(target).spawn().map(|_| ()).map_err(|e| format!("Failed to open {}: {}", target, e))
+}
@@ -0,0 +1,87 @@
+use crate::db::ModEntry;
+use crate::installer::ModInstaller;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+// An enabled mod pinned to the exact release it was installed from
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockedMod {
+    pub mod_id: String,
+    pub mod_name: String,
+    pub mod_link: String,
+    pub version: String,
+    #[serde(default)]
+    pub variant: String,
+    // SHA-256 of each downloaded file, by path inside the version folder
+    #[serde(default)]
+    pub hashes: BTreeMap<String, String>,
+}
+
+// The enabled set of a profile, precise enough to reproduce it on another machine
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Lockfile {
+    pub profile: String,
+    pub mods: Vec<LockedMod>,
+}
+
+impl Lockfile {
+    pub fn build(installer: &ModInstaller, mods: &[ModEntry], profile: &str) -> Self {
+        let mut locked: Vec<LockedMod> = mods
+            .iter()
+            .filter(|m| m.enabled)
+            .map(|mod_entry| LockedMod {
+                mod_id: mod_entry.mod_id.clone(),
+                mod_name: mod_entry.mod_name.clone(),
+                mod_link: mod_entry.mod_link.clone(),
+                version: mod_entry.selected_version.clone(),
+                variant: mod_entry.selected_variant.clone(),
+                hashes: installer.load_manifest(mod_entry)
+                    .map(|manifest| manifest.stored_hashes)
+                    .unwrap_or_default(),
+            })
+            .collect();
+        locked.sort_by(|a, b| a.mod_id.cmp(&b.mod_id));
+
+        Self {
+            profile: profile.to_string(),
+            mods: locked,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize lockfile: {}", e))
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        serde_json::from_str(json).map_err(|e| format!("Invalid lockfile: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read lockfile: {}", e))?;
+        Self::from_json(&json)
+    }
+
+    pub fn find(&self, mod_id: &str) -> Option<&LockedMod> {
+        self.mods.iter().find(|locked| locked.mod_id == mod_id)
+    }
+}
+
+impl LockedMod {
+    // Files of the installed release that differ from the locked hashes
+    pub fn mismatches(&self, installer: &ModInstaller, mod_entry: &ModEntry) -> Vec<String> {
+        let stored = installer.load_manifest(mod_entry)
+            .map(|manifest| manifest.stored_hashes)
+            .unwrap_or_default();
+
+        self.hashes
+            .iter()
+            .filter_map(|(path, hash)| match stored.get(path) {
+                Some(stored_hash) if stored_hash == hash => None,
+                Some(_) => Some(format!("{} has different contents", path)),
+                None => Some(format!("{} is missing", path)),
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,77 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tracing_appender::rolling::{Builder, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, Layer, Registry};
+
+// Days of log files kept next to the current one
+const MAX_LOG_FILES: usize = 7;
+const LOG_FILE_PREFIX: &str = "modman";
+
+// Swaps the level when "Enable mod debugging" is toggled
+static LEVEL: OnceLock<reload::Handle<LevelFilter, Registry>> = OnceLock::new();
+
+// Logs to the console and to a file under `log_dir` that starts over every day
+pub fn init(log_dir: &Path, debug: bool) {
+    let (level, handle) = reload::Layer::new(level_for(debug));
+    let registry = tracing_subscriber::registry().with(level);
+    // stderr keeps command output such as `plan --json` clean
+    let console = fmt::layer().with_target(false).with_writer(std::io::stderr);
+
+    let file = std::fs::create_dir_all(log_dir)
+        .map_err(|e| e.to_string())
+        .and_then(|()| {
+            Builder::new()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_FILE_PREFIX)
+                .filename_suffix("log")
+                .max_log_files(MAX_LOG_FILES)
+                .build(log_dir)
+                .map_err(|e| e.to_string())
+        });
+    let result = match file {
+        Ok(file) => registry
+            .with(console.and_then(fmt::layer().with_ansi(false).with_writer(file)))
+            .try_init(),
+        Err(e) => {
+            let result = registry.with(console).try_init();
+            tracing::warn!("Logging to the console only, {} can't be written: {}", log_dir.display(), e);
+            result
+        },
+    };
+    if result.is_ok() {
+        let _ = LEVEL.set(handle);
+    }
+}
+
+pub fn set_debug(debug: bool) {
+    if let Some(handle) = LEVEL.get()
+        && let Err(e) = handle.modify(|level| *level = level_for(debug))
+    {
+        tracing::warn!("Failed to change the log level: {}", e);
+    }
+}
+
+pub fn log_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join("logs")
+}
+
+// The file being written to today, or the newest one left
+pub fn latest_log_file(log_dir: &Path) -> Option<PathBuf> {
+    std::fs::read_dir(log_dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with(LOG_FILE_PREFIX)))
+        .max_by_key(|path| path.metadata().and_then(|metadata| metadata.modified()).ok())
+}
+
+fn level_for(debug: bool) -> LevelFilter {
+    if debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    }
+}
@@ -1,13 +1,76 @@
 mod app;
+#[cfg(feature = "audio-preview")]
+mod audio;
+mod cli;
+mod conflicts;
+mod credentials;
 mod db;
+mod download_failure;
+mod format;
 mod installer;
+mod instance;
+mod keymap;
+mod launcher;
+mod lockfile;
+mod logging;
+mod mod_files;
 mod mod_io;
+mod mod_query;
+mod mod_url;
+mod modlist;
+mod pak;
+mod plan;
+mod post_install;
+mod protocol;
+mod source_headers;
+mod startup_check;
+mod steam;
+mod sync;
+mod textures;
 mod ui;
+mod webhook;
+mod worker;
 
 use app::ModManager;
+use clap::Parser;
 use eframe::egui;
 
 fn main() -> Result<(), eframe::Error> {
+    // Before the config is read, so a broken config is logged too
+    logging::init(&logging::log_dir(&app::app_data_dir()), false);
+    logging::set_debug(app::Config::load().enable_mod_debugging);
+    
+    let cli = cli::Cli::parse();
+    let claimed = instance::claim();
+    
+    // Subcommands such as `install` or `plan` run without opening the window. They change the same
+    // database and game folder as an open window, so they wait until it's closed
+    if let Some(command) = cli.command {
+        if !claimed {
+            eprintln!("The mod manager is open, close it before running commands");
+            std::process::exit(1);
+        }
+        if let Err(e) = cli::run(command) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+    
+    // A second window would share the database, so hand the arguments to the open one instead
+    let arguments: Vec<String> = cli.arguments.iter().map(|argument| absolute_path(argument)).collect();
+    if !claimed {
+        // The open window may still be starting up and not listening yet
+        for _ in 0..10 {
+            if instance::forward(&arguments) {
+                return Ok(());
+            }
+            std::thread::sleep(std::time::Duration::from_millis(200));
+        }
+        eprintln!("The mod manager is already running, but doesn't respond");
+        std::process::exit(1);
+    }
+    
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default().with_inner_size([1000.0, 600.0]),
         ..Default::default()
@@ -16,8 +79,25 @@ fn main() -> Result<(), eframe::Error> {
     eframe::run_native(
         "DRG Mod Manager",
         options,
-        Box::new(|_cc| -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync + 'static>> {
-            Ok(Box::new(ModManager::default()))
+        Box::new(|cc| -> Result<Box<dyn eframe::App>, Box<dyn std::error::Error + Send + Sync + 'static>> {
+            let mut app = ModManager::default();
+            app.incoming_launches = instance::listen(cc.egui_ctx.clone());
+            for argument in &arguments {
+                app.open_argument(argument);
+            }
+            Ok(Box::new(app))
         }),
     )
 }
+
+// The open window may run in another folder, so relative paths of existing files are resolved first
+fn absolute_path(argument: &str) -> String {
+    let path = std::path::Path::new(argument);
+    if path.is_relative()
+        && path.exists()
+        && let Ok(path) = std::path::absolute(path)
+    {
+        return path.to_string_lossy().to_string();
+    }
+    argument.to_string()
+}
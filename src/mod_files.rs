@@ -0,0 +1,107 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+// Every file a mod copied into a game install, in the mod_files table of the manager's database,
+// and the verified hashes of downloaded releases in download_hashes.
+// The installer keeps its own connection, as it runs on the worker thread as well
+
+// One file in a game folder, with its SHA-256 once known
+pub struct DeployedFile {
+    pub path: PathBuf,
+    pub hash: Option<String>,
+    pub deployed_at: i64,
+}
+
+pub struct ModFiles {
+    conn: Connection,
+}
+
+impl ModFiles {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path).map_err(|e| format!("Failed to open the file index: {}", e))?;
+        Ok(Self { conn })
+    }
+
+    pub fn record(&self, mod_id: &str, file: &DeployedFile) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO mod_files (mod_id, path, hash, deployed_at) VALUES (?1, ?2, ?3, ?4)",
+            params![mod_id, file.path.to_string_lossy(), file.hash, file.deployed_at],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to record {:?}: {}", file.path, e))
+    }
+
+    pub fn set_hash(&self, mod_id: &str, path: &Path, hash: &str) -> Result<(), String> {
+        self.conn.execute(
+            "UPDATE mod_files SET hash = ?3 WHERE mod_id = ?1 AND path = ?2",
+            params![mod_id, path.to_string_lossy(), hash],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to record the hash of {:?}: {}", path, e))
+    }
+
+    pub fn remove(&self, mod_id: &str, path: &Path) -> Result<(), String> {
+        self.conn.execute(
+            "DELETE FROM mod_files WHERE mod_id = ?1 AND path = ?2",
+            params![mod_id, path.to_string_lossy()],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to forget {:?}: {}", path, e))
+    }
+
+    // Another mod that has a file deployed at `path`, which deploying this one would overwrite
+    pub fn other_owner(&self, mod_id: &str, path: &Path) -> Result<Option<String>, String> {
+        self.conn.query_row(
+            "SELECT mod_id FROM mod_files WHERE path = ?1 AND mod_id != ?2 LIMIT 1",
+            params![path.to_string_lossy(), mod_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to look up {:?}: {}", path, e))
+    }
+
+    pub fn for_mod(&self, mod_id: &str) -> Result<Vec<DeployedFile>, String> {
+        let mut stmt = self.conn
+            .prepare("SELECT path, hash, deployed_at FROM mod_files WHERE mod_id = ?1 ORDER BY path")
+            .map_err(|e| e.to_string())?;
+        stmt.query_map(params![mod_id], |row| {
+            Ok(DeployedFile {
+                path: PathBuf::from(row.get::<_, String>(0)?),
+                hash: row.get(1)?,
+                deployed_at: row.get(2)?,
+            })
+        })
+        .and_then(|rows| rows.collect())
+        .map_err(|e| format!("Failed to read the files of {}: {}", mod_id, e))
+    }
+
+    pub fn record_download_hash(&self, mod_id: &str, version: &str, file_name: &str, md5: &str) -> Result<(), String> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO download_hashes (mod_id, version, file_name, md5, verified_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![mod_id, version, file_name, md5, chrono::Utc::now().timestamp()],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to record the hash of {}: {}", file_name, e))
+    }
+
+    // File name and MD5 of the release as it was verified after download
+    pub fn download_hash(&self, mod_id: &str, version: &str) -> Result<Option<(String, String)>, String> {
+        self.conn.query_row(
+            "SELECT file_name, md5 FROM download_hashes WHERE mod_id = ?1 AND version = ?2",
+            params![mod_id, version],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read the download hash of {}: {}", mod_id, e))
+    }
+
+    // mod_id and path of every file the manager deployed, for all mods
+    pub fn all(&self) -> Result<Vec<(String, PathBuf)>, String> {
+        let mut stmt = self.conn
+            .prepare("SELECT mod_id, path FROM mod_files ORDER BY mod_id, path")
+            .map_err(|e| e.to_string())?;
+        stmt.query_map([], |row| Ok((row.get(0)?, PathBuf::from(row.get::<_, String>(1)?))))
+            .and_then(|rows| rows.collect())
+            .map_err(|e| format!("Failed to read the file index: {}", e))
+    }
+}
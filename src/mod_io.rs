@@ -1,41 +1,320 @@
 use serde::{Deserialize, Serialize};
-use reqwest::blocking::Client;
-use std::{error::Error, io::Read};
+use reqwest::blocking::{Client, RequestBuilder, Response};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, warn};
 
 const MOD_IO_API_URL: &str = "https://api.mod.io/v1";
 const MOD_IO_GAME_ID: u32 = 2475; // Deep Rock Galactic game ID
+pub const MOD_HUB_URL: &str = "https://mod.io/g/drg/m/mod-hub";
 
-#[derive(Debug, Serialize, Deserialize)]
+// A request that hits the rate limit is retried this often, waiting at most this many seconds each time
+const MAX_RATE_LIMIT_RETRIES: u32 = 4;
+const MAX_RATE_LIMIT_WAIT: i64 = 60;
+
+// Cached responses nobody asked for in this long are dropped
+const RESPONSE_CACHE_MAX_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+// A cached API response with the validators to ask whether it changed
+#[derive(Serialize, Deserialize)]
+struct CachedResponse {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    body: String,
+}
+
+// Quota mod.io reported with its last response, shared by every client in the process
+#[derive(Clone, Copy, Default)]
+pub struct RateLimit {
+    pub limit: Option<u32>,
+    pub remaining: Option<u32>,
+    // Unix time until which mod.io asked to hold off
+    pub blocked_until: Option<i64>,
+}
+
+static RATE_LIMIT: Mutex<RateLimit> = Mutex::new(RateLimit {
+    limit: None,
+    remaining: None,
+    blocked_until: None,
+});
+
+pub fn rate_limit() -> RateLimit {
+    *RATE_LIMIT.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+// Remember the quota headers; returns how long mod.io asked to wait, if it did
+fn record_rate_limit(response: &Response) -> Option<i64> {
+    let header = |name: &str| response.headers()
+        .get(name)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<i64>().ok());
+    let retry_after = header("retry-after").or_else(|| header("x-ratelimit-retryafter"));
+    
+    let mut rate_limit = RATE_LIMIT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(limit) = header("x-ratelimit-limit") {
+        rate_limit.limit = u32::try_from(limit).ok();
+    }
+    if let Some(remaining) = header("x-ratelimit-remaining") {
+        rate_limit.remaining = u32::try_from(remaining).ok();
+    }
+    rate_limit.blocked_until = retry_after.map(|seconds| chrono::Utc::now().timestamp() + seconds);
+    retry_after
+}
+
+// Sends API requests one after another through the rate limit: a 429 answer is retried after
+// the time mod.io asks for, or with exponential backoff when it doesn't say
+trait SendWithBackoff {
+    fn send_with_backoff(self) -> reqwest::Result<Response>;
+}
+
+impl SendWithBackoff for RequestBuilder {
+    fn send_with_backoff(self) -> reqwest::Result<Response> {
+        let mut request = self;
+        let mut attempt = 0;
+        loop {
+            // Hold queued requests back while a short block is in effect
+            if let Some(blocked_until) = rate_limit().blocked_until {
+                let wait = blocked_until - chrono::Utc::now().timestamp();
+                if wait > 0 && wait <= MAX_RATE_LIMIT_WAIT {
+                    std::thread::sleep(Duration::from_secs(wait as u64));
+                }
+            }
+            
+            let retry = request.try_clone();
+            let response = request.send()?;
+            let retry_after = record_rate_limit(&response);
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+            
+            attempt += 1;
+            let wait = retry_after.unwrap_or(1 << attempt);
+            let Some(retry) = retry.filter(|_| attempt <= MAX_RATE_LIMIT_RETRIES && wait <= MAX_RATE_LIMIT_WAIT) else {
+                return Ok(response);
+            };
+            warn!("mod.io rate limit reached, retrying in {}s ({}/{})", wait, attempt, MAX_RATE_LIMIT_RETRIES);
+            RATE_LIMIT.lock().unwrap_or_else(|e| e.into_inner()).blocked_until = Some(chrono::Utc::now().timestamp() + wait);
+            std::thread::sleep(Duration::from_secs(wait as u64));
+            request = retry;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModIoMod {
     pub id: u32,
     pub name: String,
+    #[serde(default)]
+    pub name_id: String,
     pub summary: String,
+    #[serde(default)]
     pub description: String,
     pub logo: ModIoLogo,
     pub submitted_by: ModIoUser,
     pub date_added: i64,
     pub date_updated: i64,
     pub stats: ModIoStats,
+    // Current release of the mod, absent until a file is uploaded
+    #[serde(default)]
+    pub modfile: Option<ModIoFile>,
+    #[serde(default)]
+    pub tags: Vec<ModIoTag>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoTag {
+    pub name: String,
+}
+
+// DRG's mod.io approval categories
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Approval {
+    // Safe to use in public games
+    Verified,
+    // Only loads in private sandbox games
+    Sandbox,
+    Unknown,
+}
+
+impl ModIoMod {
+    pub fn approval(&self) -> Approval {
+        let has_tag = |name: &str| self.tags.iter().any(|tag| tag.name.eq_ignore_ascii_case(name));
+        if has_tag("Verified") || has_tag("Approved") {
+            Approval::Verified
+        } else if has_tag("Sandbox") {
+            Approval::Sandbox
+        } else {
+            Approval::Unknown
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoFile {
+    pub id: u32,
+    pub filename: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    pub filesize: u64,
+    pub date_added: i64,
+    #[serde(default)]
+    pub filehash: Option<ModIoFilehash>,
+    #[serde(default)]
+    pub download: Option<ModIoDownload>,
+}
+
+impl ModIoFile {
+    // Authors don't always set a version, so fall back to the file ID
+    pub fn version_label(&self) -> String {
+        match self.version.as_deref().map(str::trim) {
+            Some(version) if !version.is_empty() => version.to_string(),
+            _ => format!("file-{}", self.id),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoFilehash {
+    pub md5: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoDownload {
+    // Signed link to the file; requests need the OAuth token and it expires
+    pub binary_url: String,
+    pub date_expires: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModIoFilesResponse {
+    pub data: Vec<ModIoFile>,
+}
+
+// A mod another mod needs in order to work
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoDependency {
+    pub mod_id: u32,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub name_id: String,
+    // Current release of the dependency, when mod.io includes it
+    #[serde(default)]
+    pub modfile: Option<ModIoFile>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModIoDependenciesResponse {
+    pub data: Vec<ModIoDependency>,
+}
+
+// A group of tags mods can be labelled with, e.g. "Type" with "Gameplay" and "Audio"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoTagOption {
+    pub name: String,
+    pub tags: Vec<String>,
+    // Hidden groups are for the game's own use
+    #[serde(default)]
+    pub hidden: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ModIoTagOptionsResponse {
+    pub data: Vec<ModIoTagOption>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModIoLogo {
     pub filename: String,
     pub original: String,
     pub thumb_320x180: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModIoUser {
+    #[serde(default)]
+    pub id: u32,
     pub username: String,
     pub profile_url: String,
+    #[serde(default)]
+    pub avatar: Option<ModIoAvatar>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModIoAvatar {
+    #[serde(default)]
+    pub thumb_100x100: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModIoStats {
     pub downloads_total: u32,
     pub subscribers_total: u32,
+    #[serde(rename = "ratings_total", default)]
     pub rating_total: u32,
+    // Rating from 0 to 1, weighted so mods with few votes don't top the list
+    #[serde(default)]
+    pub ratings_weighted_aggregate: f32,
+}
+
+// Catalog orderings offered in the Browse tab
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CatalogSort {
+    Trending,
+    Name,
+    MostDownloaded,
+    MostSubscribed,
+    HighestRated,
+    RecentlyUpdated,
+    NewlyAdded,
+}
+
+impl CatalogSort {
+    pub const ALL: [CatalogSort; 7] = [
+        CatalogSort::Trending,
+        CatalogSort::Name,
+        CatalogSort::MostDownloaded,
+        CatalogSort::MostSubscribed,
+        CatalogSort::HighestRated,
+        CatalogSort::RecentlyUpdated,
+        CatalogSort::NewlyAdded,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            CatalogSort::Trending => "Trending",
+            CatalogSort::Name => "Name",
+            CatalogSort::MostDownloaded => "Most downloaded",
+            CatalogSort::MostSubscribed => "Most subscribers",
+            CatalogSort::HighestRated => "Highest rated",
+            CatalogSort::RecentlyUpdated => "Recently updated",
+            CatalogSort::NewlyAdded => "Newly added",
+        }
+    }
+
+    // Value of the API's _sort parameter
+    pub fn api_param(&self) -> &'static str {
+        match self {
+            CatalogSort::Trending => "-downloads_today",
+            CatalogSort::Name => "name",
+            CatalogSort::MostDownloaded => "-downloads_total",
+            CatalogSort::MostSubscribed => "-subscribers_total",
+            CatalogSort::HighestRated => "-ratings_weighted_aggregate",
+            CatalogSort::RecentlyUpdated => "-date_updated",
+            CatalogSort::NewlyAdded => "-date_live",
+        }
+    }
+}
+
+// Token handed out for an email security code
+#[derive(Debug, Deserialize)]
+pub struct ModIoAccessToken {
+    pub access_token: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -47,6 +326,9 @@ pub struct ModIoClient {
     client: Client,
     initialized: bool,
     user_id: Option<u32>,
+    oauth_token: Option<String>,
+    // Where catalog responses are kept between sessions; nothing is cached without one
+    cache_dir: Option<PathBuf>,
 }
 
 impl ModIoClient {
@@ -55,6 +337,8 @@ impl ModIoClient {
             client: Client::new(),
             initialized: true,
             user_id: None,
+            oauth_token: None,
+            cache_dir: None,
         }
     }
 
@@ -63,6 +347,8 @@ impl ModIoClient {
             client: Client::new(),
             initialized: false,
             user_id: None,
+            oauth_token: None,
+            cache_dir: None,
         }
     }
     
@@ -70,6 +356,116 @@ impl ModIoClient {
         !self.initialized
     }
     
+    // Token sent with catalog requests; mod.io rejects anonymous reads
+    pub fn set_oauth_token(&mut self, token: &str) {
+        let token = (!token.is_empty()).then(|| token.to_string());
+        // The cached user ID belongs to the previous token's account
+        if token != self.oauth_token {
+            self.user_id = None;
+        }
+        self.oauth_token = token;
+    }
+    
+    // Keep catalog responses under `cache_dir` and drop those unused for a month
+    pub fn set_cache_dir(&mut self, cache_dir: PathBuf) {
+        if let Ok(entries) = std::fs::read_dir(&cache_dir) {
+            for entry in entries.filter_map(|entry| entry.ok()) {
+                let stale = entry.metadata()
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+                    .and_then(|modified| modified.elapsed().ok())
+                    .is_some_and(|age| age > RESPONSE_CACHE_MAX_AGE);
+                if stale {
+                    let _ = std::fs::remove_file(entry.path());
+                }
+            }
+        }
+        self.cache_dir = Some(cache_dir);
+    }
+    
+    fn cache_path(&self, url: &str) -> Option<PathBuf> {
+        let cache_dir = self.cache_dir.as_ref()?;
+        let key = format!("{:x}", Sha256::digest(url.as_bytes()));
+        Some(cache_dir.join(format!("{}.json", key)))
+    }
+    
+    // GET a JSON response through the on-disk cache: the stored copy is revalidated with
+    // If-None-Match / If-Modified-Since. With `offline_fallback` it's used as it is when mod.io
+    // can't be reached; lookups like update checks leave it off so an outage isn't taken as an answer
+    fn get_json_cached<T: DeserializeOwned>(&self, request: RequestBuilder, offline_fallback: bool) -> Result<T, Box<dyn Error>> {
+        let url = request.try_clone()
+            .and_then(|request| request.build().ok())
+            .map(|request| request.url().to_string());
+        let cache_path = url.as_deref().and_then(|url| self.cache_path(url));
+        let cached: Option<CachedResponse> = cache_path.as_ref()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|json| serde_json::from_str(&json).ok());
+        
+        let mut request = request;
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        
+        let response = match request.send_with_backoff() {
+            Ok(response) => response,
+            Err(e) => match cached {
+                Some(cached) if offline_fallback => {
+                    warn!("mod.io unreachable, using the cached response: {}", e);
+                    return Ok(serde_json::from_str(&cached.body)?);
+                },
+                _ => return Err(e.into()),
+            },
+        };
+        if response.status() == StatusCode::NOT_MODIFIED
+            && let Some(cached) = cached
+        {
+            // Touch the entry so pruning keeps what is still in use
+            if let Some(path) = &cache_path {
+                let _ = std::fs::write(path, serde_json::to_string(&cached)?);
+            }
+            return Ok(serde_json::from_str(&cached.body)?);
+        }
+        
+        let response = response.error_for_status()?;
+        let header = |name| response.headers()
+            .get(name)
+            .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+            .map(str::to_string);
+        let etag = header(reqwest::header::ETAG);
+        let last_modified = header(reqwest::header::LAST_MODIFIED);
+        let body = response.text()?;
+        let parsed = serde_json::from_str(&body)?;
+        
+        // Kept even without validators, for offline use
+        if let Some(path) = cache_path {
+            let entry = CachedResponse { etag, last_modified, body };
+            let result = serde_json::to_string(&entry)
+                .map_err(std::io::Error::from)
+                .and_then(|json| {
+                    std::fs::create_dir_all(path.parent().unwrap_or(&path))?;
+                    std::fs::write(&path, json)
+                });
+            if let Err(e) = result {
+                warn!("Failed to cache a mod.io response: {}", e);
+            }
+        }
+        Ok(parsed)
+    }
+    
+    fn get(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url)
+            .header("Accept", "application/json");
+        match &self.oauth_token {
+            Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+            None => request,
+        }
+    }
+    
     // Get the API URL, using user-specific URL if user_id is available
     fn get_api_url(&self) -> String {
         if let Some(user_id) = self.user_id {
@@ -79,222 +475,336 @@ impl ModIoClient {
         }
     }
     
-    // Get user ID from the API
-    pub fn get_user_id(&mut self, api_key: &str) -> Result<u32, Box<dyn Error>> {
+    // Have mod.io mail a 5-digit security code to the address. Needs the user's API key from mod.io/me/access
+    pub fn request_email_code(&self, api_key: &str, email: &str) -> Result<(), Box<dyn Error>> {
+        let url = format!("{}/oauth/emailrequest", MOD_IO_API_URL);
+        let response = self.client.post(&url)
+            .header("Accept", "application/json")
+            .form(&[("api_key", api_key), ("email", email)])
+            .send_with_backoff()?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(format!("HTTP {}, {}", status, error_text).into());
+        }
+        Ok(())
+    }
+    
+    // Trade the mailed security code for an OAuth token
+    pub fn exchange_email_code(&self, api_key: &str, security_code: &str) -> Result<ModIoAccessToken, Box<dyn Error>> {
+        let url = format!("{}/oauth/emailexchange", MOD_IO_API_URL);
+        let response = self.client.post(&url)
+            .header("Accept", "application/json")
+            .form(&[("api_key", api_key), ("security_code", security_code)])
+            .send_with_backoff()?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(format!("HTTP {}, {}", status, error_text).into());
+        }
+        Ok(response.json()?)
+    }
+    
+    // Account the token belongs to; also caches its user ID
+    pub fn get_account(&mut self, api_key: &str) -> Result<ModIoUser, Box<dyn Error>> {
         // Use the standard API URL to get user info
         let url = format!("{}/me", MOD_IO_API_URL);
         
-        println!("Fetching user info from mod.io: {}", url);
+        debug!("Fetching user info from mod.io: {}", url);
         
         let response = self.client.get(&url)
             .header("Accept", "application/json")
             .header("Authorization", format!("Bearer {}", api_key))
-            .send()?;
+            .send_with_backoff()?;
         
         if response.status().is_success() {
-            let body = response.text()?;
-            println!("User info response: {}", body);
-            
-            // Parse the JSON to extract user ID
-            let json: serde_json::Value = serde_json::from_str(&body)?;
-            if let Some(user_id) = json.get("id").and_then(|id| id.as_u64()) {
-                let user_id = user_id as u32;
-                self.user_id = Some(user_id);
-                println!("Got user ID: {}", user_id);
-                return Ok(user_id);
-            } else {
+            let user: ModIoUser = response.json()?;
+            if user.id == 0 {
                 return Err("User ID not found in response".into());
             }
+            self.user_id = Some(user.id);
+            debug!("Got user ID: {}", user.id);
+            Ok(user)
         } else {
             let status = response.status();
             let error_text = response.text().unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(format!("Error fetching user info: HTTP {}, {}", status, error_text).into());
+            Err(format!("Error fetching user info: HTTP {}, {}", status, error_text).into())
         }
     }
     
-    pub fn list_user_games(&mut self, api_key: &str) -> Result<(), Box<dyn Error>> {
-        // First, get the user ID if we don't have it yet
-        if self.user_id.is_none() {
-            self.get_user_id(api_key)?;
-        }
+    // Update other methods to use get_api_url()
+    // An empty search lists the whole catalog
+    // Only mods carrying at least one of `tags` are returned, unless it is empty
+    pub fn get_mods(&self, offset: u32, limit: u32, sort: CatalogSort, search: &str, tags: &[String]) -> Result<Vec<ModIoMod>, Box<dyn Error>> {
+        let url = format!("{}/games/{}/mods?_offset={}&_limit={}&_sort={}", 
+                         self.get_api_url(), MOD_IO_GAME_ID, offset, limit, sort.api_param());
         
-        // Now use the user-specific API URL
-        let url = format!("{}/me/games", self.get_api_url());
+        debug!("Fetching mods from mod.io: {} (search: {:?})", url, search);
         
-        println!("Fetching user games from mod.io: {}", url);
+        let mut request = self.get(&url);
+        if !search.is_empty() {
+            // _q matches the name and URL slug of each mod
+            request = request.query(&[("_q", search)]);
+        }
+        if !tags.is_empty() {
+            request = request.query(&[("tags-in", tags.join(","))]);
+        }
+        let response: ModIoResponse = self.get_json_cached(request, true)?;
         
-        let response = self.client.get(&url)
-            .header("Accept", "application/json")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .send()?;
+        Ok(response.data)
+    }
+    
+    pub fn get_mod_by_id(&self, mod_id: u32) -> Result<ModIoMod, Box<dyn Error>> {
+        let url = format!("{}/games/{}/mods/{}", 
+                         self.get_api_url(), MOD_IO_GAME_ID, mod_id);
         
-        // Check if the request was successful
-        if response.status().is_success() {
-            // Get the response body as text
-            let body = response.text()?;
-            
-            // Debug print the response
-            println!("Response from mod.io API:");
-            println!("{}", body);
-            
-            // In a real implementation, you would parse this into a struct
-            // let games: ModIoGamesResponse = serde_json::from_str(&body)?;
-            // return Ok(games);
-        } else {
-            println!("Error fetching user games: HTTP {}", response.status());
-            if let Ok(error_text) = response.text() {
-                println!("Error details: {}", error_text);
-            }
-        }
+        debug!("Fetching mod details from mod.io: {}", url);
         
-        Ok(())
+        self.get_json_cached(self.get(&url), false)
     }
     
-    // Update other methods to use get_api_url()
-    pub fn get_mods(&self, offset: u32, limit: u32) -> Result<Vec<ModIoMod>, Box<dyn Error>> {
-        let url = format!("{}/games/{}/mods?offset={}&limit={}", 
-                         self.get_api_url(), MOD_IO_GAME_ID, offset, limit);
+    // Mod whose web page link ends in `name_id`
+    pub fn get_mod_by_name_id(&self, name_id: &str) -> Result<ModIoMod, Box<dyn Error>> {
+        let url = format!("{}/games/{}/mods", self.get_api_url(), MOD_IO_GAME_ID);
         
-        println!("Fetching mods from mod.io: {}", url);
+        debug!("Looking up mod {} on mod.io", name_id);
         
-        let response = self.client.get(&url)
-            .header("Accept", "application/json")
-            .send()?
+        let response = self.get(&url)
+            .query(&[("name_id", name_id)])
+            .send_with_backoff()?
+            .error_for_status()?
             .json::<ModIoResponse>()?;
         
-        Ok(response.data)
+        response.data
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("No mod.io mod is called {}", name_id).into())
+    }
+
+    // Tag groups DRG mods can be filtered by
+    pub fn get_tag_options(&self) -> Result<Vec<ModIoTagOption>, Box<dyn Error>> {
+        let url = format!("{}/games/{}/tags", self.get_api_url(), MOD_IO_GAME_ID);
+        
+        debug!("Fetching tag options from mod.io: {}", url);
+        
+        let response: ModIoTagOptionsResponse = self.get_json_cached(self.get(&url), true)?;
+        
+        Ok(response.data.into_iter().filter(|option| !option.hidden).collect())
     }
     
-    pub fn get_mod_by_id(&self, mod_id: u32) -> Result<ModIoMod, Box<dyn Error>> {
-        let url = format!("{}/games/{}/mods/{}", 
+    // All files uploaded for a mod, newest first
+    pub fn get_mod_files(&self, mod_id: u32) -> Result<Vec<ModIoFile>, Box<dyn Error>> {
+        let url = format!("{}/games/{}/mods/{}/files?_sort=-date_added",
                          self.get_api_url(), MOD_IO_GAME_ID, mod_id);
         
-        println!("Fetching mod details from mod.io: {}", url);
+        debug!("Fetching mod files from mod.io: {}", url);
         
-        let response = self.client.get(&url)
-            .header("Accept", "application/json")
-            .send()?
-            .json::<ModIoMod>()?;
+        let response = self.get(&url)
+            .send_with_backoff()?
+            .error_for_status()?
+            .json::<ModIoFilesResponse>()?;
         
-        Ok(response)
+        Ok(response.data)
+    }
+    
+    // Start downloading a file; the caller streams the body to disk
+    // Starts at `offset` bytes when resuming; the caller checks the status, since an
+    // expired link answers 403 or 410
+    pub fn download_modfile(&self, mod_id: u32, file_id: u32, offset: u64) -> Result<Response, Box<dyn Error>> {
+        // Fetch the file again for a binary_url that hasn't expired
+        let url = format!("{}/games/{}/mods/{}/files/{}",
+                         self.get_api_url(), MOD_IO_GAME_ID, mod_id, file_id);
+        let file = self.get(&url)
+            .send_with_backoff()?
+            .error_for_status()?
+            .json::<ModIoFile>()?;
+        let download = file.download.ok_or("mod.io returned no download link for this file")?;
+        
+        debug!("Downloading mod file from mod.io: {}", download.binary_url);
+        
+        // No overall timeout: large sound packs can take minutes to download
+        let client = Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(30))
+            .timeout(None)
+            .build()?;
+        let mut request = client.get(&download.binary_url);
+        if let Some(token) = &self.oauth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        if offset > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+        }
+        
+        Ok(request.send()?)
     }
 
-    pub fn parse_mod_io_url(url: &str) -> Option<(String, u32)> {
-        // List of supported games
-        const SUPPORTED_GAMES: &[&str] = &["drg", "deeprockgalactic"];
+    // Every DRG mod the token's owner is subscribed to on mod.io
+    pub fn get_subscriptions(&self) -> Result<Vec<ModIoMod>, Box<dyn Error>> {
+        const PAGE_SIZE: usize = 100;
+        if self.oauth_token.is_none() {
+            return Err("Listing subscriptions needs a mod.io OAuth token".into());
+        }
         
-        // Parse URLs like "https://mod.io/g/drg/m/mod-hub#description"
-        if url.contains("mod.io/g/") {
-            // Extract the game name from the URL
-            let parts: Vec<&str> = url.split("/g/").collect();
-            if parts.len() > 1 {
-                let game_parts: Vec<&str> = parts[1].split('/').collect();
-                if game_parts.is_empty() {
-                    return None;
-                }
-                
-                let game_name = game_parts[0].to_lowercase();
-                
-                // Check if the game is supported
-                if !SUPPORTED_GAMES.contains(&game_name.as_str()) {
-                    return None;
-                }
-                
-                // Extract the mod name from the URL
-                if url.contains("/m/") {
-                    let mod_parts: Vec<&str> = url.split("/m/").collect();
-                    if mod_parts.len() > 1 {
-                        // Extract just the mod name, removing any fragments or query parameters
-                        let mod_name_with_extras = mod_parts[1];
-                        let mod_name = mod_name_with_extras
-                            .split('#').next().unwrap_or(mod_name_with_extras) // Remove fragment
-                            .split('?').next().unwrap_or(mod_name_with_extras); // Remove query parameters
-                        
-                        // For now, we'll just return a dummy ID with the game name
-                        // In a real implementation, you would query the mod.io API to get the actual mod ID
-                        return Some((game_name.to_string(), 12345));
-                    }
-                }
+        let mut subscriptions = Vec::new();
+        loop {
+            let url = format!("{}/me/subscribed?game_id={}&_offset={}&_limit={}",
+                             self.get_api_url(), MOD_IO_GAME_ID, subscriptions.len(), PAGE_SIZE);
+            
+            debug!("Fetching subscriptions from mod.io: {}", url);
+            
+            let page = self.get(&url)
+                .send_with_backoff()?
+                .error_for_status()?
+                .json::<ModIoResponse>()?
+                .data;
+            let last_page = page.len() < PAGE_SIZE;
+            subscriptions.extend(page);
+            if last_page {
+                return Ok(subscriptions);
             }
         }
-        None
     }
-
-/*
-    pub fn get_mods(&self, offset: u32, limit: u32) -> Result<Vec<ModIoMod>, Box<dyn Error>> {
-        let url = format!("{}/games/{}/mods?offset={}&limit={}", 
-                         MOD_IO_API_URL, MOD_IO_GAME_ID, offset, limit);
+    
+    pub fn set_subscribed(&self, mod_id: u32, subscribed: bool) -> Result<(), Box<dyn Error>> {
+        let token = self.oauth_token.as_ref().ok_or("Subscribing needs a mod.io OAuth token")?;
+        let url = format!("{}/games/{}/mods/{}/subscribe",
+                         self.get_api_url(), MOD_IO_GAME_ID, mod_id);
         
-        println!("Fetching mods from mod.io: {}", url);
+        debug!("{} on mod.io: {}", if subscribed { "Subscribing" } else { "Unsubscribing" }, url);
         
-        let response = self.client.get(&url)
+        let request = if subscribed {
+            self.client.post(&url)
+        } else {
+            self.client.delete(&url)
+        };
+        request
             .header("Accept", "application/json")
-            .send()?
-            .json::<ModIoResponse>()?;
+            .header("Authorization", format!("Bearer {}", token))
+            .send_with_backoff()?
+            .error_for_status()?;
+        
+        Ok(())
+    }
+    
+    // Mods this mod requires, including the dependencies of those
+    pub fn get_mod_dependencies(&self, mod_id: u32) -> Result<Vec<ModIoDependency>, Box<dyn Error>> {
+        let url = format!("{}/games/{}/mods/{}/dependencies?recursive=true",
+                         self.get_api_url(), MOD_IO_GAME_ID, mod_id);
+        
+        debug!("Fetching mod dependencies from mod.io: {}", url);
+        
+        let response = self.get(&url)
+            .send_with_backoff()?
+            .error_for_status()?
+            .json::<ModIoDependenciesResponse>()?;
         
         Ok(response.data)
     }
     
-    pub fn get_mod_by_id(&self, mod_id: u32) -> Result<ModIoMod, Box<dyn Error>> {
-        let url = format!("{}/games/{}/mods/{}", 
-                         MOD_IO_API_URL, MOD_IO_GAME_ID, mod_id);
+    // Rate a mod for the user owning the OAuth token
+    pub fn rate_mod(&self, mod_id: u32, positive: bool) -> Result<(), Box<dyn Error>> {
+        let token = self.oauth_token.as_ref().ok_or("Rating mods needs a mod.io OAuth token")?;
+        let url = format!("{}/games/{}/mods/{}/ratings",
+                         self.get_api_url(), MOD_IO_GAME_ID, mod_id);
         
-        println!("Fetching mod details from mod.io: {}", url);
+        debug!("Rating mod on mod.io: {}", url);
         
-        let response = self.client.get(&url)
+        self.client.post(&url)
             .header("Accept", "application/json")
-            .send()?
-            .json::<ModIoMod>()?;
+            .header("Authorization", format!("Bearer {}", token))
+            .form(&[("rating", if positive { "1" } else { "-1" })])
+            .send_with_backoff()?
+            .error_for_status()?;
+        
+        Ok(())
+    }
+
+    // Descriptions are HTML; keep the text with rough line breaks
+    pub fn html_to_text(html: &str) -> String {
+        let mut text = String::new();
+        let mut tag = String::new();
+        let mut in_tag = false;
+        for c in html.chars() {
+            match c {
+                '<' => {
+                    in_tag = true;
+                    tag.clear();
+                },
+                '>' if in_tag => {
+                    in_tag = false;
+                    let name = tag.trim_start_matches('/').split_whitespace().next().unwrap_or("").to_lowercase();
+                    if matches!(name.as_str(), "br" | "br/" | "p" | "div" | "li" | "h1" | "h2" | "h3" | "h4") {
+                        text.push('\n');
+                    }
+                },
+                _ if in_tag => tag.push(c),
+                _ => text.push(c),
+            }
+        }
+        
+        let text = text
+            .replace("&nbsp;", " ")
+            .replace("&lt;", "<")
+            .replace("&gt;", ">")
+            .replace("&quot;", "\"")
+            .replace("&#39;", "'")
+            .replace("&amp;", "&");
         
-        Ok(response)
+        // Collapse the blank lines left by nested block tags
+        let mut result = String::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() && (result.is_empty() || result.ends_with("\n\n")) {
+                continue;
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.trim_end().to_string()
+    }
+
+    // Many mods depend on the Mod Hub framework and say so in their description
+    pub fn mentions_mod_hub(text: &str) -> bool {
+        let normalized: String = text
+            .to_lowercase()
+            .chars()
+            .filter(|c| c.is_alphanumeric())
+            .collect();
+        normalized.contains("modhub")
     }
-*/
     
     pub fn convert_to_mod_entry(&self, mod_io_mod: &ModIoMod) -> crate::db::ModEntry {
+        // Prefer the readable slug for the link when mod.io provides one
+        let slug = if mod_io_mod.name_id.is_empty() {
+            mod_io_mod.id.to_string()
+        } else {
+            mod_io_mod.name_id.clone()
+        };
+        
+        let version = mod_io_mod.modfile
+            .as_ref()
+            .map(ModIoFile::version_label)
+            .unwrap_or_else(|| "1.0.0".to_string());
+        
         crate::db::ModEntry {
             mod_id: format!("modio_{}", mod_io_mod.id),
             mod_name: mod_io_mod.name.clone(),
-            mod_link: format!("https://mod.io/g/drg/m/{}", mod_io_mod.id),
+            mod_link: format!("https://mod.io/g/drg/m/{}", slug),
             download_folder: "downloads".to_string(),
-            selected_version: "1.0.0".to_string(), // Default version
+            // Start from the current release; older ones are fetched when needed
+            selected_version: version.clone(),
+            versions: vec![version],
             installed: false,
             enabled: false,
+            variants: Vec::new(),
+            selected_variant: String::new(),
+            last_checked: None,
+            disabled_reason: None,
+            thumbnail_url: Some(mod_io_mod.logo.thumb_320x180.clone())
+                .filter(|url| !url.is_empty()),
+            update_available: None,
+            notes: String::new(),
         }
     }
-
-/*
-    pub fn list_user_games(&self, api_key: &str) -> Result<(), Box<dyn Error>> {
-        let url = format!("{}/me/games", MOD_IO_API_URL);
-        
-        println!("Fetching user games from mod.io: {}", url);
-        
-        let response = self.client.get(&url)
-            .header("Accept", "application/json")
-            .header("Authorization", format!("Bearer {}", api_key))
-            .send()?;
-        
-        // Check if the request was successful
-        if response.status().is_success() {
-            // Get the response body as text
-            let body = response.text()?;
-            
-            // Debug print the response
-            println!("Response from mod.io API:");
-            println!("{}", body);
-            
-            // In a real implementation, you would parse this into a struct
-            // let games: ModIoGamesResponse = serde_json::from_str(&body)?;
-            // return Ok(games);
-        } else {
-            println!("Error fetching user games: HTTP {}", response.status());
-            if let Ok(error_text) = response.text() {
-                println!("Error details: {}", error_text);
-            }
-        }
-        
-        Ok(())
-    }
-*/
 }
 
 impl Default for ModIoClient {
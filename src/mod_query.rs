@@ -0,0 +1,456 @@
+use crate::app::{ListSort, Tab, TableColumn};
+use crate::db::ModEntry;
+use crate::download_failure;
+use crate::mod_io::ModIoMod;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// Which mods a list shows and in what order, kept apart from the UI so it can be tested
+pub struct ModQuery<'a> {
+    pub tab: &'a Tab,
+    pub search: &'a str,
+    // Browse tab only
+    pub installed_only: bool,
+    // Starred mods by mod_id, and whether only those are shown
+    pub favorites: &'a HashSet<String>,
+    pub favorites_only: bool,
+    // mod.io tags of which a mod needs at least one; empty shows everything
+    pub tags: &'a BTreeSet<String>,
+    // The user's own tags of each mod, and those of which a mod needs at least one
+    pub local_tags: &'a HashMap<String, BTreeSet<String>>,
+    pub local_tag_filter: &'a BTreeSet<String>,
+    pub sort: ListSort,
+    // Column of the table layout and whether descending; replaces `sort` when set
+    pub table_sort: Option<(TableColumn, bool)>,
+    pub metadata: &'a HashMap<String, ModIoMod>,
+    pub downloading: HashSet<&'a str>,
+    pub archived: HashSet<&'a str>,
+}
+
+impl ModQuery<'_> {
+    // Matching mods of the list, sorted
+    pub fn run(&self, mods: &[ModEntry]) -> Vec<ModEntry> {
+        let mut matching: Vec<ModEntry> = mods.iter().filter(|m| self.matches(m)).cloned().collect();
+        self.sort_list(&mut matching);
+        matching
+    }
+
+    pub fn matches(&self, mod_entry: &ModEntry) -> bool {
+        if !self.matches_search(mod_entry) || !self.matches_tags(mod_entry) || !self.matches_local_tags(mod_entry) {
+            return false;
+        }
+        if self.favorites_only && !self.favorites.contains(&mod_entry.mod_id) {
+            return false;
+        }
+        match self.tab {
+            Tab::Browse => !self.installed_only || mod_entry.installed,
+            // Only mods installed in the current profile
+            Tab::Installed => mod_entry.installed,
+            Tab::Downloads | Tab::Settings => false,
+        }
+    }
+
+    // Case-insensitive part of the name or the notes
+    pub fn matches_search(&self, mod_entry: &ModEntry) -> bool {
+        let search = self.search.to_lowercase();
+        mod_entry.mod_name.to_lowercase().contains(&search) || mod_entry.notes.to_lowercase().contains(&search)
+    }
+
+    // Mods without cached metadata have no tags, so they only show without a tag filter
+    pub fn matches_tags(&self, mod_entry: &ModEntry) -> bool {
+        if self.tags.is_empty() {
+            return true;
+        }
+        self.metadata
+            .get(&mod_entry.mod_id)
+            .is_some_and(|metadata| metadata.tags.iter().any(|tag| self.tags.contains(&tag.name)))
+    }
+
+    pub fn matches_local_tags(&self, mod_entry: &ModEntry) -> bool {
+        if self.local_tag_filter.is_empty() {
+            return true;
+        }
+        self.local_tags
+            .get(&mod_entry.mod_id)
+            .is_some_and(|tags| !tags.is_disjoint(self.local_tag_filter))
+    }
+
+    // Mods without mod.io metadata go last for the mod.io orderings
+    pub fn sort_list(&self, mods: &mut [ModEntry]) {
+        let metadata = |mod_entry: &ModEntry| self.metadata.get(&mod_entry.mod_id);
+        match self.sort {
+            ListSort::Name => mods.sort_by_key(|m| m.mod_name.to_lowercase()),
+            ListSort::RecentlyUpdated => {
+                mods.sort_by_key(|m| std::cmp::Reverse(metadata(m).map(|d| d.date_updated)));
+            },
+            ListSort::MostDownloaded => {
+                mods.sort_by_key(|m| std::cmp::Reverse(metadata(m).map(|d| d.stats.downloads_total)));
+            },
+            ListSort::MostSubscribed => {
+                mods.sort_by_key(|m| std::cmp::Reverse(metadata(m).map(|d| d.stats.subscribers_total)));
+            },
+            ListSort::HighestRated => {
+                let rating = |m: &ModEntry| metadata(m).map_or(-1.0, |d| d.stats.ratings_weighted_aggregate);
+                mods.sort_by(|a, b| rating(b).total_cmp(&rating(a)));
+            },
+            // Enabled first, then installed, then the rest
+            ListSort::InstallStatus => mods.sort_by_key(|m| (!m.enabled, !m.installed, m.mod_name.to_lowercase())),
+        }
+    }
+
+    // Sorts stably, so mods that tie keep the list order
+    pub fn sort_table(&self, mods: &mut [ModEntry]) {
+        let Some((column, descending)) = self.table_sort else {
+            return;
+        };
+        match column {
+            TableColumn::Name => mods.sort_by_key(|m| m.mod_name.to_lowercase()),
+            TableColumn::Version => mods.sort_by(|a, b| a.selected_version.cmp(&b.selected_version)),
+            // Most active first, like the Install status ordering
+            TableColumn::Status => mods.sort_by_key(|m| {
+                let id = m.mod_id.as_str();
+                (!self.downloading.contains(id), !m.enabled, !m.installed, !self.archived.contains(id))
+            }),
+            TableColumn::Source => mods.sort_by_key(download_failure::source_of),
+            TableColumn::Size => mods.sort_by_key(|m| self.size(m)),
+            TableColumn::LastUpdated => mods.sort_by_key(|m| self.updated_at(m)),
+        }
+        if descending {
+            mods.reverse();
+        }
+    }
+
+    // Text of the Status column
+    pub fn status(&self, mod_entry: &ModEntry) -> &'static str {
+        if self.downloading.contains(mod_entry.mod_id.as_str()) {
+            "Downloading"
+        } else if mod_entry.enabled {
+            "Enabled"
+        } else if mod_entry.installed {
+            "Installed"
+        } else if self.archived.contains(mod_entry.mod_id.as_str()) {
+            "Archived"
+        } else {
+            "Not installed"
+        }
+    }
+
+    // Size of the mod.io file, when its metadata is cached
+    pub fn size(&self, mod_entry: &ModEntry) -> Option<u64> {
+        self.metadata.get(&mod_entry.mod_id)?.modfile.as_ref().map(|file| file.filesize)
+    }
+
+    pub fn updated_at(&self, mod_entry: &ModEntry) -> Option<i64> {
+        self.metadata.get(&mod_entry.mod_id).map(|metadata| metadata.date_updated)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(mod_id: &str, mod_name: &str, installed: bool, enabled: bool) -> ModEntry {
+        ModEntry {
+            mod_id: mod_id.to_string(),
+            mod_name: mod_name.to_string(),
+            mod_link: format!("https://mod.io/g/drg/m/{}", mod_id),
+            download_folder: "downloads".to_string(),
+            selected_version: "1.0".to_string(),
+            versions: vec!["1.0".to_string()],
+            installed,
+            enabled,
+            variants: Vec::new(),
+            selected_variant: String::new(),
+            last_checked: None,
+            disabled_reason: None,
+            thumbnail_url: None,
+            update_available: None,
+            notes: String::new(),
+        }
+    }
+
+    fn metadata(id: u32, date_updated: i64, downloads: u32, rating: f32, tags: &[&str], filesize: Option<u64>) -> ModIoMod {
+        let tags: Vec<_> = tags.iter().map(|tag| serde_json::json!({ "name": tag })).collect();
+        let modfile = filesize.map(|filesize| serde_json::json!({
+            "id": 1, "filename": "mod.zip", "filesize": filesize, "date_added": 0
+        }));
+        serde_json::from_value(serde_json::json!({
+            "id": id,
+            "name": format!("mod {}", id),
+            "summary": "",
+            "logo": { "filename": "", "original": "", "thumb_320x180": "" },
+            "submitted_by": { "username": "author", "profile_url": "" },
+            "date_added": 0,
+            "date_updated": date_updated,
+            "stats": {
+                "downloads_total": downloads,
+                "subscribers_total": downloads / 2,
+                "ratings_weighted_aggregate": rating
+            },
+            "modfile": modfile,
+            "tags": tags
+        }))
+        .expect("valid mod.io mod")
+    }
+
+    struct Fixture {
+        tab: Tab,
+        tags: BTreeSet<String>,
+        local_tags: HashMap<String, BTreeSet<String>>,
+        local_tag_filter: BTreeSet<String>,
+        favorites: HashSet<String>,
+        metadata: HashMap<String, ModIoMod>,
+    }
+
+    impl Fixture {
+        fn new(tab: Tab) -> Self {
+            Self {
+                tab,
+                tags: BTreeSet::new(),
+                local_tags: HashMap::new(),
+                local_tag_filter: BTreeSet::new(),
+                favorites: HashSet::new(),
+                metadata: HashMap::new(),
+            }
+        }
+
+        fn query(&self) -> ModQuery<'_> {
+            ModQuery {
+                tab: &self.tab,
+                search: "",
+                installed_only: false,
+                favorites: &self.favorites,
+                favorites_only: false,
+                tags: &self.tags,
+                local_tags: &self.local_tags,
+                local_tag_filter: &self.local_tag_filter,
+                sort: ListSort::Name,
+                table_sort: None,
+                metadata: &self.metadata,
+                downloading: HashSet::new(),
+                archived: HashSet::new(),
+            }
+        }
+    }
+
+    fn names(mods: &[ModEntry]) -> Vec<&str> {
+        mods.iter().map(|m| m.mod_name.as_str()).collect()
+    }
+
+    fn library() -> Vec<ModEntry> {
+        vec![
+            entry("modio_1", "Better Spawns", true, true),
+            entry("modio_2", "another HUD", true, false),
+            entry("local_3", "Custom Skins", false, false),
+        ]
+    }
+
+    #[test]
+    fn browse_shows_every_mod() {
+        let fixture = Fixture::new(Tab::Browse);
+        assert_eq!(names(&fixture.query().run(&library())), ["another HUD", "Better Spawns", "Custom Skins"]);
+    }
+
+    #[test]
+    fn browse_can_hide_mods_that_are_not_installed() {
+        let fixture = Fixture::new(Tab::Browse);
+        let query = ModQuery { installed_only: true, ..fixture.query() };
+        assert_eq!(names(&query.run(&library())), ["another HUD", "Better Spawns"]);
+    }
+
+    #[test]
+    fn installed_tab_shows_only_installed_mods() {
+        let fixture = Fixture::new(Tab::Installed);
+        assert_eq!(names(&fixture.query().run(&library())), ["another HUD", "Better Spawns"]);
+    }
+
+    #[test]
+    fn settings_tab_shows_no_mods() {
+        let fixture = Fixture::new(Tab::Settings);
+        assert!(fixture.query().run(&library()).is_empty());
+    }
+
+    #[test]
+    fn favorites_filter_shows_only_starred_mods_on_both_tabs() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        fixture.favorites.insert("modio_2".to_string());
+        fixture.favorites.insert("local_3".to_string());
+        let query = ModQuery { favorites_only: true, ..fixture.query() };
+        assert_eq!(names(&query.run(&library())), ["another HUD", "Custom Skins"]);
+
+        fixture.tab = Tab::Installed;
+        let query = ModQuery { favorites_only: true, ..fixture.query() };
+        assert_eq!(names(&query.run(&library())), ["another HUD"]);
+    }
+
+    #[test]
+    fn search_ignores_case_and_matches_inside_names() {
+        let fixture = Fixture::new(Tab::Browse);
+        let query = ModQuery { search: "HUD", ..fixture.query() };
+        assert_eq!(names(&query.run(&library())), ["another HUD"]);
+        let query = ModQuery { search: "spawn", ..fixture.query() };
+        assert_eq!(names(&query.run(&library())), ["Better Spawns"]);
+        let query = ModQuery { search: "missing", ..fixture.query() };
+        assert!(query.run(&library()).is_empty());
+    }
+
+    #[test]
+    fn search_matches_notes() {
+        let fixture = Fixture::new(Tab::Browse);
+        let mut mods = library();
+        mods[2].notes = "Use with the Purple Haze preset".to_string();
+        let query = ModQuery { search: "purple", ..fixture.query() };
+        assert_eq!(names(&query.run(&mods)), ["Custom Skins"]);
+    }
+
+    #[test]
+    fn tag_filter_needs_one_matching_tag() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        fixture.metadata.insert("modio_1".to_string(), metadata(1, 0, 0, 0.0, &["QoL", "Verified"], None));
+        fixture.metadata.insert("modio_2".to_string(), metadata(2, 0, 0, 0.0, &["Cosmetic"], None));
+        fixture.tags.insert("QoL".to_string());
+        fixture.tags.insert("Tools".to_string());
+        assert_eq!(names(&fixture.query().run(&library())), ["Better Spawns"]);
+    }
+
+    #[test]
+    fn tag_filter_hides_mods_without_metadata() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        fixture.tags.insert("QoL".to_string());
+        assert!(fixture.query().run(&library()).is_empty());
+    }
+
+    #[test]
+    fn local_tag_filter_needs_one_matching_tag_and_ignores_mod_io_tags() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        fixture.local_tags.insert("modio_1".to_string(), BTreeSet::from(["audio".to_string()]));
+        fixture.local_tags.insert("local_3".to_string(), BTreeSet::from(["must-have".to_string(), "testing".to_string()]));
+        fixture.metadata.insert("modio_2".to_string(), metadata(2, 0, 0, 0.0, &["testing"], None));
+        fixture.local_tag_filter.insert("testing".to_string());
+        fixture.local_tag_filter.insert("unused".to_string());
+        assert_eq!(names(&fixture.query().run(&library())), ["Custom Skins"]);
+    }
+
+    #[test]
+    fn name_sort_ignores_case() {
+        let fixture = Fixture::new(Tab::Browse);
+        let mut mods = library();
+        fixture.query().sort_list(&mut mods);
+        assert_eq!(names(&mods), ["another HUD", "Better Spawns", "Custom Skins"]);
+    }
+
+    #[test]
+    fn install_status_sort_puts_enabled_then_installed_first() {
+        let fixture = Fixture::new(Tab::Browse);
+        let query = ModQuery { sort: ListSort::InstallStatus, ..fixture.query() };
+        let mut mods = library();
+        mods.push(entry("modio_4", "Zero Gravity", true, true));
+        query.sort_list(&mut mods);
+        assert_eq!(names(&mods), ["Better Spawns", "Zero Gravity", "another HUD", "Custom Skins"]);
+    }
+
+    #[test]
+    fn mod_io_sorts_put_mods_without_metadata_last() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        fixture.metadata.insert("modio_1".to_string(), metadata(1, 100, 50, 0.9, &[], None));
+        fixture.metadata.insert("modio_2".to_string(), metadata(2, 200, 500, 0.4, &[], None));
+
+        let cases = [
+            (ListSort::RecentlyUpdated, ["another HUD", "Better Spawns", "Custom Skins"]),
+            (ListSort::MostDownloaded, ["another HUD", "Better Spawns", "Custom Skins"]),
+            (ListSort::MostSubscribed, ["another HUD", "Better Spawns", "Custom Skins"]),
+            (ListSort::HighestRated, ["Better Spawns", "another HUD", "Custom Skins"]),
+        ];
+        for (sort, expected) in cases {
+            let query = ModQuery { sort, ..fixture.query() };
+            let mut mods = library();
+            query.sort_list(&mut mods);
+            assert_eq!(names(&mods), expected, "sorted by {}", sort.label());
+        }
+    }
+
+    #[test]
+    fn table_sort_keeps_list_order_until_a_column_is_picked() {
+        let fixture = Fixture::new(Tab::Browse);
+        let mut mods = library();
+        fixture.query().sort_table(&mut mods);
+        assert_eq!(names(&mods), ["Better Spawns", "another HUD", "Custom Skins"]);
+    }
+
+    #[test]
+    fn table_sort_reverses_when_descending() {
+        let fixture = Fixture::new(Tab::Browse);
+        let query = ModQuery { table_sort: Some((TableColumn::Name, true)), ..fixture.query() };
+        let mut mods = library();
+        query.sort_table(&mut mods);
+        assert_eq!(names(&mods), ["Custom Skins", "Better Spawns", "another HUD"]);
+    }
+
+    #[test]
+    fn table_status_sort_puts_downloads_first_and_archived_before_the_rest() {
+        let fixture = Fixture::new(Tab::Browse);
+        let mut mods = library();
+        mods.push(entry("local_4", "Archived Mod", false, false));
+        let query = ModQuery {
+            table_sort: Some((TableColumn::Status, false)),
+            downloading: HashSet::from(["local_3"]),
+            archived: HashSet::from(["local_4"]),
+            ..fixture.query()
+        };
+        query.sort_table(&mut mods);
+        assert_eq!(names(&mods), ["Custom Skins", "Better Spawns", "another HUD", "Archived Mod"]);
+        assert_eq!(query.status(&mods[0]), "Downloading");
+        assert_eq!(query.status(&mods[1]), "Enabled");
+        assert_eq!(query.status(&mods[2]), "Installed");
+        assert_eq!(query.status(&mods[3]), "Archived");
+    }
+
+    #[test]
+    fn table_size_sort_puts_unknown_sizes_first() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        fixture.metadata.insert("modio_1".to_string(), metadata(1, 0, 0, 0.0, &[], Some(2048)));
+        fixture.metadata.insert("modio_2".to_string(), metadata(2, 0, 0, 0.0, &[], Some(1024)));
+        let query = ModQuery { table_sort: Some((TableColumn::Size, false)), ..fixture.query() };
+        let mut mods = library();
+        query.sort_table(&mut mods);
+        assert_eq!(names(&mods), ["Custom Skins", "another HUD", "Better Spawns"]);
+        assert_eq!(query.size(&mods[0]), None);
+        assert_eq!(query.size(&mods[2]), Some(2048));
+    }
+
+    #[test]
+    fn table_source_sort_groups_by_host() {
+        let fixture = Fixture::new(Tab::Browse);
+        let mut mods = library();
+        mods[2].mod_link = "https://github.com/author/skins/releases/download/v1/skins.zip".to_string();
+        let query = ModQuery { table_sort: Some((TableColumn::Source, false)), ..fixture.query() };
+        query.sort_table(&mut mods);
+        assert_eq!(names(&mods), ["Custom Skins", "Better Spawns", "another HUD"]);
+    }
+
+    // Timing over a large library; run with `cargo test --release -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn bench_filter_and_sort_large_library() {
+        let mut fixture = Fixture::new(Tab::Browse);
+        let mods: Vec<ModEntry> = (0..10_000)
+            .map(|i| entry(&format!("modio_{}", i), &format!("Mod {:05}", (i * 7919) % 10_000), i % 3 != 0, i % 5 == 0))
+            .collect();
+        for i in (0..10_000).step_by(2) {
+            fixture.metadata.insert(format!("modio_{}", i), metadata(i, i64::from(i), i * 3, 0.5, &["QoL"], Some(u64::from(i))));
+        }
+
+        for sort in ListSort::ALL {
+            let query = ModQuery { sort, search: "mod 1", ..fixture.query() };
+            let started = std::time::Instant::now();
+            let shown = query.run(&mods).len();
+            println!("{:<20} {:>6} mods in {:?}", sort.label(), shown, started.elapsed());
+        }
+        for column in TableColumn::ALL {
+            let query = ModQuery { table_sort: Some((column, false)), ..fixture.query() };
+            let mut shown = mods.clone();
+            let started = std::time::Instant::now();
+            query.sort_table(&mut shown);
+            println!("{:<20} {:>6} mods in {:?}", column.label(), shown.len(), started.elapsed());
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use reqwest::Url;
+
+// Game slugs, and legacy subdomains, mod.io uses for Deep Rock Galactic
+const SUPPORTED_GAMES: &[&str] = &["drg", "deeprockgalactic"];
+
+// A mod.io mod referred to by a link to its web page
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedModRef {
+    // Numeric mod ID, as in links built from the game's own mod list
+    Id(u32),
+    // The mod's URL slug, its name_id in the API
+    NameId(String),
+}
+
+impl ParsedModRef {
+    // Accepts mod.io/g/drg/m/<mod> with or without a locale prefix and the legacy drg.mod.io/<mod>.
+    // Query strings, fragments, trailing slashes and subpages such as /files are ignored
+    pub fn parse(link: &str) -> Option<Self> {
+        let url = Url::parse(link.trim()).ok()?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return None;
+        }
+        let host = url.host_str()?.to_lowercase();
+        let host = host.strip_prefix("www.").unwrap_or(&host);
+        let segments: Vec<&str> = url.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+
+        let slug = if host == "mod.io" {
+            let segments = match segments.as_slice() {
+                [locale, rest @ ..] if is_locale(locale) => rest,
+                segments => segments,
+            };
+            match segments {
+                ["g", game, "m", slug, ..] if is_supported_game(game) => *slug,
+                _ => return None,
+            }
+        } else {
+            let game = host.strip_suffix(".mod.io")?;
+            if !is_supported_game(game) {
+                return None;
+            }
+            *segments.first()?
+        };
+
+        Some(Self::from_slug(slug))
+    }
+
+    // Also accepts the short forms typed into the add field: @<slug>, drg/<slug> and a bare mod ID
+    pub fn parse_input(input: &str) -> Option<Self> {
+        let input = input.trim();
+        if let Some(parsed) = Self::parse(input) {
+            return Some(parsed);
+        }
+
+        let slug = match input.strip_prefix('@') {
+            Some(slug) => slug,
+            None => match input.split_once('/') {
+                Some((game, slug)) if is_supported_game(game) => slug,
+                Some(_) => return None,
+                None if input.bytes().all(|b| b.is_ascii_digit()) => input,
+                None => return None,
+            },
+        };
+        let valid = !slug.is_empty() && slug.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then(|| Self::from_slug(slug))
+    }
+
+    fn from_slug(slug: &str) -> Self {
+        let slug = slug.to_lowercase();
+        match slug.parse() {
+            Ok(id) if slug.bytes().all(|b| b.is_ascii_digit()) => ParsedModRef::Id(id),
+            _ => ParsedModRef::NameId(slug),
+        }
+    }
+}
+
+fn is_supported_game(game: &str) -> bool {
+    SUPPORTED_GAMES.contains(&game.to_lowercase().as_str())
+}
+
+// Language prefixes like "en" or "pt-br"
+fn is_locale(segment: &str) -> bool {
+    let mut parts = segment.split('-');
+    let language = parts.next().unwrap_or_default();
+    let region = parts.next();
+    language.len() == 2
+        && language.chars().all(|c| c.is_ascii_alphabetic())
+        && region.is_none_or(|region| (2..=4).contains(&region.len()) && region.chars().all(|c| c.is_ascii_alphanumeric()))
+        && parts.next().is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_id(slug: &str) -> Option<ParsedModRef> {
+        Some(ParsedModRef::NameId(slug.to_string()))
+    }
+
+    #[test]
+    fn parses_mod_pages_with_and_without_locale() {
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/mod-hub"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("https://mod.io/en/g/drg/m/mod-hub"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("https://mod.io/pt-br/g/drg/m/mod-hub"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("http://www.mod.io/g/deeprockgalactic/m/mod-hub"), name_id("mod-hub"));
+    }
+
+    #[test]
+    fn ignores_query_fragment_trailing_slash_and_subpages() {
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/mod-hub/"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/mod-hub?tab=files#top"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/mod-hub/files/123"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("  https://mod.io/g/drg/m/mod-hub  "), name_id("mod-hub"));
+    }
+
+    #[test]
+    fn parses_legacy_game_subdomains() {
+        assert_eq!(ParsedModRef::parse("https://drg.mod.io/mod-hub"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse("https://deeprockgalactic.mod.io/mod-hub/files"), name_id("mod-hub"));
+    }
+
+    #[test]
+    fn numeric_slugs_are_ids_and_slugs_are_lowercased() {
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/1234567"), Some(ParsedModRef::Id(1234567)));
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/DRG/m/Mod-Hub"), name_id("mod-hub"));
+        // Too large for an ID, so it can only be a slug
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/99999999999"), name_id("99999999999"));
+    }
+
+    #[test]
+    fn rejects_other_games_hosts_and_pages() {
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/other-game/m/mod-hub"), None);
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg"), None);
+        assert_eq!(ParsedModRef::parse("https://mod.io/g/drg/m/"), None);
+        assert_eq!(ParsedModRef::parse("https://mod.io/english/g/drg/m/mod-hub"), None);
+        assert_eq!(ParsedModRef::parse("https://othergame.mod.io/mod-hub"), None);
+        assert_eq!(ParsedModRef::parse("https://drg.mod.io/"), None);
+        assert_eq!(ParsedModRef::parse("https://example.com/g/drg/m/mod-hub"), None);
+        assert_eq!(ParsedModRef::parse("ftp://mod.io/g/drg/m/mod-hub"), None);
+        assert_eq!(ParsedModRef::parse("mod-hub"), None);
+    }
+
+    #[test]
+    fn input_accepts_short_forms() {
+        assert_eq!(ParsedModRef::parse_input("@mod-hub"), name_id("mod-hub"));
+        assert_eq!(ParsedModRef::parse_input("drg/Mod_Hub"), name_id("mod_hub"));
+        assert_eq!(ParsedModRef::parse_input(" 1234567 "), Some(ParsedModRef::Id(1234567)));
+        assert_eq!(ParsedModRef::parse_input("@1234567"), Some(ParsedModRef::Id(1234567)));
+        assert_eq!(ParsedModRef::parse_input("https://mod.io/g/drg/m/mod-hub"), name_id("mod-hub"));
+    }
+
+    #[test]
+    fn input_rejects_bare_names_and_invalid_slugs() {
+        assert_eq!(ParsedModRef::parse_input("mod-hub"), None);
+        assert_eq!(ParsedModRef::parse_input("@"), None);
+        assert_eq!(ParsedModRef::parse_input("@mod hub"), None);
+        assert_eq!(ParsedModRef::parse_input("drg/mod/hub"), None);
+        assert_eq!(ParsedModRef::parse_input("other/mod-hub"), None);
+        assert_eq!(ParsedModRef::parse_input(""), None);
+    }
+}
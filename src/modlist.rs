@@ -0,0 +1,181 @@
+use crate::db::ModEntry;
+use crate::mod_url::ParsedModRef;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// The mod list shape community lobby checkers share: mod.io IDs with the release in use
+#[derive(Serialize, Deserialize)]
+pub struct ModList {
+    pub mods: Vec<ModListEntry>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ModListEntry {
+    pub id: u32,
+    #[serde(default)]
+    pub name: String,
+    // Empty when the list doesn't say, in which case the newest release is used
+    #[serde(default)]
+    pub version: String,
+}
+
+// A mod both lists have, on different releases
+#[derive(Serialize)]
+pub struct VersionDifference {
+    pub id: u32,
+    pub name: String,
+    pub mine: String,
+    pub theirs: String,
+}
+
+// How my enabled mods line up with a list someone else shared
+#[derive(Serialize)]
+pub struct ModListComparison {
+    // In their list but not enabled here
+    pub missing: Vec<ModListEntry>,
+    pub version_differs: Vec<VersionDifference>,
+    // Enabled here but not in their list
+    pub extra: Vec<ModListEntry>,
+}
+
+impl ModList {
+    // Enabled mod.io mods; other sources have no ID the tools could check
+    pub fn build(mods: &[ModEntry]) -> Self {
+        let mut entries: Vec<ModListEntry> = mods
+            .iter()
+            .filter(|m| m.enabled)
+            .filter_map(|mod_entry| Some(ModListEntry {
+                id: mod_entry.modio_id()?,
+                name: mod_entry.mod_name.clone(),
+                version: mod_entry.selected_version.clone(),
+            }))
+            .collect();
+        entries.sort_by_key(|entry| entry.id);
+        Self { mods: entries }
+    }
+
+    pub fn to_json(&self) -> Result<String, String> {
+        serde_json::to_string_pretty(self).map_err(|e| format!("Failed to serialize mod list: {}", e))
+    }
+
+    pub fn load(path: &Path) -> Result<Self, String> {
+        std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+            .and_then(|text| Self::parse(&text))
+    }
+
+    // JSON as written by `to_json` or as a bare array, or text with one mod.io ID or link per line,
+    // optionally followed by a version, as lists pasted in chat usually are
+    pub fn parse(text: &str) -> Result<Self, String> {
+        if let Ok(list) = serde_json::from_str::<ModList>(text) {
+            return Ok(list);
+        }
+        if let Ok(mods) = serde_json::from_str::<Vec<ModListEntry>>(text) {
+            return Ok(Self { mods });
+        }
+
+        let mut mods = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let reference = parts.next().unwrap_or_default();
+            let id = match reference.parse() {
+                Ok(id) => id,
+                Err(_) => match ParsedModRef::parse(reference) {
+                    Some(ParsedModRef::Id(id)) => id,
+                    Some(ParsedModRef::NameId(_)) => {
+                        return Err(format!("Line {}: links need the numeric mod ID, not the name", number + 1));
+                    },
+                    None => return Err(format!("Line {}: not a mod.io ID or link: {}", number + 1, reference)),
+                },
+            };
+            mods.push(ModListEntry {
+                id,
+                name: String::new(),
+                version: parts.next().unwrap_or_default().to_string(),
+            });
+        }
+        if mods.is_empty() {
+            return Err("The mod list is empty".to_string());
+        }
+        Ok(Self { mods })
+    }
+}
+
+impl ModListEntry {
+    // Lists pasted as plain IDs carry no names
+    pub fn display_name(&self) -> String {
+        if self.name.is_empty() {
+            format!("mod {}", self.id)
+        } else {
+            self.name.clone()
+        }
+    }
+}
+
+impl ModListComparison {
+    // A release left out of either list counts as matching
+    pub fn build(mine: &ModList, theirs: &ModList) -> Self {
+        let missing = theirs.mods
+            .iter()
+            .filter(|entry| !mine.mods.iter().any(|own| own.id == entry.id))
+            .cloned()
+            .collect();
+        let extra = mine.mods
+            .iter()
+            .filter(|entry| !theirs.mods.iter().any(|other| other.id == entry.id))
+            .cloned()
+            .collect();
+        let version_differs = theirs.mods
+            .iter()
+            .filter_map(|entry| {
+                let own = mine.mods.iter().find(|own| own.id == entry.id)?;
+                if own.version.is_empty() || entry.version.is_empty() || own.version == entry.version {
+                    return None;
+                }
+                Some(VersionDifference {
+                    id: entry.id,
+                    // My library knows the name even when their list doesn't
+                    name: own.display_name(),
+                    mine: own.version.clone(),
+                    theirs: entry.version.clone(),
+                })
+            })
+            .collect();
+        Self { missing, version_differs, extra }
+    }
+
+    pub fn is_match(&self) -> bool {
+        self.missing.is_empty() && self.version_differs.is_empty() && self.extra.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        if self.is_match() {
+            return "Both lists have the same mods and versions.\n".to_string();
+        }
+        
+        let mut text = String::new();
+        if !self.missing.is_empty() {
+            text.push_str(&format!("Missing here ({}):\n", self.missing.len()));
+            for entry in &self.missing {
+                text.push_str(&format!("  {} ({})\n", entry.display_name(), entry.id));
+            }
+        }
+        if !self.version_differs.is_empty() {
+            text.push_str(&format!("Different versions ({}):\n", self.version_differs.len()));
+            for difference in &self.version_differs {
+                text.push_str(&format!("  {} ({}): mine {}, theirs {}\n", difference.name, difference.id, difference.mine, difference.theirs));
+            }
+        }
+        if !self.extra.is_empty() {
+            text.push_str(&format!("Not in their list ({}):\n", self.extra.len()));
+            for entry in &self.extra {
+                text.push_str(&format!("  {} ({})\n", entry.display_name(), entry.id));
+            }
+        }
+        text
+    }
+}
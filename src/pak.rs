@@ -0,0 +1,529 @@
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+
+// Reads the file index of Unreal Engine 4 .pak files, enough to list the assets they contain
+
+const PAK_MAGIC: u32 = 0x5A6F_12E1;
+
+// Bytes from the magic number to the end of the footer, without compression method names
+const FOOTER_TAIL: u64 = 44;
+
+// Where the footer's compression method names push the magic number back, by pak version:
+// none before version 8, four names in 8A, five from 8B on and an extra frozen-index flag in 9
+const NAME_TABLE_SIZES: [u64; 4] = [0, 4 * 32, 5 * 32, 5 * 32 + 1];
+
+// Versions that changed how the index is laid out
+const VERSION_NO_TIMESTAMPS: u32 = 2;
+const VERSION_COMPRESSION_ENCRYPTION: u32 = 3;
+const VERSION_INDEX_ENCRYPTION: u32 = 7;
+const VERSION_NAME_BASED_COMPRESSION: u32 = 8;
+const VERSION_PATH_HASH_INDEX: u32 = 10;
+
+// Largest index read into memory; real mod paks are far below this
+const MAX_INDEX_SIZE: u64 = 256 * 1024 * 1024;
+
+// Marks a compression block size too large for the encoded entry's six bits
+const BLOCK_SIZE_STORED_SEPARATELY: u32 = 0x3f;
+
+// A mebibyte of uncompressed data, or this many files, add one point of load impact
+const IMPACT_BYTES_PER_POINT: f64 = 1024.0 * 1024.0;
+const IMPACT_FILES_PER_POINT: f64 = 50.0;
+
+struct Footer {
+    version: u32,
+    index_offset: u64,
+    index_size: u64,
+    // Pak 8A stores compression methods as a byte, every other version as a u32
+    compression_index_is_byte: bool,
+}
+
+// What the index tells about a pak as a whole
+pub struct PakStats {
+    pub mount_point: String,
+    pub file_count: usize,
+    // Bytes the files take up in the pak, and once the game has decompressed them
+    pub stored_size: u64,
+    pub uncompressed_size: u64,
+}
+
+impl PakStats {
+    // Stored size relative to the uncompressed size; 1.0 when nothing is compressed
+    pub fn compression_ratio(&self) -> f64 {
+        if self.uncompressed_size == 0 {
+            return 1.0;
+        }
+        self.stored_size as f64 / self.uncompressed_size as f64
+    }
+
+    // Rough estimate of what the pak adds to load times: the game reads and decompresses every byte,
+    // and looks up and opens each file on its own
+    pub fn load_impact(&self) -> f64 {
+        self.uncompressed_size as f64 / IMPACT_BYTES_PER_POINT + self.file_count as f64 / IMPACT_FILES_PER_POINT
+    }
+}
+
+pub fn read_stats(path: &Path) -> Result<PakStats, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let footer = read_footer(&mut file)?;
+    let index = read_range(&mut file, footer.index_offset, footer.index_size)?;
+    let mut reader = ByteReader::new(&index);
+
+    let mut stats = PakStats {
+        mount_point: reader.string()?,
+        file_count: reader.count()?,
+        stored_size: 0,
+        uncompressed_size: 0,
+    };
+    let mut add = |(stored, uncompressed): (u64, u64)| {
+        stats.stored_size += stored;
+        stats.uncompressed_size += uncompressed;
+    };
+
+    if footer.version >= VERSION_PATH_HASH_INDEX {
+        // Path hash seed, then where the path hash and directory indexes are
+        reader.skip(8)?;
+        for _ in 0..2 {
+            if reader.u32()? != 0 {
+                reader.skip(8 + 8 + 20)?;
+            }
+        }
+        let encoded_size = reader.count()?;
+        let mut encoded = ByteReader::new(reader.take(encoded_size)?);
+        while !encoded.is_empty() {
+            add(decode_entry(&mut encoded)?);
+        }
+        // Entries that didn't fit the compact encoding
+        for _ in 0..reader.count()? {
+            add(read_entry(&mut reader, &footer)?);
+        }
+    } else {
+        for _ in 0..stats.file_count {
+            reader.string()?;
+            add(read_entry(&mut reader, &footer)?);
+        }
+    }
+    Ok(stats)
+}
+
+// Asset paths inside the pak, relative to the game's root folder, e.g. FSD/Content/Weapons/Foo.uasset
+pub fn read_asset_paths(path: &Path) -> Result<Vec<String>, String> {
+    let mut file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let footer = read_footer(&mut file)?;
+    let index = read_range(&mut file, footer.index_offset, footer.index_size)?;
+    let mut reader = ByteReader::new(&index);
+
+    let mount_point = reader.string()?;
+    let entry_count = reader.count()?;
+
+    let names = if footer.version >= VERSION_PATH_HASH_INDEX {
+        read_directory_index(&mut file, &mut reader)?
+    } else {
+        let mut names = Vec::with_capacity(entry_count.min(65536));
+        for _ in 0..entry_count {
+            names.push(reader.string()?);
+            read_entry(&mut reader, &footer)?;
+        }
+        names
+    };
+
+    Ok(names.iter().map(|name| normalize_asset_path(&mount_point, name)).collect())
+}
+
+fn read_footer(file: &mut File) -> Result<Footer, String> {
+    let len = file.metadata().map_err(|e| e.to_string())?.len();
+
+    for name_table_size in NAME_TABLE_SIZES {
+        let Some(magic_offset) = len.checked_sub(FOOTER_TAIL + name_table_size) else {
+            continue;
+        };
+        let tail = read_range(file, magic_offset, FOOTER_TAIL)?;
+        let mut reader = ByteReader::new(&tail);
+        if reader.u32()? != PAK_MAGIC {
+            continue;
+        }
+
+        let version = reader.u32()?;
+        let index_offset = reader.u64()?;
+        let index_size = reader.u64()?;
+
+        // The index encryption flag sits right before the magic number
+        if version >= VERSION_INDEX_ENCRYPTION && magic_offset > 0 {
+            let encrypted = read_range(file, magic_offset - 1, 1)?;
+            if encrypted[0] != 0 {
+                return Err("The pak index is encrypted".to_string());
+            }
+        }
+
+        return Ok(Footer {
+            version,
+            index_offset,
+            index_size,
+            compression_index_is_byte: version == VERSION_NAME_BASED_COMPRESSION && name_table_size == 4 * 32,
+        });
+    }
+
+    Err("Not a pak file or unsupported pak version".to_string())
+}
+
+// Read past a serialized FPakEntry; returns its stored and uncompressed size
+fn read_entry(reader: &mut ByteReader, footer: &Footer) -> Result<(u64, u64), String> {
+    // Offset
+    reader.skip(8)?;
+    let stored = reader.u64()?;
+    let uncompressed = reader.u64()?;
+    let compression = if footer.compression_index_is_byte {
+        reader.u8()? as u32
+    } else {
+        reader.u32()?
+    };
+    if footer.version < VERSION_NO_TIMESTAMPS {
+        reader.skip(8)?;
+    }
+    // SHA-1 of the file
+    reader.skip(20)?;
+
+    if footer.version >= VERSION_COMPRESSION_ENCRYPTION {
+        if compression != 0 {
+            let block_count = reader.count()?;
+            reader.skip(block_count * 16)?;
+        }
+        // Encryption flag and compression block size
+        reader.skip(1 + 4)?;
+    }
+    Ok((stored, uncompressed))
+}
+
+// Pak 10 and later pack most entries into a bit field followed by only the values they need;
+// returns the stored and uncompressed size
+fn decode_entry(reader: &mut ByteReader) -> Result<(u64, u64), String> {
+    let flags = reader.u32()?;
+    let offset_is_u32 = flags & (1 << 31) != 0;
+    let uncompressed_is_u32 = flags & (1 << 30) != 0;
+    let size_is_u32 = flags & (1 << 29) != 0;
+    let compression = (flags >> 23) & 0x3f;
+    let encrypted = flags & (1 << 22) != 0;
+    let block_count = ((flags >> 6) & 0xffff) as usize;
+
+    if flags & 0x3f == BLOCK_SIZE_STORED_SEPARATELY {
+        reader.skip(4)?;
+    }
+    let mut value = |is_u32: bool| if is_u32 { reader.u32().map(u64::from) } else { reader.u64() };
+    value(offset_is_u32)?;
+    let uncompressed = value(uncompressed_is_u32)?;
+    let stored = if compression != 0 { value(size_is_u32)? } else { uncompressed };
+
+    // A single unencrypted block spans the whole entry, so its size isn't stored
+    if block_count > 0 && (encrypted || block_count != 1) {
+        reader.skip(block_count * 4)?;
+    }
+    Ok((stored, uncompressed))
+}
+
+// Pak 10 and later keep the file names in a separate directory index
+fn read_directory_index(file: &mut File, reader: &mut ByteReader) -> Result<Vec<String>, String> {
+    // Path hash seed
+    reader.skip(8)?;
+
+    // Path hash index: offset, size and SHA-1 when present
+    if reader.u32()? != 0 {
+        reader.skip(8 + 8 + 20)?;
+    }
+    if reader.u32()? == 0 {
+        return Err("The pak has no full directory index".to_string());
+    }
+    let directory_offset = reader.u64()?;
+    let directory_size = reader.u64()?;
+
+    let directory_index = read_range(file, directory_offset, directory_size)?;
+    let mut reader = ByteReader::new(&directory_index);
+    let mut names = Vec::new();
+    for _ in 0..reader.count()? {
+        let directory = reader.string()?;
+        for _ in 0..reader.count()? {
+            let file_name = reader.string()?;
+            // Index of the encoded entry, not needed for the name
+            reader.skip(4)?;
+            names.push(format!("{}{}", directory, file_name));
+        }
+    }
+    Ok(names)
+}
+
+// Mount points are usually "../../../", which stands for the game's root folder
+fn normalize_asset_path(mount_point: &str, name: &str) -> String {
+    let mut path = format!("{}{}", mount_point, name).replace('\\', "/");
+    while let Some(rest) = path.strip_prefix("../") {
+        path = rest.to_string();
+    }
+    path.trim_start_matches('/').replace("//", "/")
+}
+
+fn read_range(file: &mut File, offset: u64, size: u64) -> Result<Vec<u8>, String> {
+    if size > MAX_INDEX_SIZE {
+        return Err(format!("Pak index of {} bytes is too large", size));
+    }
+    let mut buffer = vec![0; size as usize];
+    file.seek(SeekFrom::Start(offset))
+        .and_then(|_| file.read_exact(&mut buffer))
+        .map_err(|e| format!("Failed to read pak: {}", e))?;
+    Ok(buffer)
+}
+
+struct ByteReader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let end = self.position.checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| "The pak index ends unexpectedly".to_string())?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn skip(&mut self, len: usize) -> Result<(), String> {
+        self.take(len).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap_or_default()))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap_or_default()))
+    }
+
+    // Element counts are signed in the format; a negative one means a corrupt index
+    fn count(&mut self) -> Result<usize, String> {
+        let count = self.u32()? as i32;
+        usize::try_from(count).map_err(|_| format!("Invalid element count {}", count))
+    }
+
+    // FString: length including the terminating null, negative for UTF-16
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u32()? as i32;
+        if len >= 0 {
+            let bytes = self.take(len as usize)?;
+            let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+            Ok(String::from_utf8_lossy(bytes).to_string())
+        } else {
+            let units: Vec<u16> = self.take(len.unsigned_abs() as usize * 2)?
+                .chunks_exact(2)
+                .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            let units = units.strip_suffix(&[0]).unwrap_or(&units);
+            Ok(String::from_utf16_lossy(units))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    // Removes the written pak when the test ends
+    struct TestPak(PathBuf);
+
+    impl TestPak {
+        fn write(name: &str, bytes: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!("ue4-drg-modman-{}-{}.pak", std::process::id(), name));
+            std::fs::write(&path, bytes).expect("write test pak");
+            Self(path)
+        }
+    }
+
+    impl Drop for TestPak {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn put_u32(out: &mut Vec<u8>, value: u32) {
+        out.extend(value.to_le_bytes());
+    }
+
+    fn put_u64(out: &mut Vec<u8>, value: u64) {
+        out.extend(value.to_le_bytes());
+    }
+
+    fn put_string(out: &mut Vec<u8>, value: &str) {
+        put_u32(out, value.len() as u32 + 1);
+        out.extend(value.as_bytes());
+        out.push(0);
+    }
+
+    // FPakEntry as written before pak 10; a compressed entry gets a single block
+    fn put_entry(out: &mut Vec<u8>, stored: u64, uncompressed: u64, compressed: bool, compression_is_byte: bool) {
+        put_u64(out, 0);
+        put_u64(out, stored);
+        put_u64(out, uncompressed);
+        let compression = u32::from(compressed);
+        if compression_is_byte {
+            out.push(compression as u8);
+        } else {
+            put_u32(out, compression);
+        }
+        out.extend([0; 20]);
+        if compressed {
+            put_u32(out, 1);
+            out.extend([0; 16]);
+        }
+        out.push(0);
+        put_u32(out, 64 * 1024);
+    }
+
+    // `data` goes first, then the index and the footer with the given name table size
+    fn pak(version: u32, name_table_size: u64, data: Vec<u8>, index: &[u8], encrypted_index: bool) -> Vec<u8> {
+        let mut pak = data;
+        let index_offset = pak.len() as u64;
+        pak.extend(index);
+        if version >= VERSION_INDEX_ENCRYPTION {
+            pak.push(u8::from(encrypted_index));
+        }
+        put_u32(&mut pak, PAK_MAGIC);
+        put_u32(&mut pak, version);
+        put_u64(&mut pak, index_offset);
+        put_u64(&mut pak, index.len() as u64);
+        pak.extend([0; 20]);
+        pak.extend(vec![0; name_table_size as usize]);
+        pak
+    }
+
+    // Two files under the usual mount point, the second one compressed
+    fn legacy_index(compression_is_byte: bool) -> Vec<u8> {
+        let mut index = Vec::new();
+        put_string(&mut index, "../../../FSD/Content/");
+        put_u32(&mut index, 2);
+        put_string(&mut index, "Weapons/Foo.uasset");
+        put_entry(&mut index, 10, 10, false, compression_is_byte);
+        put_string(&mut index, "Weapons/Foo.uexp");
+        put_entry(&mut index, 4, 16, true, compression_is_byte);
+        index
+    }
+
+    const ASSETS: [&str; 2] = ["FSD/Content/Weapons/Foo.uasset", "FSD/Content/Weapons/Foo.uexp"];
+
+    fn assert_legacy_pak(pak: &TestPak) {
+        assert_eq!(read_asset_paths(&pak.0).unwrap(), ASSETS);
+        let stats = read_stats(&pak.0).unwrap();
+        assert_eq!(stats.mount_point, "../../../FSD/Content/");
+        assert_eq!(stats.file_count, 2);
+        assert_eq!(stats.stored_size, 14);
+        assert_eq!(stats.uncompressed_size, 26);
+    }
+
+    #[test]
+    fn reads_pak_8a_with_byte_compression_index() {
+        let pak = TestPak::write("v8a", &pak(8, 4 * 32, Vec::new(), &legacy_index(true), false));
+        assert_legacy_pak(&pak);
+    }
+
+    #[test]
+    fn reads_pak_8b_with_u32_compression_index() {
+        let pak = TestPak::write("v8b", &pak(8, 5 * 32, Vec::new(), &legacy_index(false), false));
+        assert_legacy_pak(&pak);
+    }
+
+    #[test]
+    fn reads_pak_11_encoded_entries_and_directory_index() {
+        // The directory index goes first so its offset is known while writing the main index
+        let mut directory = Vec::new();
+        put_u32(&mut directory, 1);
+        put_string(&mut directory, "Weapons/");
+        put_u32(&mut directory, 2);
+        put_string(&mut directory, "Foo.uasset");
+        put_u32(&mut directory, 0);
+        put_string(&mut directory, "Foo.uexp");
+        put_u32(&mut directory, 1);
+
+        // Offsets and sizes as u32, uncompressed, no blocks
+        let mut encoded = Vec::new();
+        for size in [10, 16] {
+            put_u32(&mut encoded, (1 << 31) | (1 << 30));
+            put_u32(&mut encoded, 0);
+            put_u32(&mut encoded, size);
+        }
+
+        let mut index = Vec::new();
+        put_string(&mut index, "../../../FSD/Content/");
+        put_u32(&mut index, 2);
+        put_u64(&mut index, 0);
+        // No path hash index, then the full directory index
+        put_u32(&mut index, 0);
+        put_u32(&mut index, 1);
+        put_u64(&mut index, 0);
+        put_u64(&mut index, directory.len() as u64);
+        index.extend([0; 20]);
+        put_u32(&mut index, encoded.len() as u32);
+        index.extend(&encoded);
+        put_u32(&mut index, 0);
+
+        let pak = TestPak::write("v11", &pak(11, 5 * 32 + 1, directory, &index, false));
+        assert_eq!(read_asset_paths(&pak.0).unwrap(), ASSETS);
+        let stats = read_stats(&pak.0).unwrap();
+        assert_eq!(stats.file_count, 2);
+        assert_eq!(stats.stored_size, 26);
+        assert_eq!(stats.uncompressed_size, 26);
+    }
+
+    #[test]
+    fn rejects_index_with_fewer_entries_than_counted() {
+        let mut index = legacy_index(false);
+        // Claim a third entry that isn't there
+        index[4 + "../../../FSD/Content/".len() + 1] = 3;
+        let pak = TestPak::write("truncated", &pak(8, 5 * 32, Vec::new(), &index, false));
+        assert_eq!(read_asset_paths(&pak.0).unwrap_err(), "The pak index ends unexpectedly");
+        assert!(read_stats(&pak.0).is_err());
+    }
+
+    #[test]
+    fn rejects_index_past_the_end_of_the_file() {
+        let mut bytes = pak(8, 5 * 32, Vec::new(), &legacy_index(false), false);
+        // Index size in the footer, after the magic, version and index offset
+        let size_offset = bytes.len() - (FOOTER_TAIL + 5 * 32) as usize + 16;
+        let too_large = bytes.len() as u64 + 1;
+        bytes[size_offset..size_offset + 8].copy_from_slice(&too_large.to_le_bytes());
+        let pak = TestPak::write("short", &bytes);
+        assert!(read_asset_paths(&pak.0).unwrap_err().starts_with("Failed to read pak"));
+        assert!(read_stats(&pak.0).is_err());
+    }
+
+    #[test]
+    fn rejects_negative_entry_count() {
+        let mut index = Vec::new();
+        put_string(&mut index, "../../../");
+        put_u32(&mut index, -1i32 as u32);
+        let pak = TestPak::write("negative", &pak(8, 5 * 32, Vec::new(), &index, false));
+        assert_eq!(read_asset_paths(&pak.0).unwrap_err(), "Invalid element count -1");
+        assert_eq!(read_stats(&pak.0).err().unwrap(), "Invalid element count -1");
+    }
+
+    #[test]
+    fn rejects_encrypted_index() {
+        let pak = TestPak::write("encrypted", &pak(8, 5 * 32, Vec::new(), &legacy_index(false), true));
+        assert_eq!(read_asset_paths(&pak.0).unwrap_err(), "The pak index is encrypted");
+    }
+
+    #[test]
+    fn rejects_files_without_a_footer() {
+        let pak = TestPak::write("not-a-pak", &[0; 300]);
+        assert_eq!(read_asset_paths(&pak.0).unwrap_err(), "Not a pak file or unsupported pak version");
+    }
+}
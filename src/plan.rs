@@ -0,0 +1,107 @@
+use crate::db::ModEntry;
+use crate::installer::ModInstaller;
+use serde::Serialize;
+
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    // Enabled but never downloaded
+    Install,
+    // Downloaded but not in the game folder
+    Deploy,
+    // Deployed files went missing or were changed
+    Repair,
+    // Disabled but still in the game folder
+    Remove,
+}
+
+impl PlanAction {
+    fn label(&self) -> &'static str {
+        match self {
+            PlanAction::Install => "install",
+            PlanAction::Deploy => "deploy",
+            PlanAction::Repair => "repair",
+            PlanAction::Remove => "remove",
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct PlannedChange {
+    pub action: PlanAction,
+    pub mod_id: String,
+    pub mod_name: String,
+    pub details: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct Plan {
+    pub profile: String,
+    pub changes: Vec<PlannedChange>,
+}
+
+impl Plan {
+    // Compare the current profile with what is actually deployed to the game install; nothing is modified
+    pub fn build(installer: &ModInstaller, mods: &[ModEntry], profile: &str, game_path: &str) -> Self {
+        let mut changes = Vec::new();
+        for mod_entry in mods {
+            let deployed_files = installer.deployed_files(mod_entry, game_path);
+            
+            let (action, details) = if !mod_entry.enabled {
+                if deployed_files.is_empty() {
+                    continue;
+                }
+                let details = deployed_files.iter().map(|path| path.display().to_string()).collect();
+                (PlanAction::Remove, details)
+            } else if !mod_entry.installed {
+                (PlanAction::Install, vec![mod_entry.mod_link.clone()])
+            } else if deployed_files.is_empty() {
+                (PlanAction::Deploy, installer.deploy_targets(mod_entry))
+            } else {
+                let problems = installer.deployment_problems(mod_entry, game_path);
+                if problems.is_empty() {
+                    continue;
+                }
+                (PlanAction::Repair, problems)
+            };
+            
+            changes.push(PlannedChange {
+                action,
+                mod_id: mod_entry.mod_id.clone(),
+                mod_name: mod_entry.mod_name.clone(),
+                details,
+            });
+        }
+        
+        Self {
+            profile: profile.to_string(),
+            changes,
+        }
+    }
+    
+    pub fn to_table(&self) -> String {
+        let mut table = format!("Profile: {}\n", self.profile);
+        if self.changes.is_empty() {
+            table.push_str("Nothing to do.\n");
+            return table;
+        }
+        
+        let name_width = self.changes.iter().map(|change| change.mod_name.len()).max().unwrap_or(0).max(4);
+        table.push_str(&format!("{:<8} {:<width$} DETAILS\n", "ACTION", "MOD", width = name_width));
+        for change in &self.changes {
+            let mut details = change.details.iter();
+            table.push_str(&format!(
+                "{:<8} {:<width$} {}\n",
+                change.action.label(),
+                change.mod_name,
+                details.next().map(String::as_str).unwrap_or(""),
+                width = name_width
+            ));
+            // Further details go on their own lines under the first one
+            for detail in details {
+                table.push_str(&format!("{:<8} {:<width$} {}\n", "", "", detail, width = name_width));
+            }
+        }
+        table
+    }
+}
@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::process::Command;
+use tracing::info;
+
+// What to do once a batch of installs or updates has finished
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum PostInstallAction {
+    #[default]
+    Nothing,
+    Notify,
+    LaunchGame,
+    OpenGameFolder,
+    RunCommand,
+}
+
+impl PostInstallAction {
+    pub const ALL: [PostInstallAction; 5] = [
+        PostInstallAction::Nothing,
+        PostInstallAction::Notify,
+        PostInstallAction::LaunchGame,
+        PostInstallAction::OpenGameFolder,
+        PostInstallAction::RunCommand,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            PostInstallAction::Nothing => "Do nothing",
+            PostInstallAction::Notify => "Notify",
+            PostInstallAction::LaunchGame => "Launch the game",
+            PostInstallAction::OpenGameFolder => "Open the game folder",
+            PostInstallAction::RunCommand => "Run a command",
+        }
+    }
+
+    // Stored in the database
+    pub fn key(&self) -> &'static str {
+        match self {
+            PostInstallAction::Nothing => "nothing",
+            PostInstallAction::Notify => "notify",
+            PostInstallAction::LaunchGame => "launch_game",
+            PostInstallAction::OpenGameFolder => "open_game_folder",
+            PostInstallAction::RunCommand => "run_command",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Self {
+        Self::ALL.into_iter().find(|action| action.key() == key).unwrap_or_default()
+    }
+}
+
+// Post-install action of a profile, with the command line for RunCommand
+#[derive(Clone, PartialEq, Default)]
+pub struct PostInstall {
+    pub action: PostInstallAction,
+    pub command: String,
+}
+
+// Started through the shell in `folder` and left running on its own
+pub fn run_command(command: &str, folder: &Path) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut shell = {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C");
+        shell
+    };
+    #[cfg(not(windows))]
+    let mut shell = {
+        let mut shell = Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    if folder.is_dir() {
+        shell.current_dir(folder);
+    }
+    let child = shell.arg(command).spawn().map_err(|e| format!("Failed to run {}: {}", command, e))?;
+    info!("Started post-install command (pid {}): {}", child.id(), command);
+    Ok(())
+}
@@ -0,0 +1,171 @@
+use crate::mod_url::ParsedModRef;
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+use std::process::Command;
+
+// Links like drgmodman://install/mod-hub, opened by "Open in mod manager" buttons on web pages.
+// modio:// is accepted the same way, for links written against mod.io's own paths
+pub const SCHEMES: &[&str] = &["drgmodman", "modio"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LinkAction {
+    // Add the mod if needed, then download and deploy it
+    Install,
+    // Add the mod if needed and show its details
+    Open,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProtocolLink {
+    pub action: LinkAction,
+    pub reference: ParsedModRef,
+}
+
+pub fn is_protocol_link(link: &str) -> bool {
+    scheme_rest(link).is_some()
+}
+
+// Accepts <scheme>://install/<mod>, <scheme>://open/<mod> and <scheme>://<mod>, where <mod> is a
+// slug or numeric ID, as well as mod.io paths such as modio://g/drg/m/<mod>
+pub fn parse(link: &str) -> Option<ProtocolLink> {
+    let rest = scheme_rest(link)?;
+    let rest = rest.split(['?', '#']).next().unwrap_or_default().trim_matches('/');
+
+    if rest.starts_with("g/") || rest.contains("/g/") {
+        return ParsedModRef::parse(&format!("https://mod.io/{}", rest))
+            .map(|reference| ProtocolLink { action: LinkAction::Open, reference });
+    }
+
+    let (action, target) = match rest.split_once('/') {
+        Some((action, target)) if action.eq_ignore_ascii_case("install") => (LinkAction::Install, target),
+        Some((action, target)) if action.eq_ignore_ascii_case("open") || action.eq_ignore_ascii_case("mod") => (LinkAction::Open, target),
+        Some(_) => return None,
+        // An action without a mod, e.g. drgmodman://install/
+        None if ["install", "open", "mod"].iter().any(|action| action.eq_ignore_ascii_case(rest)) => return None,
+        None => (LinkAction::Open, rest),
+    };
+    // Bare IDs and slugs only; "@" and "drg/" forms aren't used in links
+    let target = target.trim_matches('/');
+    if target.contains('/') {
+        return None;
+    }
+    ParsedModRef::parse_input(&format!("@{}", target)).map(|reference| ProtocolLink { action, reference })
+}
+
+fn scheme_rest(link: &str) -> Option<&str> {
+    let (scheme, rest) = link.trim().split_once("://")?;
+    SCHEMES.iter().any(|known| known.eq_ignore_ascii_case(scheme)).then_some(rest)
+}
+
+// Make this executable the handler of the link schemes for the current user
+pub fn register() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("Failed to find the manager's executable: {}", e))?;
+    register_handler(&exe.to_string_lossy())
+}
+
+#[cfg(windows)]
+fn register_handler(exe: &str) -> Result<(), String> {
+    for scheme in SCHEMES {
+        let key = format!("HKCU\\Software\\Classes\\{}", scheme);
+        let command = format!("\"{}\" \"%1\"", exe);
+        run(Command::new("reg").args(["add", &key, "/ve", "/d", "URL:DRG Mod Manager", "/f"]))?;
+        run(Command::new("reg").args(["add", &key, "/v", "URL Protocol", "/d", "", "/f"]))?;
+        run(Command::new("reg").args(["add", &format!("{}\\shell\\open\\command", key), "/ve", "/d", &command, "/f"]))?;
+    }
+    Ok(())
+}
+
+// A desktop entry in the user's applications folder, set as default for each scheme
+#[cfg(all(unix, not(target_os = "macos")))]
+fn register_handler(exe: &str) -> Result<(), String> {
+    const DESKTOP_FILE: &str = "ue4-drg-modman-links.desktop";
+
+    let applications = dirs::data_dir()
+        .ok_or("Failed to find the applications folder")?
+        .join("applications");
+    let mime_types: String = SCHEMES.iter().map(|scheme| format!("x-scheme-handler/{};", scheme)).collect();
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=DRG Mod Manager\nExec=\"{}\" %u\nNoDisplay=true\nTerminal=false\nMimeType={}\n",
+        exe.replace('"', "\\\""),
+        mime_types
+    );
+    std::fs::create_dir_all(&applications)
+        .and_then(|()| std::fs::write(applications.join(DESKTOP_FILE), entry))
+        .map_err(|e| format!("Failed to write the desktop entry: {}", e))?;
+
+    for scheme in SCHEMES {
+        run(Command::new("xdg-mime").args(["default", DESKTOP_FILE, &format!("x-scheme-handler/{}", scheme)]))?;
+    }
+    Ok(())
+}
+
+// macOS reads URL schemes from the app bundle's Info.plist only
+#[cfg(not(any(windows, all(unix, not(target_os = "macos")))))]
+fn register_handler(_exe: &str) -> Result<(), String> {
+    Err("Registering the link handler isn't supported on this platform".to_string())
+}
+
+#[cfg(any(windows, all(unix, not(target_os = "macos"))))]
+fn run(command: &mut Command) -> Result<(), String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let output = command.output().map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn link(action: LinkAction, reference: ParsedModRef) -> Option<ProtocolLink> {
+        Some(ProtocolLink { action, reference })
+    }
+
+    fn slug(slug: &str) -> ParsedModRef {
+        ParsedModRef::NameId(slug.to_string())
+    }
+
+    #[test]
+    fn parses_install_and_open_actions() {
+        assert_eq!(parse("drgmodman://install/mod-hub"), link(LinkAction::Install, slug("mod-hub")));
+        assert_eq!(parse("drgmodman://open/mod-hub"), link(LinkAction::Open, slug("mod-hub")));
+        assert_eq!(parse("drgmodman://mod/mod-hub"), link(LinkAction::Open, slug("mod-hub")));
+        assert_eq!(parse("DRGModMan://Install/1234567/"), link(LinkAction::Install, ParsedModRef::Id(1234567)));
+    }
+
+    #[test]
+    fn bare_slug_or_id_opens_the_mod() {
+        assert_eq!(parse("drgmodman://mod-hub"), link(LinkAction::Open, slug("mod-hub")));
+        assert_eq!(parse("modio://1234567"), link(LinkAction::Open, ParsedModRef::Id(1234567)));
+        assert_eq!(parse("drgmodman://mod-hub/?source=web#top"), link(LinkAction::Open, slug("mod-hub")));
+    }
+
+    #[test]
+    fn accepts_mod_io_paths() {
+        assert_eq!(parse("modio://g/drg/m/mod-hub"), link(LinkAction::Open, slug("mod-hub")));
+        assert_eq!(parse("modio://en/g/drg/m/mod-hub/files"), link(LinkAction::Open, slug("mod-hub")));
+        assert_eq!(parse("modio://g/other-game/m/mod-hub"), None);
+    }
+
+    #[test]
+    fn rejects_other_schemes_actions_and_targets() {
+        assert_eq!(parse("https://mod.io/g/drg/m/mod-hub"), None);
+        assert_eq!(parse("mod-hub"), None);
+        assert_eq!(parse("drgmodman://"), None);
+        assert_eq!(parse("drgmodman://uninstall/mod-hub"), None);
+        assert_eq!(parse("drgmodman://install/"), None);
+        assert_eq!(parse("drgmodman://install/drg/mod-hub"), None);
+        assert_eq!(parse("drgmodman://install/@mod-hub"), None);
+        assert_eq!(parse("drgmodman://install/mod hub"), None);
+    }
+
+    #[test]
+    fn recognizes_links_by_scheme_only() {
+        assert!(is_protocol_link(" drgmodman://anything "));
+        assert!(is_protocol_link("MODIO://g/drg"));
+        assert!(!is_protocol_link("https://mod.io"));
+        assert!(!is_protocol_link("C:\\mods\\mod.zip"));
+    }
+}
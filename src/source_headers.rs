@@ -0,0 +1,40 @@
+use crate::credentials;
+
+// Custom HTTP headers for file hosts that need an auth header or cookie. They are secrets,
+// so they are stored through credentials, one entry per host as a JSON list of [name, value]
+
+pub type Headers = Vec<(String, String)>;
+
+fn credential_name(host: &str) -> String {
+    format!("source_headers:{}", host)
+}
+
+// Host a direct-download link points to
+pub fn host_of(url: &str) -> Option<String> {
+    reqwest::Url::parse(url).ok()?.host_str().map(|host| host.to_lowercase())
+}
+
+pub fn load(host: &str) -> Headers {
+    credentials::get(&credential_name(host))
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .unwrap_or_default()
+}
+
+// Rows without a name are dropped; saving none removes the host's entry
+pub fn save(host: &str, headers: &[(String, String)]) -> Result<(), String> {
+    let headers: Headers = headers
+        .iter()
+        .map(|(name, value)| (name.trim().to_string(), value.clone()))
+        .filter(|(name, _)| !name.is_empty())
+        .collect();
+    if headers.is_empty() {
+        return credentials::delete(&credential_name(host));
+    }
+    let json = serde_json::to_string(&headers).map_err(|e| e.to_string())?;
+    credentials::set(&credential_name(host), &json)
+}
+
+// Headers configured for the host of `url`, ready for a request builder
+pub fn for_url(url: &str) -> Headers {
+    host_of(url).map(|host| load(&host)).unwrap_or_default()
+}
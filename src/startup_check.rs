@@ -0,0 +1,58 @@
+use std::path::Path;
+
+// A saved setting that doesn't hold up, found when the manager starts instead of when
+// something later fails because of it
+#[derive(Clone, PartialEq)]
+pub enum StartupIssue {
+    // A mod.io token is in the keyring but was never confirmed to work
+    UnvalidatedToken,
+    GamePathMissing(String),
+    // Downloads and the database can't be written, with the error
+    StorageUnwritable(String),
+    SyncFolderMissing(String),
+}
+
+impl StartupIssue {
+    pub fn description(&self) -> String {
+        match self {
+            StartupIssue::UnvalidatedToken => {
+                "A mod.io token is saved but was never validated. Downloads from mod.io fail if it doesn't work.".to_string()
+            },
+            StartupIssue::GamePathMissing(path) => {
+                format!("The game path {} doesn't exist. Mods can't be deployed until it points at the game.", path)
+            },
+            StartupIssue::StorageUnwritable(error) => {
+                format!("The mod storage folder can't be written ({}). Downloads and settings won't be saved.", error)
+            },
+            StartupIssue::SyncFolderMissing(folder) => {
+                format!("The sync folder {} can't be reached, so profiles aren't synced. Is the drive connected?", folder)
+            },
+        }
+    }
+}
+
+pub fn find_issues(token: &str, token_validated: bool, game_path: &str, storage: &Path, sync_folder: &str) -> Vec<StartupIssue> {
+    let mut issues = Vec::new();
+    if !token.is_empty() && !token_validated {
+        issues.push(StartupIssue::UnvalidatedToken);
+    }
+    if !game_path.is_empty() && !Path::new(game_path).exists() {
+        issues.push(StartupIssue::GamePathMissing(game_path.to_string()));
+    }
+    if let Err(e) = check_writable(storage) {
+        issues.push(StartupIssue::StorageUnwritable(e));
+    }
+    if !sync_folder.is_empty() && !Path::new(sync_folder).is_dir() {
+        issues.push(StartupIssue::SyncFolderMissing(sync_folder.to_string()));
+    }
+    issues
+}
+
+// Writes and removes a probe file; only trying tells about permissions, full or read-only drives
+fn check_writable(folder: &Path) -> Result<(), String> {
+    let probe = folder.join(".write_check");
+    std::fs::create_dir_all(folder)
+        .and_then(|()| std::fs::write(&probe, b"ok"))
+        .and_then(|()| std::fs::remove_file(&probe))
+        .map_err(|e| e.to_string())
+}
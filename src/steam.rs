@@ -0,0 +1,269 @@
+use crate::launcher::STEAM_APP_ID;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+// The game's folder under steamapps/common unless its app manifest says otherwise
+const DEFAULT_INSTALL_DIR: &str = "Deep Rock Galactic";
+
+// FSD.exe of the first Steam library that has the game installed
+pub fn find_game() -> Option<PathBuf> {
+    for (steam_root, prefix) in steam_roots() {
+        for library in library_folders(&steam_root, prefix.as_deref()) {
+            let steamapps = library.join("steamapps");
+            let install_dir = std::fs::read_to_string(steamapps.join(format!("appmanifest_{}.acf", STEAM_APP_ID)))
+                .ok()
+                .and_then(|manifest| vdf_values(&manifest, "installdir").into_iter().next())
+                .unwrap_or_else(|| DEFAULT_INSTALL_DIR.to_string());
+            let exe = steamapps.join("common").join(install_dir).join("FSD.exe");
+            if exe.is_file() {
+                info!("Found Deep Rock Galactic in Steam library {}", library.display());
+                return Some(exe);
+            }
+        }
+    }
+    None
+}
+
+// Steam installs that may exist, each with the Wine or Proton prefix it lives in, if any
+fn steam_roots() -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut roots = Vec::new();
+    if cfg!(windows) {
+        for variable in ["ProgramFiles(x86)", "ProgramFiles"] {
+            if let Some(program_files) = std::env::var_os(variable) {
+                roots.push((PathBuf::from(program_files).join("Steam"), None));
+            }
+        }
+        return roots;
+    }
+
+    let Some(home) = dirs::home_dir() else {
+        return roots;
+    };
+    for native in [
+        ".steam/steam",
+        ".local/share/Steam",
+        // Flatpak and Snap packages
+        ".var/app/com.valvesoftware.Steam/.local/share/Steam",
+        "snap/steam/common/.local/share/Steam",
+    ] {
+        roots.push((home.join(native), None));
+    }
+
+    // Windows Steam installed into a prefix, e.g. by Lutris or Bottles
+    let mut prefixes = vec![home.join(".wine")];
+    for parent in ["Games", ".local/share/bottles/bottles"] {
+        if let Ok(entries) = std::fs::read_dir(home.join(parent)) {
+            prefixes.extend(entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()));
+        }
+    }
+    for prefix in prefixes {
+        for program_files in ["Program Files (x86)", "Program Files"] {
+            roots.push((prefix.join("drive_c").join(program_files).join("Steam"), Some(prefix.clone())));
+        }
+    }
+
+    roots.retain(|(root, _)| root.is_dir());
+    // ~/.steam/steam is usually a link to one of the others
+    let mut seen = Vec::new();
+    roots.retain(|(root, _)| {
+        let canonical = root.canonicalize().unwrap_or_else(|_| root.clone());
+        let new = !seen.contains(&canonical);
+        seen.push(canonical);
+        new
+    });
+    roots
+}
+
+// The Steam install itself and every library listed in its libraryfolders.vdf
+fn library_folders(steam_root: &Path, prefix: Option<&Path>) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_root.to_path_buf()];
+    // Older clients kept the file under steamapps, newer ones under config
+    for file in ["steamapps/libraryfolders.vdf", "config/libraryfolders.vdf"] {
+        let Ok(vdf) = std::fs::read_to_string(steam_root.join(file)) else {
+            continue;
+        };
+        for path in vdf_values(&vdf, "path") {
+            let library = match prefix {
+                Some(prefix) => windows_path_in_prefix(&path, prefix),
+                None => PathBuf::from(path),
+            };
+            if !libraries.contains(&library) {
+                libraries.push(library);
+            }
+        }
+    }
+    libraries
+}
+
+// Maps a path like D:\Games\SteamLibrary to the prefix's drive links
+fn windows_path_in_prefix(path: &str, prefix: &Path) -> PathBuf {
+    let Some((drive, rest)) = path.split_once(':') else {
+        return PathBuf::from(path);
+    };
+    let mut resolved = prefix.join("dosdevices").join(format!("{}:", drive.to_lowercase()));
+    for component in rest.split(['\\', '/']).filter(|component| !component.is_empty()) {
+        resolved.push(component);
+    }
+    resolved
+}
+
+// String values of `key` anywhere in a Valve KeyValues file. Libraries in files written before
+// 2021 are numbered keys right inside the root block whose value is the path itself, so those
+// count as "path" too. Deeper numbered keys, like the app IDs of a library, don't
+fn vdf_values(vdf: &str, key: &str) -> Vec<String> {
+    let tokens = vdf_tokens(vdf);
+    let mut values = Vec::new();
+    let mut depth = 0;
+    let mut index = 0;
+    while index < tokens.len() {
+        match (&tokens[index], tokens.get(index + 1)) {
+            (VdfToken::String(name), Some(VdfToken::String(value))) => {
+                let legacy_library = key == "path"
+                    && depth == 1
+                    && !name.is_empty()
+                    && name.bytes().all(|b| b.is_ascii_digit());
+                if name.eq_ignore_ascii_case(key) || legacy_library {
+                    values.push(value.clone());
+                }
+                index += 2;
+            },
+            (VdfToken::Open, _) => {
+                depth += 1;
+                index += 1;
+            },
+            (VdfToken::Close, _) => {
+                depth -= 1;
+                index += 1;
+            },
+            _ => index += 1,
+        }
+    }
+    values
+}
+
+enum VdfToken {
+    String(String),
+    Open,
+    Close,
+}
+
+fn vdf_tokens(vdf: &str) -> Vec<VdfToken> {
+    let mut tokens = Vec::new();
+    let mut chars = vdf.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' => tokens.push(VdfToken::Open),
+            '}' => tokens.push(VdfToken::Close),
+            '"' => {
+                let mut value = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                value.push(match escaped {
+                                    'n' => '\n',
+                                    't' => '\t',
+                                    other => other,
+                                });
+                            }
+                        },
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(VdfToken::String(value));
+            },
+            // Comments run to the end of the line
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn libraries_before_2021() {
+        let vdf = r#"
+"LibraryFolders"
+{
+	"TimeNextStatsReport"		"1612345678"
+	"ContentStatsID"		"-1234567890123456789"
+	"1"		"D:\\SteamLibrary"
+	"2"		"/mnt/games/SteamLibrary"
+}
+"#;
+        assert_eq!(vdf_values(vdf, "path"), ["D:\\SteamLibrary", "/mnt/games/SteamLibrary"]);
+    }
+
+    #[test]
+    fn libraries_since_2021_skip_app_ids() {
+        let vdf = r#"
+"libraryfolders"
+{
+	"contentstatsid"		"-1234567890123456789"
+	"0"
+	{
+		"path"		"C:\\Program Files (x86)\\Steam"
+		"label"		""
+		"totalsize"		"0"
+		"apps"
+		{
+			"228980"		"123456"
+		}
+	}
+	"1"
+	{
+		"path"		"E:\\Games\\SteamLibrary"
+		"apps"
+		{
+			"548430"		"3456789012"
+		}
+	}
+}
+"#;
+        assert_eq!(
+            vdf_values(vdf, "path"),
+            ["C:\\Program Files (x86)\\Steam", "E:\\Games\\SteamLibrary"]
+        );
+    }
+
+    #[test]
+    fn install_dir_from_app_manifest() {
+        let acf = r#"
+"AppState"
+{
+	"appid"		"548430"
+	"installdir"		"Deep Rock Galactic"
+}
+"#;
+        assert_eq!(vdf_values(acf, "installdir"), ["Deep Rock Galactic"]);
+    }
+
+    #[test]
+    fn windows_paths_map_into_the_prefix() {
+        let prefix = Path::new("/home/user/.wine");
+        assert_eq!(
+            windows_path_in_prefix("D:\\Games\\SteamLibrary", prefix),
+            prefix.join("dosdevices").join("d:").join("Games").join("SteamLibrary")
+        );
+        assert_eq!(
+            windows_path_in_prefix("C:/Program Files (x86)/Steam/", prefix),
+            prefix.join("dosdevices").join("c:").join("Program Files (x86)").join("Steam")
+        );
+        // Paths without a drive are left as they are
+        assert_eq!(
+            windows_path_in_prefix("/mnt/games/SteamLibrary", prefix),
+            PathBuf::from("/mnt/games/SteamLibrary")
+        );
+    }
+}
@@ -0,0 +1,439 @@
+use crate::db::Database;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+// Subfolder of the user-chosen synced folder holding the journals
+const SYNC_DIR_NAME: &str = "ue4-drg-modman-sync";
+
+// Exported state of a removed mod or profile
+const REMOVED: &str = "removed";
+
+// State of one mod in one profile at the time it was changed. Whether it's installed isn't
+// synced, since that depends on the files present on each machine
+#[derive(Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub profile: String,
+    pub mod_id: String,
+    pub mod_name: String,
+    pub mod_link: String,
+    pub download_folder: String,
+    pub selected_version: String,
+    pub enabled: bool,
+    // When the row last changed, in milliseconds since the epoch, used for last-writer-wins
+    pub timestamp: i64,
+    // The mod was removed from the profile, or with an empty mod_id the whole profile was deleted
+    #[serde(default)]
+    pub removed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Journal {
+    pub machine_id: String,
+    pub created_at: i64,
+    pub entries: Vec<JournalEntry>,
+}
+
+pub struct SyncReport {
+    pub exported: usize,
+    pub applied: usize,
+}
+
+// Identifier of this machine, generated once and stored in the database
+pub fn machine_id(db: &Database) -> String {
+    if let Ok(Some(id)) = db.get_state("machine_id") {
+        return id;
+    }
+    
+    let nanos = chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default() as u64;
+    let id = format!("{:x}", nanos ^ ((std::process::id() as u64) << 32));
+    if let Err(e) = db.set_state("machine_id", &id) {
+        warn!("Failed to store machine id: {}", e);
+    }
+    id
+}
+
+fn fingerprint(selected_version: &str, enabled: bool) -> String {
+    format!("{}|{}", selected_version, enabled)
+}
+
+fn entry_fingerprint(entry: &JournalEntry) -> String {
+    if entry.removed {
+        REMOVED.to_string()
+    } else {
+        fingerprint(&entry.selected_version, entry.enabled)
+    }
+}
+
+fn sync_dir(folder: &Path) -> PathBuf {
+    folder.join(SYNC_DIR_NAME)
+}
+
+// Export local changes, then merge journals written by other machines
+pub fn sync(db: &Database, folder: &Path) -> Result<SyncReport, String> {
+    let dir = sync_dir(folder);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create sync folder: {}", e))?;
+    
+    let machine_id = machine_id(db);
+    let exported = export_changes(db, &dir, &machine_id)?;
+    let applied = merge_journals(db, &dir, &machine_id)?;
+    
+    Ok(SyncReport { exported, applied })
+}
+
+fn export_changes(db: &Database, dir: &Path, machine_id: &str) -> Result<usize, String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let states = db.get_sync_states().map_err(|e| e.to_string())?;
+    let global: HashMap<String, (String, String, String)> = db.get_global_mods()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(id, name, link, folder)| (id, (name, link, folder)))
+        .collect();
+    
+    let profiles = db.get_profiles().map_err(|e| e.to_string())?;
+    let mut present = HashSet::new();
+    let mut entries = Vec::new();
+    for profile in &profiles {
+        for (mod_id, selected_version, enabled, changed_at) in db.get_profile_rows(profile).map_err(|e| e.to_string())? {
+            present.insert((profile.clone(), mod_id.clone()));
+            let current = fingerprint(&selected_version, enabled);
+            
+            // Only rows that changed since the last export go into the journal
+            let unchanged = states
+                .get(&(profile.clone(), mod_id.clone()))
+                .map(|(stored, _)| *stored == current)
+                .unwrap_or(false);
+            if unchanged {
+                continue;
+            }
+            
+            let (mod_name, mod_link, download_folder) = global.get(&mod_id).cloned().unwrap_or_default();
+            entries.push(JournalEntry {
+                profile: profile.clone(),
+                mod_id,
+                mod_name,
+                mod_link,
+                download_folder,
+                selected_version,
+                enabled,
+                // Rows from before change times were recorded count as changed now
+                timestamp: if changed_at > 0 { changed_at } else { now },
+                removed: false,
+            });
+        }
+    }
+    
+    // Removals not exported yet, unless the profile or mod has been added back since
+    for ((profile, mod_id), removed_at) in db.get_sync_tombstones().map_err(|e| e.to_string())? {
+        let restored = if mod_id.is_empty() {
+            profiles.contains(&profile)
+        } else {
+            present.contains(&(profile.clone(), mod_id.clone()))
+        };
+        let exported = states
+            .get(&(profile.clone(), mod_id.clone()))
+            .is_some_and(|(stored, _)| stored == REMOVED);
+        if restored || exported {
+            continue;
+        }
+        
+        let (mod_name, mod_link, download_folder) = global.get(&mod_id).cloned().unwrap_or_default();
+        entries.push(JournalEntry {
+            profile,
+            mod_id,
+            mod_name,
+            mod_link,
+            download_folder,
+            selected_version: String::new(),
+            enabled: false,
+            timestamp: removed_at,
+            removed: true,
+        });
+    }
+    
+    if entries.is_empty() {
+        return Ok(0);
+    }
+    
+    let journal = Journal {
+        machine_id: machine_id.to_string(),
+        created_at: now,
+        entries,
+    };
+    
+    // Write to a temporary name first so sync clients never pick up half a file
+    let file_name = format!("{}-{}.json", machine_id, now);
+    let temp_path = dir.join(format!("{}.tmp", file_name));
+    let contents = serde_json::to_string_pretty(&journal)
+        .map_err(|e| format!("Failed to serialize journal: {}", e))?;
+    std::fs::write(&temp_path, contents)
+        .map_err(|e| format!("Failed to write journal: {}", e))?;
+    std::fs::rename(&temp_path, dir.join(&file_name))
+        .map_err(|e| format!("Failed to write journal: {}", e))?;
+    
+    for entry in &journal.entries {
+        db.set_sync_state(&entry.profile, &entry.mod_id, &entry_fingerprint(entry), entry.timestamp)
+            .map_err(|e| e.to_string())?;
+    }
+    db.mark_journal_applied(&file_name).map_err(|e| e.to_string())?;
+    
+    info!("Exported {} changes to {}", journal.entries.len(), file_name);
+    Ok(journal.entries.len())
+}
+
+fn merge_journals(db: &Database, dir: &Path, machine_id: &str) -> Result<usize, String> {
+    let read_dir = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read sync folder: {}", e))?;
+    
+    let mut new_journals = Vec::new();
+    for path in read_dir.filter_map(|entry| entry.ok()).map(|entry| entry.path()) {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()).map(|name| name.to_string()) else {
+            continue;
+        };
+        if !file_name.ends_with(".json") || file_name.starts_with(&format!("{}-", machine_id)) {
+            continue;
+        }
+        if db.is_journal_applied(&file_name).map_err(|e| e.to_string())? {
+            continue;
+        }
+        
+        match std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| serde_json::from_str::<Journal>(&contents).map_err(|e| e.to_string()))
+        {
+            Ok(journal) => new_journals.push((file_name, journal)),
+            Err(e) => warn!("Skipping unreadable journal {}: {}", file_name, e),
+        }
+    }
+    
+    let mut entries: Vec<JournalEntry> = new_journals
+        .iter()
+        .flat_map(|(_, journal)| journal.entries.iter().cloned())
+        .collect();
+    entries.sort_by_key(|entry| entry.timestamp);
+    
+    let mut profiles = db.get_profiles().map_err(|e| e.to_string())?;
+    let mut changed_at: HashMap<(String, String), i64> = HashMap::new();
+    for profile in &profiles {
+        for (mod_id, _, _, row_changed_at) in db.get_profile_rows(profile).map_err(|e| e.to_string())? {
+            changed_at.insert((profile.clone(), mod_id), row_changed_at);
+        }
+    }
+    let mut tombstones = db.get_sync_tombstones().map_err(|e| e.to_string())?;
+    let mut applied = 0;
+    
+    for entry in entries {
+        // Last writer wins per (profile, mod), against the time the local row last changed or was
+        // removed. A profile's deletion loses to any later change in it
+        let key = (entry.profile.clone(), entry.mod_id.clone());
+        let profile_removed_at = tombstones.get(&(entry.profile.clone(), String::new())).copied();
+        let local_time = if entry.mod_id.is_empty() {
+            changed_at.iter()
+                .filter(|((profile, _), _)| *profile == entry.profile)
+                .map(|(_, time)| *time)
+                .chain(profile_removed_at)
+                .max()
+        } else {
+            [changed_at.get(&key).copied(), tombstones.get(&key).copied(), profile_removed_at]
+                .into_iter()
+                .flatten()
+                .max()
+        };
+        if entry.timestamp <= local_time.unwrap_or(i64::MIN) {
+            continue;
+        }
+        
+        if entry.removed {
+            if !apply_removal(db, &entry, &mut profiles)? {
+                continue;
+            }
+            db.set_sync_state(&entry.profile, &entry.mod_id, REMOVED, entry.timestamp)
+                .map_err(|e| e.to_string())?;
+            tombstones.insert(key, entry.timestamp);
+            applied += 1;
+            continue;
+        }
+        
+        if !profiles.contains(&entry.profile) {
+            db.create_profile(&entry.profile).map_err(|e| e.to_string())?;
+            profiles.push(entry.profile.clone());
+        }
+        
+        db.ensure_global_mod(&entry.mod_id, &entry.mod_name, &entry.mod_link, &entry.download_folder)
+            .map_err(|e| e.to_string())?;
+        db.set_profile_row(&entry.profile, &entry.mod_id, &entry.selected_version, entry.enabled, entry.timestamp)
+            .map_err(|e| e.to_string())?;
+        
+        // Record the merged state so it isn't exported again as a local change
+        db.set_sync_state(&entry.profile, &entry.mod_id, &entry_fingerprint(&entry), entry.timestamp)
+            .map_err(|e| e.to_string())?;
+        changed_at.insert(key, entry.timestamp);
+        applied += 1;
+    }
+    
+    for (file_name, _) in &new_journals {
+        db.mark_journal_applied(file_name).map_err(|e| e.to_string())?;
+    }
+    
+    Ok(applied)
+}
+
+// Remove what another machine removed; false when it has to stay here
+fn apply_removal(db: &Database, entry: &JournalEntry, profiles: &mut Vec<String>) -> Result<bool, String> {
+    if !entry.mod_id.is_empty() {
+        db.remove_profile_row(&entry.profile, &entry.mod_id, entry.timestamp)
+            .map_err(|e| e.to_string())?;
+        return Ok(true);
+    }
+    
+    // The window would be left on a profile that no longer exists
+    if entry.profile == "Default" || entry.profile == db.get_current_profile() {
+        info!("Keeping profile {}, deleted on another machine, since it's in use here", entry.profile);
+        return Ok(false);
+    }
+    if profiles.contains(&entry.profile) {
+        db.delete_profile(&entry.profile).map_err(|e| e.to_string())?;
+        profiles.retain(|profile| *profile != entry.profile);
+    }
+    db.set_sync_tombstone(&entry.profile, "", entry.timestamp).map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Journal folder under the temp dir, removed when dropped
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!("ue4-drg-modman-sync-{}-{}", std::process::id(), name));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn memory_db() -> Database {
+        Database::new(Path::new(":memory:")).unwrap()
+    }
+
+    fn set_row(db: &Database, profile: &str, version: &str, enabled: bool, changed_at: i64) {
+        db.ensure_global_mod("modio_1", "Mod", "https://mod.io/g/drg/m/mod", "downloads").unwrap();
+        db.set_profile_row(profile, "modio_1", version, enabled, changed_at).unwrap();
+    }
+
+    fn entry(profile: &str, version: &str, timestamp: i64) -> JournalEntry {
+        JournalEntry {
+            profile: profile.to_string(),
+            mod_id: "modio_1".to_string(),
+            mod_name: "Mod".to_string(),
+            mod_link: "https://mod.io/g/drg/m/mod".to_string(),
+            download_folder: "downloads".to_string(),
+            selected_version: version.to_string(),
+            enabled: true,
+            timestamp,
+            removed: false,
+        }
+    }
+
+    fn write_journal(dir: &Path, file_name: &str, entries: Vec<JournalEntry>) {
+        let journal = Journal {
+            machine_id: file_name.split('-').next().unwrap().to_string(),
+            created_at: 0,
+            entries,
+        };
+        std::fs::write(dir.join(file_name), serde_json::to_string(&journal).unwrap()).unwrap();
+    }
+
+    fn version(db: &Database, profile: &str) -> Option<String> {
+        db.get_profile_rows(profile).unwrap().into_iter().next().map(|(_, version, _, _)| version)
+    }
+
+    #[test]
+    fn own_journals_are_skipped() {
+        let dir = TestDir::new("own");
+        let db = memory_db();
+        set_row(&db, "Default", "1.0", true, 100);
+        write_journal(&dir.0, "local-1.json", vec![entry("Default", "2.0", 200)]);
+        
+        assert_eq!(merge_journals(&db, &dir.0, "local").unwrap(), 0);
+        assert_eq!(version(&db, "Default").as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn newer_remote_entry_wins() {
+        let dir = TestDir::new("newer");
+        let db = memory_db();
+        set_row(&db, "Default", "1.0", true, 100);
+        write_journal(&dir.0, "other-1.json", vec![entry("Default", "2.0", 200)]);
+        
+        assert_eq!(merge_journals(&db, &dir.0, "local").unwrap(), 1);
+        assert_eq!(version(&db, "Default").as_deref(), Some("2.0"));
+    }
+
+    #[test]
+    fn older_remote_entry_loses() {
+        let dir = TestDir::new("older");
+        let db = memory_db();
+        set_row(&db, "Default", "1.0", true, 100);
+        write_journal(&dir.0, "other-1.json", vec![entry("Default", "2.0", 50)]);
+        
+        assert_eq!(merge_journals(&db, &dir.0, "local").unwrap(), 0);
+        assert_eq!(version(&db, "Default").as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn applied_journal_is_skipped() {
+        let dir = TestDir::new("applied");
+        let db = memory_db();
+        set_row(&db, "Default", "1.0", true, 100);
+        write_journal(&dir.0, "other-1.json", vec![entry("Default", "2.0", 200)]);
+        db.mark_journal_applied("other-1.json").unwrap();
+        
+        assert_eq!(merge_journals(&db, &dir.0, "local").unwrap(), 0);
+        assert_eq!(version(&db, "Default").as_deref(), Some("1.0"));
+    }
+
+    #[test]
+    fn deleted_profile_is_not_brought_back() {
+        let dir = TestDir::new("deleted");
+        let db = memory_db();
+        db.create_profile("Work").unwrap();
+        set_row(&db, "Work", "1.0", true, 100);
+        db.delete_profile("Work").unwrap();
+        write_journal(&dir.0, "other-1.json", vec![entry("Work", "2.0", 200)]);
+        
+        assert_eq!(merge_journals(&db, &dir.0, "local").unwrap(), 0);
+        assert!(!db.get_profiles().unwrap().contains(&"Work".to_string()));
+    }
+
+    #[test]
+    fn profile_deletion_reaches_other_machines() {
+        let dir = TestDir::new("deletion");
+        let remote = memory_db();
+        remote.create_profile("Work").unwrap();
+        set_row(&remote, "Work", "1.0", true, 100);
+        export_changes(&remote, &dir.0, "remote").unwrap();
+        
+        let db = memory_db();
+        merge_journals(&db, &dir.0, "local").unwrap();
+        assert_eq!(version(&db, "Work").as_deref(), Some("1.0"));
+        
+        remote.delete_profile("Work").unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        export_changes(&remote, &dir.0, "remote").unwrap();
+        
+        merge_journals(&db, &dir.0, "local").unwrap();
+        assert!(!db.get_profiles().unwrap().contains(&"Work".to_string()));
+    }
+}
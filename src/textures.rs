@@ -0,0 +1,110 @@
+use eframe::egui;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+// GPU memory thumbnails may use before the least recently shown are dropped
+pub const DEFAULT_TEXTURE_BUDGET: usize = 128 * 1024 * 1024;
+// Small enough for the Steam Deck's shared memory
+pub const LOW_MEMORY_TEXTURE_BUDGET: usize = 24 * 1024 * 1024;
+
+struct CachedTexture {
+    handle: egui::TextureHandle,
+    bytes: usize,
+    // Pass in which the texture was last requested
+    last_used: u64,
+}
+
+// Textures decoded from the disk cache, evicted least recently used first.
+// Evicted entries are simply re-uploaded from disk when shown again.
+pub struct TextureCache {
+    textures: HashMap<PathBuf, CachedTexture>,
+    failed: HashSet<PathBuf>,
+    used_bytes: usize,
+    budget: usize,
+}
+
+impl TextureCache {
+    pub fn new(budget: usize) -> Self {
+        Self {
+            textures: HashMap::new(),
+            failed: HashSet::new(),
+            used_bytes: 0,
+            budget,
+        }
+    }
+    
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict(u64::MAX);
+    }
+    
+    pub fn get(&mut self, ctx: &egui::Context, path: &Path) -> Option<egui::TextureHandle> {
+        let pass = ctx.cumulative_pass_nr();
+        if let Some(texture) = self.textures.get_mut(path) {
+            texture.last_used = pass;
+            return Some(texture.handle.clone());
+        }
+        if self.failed.contains(path) {
+            return None;
+        }
+        
+        let image = match Self::load_image(path) {
+            Ok(image) => image,
+            Err(e) => {
+                warn!("Failed to load texture {:?}: {}", path, e);
+                self.failed.insert(path.to_path_buf());
+                return None;
+            }
+        };
+        let bytes = image.pixels.len() * 4;
+        let handle = ctx.load_texture(path.to_string_lossy(), image, egui::TextureOptions::LINEAR);
+        self.textures.insert(path.to_path_buf(), CachedTexture {
+            handle: handle.clone(),
+            bytes,
+            last_used: pass,
+        });
+        self.used_bytes += bytes;
+        
+        // Keep everything shown in this pass, or visible rows would thrash
+        self.evict(pass);
+        Some(handle)
+    }
+    
+    // Drop the texture of a file that changed on disk
+    pub fn forget(&mut self, path: &Path) {
+        if let Some(texture) = self.textures.remove(path) {
+            self.used_bytes -= texture.bytes;
+        }
+        self.failed.remove(path);
+    }
+    
+    // Drop the least recently used textures not used since `keep_from` until within budget
+    fn evict(&mut self, keep_from: u64) {
+        while self.used_bytes > self.budget {
+            let Some(oldest) = self.textures
+                .iter()
+                .filter(|(_, texture)| texture.last_used < keep_from)
+                .min_by_key(|(_, texture)| texture.last_used)
+                .map(|(path, _)| path.clone())
+            else {
+                break;
+            };
+            if let Some(texture) = self.textures.remove(&oldest) {
+                self.used_bytes -= texture.bytes;
+            }
+        }
+    }
+    
+    fn load_image(path: &Path) -> Result<egui::ColorImage, String> {
+        // Cached thumbnails have no extension, so go by the file contents
+        let image = image::ImageReader::open(path)
+            .and_then(|reader| reader.with_guessed_format())
+            .map_err(|e| e.to_string())?
+            .decode()
+            .map_err(|e| e.to_string())?
+            .to_rgba8();
+        let size = [image.width() as usize, image.height() as usize];
+        Ok(egui::ColorImage::from_rgba_unmultiplied(size, image.as_raw()))
+    }
+}
@@ -1,47 +1,95 @@
-use crate::app::{ModAction, ModManager, Tab};
+use crate::app::{ArchivePreview, BrowseSource, DisableReason, DownloadOutcome, ListDensity, ListSort, ModAction, ModManager, NotificationCorner, Severity, Tab, TableColumn, CATALOG_SEARCH_DELAY};
+use crate::credentials;
 use crate::db::ModEntry;
-use crate::mod_io::{ModIoMod, ModIoClient};
+use crate::download_failure;
+use crate::format;
+use crate::installer::{ArchiveEntry, ModInstaller};
+use crate::keymap::{format_shortcut, ShortcutAction};
+use crate::launcher;
+use crate::logging;
+use crate::mod_io::{self, Approval, CatalogSort, ModIoClient, ModIoMod, MOD_HUB_URL};
+use crate::mod_url::ParsedModRef;
+use crate::pak::PakStats;
+use crate::post_install::PostInstallAction;
+use crate::protocol;
+use crate::source_headers;
+use crate::startup_check::StartupIssue;
+use crate::webhook::WebhookEvent;
+use crate::worker::{DetailsPurpose, Task};
 use eframe::egui;
+use egui_extras::{Column, TableBuilder};
 use egui::{Color32, RichText};
-use keyring::Entry;
+use std::collections::{BTreeMap, HashMap};
+use tracing::warn;
 
 pub fn render_ui(
     app: &mut ModManager,
     ctx: &egui::Context,
-    frame: &mut eframe::Frame
+    _frame: &mut eframe::Frame
 ) {
     // Set dark theme
     ctx.set_visuals(egui::Visuals::dark());
     
-    // Get frame time for animations
-    let frame_time = frame.info().cpu_usage.unwrap_or(0.016); // Default to 60 FPS if unknown
+    // Name the active profile in the window title
+    let title = format!("DRG Mod Manager - {}", app.profile_label(app.db.get_current_profile()));
+    if title != app.window_title {
+        ctx.send_viewport_cmd(egui::ViewportCommand::Title(title.clone()));
+        app.window_title = title;
+    }
     
     // Render the main UI components
     render_top_panel(app, ctx);
     render_side_panel(app, ctx);
+    render_detail_panel(app, ctx);
     render_central_panel(app, ctx);
     render_dialogs(app, ctx);
     
     // Render notifications on top
-    render_notifications(app, ctx, frame_time);
+    render_notifications(app, ctx);
 }
 //
-fn render_notifications(app: &mut ModManager, ctx: &egui::Context, frame_time: f32) {
-    if app.show_notification {
-        // Update notification time
-        app.notification_time -= frame_time;
-        if app.notification_time <= 0.0 {
-            app.show_notification = false;
+fn render_notifications(app: &mut ModManager, ctx: &egui::Context) {
+    if app.notifications.is_empty() {
+        return;
+    }
+    
+    // Use real elapsed time so the timers don't depend on the frame rate
+    let elapsed = ctx.input(|i| i.unstable_dt);
+    for notification in app.notifications.iter_mut() {
+        notification.remaining -= elapsed;
+    }
+    app.notifications.retain(|notification| notification.remaining > 0.0);
+    
+    // Keep repainting while notifications are counting down
+    if !app.notifications.is_empty() {
+        ctx.request_repaint();
+    }
+    
+    // Calculate position of the stack
+    let screen_rect = ctx.screen_rect();
+    let notification_width = 300.0;
+    let notification_height = 50.0;
+    let margin = 20.0;
+    let spacing = 8.0;
+    
+    let corner = app.notification_corner;
+    let x_position = match corner {
+        NotificationCorner::TopLeft | NotificationCorner::BottomLeft => screen_rect.left() + margin,
+        NotificationCorner::TopRight | NotificationCorner::BottomRight => {
+            screen_rect.right() - notification_width - margin
         }
-        
-        // Calculate position and opacity
-        let screen_rect = ctx.screen_rect();
-        let notification_width = 300.0;
-        let notification_height = 50.0;
-        let margin = 20.0;
-        
-        let x_position = screen_rect.right() - notification_width - margin;
-        let y_position = screen_rect.top() + margin;
+    };
+    
+    // Newest notification sits closest to the corner
+    let mut dismissed = None;
+    for (index, notification) in app.notifications.iter().enumerate().rev() {
+        let offset = (app.notifications.len() - 1 - index) as f32 * (notification_height + spacing);
+        let y_position = match corner {
+            NotificationCorner::TopLeft | NotificationCorner::TopRight => screen_rect.top() + margin + offset,
+            NotificationCorner::BottomLeft | NotificationCorner::BottomRight => {
+                screen_rect.bottom() - margin - notification_height - offset
+            }
+        };
         
         let rect = egui::Rect::from_min_size(
             egui::pos2(x_position, y_position),
@@ -49,27 +97,40 @@ fn render_notifications(app: &mut ModManager, ctx: &egui::Context, frame_time: f
         );
         
         // Calculate opacity (fade out at the end)
-        let opacity = if app.notification_time < 1.0 {
-            app.notification_time
-        } else {
-            1.0
-        };
+        let opacity = notification.remaining.min(1.0);
         
         // Draw notification
-        let notification_color = Color32::from_rgba_premultiplied(0, 150, 0, (opacity * 220.0) as u8);
+        let (icon, [r, g, b]) = match notification.severity {
+            Severity::Info => ("ℹ", [40, 90, 160]),
+            Severity::Success => ("✔", [0, 150, 0]),
+            Severity::Warning => ("⚠", [190, 120, 0]),
+            Severity::Error => ("✖", [180, 30, 30]),
+        };
+        let notification_color = Color32::from_rgba_premultiplied(r, g, b, (opacity * 220.0) as u8);
         let text_color = Color32::from_rgba_premultiplied(255, 255, 255, (opacity * 255.0) as u8);
         
         egui::Window::new("Notification")
-            .frame(egui::Frame::none().fill(notification_color))
+            .id(egui::Id::new(("notification", index)))
+            .frame(egui::Frame::NONE.fill(notification_color))
             .title_bar(false)
             .resizable(false)
             .fixed_rect(rect)
             .show(ctx, |ui| {
                 ui.centered_and_justified(|ui| {
-                    ui.label(RichText::new(&app.notification_message).color(text_color).strong());
+                    let text = format!("{} {}", icon, app.redact(&notification.message));
+                    // Click to dismiss before the timer runs out
+                    if ui.add(egui::Label::new(RichText::new(text).color(text_color).strong()).sense(egui::Sense::click()))
+                        .on_hover_cursor(egui::CursorIcon::PointingHand)
+                        .clicked()
+                    {
+                        dismissed = Some(index);
+                    }
                 });
             });
     }
+    if let Some(index) = dismissed {
+        app.notifications.remove(index);
+    }
 }
 //
 fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
@@ -83,8 +144,83 @@ fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
                         app.mods = mods;
                     }
                 }
+                
+                if ui.add_enabled(!app.game_path.is_empty(), egui::Button::new("▶ Launch DRG"))
+                    .on_hover_text("Deploy the enabled mods of this profile and start the game")
+                    .on_disabled_hover_text("Set the game path in Settings first")
+                    .clicked()
+                {
+                    app.launch_game();
+                }
+                
+                // Unmodded run to tell whether a crash comes from a mod; the profile stays as it is
+                if app.vanilla_session.is_some() {
+                    if ui.button("Restore mods")
+                        .on_hover_text("Put the mods back now instead of when the game exits")
+                        .clicked()
+                    {
+                        app.restore_vanilla_session();
+                    }
+                    ui.label(RichText::new("Vanilla session").color(Color32::from_rgb(255, 165, 0)));
+                } else if ui.add_enabled(!app.game_path.is_empty(), egui::Button::new("Launch vanilla"))
+                    .on_hover_text("Start the game with the mods removed, and put them back once it exits")
+                    .on_disabled_hover_text("Set the game path in Settings first")
+                    .clicked()
+                {
+                    app.launch_vanilla();
+                }
+                
+                if app.guest_mode {
+                    ui.label(RichText::new("GUEST MODE").color(Color32::from_rgb(255, 165, 0)).strong());
+                }
+                
+                let outdated = app.outdated_mod_count();
+                if outdated > 0
+                    && ui.button(format!("Update All ({})", outdated))
+                        .on_hover_text("Download the newest release of every out-of-date mod")
+                        .clicked()
+                {
+                    app.update_all();
+                }
+                
+                // Undo a whole update run while its snapshot is kept
+                if app.update_run.is_none()
+                    && let Some(taken_at) = app.update_snapshot_time()
+                    && ui.button("Revert last update run")
+                        .on_hover_text(format!(
+                            "Go back to the versions and enabled mods from before the update {}",
+                            format::relative_date(taken_at)
+                        ))
+                        .clicked()
+                {
+                    app.revert_last_update();
+                }
+                
+                // Overall progress of running downloads
+                if !app.downloads.is_empty() || !app.download_queue.is_empty() {
+                    let mut text = format!("Downloading {} mod(s)", app.downloads.len());
+                    if !app.download_queue.is_empty() {
+                        text.push_str(&format!(", {} queued", app.download_queue.len()));
+                    }
+                    let progress_bar = match app.download_progress() {
+                        Some(fraction) => egui::ProgressBar::new(fraction).show_percentage(),
+                        None => egui::ProgressBar::new(0.0).animate(true),
+                    };
+                    ui.add(progress_bar.desired_width(120.0));
+                    if ui.link(text).on_hover_text("Show the Downloads tab").clicked() {
+                        app.current_tab = Tab::Downloads;
+                    }
+                }
             });
         });
+        
+        if let Some(warning) = &app.cloud_sync_warning {
+            ui.label(RichText::new(warning).color(Color32::from_rgb(255, 200, 0)));
+        }
+        if let Some(warning) = &app.load_limit_warning {
+            ui.label(RichText::new(warning).color(Color32::from_rgb(255, 200, 0)))
+                .on_hover_text("The limits can be changed in Settings");
+        }
         ui.separator();
         
         // Tab selection
@@ -95,6 +231,9 @@ fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
             if ui.selectable_label(matches!(app.current_tab, Tab::Installed), "Installed").clicked() {
                 app.current_tab = Tab::Installed;
             }
+            if ui.selectable_label(matches!(app.current_tab, Tab::Downloads), "Downloads").clicked() {
+                app.current_tab = Tab::Downloads;
+            }
             if ui.selectable_label(matches!(app.current_tab, Tab::Settings), "Settings").clicked() {
                 app.current_tab = Tab::Settings;
             }
@@ -104,14 +243,32 @@ fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
         if matches!(app.current_tab, Tab::Browse) {
             ui.horizontal(|ui| {
                 // Add button to process the file path
-                if ui.button("[+]").clicked() && !app.file_path.is_empty() {
+                let add_clicked = ui.add_enabled_ui(app.resolving_link.is_none(), |ui| {
+                    icon_button(ui, "[+]", "Add mod from path, URL, @slug or mod.io ID").clicked()
+                }).inner;
+                if add_clicked && !app.file_path.is_empty() {
+                    if protocol::is_protocol_link(&app.file_path) {
+                        let link = std::mem::take(&mut app.file_path);
+                        app.open_protocol_link(link.trim());
+                        return;
+                    }
+                    
+                    // mod.io links, slugs and IDs are looked up first, so the entry gets the real ID and name.
+                    // An existing local file wins over a short form that happens to match its path
+                    let reference = ParsedModRef::parse_input(&app.file_path)
+                        .filter(|_| !std::path::Path::new(app.file_path.trim()).exists());
+                    if let Some(reference) = reference {
+                        app.add_mod_io_link(reference);
+                        return;
+                    }
+                    
                     // Create a new mod entry
                     let mod_id = format!("mod_{}", chrono::Utc::now().timestamp());
                     let is_url = app.file_path.starts_with("http");
                     
                     let mod_name = if is_url {
                         // Extract name from URL if possible
-                        app.file_path.split('/').last().unwrap_or("New Mod").to_string()
+                        app.file_path.split('/').next_back().unwrap_or("New Mod").to_string()
                     } else {
                         // Extract name from file path
                         std::path::Path::new(&app.file_path)
@@ -127,8 +284,16 @@ fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
                         mod_link: app.file_path.clone(),
                         download_folder: "downloads".to_string(),
                         selected_version: "1.0.0".to_string(),
+                        versions: Vec::new(),
                         installed: false,
                         enabled: false,
+                        variants: Vec::new(),
+                        selected_variant: String::new(),
+                        last_checked: None,
+                        disabled_reason: None,
+                        thumbnail_url: None,
+                        update_available: None,
+                        notes: String::new(),
                     };
                     
                     // Add the mod to the database
@@ -139,18 +304,22 @@ fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
                         }
                         // Clear the file path
                         app.file_path.clear();
+                        check_mod_hub_dependency(app, &new_mod.mod_id);
                     }
                 }
                 
+                if app.resolving_link.is_some() {
+                    ui.spinner();
+                }
+                
                 ui.add_space(4.0);
                 
                 // File selector button
-                if ui.button("Browse").clicked() {
-                    if let Some(path) = rfd::FileDialog::new().pick_file() {
-                        if let Some(path_str) = path.to_str() {
-                            app.file_path = path_str.to_string();
-                        }
-                    }
+                if ui.button("Browse").clicked()
+                    && let Some(path) = rfd::FileDialog::new().pick_file()
+                    && let Some(path_str) = path.to_str()
+                {
+                    app.file_path = path_str.to_string();
                 }
                 
                 ui.add_space(4.0);
@@ -158,54 +327,95 @@ fn render_top_panel(app: &mut ModManager, ctx: &egui::Context) {
                 // File path input that stretches to fill available space
                 ui.add(egui::TextEdit::singleline(&mut app.file_path)
                     .desired_width(ui.available_width())
-                    .hint_text("Mod file path or URL...")
+                    .hint_text("Mod file path, URL, @slug or mod.io ID...")
                 );
             });
         }
     });
 }
 
+// Icons offered for profiles; empty means none
+const PROFILE_ICONS: [&str; 9] = ["", "⛏", "🍺", "🎮", "🔧", "⭐", "🔥", "🛡", "💀"];
+
+// Name a widget for screen readers, for icons whose text says nothing when read out
+fn with_label(response: egui::Response, widget_type: egui::WidgetType, label: &str) -> egui::Response {
+    response.widget_info(|| egui::WidgetInfo::labeled(widget_type, response.enabled(), label));
+    response
+}
+
+// Icon-only button; sighted users get the same name as a tooltip
+fn icon_button(ui: &mut egui::Ui, icon: impl Into<egui::WidgetText>, label: &str) -> egui::Response {
+    let response = ui.add(egui::Button::new(icon));
+    with_label(response, egui::WidgetType::Button, label).on_hover_text(label)
+}
+
+// Move the keyboard into a dialog when it opens, unless another widget is being used
+fn focus_if_idle(ui: &egui::Ui, response: &egui::Response) {
+    if ui.memory(|memory| memory.focused().is_none()) {
+        response.request_focus();
+    }
+}
+
+fn profile_text(app: &ModManager, profile: &str) -> RichText {
+    let [r, g, b] = app.profile_style(profile).color;
+    RichText::new(app.profile_label(profile)).color(Color32::from_rgb(r, g, b))
+}
+
 fn render_side_panel(app: &mut ModManager, ctx: &egui::Context) {
-    egui::SidePanel::left("side_panel")
+    let panel = egui::SidePanel::left(egui::Id::new("side_panel").with(app.layout_generation))
         .resizable(true)
-        .default_width(200.0)
+        .default_width(app.layout.side_panel_width)
         .show(ctx, |ui| {
             ui.heading("Profiles");
             ui.horizontal(|ui| {
+                let current_profile = app.db.get_current_profile().to_string();
                 egui::ComboBox::from_label("")
-                    .selected_text(app.db.get_current_profile())
+                    .selected_text(profile_text(app, &current_profile))
                     .show_ui(ui, |ui| {
-                        for profile in &app.profiles {
+                        for profile in app.profiles.clone() {
                             if ui.selectable_label(
-                                profile == app.db.get_current_profile(),
-                                profile
+                                profile == current_profile,
+                                profile_text(app, &profile)
                             ).clicked() {
                                 app.db.set_current_profile(profile.clone());
                                 // Reload mods for this profile
                                 if let Ok(mods) = app.db.get_mods() {
                                     app.mods = mods;
                                 }
+                                let enabled = app.mods.iter().filter(|m| m.enabled).count();
+                                app.notify_webhook(
+                                    WebhookEvent::ProfileApplied,
+                                    format!("Switched to profile {} with {} enabled mods", profile, enabled)
+                                );
                             }
                         }
                     });
                 
+                if !app.guest_mode
+                    && ui.button("Duplicate")
+                        .on_hover_text("Copy this profile to tweak it without changing the original")
+                        .clicked()
+                {
+                    app.duplicate_profile();
+                }
+                
                 let current_profile = app.db.get_current_profile().to_string();
-                if current_profile != "Default" {
+                if current_profile != "Default" && !app.guest_mode {
                     ui.horizontal(|ui| {
                         if !app.delete_confirmation_requested {
-                            if ui.button("🗑").clicked() {
+                            if icon_button(ui, "🗑", "Delete profile").clicked() {
                                 app.delete_confirmation_requested = true;
                             }
                         } else {
                             // First button (cancel)
-                            if ui.button("🗑").clicked() {
+                            let cancel = icon_button(ui, "🗑", "Keep profile");
+                            if cancel.clicked() {
                                 app.delete_confirmation_requested = false;
                             }
                             
                             // Second button (confirm - red)
-                            if ui.add(egui::Button::new(
-                                RichText::new("🗑").color(Color32::RED)
-                            )).clicked() {
+                            let confirm = icon_button(ui, RichText::new("🗑").color(Color32::RED), "Confirm deleting profile");
+                            if confirm.clicked() {
                                 if let Ok(()) = app.db.delete_profile(&current_profile) {
                                     app.profiles = app.db.get_profiles().unwrap_or_default();
                                     app.db.set_current_profile("Default".to_string());
@@ -216,8 +426,8 @@ fn render_side_panel(app: &mut ModManager, ctx: &egui::Context) {
                                 app.delete_confirmation_requested = false;
                             }
                             
-                            // Auto-cancel if mouse moves away
-                            if !ui.ui_contains_pointer() {
+                            // Auto-cancel if mouse moves away, unless the keyboard is on the buttons
+                            if !ui.ui_contains_pointer() && !cancel.has_focus() && !confirm.has_focus() {
                                 app.delete_confirmation_requested = false;
                             }
                         }
@@ -225,19 +435,89 @@ fn render_side_panel(app: &mut ModManager, ctx: &egui::Context) {
                 }
             });
             
+            // Color and icon of the current profile
+            ui.horizontal(|ui| {
+                ui.label("Style:");
+                let mut style = app.profile_style(app.db.get_current_profile());
+                let mut changed = ui.color_edit_button_srgb(&mut style.color).changed();
+                egui::ComboBox::from_id_salt("profile_icon")
+                    .width(40.0)
+                    .selected_text(if style.icon.is_empty() { "-" } else { style.icon.as_str() })
+                    .show_ui(ui, |ui| {
+                        for icon in PROFILE_ICONS {
+                            let label = if icon.is_empty() { "-" } else { icon };
+                            changed |= ui.selectable_value(&mut style.icon, icon.to_string(), label).changed();
+                        }
+                    });
+                if changed {
+                    app.set_profile_style(style);
+                }
+            });
+            
+            // What the current profile does once installs and updates finish
+            ui.horizontal(|ui| {
+                ui.label("After installs:");
+                let mut post_install = app.post_install(app.db.get_current_profile());
+                let mut changed = false;
+                egui::ComboBox::from_id_salt("post_install_action")
+                    .selected_text(post_install.action.label())
+                    .show_ui(ui, |ui| {
+                        for action in PostInstallAction::ALL {
+                            changed |= ui.selectable_value(&mut post_install.action, action, action.label()).changed();
+                        }
+                    });
+                if post_install.action == PostInstallAction::RunCommand {
+                    let response = ui.add(egui::TextEdit::singleline(&mut post_install.command)
+                        .hint_text("Command, asked before each run"));
+                    // Kept in memory while typing and saved when done
+                    if response.changed() {
+                        app.post_install_actions.insert(app.db.get_current_profile().to_string(), post_install.clone());
+                    }
+                    changed |= response.lost_focus();
+                }
+                if changed {
+                    app.set_post_install(post_install);
+                }
+            });
+            
             // Add profile creation UI
             ui.horizontal(|ui| {
                 ui.label("New profile:");
                 ui.text_edit_singleline(&mut app.new_profile_name);
             });
 
-            if ui.button("Create Profile").clicked() && !app.new_profile_name.is_empty() {
-                if let Ok(()) = app.db.create_profile(&app.new_profile_name) {
-                    app.profiles = app.db.get_profiles().unwrap_or_default();
-                    app.db.set_current_profile(app.new_profile_name.clone());
-                    app.new_profile_name.clear();
-                }
+            if ui.button("Create Profile").clicked()
+                && !app.new_profile_name.is_empty()
+                && let Ok(()) = app.db.create_profile(&app.new_profile_name)
+            {
+                app.profiles = app.db.get_profiles().unwrap_or_default();
+                app.db.set_current_profile(app.new_profile_name.clone());
+                app.new_profile_name.clear();
             }
+            
+            // Shareable mod list of the current profile
+            ui.horizontal(|ui| {
+                if ui.button("Export")
+                    .on_hover_text("Save this profile's mod list to share it")
+                    .clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .set_file_name(format!("{}.profile.json", app.db.get_current_profile()))
+                        .add_filter("Profile", &["json"])
+                        .save_file()
+                {
+                    app.export_profile(&path);
+                }
+                if !app.guest_mode
+                    && ui.button("Import")
+                        .on_hover_text("Add a shared profile and download its enabled mods")
+                        .clicked()
+                    && let Some(path) = rfd::FileDialog::new()
+                        .add_filter("Profile", &["json"])
+                        .pick_file()
+                {
+                    app.import_profile(&path);
+                }
+            });
             ui.separator();
 
             ui.heading("Filters");
@@ -246,8 +526,12 @@ fn render_side_panel(app: &mut ModManager, ctx: &egui::Context) {
             // Search field with on change trigger
             ui.horizontal(|ui| {
                 ui.label("Search:");
-                ui.text_edit_singleline(&mut app.search_query)
-                    .on_hover_text("Search mods by name");
+                if ui.text_edit_singleline(&mut app.search_query)
+                    .on_hover_text("Search mods by name")
+                    .changed()
+                {
+                    app.search_edited_at = ui.input(|input| input.time);
+                }
             });
             
             // Bool switch that slides to the side
@@ -255,15 +539,72 @@ fn render_side_panel(app: &mut ModManager, ctx: &egui::Context) {
                 ui.label("Installed only:");
                 ui.add(egui::widgets::Checkbox::new(&mut app.show_installed_only, ""));
             });
+            ui.horizontal(|ui| {
+                ui.label("Favorites only:");
+                ui.add(egui::widgets::Checkbox::new(&mut app.favorites_only, ""));
+            });
             
             // Collapsing section (rollout)
             egui::CollapsingHeader::new("Categories")
                 .default_open(true)
                 .show(ui, |ui| {
-                    ui.selectable_value(&mut (), (), "Gameplay");
-                    ui.selectable_value(&mut (), (), "Visual");
-                    ui.selectable_value(&mut (), (), "Audio");
-                    ui.selectable_value(&mut (), (), "Quality of Life");
+                    if app.tag_options.is_empty() {
+                        ui.label(RichText::new("Loading tags from mod.io...").weak());
+                    }
+                    
+                    let mut toggled = None;
+                    for option in &app.tag_options {
+                        ui.label(RichText::new(&option.name).strong());
+                        for tag in &option.tags {
+                            if ui.selectable_label(app.tag_filter.contains(tag), tag).clicked() {
+                                toggled = Some(tag.clone());
+                            }
+                        }
+                    }
+                    if let Some(tag) = toggled {
+                        app.toggle_tag_filter(&tag);
+                    }
+                    
+                    if !app.tag_filter.is_empty() && ui.small_button("Clear filters").clicked() {
+                        app.set_tag_filter(Default::default());
+                    }
+                });
+            
+            // The user's own tags; they only narrow the library, not the mod.io catalog
+            egui::CollapsingHeader::new("My tags")
+                .default_open(true)
+                .show(ui, |ui| {
+                    let tags = app.all_local_tags();
+                    if tags.is_empty() {
+                        ui.label(RichText::new("Tag mods from their details panel.").weak());
+                    }
+                    
+                    let mut toggled = None;
+                    let mut recolored = None;
+                    for tag in &tags {
+                        ui.horizontal(|ui| {
+                            let mut color = app.tag_color(tag);
+                            if ui.color_edit_button_srgb(&mut color).changed() {
+                                recolored = Some((tag.clone(), color));
+                            }
+                            if tag_chip(ui, tag, color, app.local_tag_filter.contains(tag))
+                                .on_hover_text("Show only mods with this tag")
+                                .clicked()
+                            {
+                                toggled = Some(tag.clone());
+                            }
+                        });
+                    }
+                    if let Some((tag, color)) = recolored {
+                        app.set_tag_color(&tag, color);
+                    }
+                    if let Some(tag) = toggled {
+                        app.toggle_local_tag_filter(&tag);
+                    }
+                    
+                    if !app.local_tag_filter.is_empty() && ui.small_button("Clear tag filter").clicked() {
+                        app.local_tag_filter.clear();
+                    }
                 });
             
             ui.separator();
@@ -277,26 +618,261 @@ fn render_side_panel(app: &mut ModManager, ctx: &egui::Context) {
             );
             ui.label(format!("{} mods", app.selected_mods.len()));
             
+            let mut mod_actions: Vec<ModAction> = Vec::new();
             ui.with_layout(egui::Layout::bottom_up(egui::Align::Center), |ui| {
                 if ui.button("Install Selected").clicked() {
                     // Install selected mods
                     for mod_id in &app.selected_mods.clone() {
-                        if let Some(mod_entry) = app.mods.iter().find(|m| &m.mod_id == mod_id) {
-                            if let Ok(()) = app.installer.install_mod(mod_entry) {
-                                if let Ok(()) = app.db.update_mod_installed(&mod_id, true) {
-                                    // Mod installed successfully
-                                }
-                            }
+                        app.start_install(mod_id);
+                    }
+                }
+                render_bulk_action_buttons(app, ui, &mut mod_actions);
+            });
+            
+            let mut needs_reload = false;
+            process_mod_actions(app, &mod_actions, &mut needs_reload);
+            if needs_reload {
+                reload_mods(app);
+            }
+        });
+    
+    // Saved with the config so the layout survives restarts
+    app.layout.side_panel_width = panel.response.rect.width();
+}
+
+// One action per selected mod the button applies to, e.g. only disabled mods get enabled
+fn render_bulk_action_buttons(
+    app: &ModManager,
+    ui: &mut egui::Ui,
+    mod_actions: &mut Vec<ModAction>
+) {
+    let selected: Vec<&ModEntry> = app.mods.iter()
+        .filter(|m| app.selected_mods.contains(&m.mod_id))
+        .collect();
+    let installed = || selected.iter().filter(|m| m.installed);
+    
+    let outdated: Vec<&str> = installed()
+        .filter(|m| m.update_available.is_some())
+        .map(|m| m.mod_id.as_str())
+        .collect();
+    if ui.add_enabled(!outdated.is_empty(), egui::Button::new("Update Selected")).clicked() {
+        mod_actions.extend(outdated.iter().map(|mod_id| ModAction::UpdateMod(mod_id.to_string())));
+    }
+    
+    let installed_count = installed().count();
+    let enabled_count = installed().filter(|m| m.enabled).count();
+    if ui.add_enabled(enabled_count > 0, egui::Button::new("Disable Selected")).clicked() {
+        mod_actions.extend(installed()
+            .filter(|m| m.enabled)
+            .map(|m| ModAction::ToggleModEnabled(m.mod_id.clone(), false)));
+    }
+    if ui.add_enabled(enabled_count < installed_count, egui::Button::new("Enable Selected")).clicked() {
+        mod_actions.extend(installed()
+            .filter(|m| !m.enabled)
+            .map(|m| ModAction::ToggleModEnabled(m.mod_id.clone(), true)));
+    }
+    // Guests can't remove mods one by one either
+    if !app.guest_mode
+        && ui.add_enabled(installed_count > 0, egui::Button::new("Uninstall Selected")).clicked()
+    {
+        mod_actions.extend(installed().map(|m| ModAction::UninstallMod(m.mod_id.clone())));
+    }
+}
+
+fn render_detail_panel(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(panel) = &app.detail_panel else {
+        return;
+    };
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == panel.mod_id) else {
+        app.detail_panel = None;
+        return;
+    };
+    
+    let mut close = false;
+    let mut subscribe = None;
+    let mut edit_source = None;
+    let mut notes = panel.notes.clone();
+    let mut save_notes = false;
+    let mut new_tag = panel.new_tag.clone();
+    let mut add_tag = false;
+    let mut remove_tag = None;
+    let mod_tags: Vec<(String, [u8; 3])> = app.local_tags
+        .get(&mod_entry.mod_id)
+        .into_iter()
+        .flatten()
+        .map(|tag| (tag.clone(), app.tag_color(tag)))
+        .collect();
+    let is_subscribed = app.subscriptions.contains(&mod_entry.mod_id);
+    let response = egui::SidePanel::right(egui::Id::new("mod_details").with(app.layout_generation))
+        .resizable(true)
+        .default_width(app.layout.detail_panel_width)
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading(&mod_entry.mod_name);
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if icon_button(ui, "✖", "Close details").clicked() {
+                        close = true;
+                    }
+                    if mod_entry.modio_id().is_some() {
+                        let label = if is_subscribed { "Unsubscribe" } else { "Subscribe" };
+                        if ui.button(label).on_hover_text("Update your subscription on mod.io").clicked() {
+                            subscribe = Some(!is_subscribed);
                         }
                     }
-                    
-                    // Reload mods
-                    if let Ok(mods) = app.db.get_mods() {
-                        app.mods = mods;
+                });
+            });
+            ui.separator();
+            
+            // Kept in the library, so they show in every profile and are found by the search
+            let response = ui.add(
+                egui::TextEdit::multiline(&mut notes)
+                    .hint_text("Notes, e.g. why it's installed or which settings go with it")
+                    .desired_rows(2)
+                    .desired_width(f32::INFINITY)
+            );
+            // Also when the panel is closed while typing
+            save_notes = (response.lost_focus() || close) && notes.trim() != mod_entry.notes;
+            
+            ui.horizontal_wrapped(|ui| {
+                for (tag, color) in &mod_tags {
+                    if tag_chip(ui, tag, *color, false).on_hover_text("Remove this tag").clicked() {
+                        remove_tag = Some(tag.clone());
                     }
                 }
+                let response = ui.add(egui::TextEdit::singleline(&mut new_tag).hint_text("Add tag").desired_width(90.0));
+                add_tag = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
             });
+            ui.separator();
+            
+            match &panel.details {
+                None if mod_entry.modio_id().is_none() => {
+                    ui.label(format!("Version: {}", mod_entry.selected_version));
+                    ui.label(app.redact(&mod_entry.mod_link));
+                    ui.label(RichText::new("No mod.io metadata for this mod.").weak());
+                    
+                    if mod_entry.mod_link.starts_with("http")
+                        && !app.guest_mode
+                        && ui.button("Download headers...")
+                            .on_hover_text("Auth headers or cookies the file host needs, kept in the system keyring or an encrypted file")
+                            .clicked()
+                    {
+                        edit_source = Some(mod_entry.mod_link.clone());
+                    }
+                },
+                None => {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Loading details...");
+                    });
+                },
+                Some(Err(e)) => {
+                    ui.label(RichText::new(format!("Failed to load details: {}", e)).color(Color32::RED));
+                },
+                Some(Ok(details)) => {
+                    let dependencies = app.db.get_mod_dependencies(&mod_entry.mod_id).unwrap_or_default();
+                    render_mod_io_details(ui, mod_entry, details, &dependencies);
+                },
+            }
+            
+            if !panel.paks.is_empty() {
+                ui.separator();
+                render_pak_stats(ui, &panel.paks);
+            }
         });
+    app.layout.detail_panel_width = response.response.rect.width();
+    
+    if let Some(panel) = &mut app.detail_panel {
+        panel.notes = notes;
+        panel.new_tag = if add_tag { String::new() } else { new_tag.clone() };
+        let mod_id = panel.mod_id.clone();
+        if save_notes {
+            let notes = panel.notes.clone();
+            app.save_mod_notes(&mod_id, &notes);
+        }
+        if add_tag {
+            app.add_local_tag(&mod_id, &new_tag);
+        }
+        if let Some(tag) = remove_tag {
+            app.remove_local_tag(&mod_id, &tag);
+        }
+    }
+    if let Some(subscribed) = subscribe
+        && let Some(panel) = &app.detail_panel
+    {
+        let mod_id = panel.mod_id.clone();
+        app.set_subscribed(&mod_id, subscribed);
+    }
+    if let Some(mod_link) = edit_source {
+        app.open_source_editor(&mod_link);
+    }
+    if close {
+        app.detail_panel = None;
+    }
+}
+
+fn render_mod_io_details(
+    ui: &mut egui::Ui,
+    mod_entry: &ModEntry,
+    details: &ModIoMod,
+    dependencies: &[(String, String)]
+) {
+    ui.label(format!("by {}", details.submitted_by.username));
+    ui.hyperlink_to("Open on mod.io", &mod_entry.mod_link);
+    ui.add_space(4.0);
+    
+    egui::Grid::new("mod_details_stats").num_columns(2).show(ui, |ui| {
+        ui.label("Downloads:");
+        ui.label(format::number(details.stats.downloads_total.into()));
+        ui.end_row();
+        ui.label("Subscribers:");
+        ui.label(format::number(details.stats.subscribers_total.into()));
+        ui.end_row();
+        ui.label("Ratings:");
+        ui.label(format::number(details.stats.rating_total.into()));
+        ui.end_row();
+        ui.label("Added:");
+        ui.label(format::date(details.date_added));
+        ui.end_row();
+        ui.label("Updated:");
+        ui.label(format::relative_date(details.date_updated))
+            .on_hover_text(format::date(details.date_updated));
+        ui.end_row();
+    });
+    
+    ui.add_space(4.0);
+    ui.label(RichText::new("Files").strong());
+    match &details.modfile {
+        Some(file) => {
+            ui.label(format!(
+                "{} ({}){}",
+                file.filename,
+                format::size(file.filesize),
+                file.version.as_ref().map(|version| format!(", v{}", version)).unwrap_or_default()
+            ));
+            ui.label(RichText::new(format!("Uploaded {}", format::relative_date(file.date_added))).weak())
+                .on_hover_text(format::date(file.date_added));
+        },
+        None => {
+            ui.label(RichText::new("No files uploaded yet.").weak());
+        },
+    }
+    
+    // Known once the mod has been installed
+    if !dependencies.is_empty() {
+        ui.add_space(4.0);
+        ui.label(RichText::new("Requires").strong());
+        for (_, dependency_name) in dependencies {
+            ui.label(format!("• {}", dependency_name));
+        }
+    }
+    
+    ui.add_space(4.0);
+    ui.separator();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.label(RichText::new(&details.summary).italics());
+        ui.add_space(4.0);
+        ui.label(ModIoClient::html_to_text(&details.description));
+    });
 }
 
 fn render_central_panel(app: &mut ModManager, ctx: &egui::Context) {
@@ -305,26 +881,71 @@ fn render_central_panel(app: &mut ModManager, ctx: &egui::Context) {
             Tab::Browse | Tab::Installed => {
                 render_mod_list(app, ui);
             },
+            Tab::Downloads => render_downloads_tab(app, ui),
+            Tab::Settings if app.guest_mode => {
+                ui.heading("Settings");
+                ui.separator();
+                ui.label("Guest mode is on. Settings, tokens and file paths are hidden and destructive actions are disabled.");
+                if ui.button("Leave guest mode").clicked() {
+                    app.set_guest_mode(false);
+                }
+            },
             Tab::Settings => {
                 ui.heading("Settings");
                 ui.separator();
                 
+                if ui.button("Enter guest mode")
+                    .on_hover_text("Hide tokens and file paths and disable destructive actions, e.g. while streaming")
+                    .clicked()
+                {
+                    app.set_guest_mode(true);
+                }
+                
                 ui.horizontal(|ui| {
                     ui.label("Game Path:");
-                    ui.text_edit_singleline(&mut app.game_path)
-                        .on_hover_text("Path to your Deep Rock Galactic installation");
-                    if ui.button("Browse").clicked() {
-                        if let Some(path) = rfd::FileDialog::new()
+                    if ui.text_edit_singleline(&mut app.game_path)
+                        .on_hover_text("Path to your Deep Rock Galactic installation")
+                        .lost_focus()
+                    {
+                        app.game_path_changed();
+                    }
+                    if ui.button("Browse").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
                             .set_title("Select DRG Executable")
-                            .pick_file() {
-                            if let Some(path_str) = path.to_str() {
-                                app.game_path = path_str.to_string();
-                                // Save the game path to config
-                                app.save_config();
-                            }
-                        }
+                            .pick_file()
+                        && let Some(path_str) = path.to_str()
+                    {
+                        app.game_path = path_str.to_string();
+                        app.game_path_changed();
                     }
                 });
+                
+                if ui.button("Import mods from game")
+                    .on_hover_text("Scan the game's .modio folder for mods it already manages")
+                    .clicked()
+                {
+                    app.scan_ingame_mods();
+                }
+                
+                if ui.add_enabled(!app.game_path.is_empty(), egui::Button::new("Remove all mods from game"))
+                    .on_hover_text("Delete every file this manager deployed into the game folder. The library and profiles are kept, so mods can be deployed again")
+                    .clicked()
+                {
+                    app.purge_game_folder();
+                }
+                
+                if ui.button("Open mod links with this manager")
+                    .on_hover_text("Register drgmodman:// and modio:// links, so \"Open in mod manager\" links on web pages install mods here")
+                    .clicked()
+                {
+                    match protocol::register() {
+                        Ok(()) => app.notify(Severity::Success, "Mod links now open in this manager.".to_string()),
+                        Err(e) => {
+                            app.error_message = format!("Error registering the link handler: {}", e);
+                            app.show_error_message = true;
+                        }
+                    }
+                }
 
                 ui.add_space(10.0);
                 ui.heading("Mod.io Integration");
@@ -345,79 +966,59 @@ fn render_central_panel(app: &mut ModManager, ctx: &egui::Context) {
                     // Only update the OAuth2 key in memory, don't make API calls yet
                     if response.changed() {
                         app.mod_io_oauth_key = oauth_key;
+                        // The shown account belongs to the validated token
+                        app.mod_io_account = None;
                     }
                     
                     // Add a "Check" button that will validate the OAuth2 key
-                    if ui.button("Check").clicked() && !app.mod_io_oauth_key.is_empty() {
-                        // Initialize ModIoClient if needed
-                        if app.mod_io_client.is_uninitialized() {
-                            app.mod_io_client = ModIoClient::new();
-                        }
-                        
-                        // List user games to validate the OAuth2 key
-                        match app.mod_io_client.list_user_games(&app.mod_io_oauth_key) {
-                            Ok(_) => {
-                                // API key is valid, store it in the keyring
-                                let keyring_entry = Entry::new("ue4-drg-modman", "mod_io_api_key").unwrap();
-                                if let Err(e) = keyring_entry.set_password(&app.mod_io_oauth_key) {
-                                    app.error_message = format!("Error saving OAuth2 key to keyring: {}", e);
-                                    app.show_error_message = true;
-                                } else {
-                                    // Use notification instead of error message
-                                    app.show_notification("OAuth2 validated successfully and saved to keyring.".to_string());
-                                }
-                            },
-                            Err(e) => {
-                                app.error_message = format!("Error validating Mod.io OAuth2: {}", e);
-                                app.show_error_message = true;
-                            }
-                        }
+                    let check_label = if app.oauth_check_pending { "Checking..." } else { "Check" };
+                    if ui.add_enabled(!app.oauth_check_pending, egui::Button::new(check_label)).clicked()
+                        && !app.mod_io_oauth_key.is_empty()
+                    {
+                        app.validate_mod_io_oauth_key();
                     }
                     
                     // Add delete button for clearing the API key
                     if !app.mod_io_oauth_key.is_empty() {
-                        let mut mod_io_key_delete_requested = false;
-                        
-                        if !mod_io_key_delete_requested {
-                            if ui.button("🗑").clicked() {
-                                mod_io_key_delete_requested = true;
+                        if !app.mod_io_key_delete_requested {
+                            if icon_button(ui, "🗑", "Remove OAuth2 token").clicked() {
+                                app.mod_io_key_delete_requested = true;
                             }
                         } else {
                             // First button (cancel)
-                            if ui.button("🗑").clicked() {
-                                mod_io_key_delete_requested = false;
+                            let cancel = icon_button(ui, "🗑", "Keep OAuth2 token");
+                            if cancel.clicked() {
+                                app.mod_io_key_delete_requested = false;
                             }
                             
                             // Second button (confirm - red)
-                            if ui.add(egui::Button::new(
-                                RichText::new("🗑").color(Color32::RED)
-                            )).clicked() {
-                                // Clear the OAuth2 key from memory
-                                app.mod_io_oauth_key.clear();
-                                
-                                // Remove from keyring
-                                let keyring_entry = Entry::new("ue4-drg-modman", "mod_io_oauth_key").unwrap();
-                                if let Err(e) = keyring_entry.delete_credential() {
-                                    // Only show error if it's not because the credential doesn't exist
-                                    if !e.to_string().contains("No such keyring entry") {
-                                        app.error_message = format!("Error removing OAuth2 key from keyring: {}", e);
-                                        app.show_error_message = true;
-                                    }
-                                }
-                                
-                                mod_io_key_delete_requested = false;
+                            let confirm = icon_button(ui, RichText::new("🗑").color(Color32::RED), "Confirm removing OAuth2 token");
+                            if confirm.clicked() {
+                                app.log_out_mod_io();
+                                app.mod_io_key_delete_requested = false;
                             }
                             
-                            // Auto-cancel if mouse moves away
-                            if !ui.ui_contains_pointer() {
-                                mod_io_key_delete_requested = false;
+                            // Auto-cancel if mouse moves away, unless the keyboard is on the buttons
+                            if !ui.ui_contains_pointer() && !cancel.has_focus() && !confirm.has_focus() {
+                                app.mod_io_key_delete_requested = false;
                             }
                         }
                     }
                 });
                 
                 // Display OAuth2 key status
-                if app.mod_io_oauth_key.is_empty() {
+                if let Some(account) = app.mod_io_account.clone() {
+                    ui.horizontal(|ui| {
+                        if let Some(avatar) = app.mod_io_avatar(ui.ctx()) {
+                            ui.add(egui::Image::new(&avatar).fit_to_exact_size(egui::vec2(32.0, 32.0)));
+                        }
+                        ui.label("Logged in as");
+                        ui.hyperlink_to(RichText::new(&account.username).strong(), &account.profile_url);
+                        if ui.button("Log out").clicked() {
+                            app.log_out_mod_io();
+                        }
+                    });
+                } else if app.mod_io_oauth_key.is_empty() {
                     ui.label(RichText::new("No OAuth2 token. Mod.io integration is disabled.")
                         .color(Color32::from_rgb(255, 200, 0)));
                 } else {
@@ -425,6 +1026,15 @@ fn render_central_panel(app: &mut ModManager, ctx: &egui::Context) {
                         .color(Color32::from_rgb(100, 200, 100)));
                 }
                 
+                if credentials::uses_fallback_file() {
+                    ui.label(RichText::new("⚠ The system keyring isn't available, so tokens and keys are kept in credentials.bin in the app data folder, encrypted for this user.")
+                        .color(Color32::from_rgb(255, 165, 0)));
+                }
+                render_rate_limit(ui);
+                if app.mod_io_account.is_none() {
+                    render_email_login(app, ui);
+                }
+                
                 // Add help text explaining how to get an OAuth Access token
                 ui.collapsing("How to get a Mod.io OAuth2 token", |ui| {
                     ui.label("1. Create an account on mod.io");
@@ -443,87 +1053,431 @@ fn render_central_panel(app: &mut ModManager, ctx: &egui::Context) {
 
                 ui.add_space(10.0);
                 
-                ui.checkbox(&mut app.auto_update_mods, "Auto-update mods")
-                    .on_hover_text("Automatically check for mod updates on startup");
-                
-                ui.checkbox(&mut app.enable_mod_debugging, "Enable mod debugging")
-                    .on_hover_text("Enable additional logging for mod operations");
+                let mut settings_changed = ui.checkbox(&mut app.auto_update_mods, "Auto-update mods")
+                    .on_hover_text("Automatically check for mod updates on startup")
+                    .changed();
+                settings_changed |= ui.checkbox(&mut app.refresh_on_focus, "Check for updates when returning to the app")
+                    .on_hover_text("Refresh update status and mod.io details after the window was in the background for 10 minutes")
+                    .changed();
                 
-                ui.separator();
-                ui.label(
-                    RichText::new("Warning: Modding may affect game performance")
-                        .color(Color32::from_rgb(255, 200, 0))
-                );
-            }
-        }
-    });
-}
-
-fn render_dialogs(app: &mut ModManager, ctx: &egui::Context) {
-    if app.show_delete_confirmation {
-        egui::Window::new("Confirm Deletion")
-            .collapsible(false)
-            .resizable(false)
-            .show(ctx, |ui| {
-                ui.label(format!("Are you sure you want to delete profile '{}'?", app.profile_to_delete));
                 ui.horizontal(|ui| {
-                    if ui.button("Yes").clicked() {
-                        if let Ok(()) = app.db.delete_profile(&app.profile_to_delete) {
-                            app.profiles = app.db.get_profiles().unwrap_or_default();
-                            app.db.set_current_profile("Default".to_string());
-                            if let Ok(mods) = app.db.get_mods() {
-                                app.mods = mods;
-                            }
+                    if ui.checkbox(&mut app.enable_mod_debugging, "Enable mod debugging")
+                        .on_hover_text("Write debug messages, such as every mod.io request, to the log")
+                        .changed()
+                    {
+                        logging::set_debug(app.enable_mod_debugging);
+                        settings_changed = true;
+                    }
+                    if ui.button("View log").clicked() {
+                        let log_dir = logging::log_dir(app.installer.app_data_dir());
+                        let result = match logging::latest_log_file(&log_dir) {
+                            Some(log_file) => launcher::open_file(&log_file),
+                            None => launcher::open_folder(&log_dir),
+                        };
+                        if let Err(e) = result {
+                            app.error_message = format!("Error opening the log: {}", e);
+                            app.show_error_message = true;
                         }
-                        app.show_delete_confirmation = false;
                     }
-                    if ui.button("No").clicked() {
-                        app.show_delete_confirmation = false;
+                });
+                
+                settings_changed |= ui.checkbox(&mut app.rating_prompts, "Suggest rating mods")
+                    .on_hover_text("Occasionally offer to rate mods you have used for a while on mod.io")
+                    .changed();
+                
+                ui.horizontal(|ui| {
+                    settings_changed |= ui.checkbox(&mut app.deep_verification, "Weekly deep verification")
+                        .on_hover_text("Hash every stored and deployed mod file once a week in the background")
+                        .changed();
+                    if app.verification_running {
+                        ui.spinner();
+                    } else if ui.button("Verify mods")
+                        .on_hover_text("Hash every downloaded archive and deployed pak now and report what is missing or changed")
+                        .clicked()
+                    {
+                        app.verify_mods();
                     }
                 });
-            });
-    }
-    
-    // Add any other dialog windows here
-    if app.show_error_message {
-        egui::Window::new("Error")
-            .collapsible(false)
-            .resizable(false)
-            .show(ctx, |ui| {
-                ui.label(&app.error_message);
-                if ui.button("OK").clicked() {
+                
+                ui.horizontal(|ui| {
+                    ui.label("Warn above");
+                    settings_changed |= ui.add(egui::DragValue::new(&mut app.enabled_mod_limit).range(0..=1000).suffix(" mods"))
+                        .changed();
+                    ui.label("or");
+                    settings_changed |= ui.add(egui::DragValue::new(&mut app.enabled_pak_limit).range(0..=2000).suffix(" paks"))
+                        .changed();
+                    ui.label("enabled");
+                }).response.on_hover_text("Very large mod sets can make the game skip mods without an error. 0 turns a warning off.");
+                
+                settings_changed |= ui.add(egui::Slider::new(&mut app.rollback_grace_days, 1..=90).text("days to keep replaced versions"))
+                    .on_hover_text("How long a mod can be rolled back after an update before the old version is deleted")
+                    .changed();
+                
+                ui.add_space(10.0);
+                ui.heading("Sync");
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Sync folder:");
+                    let mut sync_folder = app.sync_folder.clone();
+                    if ui.text_edit_singleline(&mut sync_folder)
+                        .on_hover_text("A folder shared between PCs (Dropbox, Syncthing, ...). Leave empty to disable.")
+                        .lost_focus()
+                    {
+                        app.set_sync_folder(sync_folder);
+                    } else {
+                        app.sync_folder = sync_folder;
+                    }
+                    if ui.button("Browse").clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        app.set_sync_folder(path.to_string_lossy().to_string());
+                    }
+                    if ui.add_enabled(!app.sync_folder.is_empty(), egui::Button::new("Sync now")).clicked() {
+                        app.sync_now();
+                    }
+                });
+                ui.label(RichText::new("Profile changes are exported as small journal files; changes from other machines are merged per mod, newest wins.").weak());
+                
+                ui.add_space(10.0);
+                ui.heading("Storage");
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Cold storage folder:");
+                    let mut cold_storage_folder = app.cold_storage_folder.clone();
+                    if ui.text_edit_singleline(&mut cold_storage_folder)
+                        .on_hover_text("Archived mods' files are moved here, e.g. to a larger drive. Leave empty to delete them instead.")
+                        .lost_focus()
+                    {
+                        app.set_cold_storage_folder(cold_storage_folder);
+                    } else {
+                        app.cold_storage_folder = cold_storage_folder;
+                    }
+                    if ui.button("Browse").clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        app.set_cold_storage_folder(path.to_string_lossy().to_string());
+                    }
+                });
+                
+                settings_changed |= ui.checkbox(&mut app.delete_downloads_on_uninstall, "Delete downloaded files on uninstall")
+                    .on_hover_text("Otherwise the download is kept so the mod can be installed again without downloading it")
+                    .changed();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Parallel downloads:");
+                    let changed = ui.add(egui::DragValue::new(&mut app.max_parallel_downloads).range(1..=10))
+                        .on_hover_text("Further installs wait in the download queue")
+                        .changed();
+                    if changed {
+                        app.start_next_downloads();
+                    }
+                    settings_changed |= changed;
+                });
+                
+                ui.add_space(10.0);
+                ui.heading("Performance");
+                ui.separator();
+                
+                let mut low_memory_mode = app.low_memory_mode;
+                if ui.checkbox(&mut low_memory_mode, "Low-memory mode")
+                    .on_hover_text("Keep fewer thumbnails in memory and reload them from disk when needed")
+                    .changed()
+                {
+                    app.set_low_memory_mode(low_memory_mode);
+                }
+                
+                ui.horizontal(|ui| {
+                    ui.label("Mod list layout:");
+                    egui::ComboBox::from_id_salt("list_density")
+                        .selected_text(app.list_density.label())
+                        .show_ui(ui, |ui| {
+                            for density in ListDensity::ALL {
+                                settings_changed |= ui.selectable_value(&mut app.list_density, density, density.label())
+                                    .changed();
+                            }
+                        });
+                });
+                
+                ui.add_space(10.0);
+                ui.heading("Notifications");
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("Duration:");
+                    let response = ui.add(egui::Slider::new(&mut app.notification_duration, 1.0..=30.0).suffix(" s"));
+                    settings_changed |= slider_committed(&response);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Position:");
+                    egui::ComboBox::from_id_salt("notification_corner")
+                        .selected_text(app.notification_corner.label())
+                        .show_ui(ui, |ui| {
+                            for corner in NotificationCorner::ALL {
+                                settings_changed |= ui.selectable_value(&mut app.notification_corner, corner, corner.label())
+                                    .changed();
+                            }
+                        });
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Max stacked:");
+                    let response = ui.add(egui::Slider::new(&mut app.notification_max_stacked, 1..=10));
+                    settings_changed |= slider_committed(&response);
+                });
+                
+                ui.add_space(10.0);
+                ui.heading("Webhook");
+                ui.separator();
+                
+                ui.horizontal(|ui| {
+                    ui.label("URL:");
+                    let response = ui.add(egui::TextEdit::singleline(&mut app.webhook_url)
+                        .hint_text("https://discord.com/api/webhooks/...")
+                        .password(true)
+                        .desired_width(300.0));
+                    if response.lost_focus() {
+                        app.save_webhook_url();
+                    }
+                });
+                ui.label("Post when:");
+                for event in WebhookEvent::ALL {
+                    let mut enabled = app.webhook_events.contains(&event);
+                    if ui.checkbox(&mut enabled, event.label()).changed() {
+                        if enabled {
+                            app.webhook_events.insert(event);
+                        } else {
+                            app.webhook_events.remove(&event);
+                        }
+                        settings_changed = true;
+                    }
+                }
+                
+                ui.add_space(10.0);
+                ui.heading("Keyboard Shortcuts");
+                ui.separator();
+                
+                settings_changed |= render_keymap_editor(app, ui);
+                
+                ui.horizontal(|ui| {
+                    if ui.button("Export settings")
+                        .on_hover_text("Save settings, shortcuts and panel layout to a file")
+                        .clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .set_file_name("drg-modman-settings.json")
+                            .add_filter("JSON", &["json"])
+                            .save_file()
+                    {
+                        match app.export_settings(&path) {
+                            Ok(()) => app.notify(Severity::Success, format!("Exported settings to {}", path.display())),
+                            Err(e) => {
+                                app.error_message = e;
+                                app.show_error_message = true;
+                            }
+                        }
+                    }
+                    if ui.button("Import settings").clicked()
+                        && let Some(path) = rfd::FileDialog::new()
+                            .add_filter("JSON", &["json"])
+                            .pick_file()
+                    {
+                        match app.import_settings(&path) {
+                            Ok(()) => app.notify(Severity::Success, "Imported settings.".to_string()),
+                            Err(e) => {
+                                app.error_message = e;
+                                app.show_error_message = true;
+                            }
+                        }
+                    }
+                    if ui.button("Reset shortcuts and layout").clicked() {
+                        app.reset_keymap_and_layout();
+                    }
+                });
+                
+                if settings_changed {
+                    app.save_config();
+                }
+                
+                ui.separator();
+                ui.label(
+                    RichText::new("Warning: Modding may affect game performance")
+                        .color(Color32::from_rgb(255, 200, 0))
+                );
+            }
+        }
+    });
+}
+
+// Returns whether a shortcut was changed
+fn render_keymap_editor(app: &mut ModManager, ui: &mut egui::Ui) -> bool {
+    let mut changed = false;
+    
+    // The next key pressed becomes the shortcut; Escape cancels
+    if let Some(action) = app.recording_shortcut {
+        let pressed = ui.input(|input| input.events.iter().find_map(|event| match event {
+            egui::Event::Key { key, pressed: true, modifiers, .. } => Some((*key, *modifiers)),
+            _ => None,
+        }));
+        if let Some((key, modifiers)) = pressed {
+            if key != egui::Key::Escape {
+                let modifiers = egui::Modifiers {
+                    alt: modifiers.alt,
+                    shift: modifiers.shift,
+                    command: modifiers.command,
+                    ..Default::default()
+                };
+                app.keymap.set_shortcut(action, egui::KeyboardShortcut::new(modifiers, key));
+                changed = true;
+            }
+            app.recording_shortcut = None;
+        }
+    }
+    
+    egui::Grid::new("keymap").striped(true).show(ui, |ui| {
+        for action in ShortcutAction::ALL {
+            ui.label(action.label());
+            let text = if app.recording_shortcut == Some(action) {
+                "Press a key…".to_string()
+            } else {
+                format_shortcut(&app.keymap.shortcut(action))
+            };
+            if ui.button(text).on_hover_text("Click, then press the new shortcut").clicked() {
+                app.recording_shortcut = Some(action);
+            }
+            ui.end_row();
+        }
+    });
+    
+    changed
+}
+
+fn render_dialogs(app: &mut ModManager, ctx: &egui::Context) {
+    if app.show_delete_confirmation {
+        egui::Window::new("Confirm Deletion")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(format!("Are you sure you want to delete profile '{}'?", app.profile_to_delete));
+                ui.horizontal(|ui| {
+                    if ui.button("Yes").clicked() {
+                        if let Ok(()) = app.db.delete_profile(&app.profile_to_delete) {
+                            app.profiles = app.db.get_profiles().unwrap_or_default();
+                            app.db.set_current_profile("Default".to_string());
+                            if let Ok(mods) = app.db.get_mods() {
+                                app.mods = mods;
+                            }
+                        }
+                        app.show_delete_confirmation = false;
+                    }
+                    let no = ui.button("No");
+                    focus_if_idle(ui, &no);
+                    if no.clicked() {
+                        app.show_delete_confirmation = false;
+                    }
+                });
+            });
+    }
+    
+    render_archive_preview(app, ctx);
+    render_mod_hub_prompt(app, ctx);
+    render_dependency_prompt(app, ctx);
+    render_post_install_prompt(app, ctx);
+    render_source_editor(app, ctx);
+    render_rating_prompt(app, ctx);
+    render_conflicts_dialog(app, ctx);
+    render_mod_list_comparison(app, ctx);
+    render_verification_report(app, ctx);
+    render_ingame_import(app, ctx);
+    render_startup_issues(app, ctx);
+    
+    // Add any other dialog windows here
+    if app.show_error_message {
+        egui::Window::new("Error")
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(app.redact(&app.error_message));
+                let ok = ui.button("OK");
+                focus_if_idle(ui, &ok);
+                if ok.clicked() {
                     app.show_error_message = false;
                 }
             });
     }
 }
 //
+// Sliders are saved once released rather than on every dragged step
+fn slider_committed(response: &egui::Response) -> bool {
+    response.drag_stopped() || (response.changed() && !response.dragged())
+}
+//
 pub fn render_mod_list(
     app: &mut ModManager,
     ui: &mut egui::Ui
 ) {
-    // Filter mods based on search and tab
-    // Clone the filtered mods to avoid borrowing app
-    let filtered_mods: Vec<ModEntry> = app.mods
-        .iter()
-        .filter(|m| {
-            // Always filter by search query
-            let matches_search = m.mod_name.to_lowercase().contains(&app.search_query.to_lowercase());
-            
-            match app.current_tab {
-                Tab::Browse => {
-                    // In Browse tab, show all mods with optional filter for installed status
-                    matches_search && (!app.show_installed_only || m.installed)
-                },
-                Tab::Installed => {
-                    // In Installed tab, only show mods that are installed in the current profile
-                    matches_search && m.installed
-                },
-                _ => false,
+    // The Browse tab can show either the local library or the mod.io catalog
+    if matches!(app.current_tab, Tab::Browse) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut app.browse_source, BrowseSource::Library, "Library");
+            if ui.selectable_value(&mut app.browse_source, BrowseSource::Catalog, "mod.io catalog").clicked()
+                && app.catalog.is_empty()
+            {
+                app.load_catalog_page();
             }
-        })
-        .cloned() // Clone each ModEntry
-        .collect();
+            
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                if app.subscription_import_pending {
+                    ui.spinner();
+                } else if ui.add_enabled(!app.mod_io_oauth_key.is_empty(), egui::Button::new("Import subscriptions"))
+                    .on_hover_text("Add the mods you subscribed to on mod.io to the library")
+                    .on_disabled_hover_text("Set a mod.io OAuth token in Settings first")
+                    .clicked()
+                {
+                    app.import_subscriptions();
+                }
+            });
+        });
+        ui.separator();
+        
+        if app.browse_source == BrowseSource::Catalog {
+            render_catalog(app, ui);
+            return;
+        }
+    }
+    
+    ui.horizontal(|ui| {
+        ui.label("Sort by:");
+        egui::ComboBox::from_id_salt("list_sort")
+            .selected_text(app.list_sort.label())
+            .show_ui(ui, |ui| {
+                for option in ListSort::ALL {
+                    ui.selectable_value(&mut app.list_sort, option, option.label());
+                }
+            });
+        
+        ui.separator();
+        if ui.button("Select all")
+            .on_hover_text("Select every mod the filters show")
+            .clicked()
+        {
+            app.select_all_shown();
+        }
+        if ui.button("Invert selection")
+            .on_hover_text("Shift-click selects a range, Ctrl-click adds or removes one mod")
+            .clicked()
+        {
+            app.invert_selection_shown();
+        }
+    });
+    
+    // Clone the filtered mods to avoid borrowing app
+    let filtered_mods = app.mod_query().run(&app.mods);
+    
+    // Update check status for the installed set
+    if matches!(app.current_tab, Tab::Installed) {
+        render_update_check_header(app, ui);
+        render_installed_stats(app, ui);
+    }
     
     // Track changes that need to be applied after rendering
     let mut needs_reload = false;
@@ -538,7 +1492,7 @@ pub fn render_mod_list(
     
     // Handle installation requests
     if let Some(mod_id) = mod_to_install {
-        install_mod(app, &mod_id, &mut needs_reload);
+        app.start_install(&mod_id);
     }
     
     // Reload mods if needed
@@ -547,72 +1501,553 @@ pub fn render_mod_list(
     }
 }
 
-fn render_mod_scrollable_list(
-    app: &mut ModManager, 
-    ui: &mut egui::Ui, 
-    filtered_mods: &[ModEntry], // Changed from &[&ModEntry] to &[ModEntry]
-    mod_actions: &mut Vec<ModAction>,
-    mod_to_install: &mut Option<String>
+fn render_catalog(
+    app: &mut ModManager,
+    ui: &mut egui::Ui
 ) {
+    // Search on mod.io once the user stops typing
+    let search = app.search_query.trim().to_string();
+    if search != app.catalog_search {
+        let idle = ui.input(|input| input.time) - app.search_edited_at;
+        if idle >= CATALOG_SEARCH_DELAY {
+            app.set_catalog_search(&search);
+        } else {
+            ui.ctx().request_repaint_after(std::time::Duration::from_secs_f64(CATALOG_SEARCH_DELAY - idle));
+        }
+    }
+    
+    let mut mod_to_add: Option<usize> = None;
+    let mut load_more = false;
+    
+    ui.horizontal(|ui| {
+        ui.label("Sort by:");
+        let mut sort = app.catalog_sort;
+        egui::ComboBox::from_id_salt("catalog_sort")
+            .selected_text(sort.label())
+            .show_ui(ui, |ui| {
+                for option in CatalogSort::ALL {
+                    ui.selectable_value(&mut sort, option, option.label());
+                }
+            });
+        app.set_catalog_sort(sort);
+    });
+    
+    let keys: Vec<String> = app.catalog.iter().map(|mod_io_mod| format!("catalog:{}", mod_io_mod.id)).collect();
+    let mut row_heights = std::mem::take(&mut app.row_heights);
     egui::ScrollArea::vertical()
         .auto_shrink([false; 2])
         .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::VisibleWhenNeeded)
-        .show(ui, |ui| {
-            for mod_item in filtered_mods {
-                render_mod_row(app, ui, mod_item, mod_actions, mod_to_install);
+        .show_viewport(ui, |ui, viewport| {
+            add_visible_rows(ui, viewport, &mut row_heights, &keys, CATALOG_ROW_HEIGHT, |ui, index| {
+                let mod_io_mod = &app.catalog[index];
+                let in_library = app.mods.iter().any(|m| m.mod_id == format!("modio_{}", mod_io_mod.id));
+                ui.horizontal(|ui| {
+                    ui.vertical(|ui| {
+                        ui.label(RichText::new(&mod_io_mod.name).strong());
+                        ui.label(&mod_io_mod.summary);
+                        ui.label(RichText::new(format!(
+                            "by {}  ·  {} downloads  ·  {} subscribers",
+                            mod_io_mod.submitted_by.username,
+                            format::number(mod_io_mod.stats.downloads_total.into()),
+                            format::number(mod_io_mod.stats.subscribers_total.into())
+                        )).weak());
+                    });
+                    
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if in_library {
+                            ui.label(RichText::new("In library").color(Color32::from_rgb(100, 200, 100)));
+                        } else if ui.button("Add").clicked() {
+                            mod_to_add = Some(index);
+                        }
+                    });
+                });
                 ui.separator();
+            });
+            
+            if app.is_catalog_loading() {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Loading...");
+                });
+            } else if app.catalog_exhausted {
+                ui.label(RichText::new("End of catalog").weak());
+            } else if ui.button("Load more").clicked() {
+                load_more = true;
             }
         });
+    app.row_heights = row_heights;
+    
+    if let Some(index) = mod_to_add {
+        app.add_catalog_mod(index);
+    }
+    if load_more {
+        app.load_catalog_page();
+    }
 }
 
-fn render_mod_row(
-    app: &mut ModManager, 
-    ui: &mut egui::Ui, 
-    mod_item: &ModEntry,
-    mod_actions: &mut Vec<ModAction>,
-    mod_to_install: &mut Option<String>
+// Totals over the installed mods, from the cached mod.io metadata
+fn render_installed_stats(
+    app: &ModManager,
+    ui: &mut egui::Ui
 ) {
-    let is_selected = app.selected_mods.contains(&mod_item.mod_id);
-    let response = ui.selectable_label(is_selected, "");
-    
-    // Make the whole row selectable
-    if response.clicked() {
-        if is_selected {
-            app.selected_mods.remove(&mod_item.mod_id);
-        } else {
-            app.selected_mods.insert(mod_item.mod_id.clone());
+    let mut subscribers: u64 = 0;
+    let mut download_size = 0;
+    let mut verified = 0;
+    let mut sandbox = 0;
+    let mut unknown = 0;
+    for mod_entry in app.mods.iter().filter(|m| m.installed) {
+        let metadata = app.mod_metadata.get(&mod_entry.mod_id);
+        if let Some(metadata) = metadata {
+            subscribers += metadata.stats.subscribers_total as u64;
+            download_size += metadata.modfile.as_ref().map(|file| file.filesize).unwrap_or(0);
+        }
+        if mod_entry.enabled {
+            match metadata.map(|metadata| metadata.approval()) {
+                Some(Approval::Verified) => verified += 1,
+                Some(Approval::Sandbox) => sandbox += 1,
+                _ => unknown += 1,
+            }
         }
     }
     
-    // Draw the row content
-    let _ = response.rect.shrink(4.0);
-    let painter = ui.painter();
-    if is_selected {
-        painter.rect_filled(
-            response.rect,
-            4.0,
-            Color32::from_rgb(60, 80, 120),
-        );
-    }
-    
+    egui::Frame::group(ui.style()).show(ui, |ui| {
+        ui.horizontal(|ui| {
+            ui.label(format!("Subscribers: {}", format::number(subscribers)));
+            ui.separator();
+            ui.label(format!("Download size: {}", format::size(download_size)));
+            ui.separator();
+            ui.label(RichText::new(format!("{} Verified", verified)).color(Color32::from_rgb(100, 200, 100)));
+            ui.label(RichText::new(format!("{} Sandbox", sandbox)).color(Color32::from_rgb(255, 165, 0)));
+            if unknown > 0 {
+                ui.label(RichText::new(format!("{} unknown", unknown)).weak())
+                    .on_hover_text("Enabled mods without cached mod.io metadata; checking for updates fetches it");
+            }
+        });
+    });
+}
+
+fn render_update_check_header(
+    app: &mut ModManager,
+    ui: &mut egui::Ui
+) {
     ui.horizontal(|ui| {
-        // Status indicator
-        render_mod_status(ui, mod_item);
+        let status = match app.last_update_check {
+            Some(checked_at) => format!("Checked {}", format::relative_date(checked_at)),
+            None => "Never checked for updates".to_string(),
+        };
+        ui.label(RichText::new(status).weak());
         
-        // Mod details
-        render_mod_details(ui, mod_item);
+        if app.pending_update_check.is_some() {
+            ui.spinner();
+        } else if ui.add_enabled(app.can_check_for_updates(), egui::Button::new("Check now"))
+            .on_disabled_hover_text("Updates were checked less than a minute ago")
+            .clicked()
+        {
+            app.check_for_updates();
+        }
         
-        // Action buttons
-        render_mod_actions(app, ui, mod_item, mod_actions, mod_to_install);
+        // Shareable list of files that enabled mods overwrite in each other
+        if ui.button("Export conflicts")
+            .on_hover_text("Save a JSON or Markdown report of conflicting mods")
+            .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .set_file_name("conflicts.md")
+                .add_filter("Markdown", &["md"])
+                .add_filter("JSON", &["json"])
+                .save_file()
+        {
+            app.export_conflict_report(&path);
+        }
+        
+        if !app.asset_conflicts.is_empty()
+            && ui.button(RichText::new(format!("⚠ Conflicts ({})", app.asset_conflicts.len())).color(Color32::from_rgb(255, 165, 0)))
+                .on_hover_text("Assets replaced by more than one enabled mod")
+                .clicked()
+        {
+            app.show_conflicts = true;
+        }
+        
+        // Exact mods, versions and hashes of the enabled set
+        if ui.button("Export lockfile")
+            .on_hover_text("Save the exact enabled set so it can be reproduced elsewhere")
+            .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .set_file_name("modman.lock.json")
+                .add_filter("Lockfile", &["json"])
+                .save_file()
+        {
+            app.export_lockfile(&path);
+        }
+        if !app.guest_mode
+            && ui.button("Install from lockfile")
+                .on_hover_text("Replace the enabled mods with the exact set of a lockfile")
+                .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("Lockfile", &["json"])
+                .pick_file()
+        {
+            app.install_from_lockfile(&path);
+        }
+        
+        // mod.io IDs and versions, as community lobby checkers read and write them
+        if ui.button("Export mod list")
+            .on_hover_text("Save the enabled mod.io mods for community mod list checkers")
+            .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .set_file_name("modlist.json")
+                .add_filter("Mod list", &["json", "txt"])
+                .save_file()
+        {
+            app.export_mod_list(&path);
+        }
+        if !app.guest_mode
+            && ui.button("Use mod list")
+                .on_hover_text("Enable exactly the mods of a shared mod list, downloading missing ones")
+                .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("Mod list", &["json", "txt"])
+                .pick_file()
+        {
+            app.install_mod_list(&path);
+        }
+        if ui.button("Compare with shared list")
+            .on_hover_text("See which mods of a friend's mod list you are missing or have on another version")
+            .clicked()
+            && let Some(path) = rfd::FileDialog::new()
+                .add_filter("Mod list", &["json", "txt"])
+                .pick_file()
+        {
+            app.compare_mod_list(&path);
+        }
     });
+    ui.separator();
 }
 
-fn render_mod_status(
-    ui: &mut egui::Ui,
-    mod_item: &ModEntry
-) {
-    let status_color = if mod_item.enabled {
-        Color32::from_rgb(100, 200, 100) // Green for enabled
+fn render_mod_scrollable_list(
+    app: &mut ModManager, 
+    ui: &mut egui::Ui, 
+    filtered_mods: &[ModEntry], // Changed from &[&ModEntry] to &[ModEntry]
+    mod_actions: &mut Vec<ModAction>,
+    mod_to_install: &mut Option<String>
+) {
+    if app.list_density == ListDensity::Table {
+        render_mod_table(app, ui, filtered_mods, mod_actions, mod_to_install);
+        return;
+    }
+    
+    // Heights differ between layouts, so each layout keeps its own
+    let (prefix, estimated_height) = match app.list_density {
+        ListDensity::Compact => ("compact", COMPACT_ROW_HEIGHT),
+        _ => ("comfortable", COMFORTABLE_ROW_HEIGHT),
+    };
+    app.list_order = filtered_mods.iter().map(|m| m.mod_id.clone()).collect();
+    let keys: Vec<String> = filtered_mods.iter().map(|m| format!("{}:{}", prefix, m.mod_id)).collect();
+    let mut row_heights = std::mem::take(&mut app.row_heights);
+    egui::ScrollArea::vertical()
+        .auto_shrink([false; 2])
+        .scroll_bar_visibility(egui::scroll_area::ScrollBarVisibility::VisibleWhenNeeded)
+        .show_viewport(ui, |ui, viewport| {
+            add_visible_rows(ui, viewport, &mut row_heights, &keys, estimated_height, |ui, index| {
+                let mod_item = &filtered_mods[index];
+                if app.list_density == ListDensity::Compact {
+                    render_compact_mod_row(app, ui, mod_item, mod_actions, mod_to_install);
+                } else {
+                    render_mod_row(app, ui, mod_item, mod_actions, mod_to_install);
+                    ui.separator();
+                }
+            });
+        });
+    app.row_heights = row_heights;
+}
+
+// A checkbox toggles its own mod as if Ctrl were held, Shift still selects a range
+fn checkbox_modifiers(ui: &egui::Ui) -> egui::Modifiers {
+    let mut modifiers = ui.input(|input| input.modifiers);
+    modifiers.command = true;
+    modifiers
+}
+
+// Guesses for rows that haven't been on screen yet, spacing included
+const COMPACT_ROW_HEIGHT: f32 = 24.0;
+const COMFORTABLE_ROW_HEIGHT: f32 = 90.0;
+const CATALOG_ROW_HEIGHT: f32 = 70.0;
+
+// Builds only the rows inside the viewport; the others are empty space of the height they had when last shown.
+// Rows must start at the top of the scroll area's content
+fn add_visible_rows(
+    ui: &mut egui::Ui,
+    viewport: egui::Rect,
+    row_heights: &mut HashMap<String, f32>,
+    keys: &[String],
+    estimated_height: f32,
+    mut add_row: impl FnMut(&mut egui::Ui, usize)
+) {
+    let height = |row_heights: &HashMap<String, f32>, key: &String| row_heights.get(key).copied().unwrap_or(estimated_height);
+    let origin = ui.cursor().top();
+    
+    let mut index = 0;
+    let mut above = 0.0;
+    while index < keys.len() && above + height(row_heights, &keys[index]) <= viewport.min.y {
+        above += height(row_heights, &keys[index]);
+        index += 1;
+    }
+    ui.add_space(above);
+    
+    while index < keys.len() && ui.cursor().top() - origin < viewport.max.y {
+        let top = ui.cursor().top();
+        add_row(ui, index);
+        row_heights.insert(keys[index].clone(), ui.cursor().top() - top);
+        index += 1;
+    }
+    
+    let below: f32 = keys[index..].iter().map(|key| height(row_heights, key)).sum();
+    ui.add_space(below);
+}
+
+fn render_mod_table(
+    app: &mut ModManager,
+    ui: &mut egui::Ui,
+    filtered_mods: &[ModEntry],
+    mod_actions: &mut Vec<ModAction>,
+    mod_to_install: &mut Option<String>
+) {
+    let mut mods = filtered_mods.to_vec();
+    // Column values are looked up before the rows borrow the app
+    let query = app.mod_query();
+    query.sort_table(&mut mods);
+    let cells: Vec<(&str, Option<u64>, Option<i64>)> = mods.iter()
+        .map(|m| (query.status(m), query.size(m), query.updated_at(m)))
+        .collect();
+    app.list_order = mods.iter().map(|m| m.mod_id.clone()).collect();
+    
+    let mut clicked_column = None;
+    let row_height = ui.spacing().interact_size.y + 4.0;
+    let mut table = TableBuilder::new(ui)
+        .striped(true)
+        .resizable(true)
+        .auto_shrink([false; 2])
+        .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+        .column(Column::exact(24.0))
+        .column(Column::initial(220.0).at_least(100.0).clip(true));
+    for _ in 1..TableColumn::ALL.len() {
+        table = table.column(Column::auto().at_least(60.0).clip(true));
+    }
+    table
+        .column(Column::remainder().at_least(120.0))
+        .header(row_height, |mut header| {
+            header.col(|_| {});
+            for column in TableColumn::ALL {
+                header.col(|ui| {
+                    let arrow = match app.table_sort {
+                        Some((sorted, false)) if sorted == column => " ⏶",
+                        Some((sorted, true)) if sorted == column => " ⏷",
+                        _ => "",
+                    };
+                    if ui.add(egui::Label::new(RichText::new(format!("{}{}", column.label(), arrow)).strong()).sense(egui::Sense::click()))
+                        .on_hover_text("Sort by this column")
+                        .clicked()
+                    {
+                        clicked_column = Some(column);
+                    }
+                });
+            }
+            header.col(|_| {});
+        })
+        .body(|body| {
+            body.rows(row_height, mods.len(), |mut row| {
+                let mod_item = &mods[row.index()];
+                let (status, size, updated_at) = cells[row.index()];
+                let approval = app.mod_metadata.get(&mod_item.mod_id).map(|metadata| metadata.approval());
+                let is_selected = app.selected_mods.contains(&mod_item.mod_id);
+                row.set_selected(is_selected);
+                
+                row.col(|ui| {
+                    if ui.checkbox(&mut is_selected.clone(), "").clicked() {
+                        app.click_selection(&mod_item.mod_id, checkbox_modifiers(ui));
+                    }
+                });
+                row.col(|ui| {
+                    render_favorite_star(app, ui, mod_item);
+                    render_mod_status(ui, mod_item);
+                    render_conflict_badge(app, ui, mod_item);
+                    if ui.add(egui::Label::new(RichText::new(&mod_item.mod_name).strong()).truncate().sense(egui::Sense::click()))
+                        .on_hover_text("Show details")
+                        .clicked()
+                    {
+                        mod_actions.push(ModAction::ShowDetails(mod_item.mod_id.clone()));
+                    }
+                });
+                row.col(|ui| {
+                    ui.label(format!("v{}", mod_item.selected_version));
+                    if let Some(latest) = &mod_item.update_available {
+                        ui.label(RichText::new("⬆").color(Color32::from_rgb(100, 170, 255)))
+                            .on_hover_text(format!("v{} was released on mod.io", latest));
+                    }
+                });
+                row.col(|ui| {
+                    ui.label(status);
+                    if let Some(approval) = approval {
+                        render_approval_badge(ui, approval);
+                    }
+                });
+                row.col(|ui| {
+                    render_source_badge(ui, mod_item);
+                });
+                row.col(|ui| {
+                    ui.label(size.map(format::size).unwrap_or_else(|| "—".to_string()));
+                });
+                row.col(|ui| {
+                    match updated_at {
+                        Some(updated_at) => {
+                            ui.label(format::relative_date(updated_at)).on_hover_text(format::date(updated_at));
+                        },
+                        None => {
+                            ui.weak("—");
+                        },
+                    }
+                });
+                row.col(|ui| {
+                    render_mod_actions(app, ui, mod_item, mod_actions, mod_to_install);
+                });
+            });
+        });
+    
+    if let Some(column) = clicked_column {
+        app.sort_table_by(column);
+    }
+}
+
+fn render_mod_row(
+    app: &mut ModManager, 
+    ui: &mut egui::Ui, 
+    mod_item: &ModEntry,
+    mod_actions: &mut Vec<ModAction>,
+    mod_to_install: &mut Option<String>
+) {
+    let is_selected = app.selected_mods.contains(&mod_item.mod_id);
+    let response = with_label(
+        ui.selectable_label(is_selected, ""),
+        egui::WidgetType::SelectableLabel,
+        &format!("Select {}", mod_item.mod_name)
+    );
+    
+    // Make the whole row selectable
+    if response.clicked() {
+        app.click_selection(&mod_item.mod_id, ui.input(|input| input.modifiers));
+    }
+    
+    // Draw the row content
+    let _ = response.rect.shrink(4.0);
+    let painter = ui.painter();
+    if is_selected {
+        painter.rect_filled(
+            response.rect,
+            4.0,
+            Color32::from_rgb(60, 80, 120),
+        );
+    }
+    
+    ui.horizontal(|ui| {
+        render_favorite_star(app, ui, mod_item);
+        // Status indicator
+        render_mod_status(ui, mod_item);
+        render_conflict_badge(app, ui, mod_item);
+        
+        // Mod details
+        let audio_preview = audio_preview_for(app, mod_item);
+        let is_playing = is_audio_preview_playing(app, &mod_item.mod_id);
+        let audio_preview = audio_preview.as_deref().map(|preview| (preview, is_playing));
+        let thumbnail = app.thumbnail(ui.ctx(), mod_item);
+        let mod_link = app.redact(&mod_item.mod_link);
+        let approval = app.mod_metadata.get(&mod_item.mod_id).map(|metadata| metadata.approval());
+        render_mod_details(ui, mod_item, &mod_link, thumbnail.as_ref(), audio_preview, approval, mod_actions);
+        render_local_tags(app, ui, mod_item);
+        
+        // Action buttons
+        render_mod_actions(app, ui, mod_item, mod_actions, mod_to_install);
+    });
+    
+    // Variants are listed as sub-entries below the parent row
+    if mod_item.variants.len() > 1 {
+        render_mod_variants(ui, mod_item, mod_actions);
+    }
+}
+
+// Name, status and version on a single line, so large collections fit on screen
+fn render_compact_mod_row(
+    app: &mut ModManager,
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry,
+    mod_actions: &mut Vec<ModAction>,
+    mod_to_install: &mut Option<String>
+) {
+    let is_selected = app.selected_mods.contains(&mod_item.mod_id);
+    
+    ui.horizontal(|ui| {
+        // The row is one line high, so the space left for it is the row itself
+        let row_rect = ui.max_rect();
+        let hovered = ui.rect_contains_pointer(row_rect);
+        if is_selected {
+            ui.painter().rect_filled(row_rect, 4.0, Color32::from_rgb(60, 80, 120));
+        } else if hovered {
+            ui.painter().rect_filled(row_rect, 4.0, ui.visuals().widgets.hovered.weak_bg_fill);
+        }
+        
+        if ui.checkbox(&mut is_selected.clone(), "").clicked() {
+            app.click_selection(&mod_item.mod_id, checkbox_modifiers(ui));
+        }
+        render_favorite_star(app, ui, mod_item);
+        render_mod_status(ui, mod_item);
+        render_conflict_badge(app, ui, mod_item);
+        
+        if ui.add(egui::Label::new(RichText::new(&mod_item.mod_name).strong()).sense(egui::Sense::click()))
+            .on_hover_text("Show details")
+            .clicked()
+        {
+            mod_actions.push(ModAction::ShowDetails(mod_item.mod_id.clone()));
+        }
+        render_mod_badges(ui, mod_item, app.mod_metadata.get(&mod_item.mod_id).map(|metadata| metadata.approval()));
+        render_local_tags(app, ui, mod_item);
+        ui.label(RichText::new(format!("v{}", mod_item.selected_version)).weak());
+        if let Some(latest) = &mod_item.update_available {
+            let description = format!("v{} was released on mod.io", latest);
+            with_label(ui.label(RichText::new("⬆").color(Color32::from_rgb(100, 170, 255))), egui::WidgetType::Label, &description)
+                .on_hover_text(description);
+        }
+        
+        // Buttons stay up while they are in use: during a download, a pending delete or an open dropdown
+        let in_use = app.downloads.contains_key(&mod_item.mod_id)
+            || app.mod_delete_confirmation_requested.get(&mod_item.mod_id).copied().unwrap_or(false)
+            || ui.memory(|memory| memory.any_popup_open());
+        if hovered || is_selected || in_use {
+            render_mod_actions(app, ui, mod_item, mod_actions, mod_to_install);
+        }
+    });
+}
+
+fn render_mod_variants(
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry,
+    mod_actions: &mut Vec<ModAction>
+) {
+    ui.indent(format!("variants_{}", mod_item.mod_id), |ui| {
+        for variant in &mod_item.variants {
+            let is_selected = *variant == mod_item.selected_variant;
+            let name = variant.strip_suffix(".pak").unwrap_or(variant);
+            
+            if ui.radio(is_selected, name).clicked() && !is_selected {
+                mod_actions.push(ModAction::SelectVariant(mod_item.mod_id.clone(), variant.clone()));
+            }
+        }
+    });
+}
+
+fn render_mod_status(
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry
+) {
+    let status_color = if mod_item.enabled {
+        Color32::from_rgb(100, 200, 100) // Green for enabled
     } else if mod_item.installed {
         Color32::from_rgb(200, 200, 100) // Yellow for installed but not enabled
     } else {
@@ -632,22 +2067,185 @@ fn render_mod_status(
             .color(status_color)
             .strong()
     );
+    
+    // Explain mods the manager turned off by itself
+    if !mod_item.enabled
+        && let Some(reason) = &mod_item.disabled_reason
+    {
+        let description = format!("Disabled automatically: {}", reason);
+        with_label(ui.label(RichText::new("⚠").color(Color32::from_rgb(255, 165, 0))), egui::WidgetType::Label, &description)
+            .on_hover_text(description);
+    }
 }
 
-fn render_mod_details(
+// Where the mod comes from and, for mod.io mods, whether it is approved for public games
+fn render_mod_badges(
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry,
+    approval: Option<Approval>
+) {
+    render_source_badge(ui, mod_item);
+    if let Some(approval) = approval {
+        render_approval_badge(ui, approval);
+    }
+}
+
+fn render_source_badge(
     ui: &mut egui::Ui,
     mod_item: &ModEntry
 ) {
-    ui.vertical(|ui| {
-        ui.label(RichText::new(&mod_item.mod_name).strong());
-        ui.horizontal(|ui| {
-            ui.label(format!("ID: {}", mod_item.mod_id));
-            ui.label(format!("v{}", mod_item.selected_version));
+    let host = source_headers::host_of(&mod_item.mod_link);
+    let (text, color) = if mod_item.modio_id().is_some() {
+        ("mod.io", Color32::from_rgb(100, 170, 255))
+    } else if host.as_deref().is_some_and(|host| host == "github.com" || host.ends_with(".githubusercontent.com")) {
+        ("GitHub", Color32::from_rgb(190, 160, 255))
+    } else if host.is_some() {
+        ("Web", Color32::from_rgb(180, 180, 180))
+    } else {
+        ("Local", Color32::from_rgb(200, 200, 100))
+    };
+    render_badge(ui, text, color, &format!("Downloaded from {}", download_failure::source_of(mod_item)));
+}
+
+fn render_approval_badge(ui: &mut egui::Ui, approval: Approval) {
+    match approval {
+        Approval::Verified => render_badge(ui, "Verified", Color32::from_rgb(100, 200, 100), "Approved for public games"),
+        Approval::Sandbox => render_badge(ui, "Sandbox", Color32::from_rgb(255, 165, 0), "Only loads in private sandbox games"),
+        Approval::Unknown => {},
+    }
+}
+
+fn render_badge(ui: &mut egui::Ui, text: &str, color: Color32, description: &str) {
+    let badge = RichText::new(text).small().color(color).background_color(color.gamma_multiply(0.2));
+    with_label(ui.label(badge), egui::WidgetType::Label, description)
+        .on_hover_text(description);
+}
+
+// Size of the mod.io thumbnail shown in each row
+const THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(96.0, 54.0);
+
+fn render_mod_details(
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry,
+    mod_link: &str,
+    thumbnail: Option<&egui::TextureHandle>,
+    // File name of the sound preview and whether it is playing
+    audio_preview: Option<(&str, bool)>,
+    approval: Option<Approval>,
+    mod_actions: &mut Vec<ModAction>
+) {
+    ui.horizontal(|ui| {
+        if let Some(thumbnail) = thumbnail {
+            ui.add(egui::Image::new(thumbnail).fit_to_exact_size(THUMBNAIL_SIZE));
+        }
+        
+        ui.vertical(|ui| {
+            if ui.add(egui::Label::new(RichText::new(&mod_item.mod_name).strong()).sense(egui::Sense::click()))
+                .on_hover_text("Show details")
+                .clicked()
+            {
+                mod_actions.push(ModAction::ShowDetails(mod_item.mod_id.clone()));
+            }
+            ui.horizontal(|ui| {
+                render_mod_badges(ui, mod_item, approval);
+                ui.label(format!("ID: {}", mod_item.mod_id));
+                ui.label(format!("v{}", mod_item.selected_version));
+                if let Some(latest) = &mod_item.update_available {
+                    ui.label(RichText::new("⬆ Update available").color(Color32::from_rgb(100, 170, 255)))
+                        .on_hover_text(format!("v{} was released on mod.io", latest));
+                }
+                if mod_item.installed && let Some(checked_at) = mod_item.last_checked {
+                    ui.label(RichText::new(format!("Checked {}", format::relative_date(checked_at))).weak());
+                }
+            });
+            ui.label(mod_link);
+        
+            // Audio mods can be heard before they are deployed
+            if let Some((preview, is_playing)) = audio_preview {
+                ui.horizontal(|ui| {
+                    if is_playing {
+                        if ui.button("⏹ Stop").clicked() {
+                            mod_actions.push(ModAction::StopAudioPreview);
+                        }
+                    } else if ui.button("▶ Play preview").clicked() {
+                        mod_actions.push(ModAction::PlayAudioPreview(mod_item.mod_id.clone()));
+                    }
+                    ui.label(RichText::new(preview).weak());
+                });
+            }
         });
-        ui.label(&mod_item.mod_link);
     });
 }
 
+// Cached lookup of the sound preview shipped with an installed mod
+fn audio_preview_for(
+    app: &mut ModManager,
+    mod_item: &ModEntry
+) -> Option<String> {
+    if !mod_item.installed {
+        return None;
+    }
+    
+    if let Some(preview) = app.audio_previews.get(&mod_item.mod_id) {
+        return preview.clone();
+    }
+    
+    let preview = app.installer.find_audio_preview(mod_item);
+    app.audio_previews.insert(mod_item.mod_id.clone(), preview.clone());
+    preview
+}
+
+#[cfg(feature = "audio-preview")]
+fn is_audio_preview_playing(app: &ModManager, mod_id: &str) -> bool {
+    app.audio_player
+        .as_ref()
+        .map(|player| player.mod_id == mod_id && player.is_playing())
+        .unwrap_or(false)
+}
+
+#[cfg(not(feature = "audio-preview"))]
+fn is_audio_preview_playing(_app: &ModManager, _mod_id: &str) -> bool {
+    false
+}
+
+#[cfg(feature = "audio-preview")]
+fn play_audio_preview(app: &mut ModManager, mod_id: &str) {
+    stop_audio_preview(app);
+    
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id) else {
+        return;
+    };
+    let Some(Some(preview)) = app.audio_previews.get(mod_id) else {
+        return;
+    };
+    
+    let result = app.installer.read_audio_preview(mod_entry, preview)
+        .and_then(|data| crate::audio::AudioPlayer::play(mod_id, data));
+    
+    match result {
+        Ok(player) => app.audio_player = Some(player),
+        Err(e) => {
+            app.error_message = e;
+            app.show_error_message = true;
+        }
+    }
+}
+
+#[cfg(not(feature = "audio-preview"))]
+fn play_audio_preview(app: &mut ModManager, _mod_id: &str) {
+    app.notify(Severity::Warning, "Built without audio preview support.".to_string());
+}
+
+#[cfg(feature = "audio-preview")]
+fn stop_audio_preview(app: &mut ModManager) {
+    if let Some(player) = app.audio_player.take() {
+        player.stop();
+    }
+}
+
+#[cfg(not(feature = "audio-preview"))]
+fn stop_audio_preview(_app: &mut ModManager) {}
+
 fn render_mod_actions(
     app: &mut ModManager, 
     ui: &mut egui::Ui, 
@@ -657,11 +2255,22 @@ fn render_mod_actions(
 ) {
     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
         // Delete button with confirmation
-        render_delete_button(app, ui, mod_item, mod_actions);
+        if !app.guest_mode {
+            render_delete_button(app, ui, mod_item, mod_actions);
+        }
 
         // Show different buttons based on tab
-        if matches!(app.current_tab, Tab::Browse) {
-            render_browse_tab_buttons(ui, mod_item, mod_to_install);
+        if let Some(download) = app.downloads.get(&mod_item.mod_id) {
+            render_download_progress(ui, download.downloaded, download.total);
+        } else if app.download_queue.contains(&mod_item.mod_id) {
+            match app.queue_position(&mod_item.mod_id) {
+                Some(position) => ui.weak(format!("Queued (#{})", position)),
+                None => ui.weak("Download paused"),
+            };
+        } else if let Some(wait) = app.retry_due_in(&mod_item.mod_id, ui.input(|input| input.time)) {
+            ui.weak(format!("Download failed, retrying in {:.0}s", wait.ceil()));
+        } else if matches!(app.current_tab, Tab::Browse) {
+            render_browse_tab_buttons(app, ui, mod_item, mod_actions, mod_to_install);
         } else if matches!(app.current_tab, Tab::Installed) {
             render_installed_tab_buttons(app, ui, mod_item, mod_actions);
         }
@@ -677,20 +2286,25 @@ fn render_delete_button(
     let mod_id = mod_item.mod_id.clone();
     let is_delete_requested = app.mod_delete_confirmation_requested.get(&mod_id).copied().unwrap_or(false);
     
+    let action = if matches!(app.current_tab, Tab::Browse) { "Delete" } else { "Uninstall" };
     if !is_delete_requested {
-        if ui.button("🗑").clicked() {
+        if icon_button(ui, "🗑", &format!("{} {}", action, mod_item.mod_name)).clicked() {
             mod_actions.push(ModAction::RequestDeleteConfirmation(mod_id.clone()));
         }
     } else {
         // First button (cancel)
-        if ui.button("🗑").clicked() {
+        let cancel = icon_button(ui, "🗑", &format!("Keep {}", mod_item.mod_name));
+        if cancel.clicked() {
             mod_actions.push(ModAction::CancelDeleteConfirmation(mod_id.clone()));
         }
         
         // Second button (confirm - red) - to the left of the first one
-        if ui.add(egui::Button::new(
-            RichText::new("🗑").color(Color32::RED)
-        )).clicked() {
+        let confirm = icon_button(
+            ui,
+            RichText::new("🗑").color(Color32::RED),
+            &format!("Confirm: {} {}", action.to_lowercase(), mod_item.mod_name)
+        );
+        if confirm.clicked() {
             if matches!(app.current_tab, Tab::Browse) {
                 mod_actions.push(ModAction::DeleteModVersion(mod_id.clone()));
             } else {
@@ -698,28 +2312,81 @@ fn render_delete_button(
             }
         }
         
-        // Auto-cancel if mouse moves away
-        if !ui.ui_contains_pointer() {
+        // Auto-cancel if mouse moves away, unless the keyboard is on the buttons
+        if !ui.ui_contains_pointer() && !cancel.has_focus() && !confirm.has_focus() {
             mod_actions.push(ModAction::CancelDeleteConfirmation(mod_id.clone()));
         }
     }
 }
 
+fn render_download_progress(
+    ui: &mut egui::Ui,
+    downloaded: u64,
+    total: Option<u64>
+) {
+    let progress_bar = match total {
+        Some(total) if total > 0 => egui::ProgressBar::new(downloaded as f32 / total as f32)
+            .text(format!("{} / {}", format::size(downloaded), format::size(total))),
+        // Servers don't always send a size, so only the bytes so far can be shown
+        _ => egui::ProgressBar::new(0.0)
+            .animate(true)
+            .text(format::size(downloaded)),
+    };
+    ui.add(progress_bar.desired_width(160.0));
+}
+
 fn render_browse_tab_buttons(
+    app: &mut ModManager,
     ui: &mut egui::Ui, 
     mod_item: &ModEntry,
+    mod_actions: &mut Vec<ModAction>,
     mod_to_install: &mut Option<String>
 ) {
-    // Show Install button in Browse tab if not installed
-    if !mod_item.installed {
-        if ui.button("Install").clicked() {
-            *mod_to_install = Some(mod_item.mod_id.clone());
+    if mod_item.installed {
+        return;
+    }
+    
+    // Archived mods come back from cold storage, or are downloaded again
+    if let Some((archived_at, location)) = app.archived_mods.get(&mod_item.mod_id) {
+        let hover = if location.is_empty() {
+            "Download the selected version again".to_string()
+        } else {
+            format!("Move the files back from {}", location)
+        };
+        if ui.button("Restore").on_hover_text(app.redact(&hover)).clicked() {
+            mod_actions.push(ModAction::RestoreMod(mod_item.mod_id.clone()));
         }
+        ui.weak(format!("Archived {}", format::relative_date(*archived_at)));
+        return;
+    }
+    
+    // Show Install button in Browse tab if not installed
+    if ui.button("Install").clicked() {
+        *mod_to_install = Some(mod_item.mod_id.clone());
+    }
+    
+    // Pick an older release before installing
+    app.request_versions(mod_item);
+    if mod_item.versions.len() > 1 {
+        egui::ComboBox::from_id_salt(("version", &mod_item.mod_id))
+            .selected_text(&mod_item.selected_version)
+            .show_ui(ui, |ui| {
+                for version in &mod_item.versions {
+                    let is_selected = *version == mod_item.selected_version;
+                    if ui.selectable_label(is_selected, version).clicked() && !is_selected {
+                        mod_actions.push(ModAction::SelectVersion(mod_item.mod_id.clone(), version.clone()));
+                    }
+                }
+            });
+    }
+    if let Some(error) = app.version_errors.get(&mod_item.mod_id) {
+        ui.label(RichText::new("Version unavailable").color(Color32::RED))
+            .on_hover_text(app.redact(error));
     }
 }
 
 fn render_installed_tab_buttons(
-    app: &ModManager, 
+    app: &ModManager,
     ui: &mut egui::Ui, 
     mod_item: &ModEntry,
     mod_actions: &mut Vec<ModAction>
@@ -732,6 +2399,32 @@ fn render_installed_tab_buttons(
         // We'll handle this in process_mod_actions
         mod_actions.push(ModAction::ToggleModEnabled(mod_id, new_status));
     }
+    
+    // Review which archive files are used by this mod
+    if ui.button("Files").clicked() {
+        mod_actions.push(ModAction::PreviewArchive(mod_item.mod_id.clone()));
+    }
+    
+    // Undo a recent update that broke something
+    if let Some(version) = app.rollback_version(mod_item)
+        && ui.button("Rollback")
+            .on_hover_text(format!("Go back to version {}", version))
+            .clicked()
+    {
+        mod_actions.push(ModAction::RollbackMod(mod_item.mod_id.clone()));
+    }
+    
+    if !app.guest_mode
+        && ui.button("Archive")
+            .on_hover_text(if app.cold_storage_folder.is_empty() {
+                "Delete the mod's files but keep it in the library to download again later"
+            } else {
+                "Move the mod's files to the cold storage folder and keep it in the library"
+            })
+            .clicked()
+    {
+        mod_actions.push(ModAction::ArchiveMod(mod_item.mod_id.clone()));
+    }
 }
 
 fn process_mod_actions(
@@ -752,20 +2445,102 @@ fn process_mod_actions(
                 *needs_reload = true;
             },
             ModAction::UninstallMod(mod_id) => {
-                if let Ok(()) = app.db.update_mod_installed(mod_id, false) {
+                // Take the mod's files out of the game folder first
+                if let Some(mod_entry) = app.mods.iter().find(|m| &m.mod_id == mod_id)
+                    && let Err(e) = app.uninstall_files(mod_entry)
+                {
+                    app.error_message = format!("Error removing mod files: {}", e);
+                    app.show_error_message = true;
+                } else if let Ok(()) = app.db.update_mod_installed(mod_id, false) {
                     *needs_reload = true;
+                    disable_mod_hub_dependents(app, mod_id);
                 }
                 app.mod_delete_confirmation_requested.remove(mod_id);
             },
             ModAction::ToggleModEnabled(mod_id, enabled) => {
-                if let Ok(()) = app.db.update_mod_enabled(mod_id, *enabled) {
+                if set_mod_enabled(app, mod_id, *enabled, needs_reload) && !*enabled {
+                    disable_mod_hub_dependents(app, mod_id);
+                }
+            },
+            ModAction::SelectVariant(mod_id, variant) => {
+                if let Some(mod_entry) = app.mods.iter().find(|m| &m.mod_id == mod_id).cloned() {
+                    select_mod_variant(app, &mod_entry, variant);
                     *needs_reload = true;
                 }
             },
+            ModAction::PlayAudioPreview(mod_id) => {
+                play_audio_preview(app, mod_id);
+            },
+            ModAction::StopAudioPreview => {
+                stop_audio_preview(app);
+            },
+            ModAction::ShowDetails(mod_id) => {
+                app.show_mod_details(mod_id);
+            },
+            ModAction::SelectVersion(mod_id, version) => {
+                app.version_errors.remove(mod_id);
+                if let Err(e) = app.db.set_selected_version(mod_id, version) {
+                    app.error_message = format!("Error selecting version: {}", e);
+                    app.show_error_message = true;
+                }
+                *needs_reload = true;
+            },
+            ModAction::RollbackMod(mod_id) => {
+                rollback_mod(app, mod_id);
+            },
+            ModAction::ArchiveMod(mod_id) => {
+                app.archive_mod(mod_id);
+            },
+            ModAction::RestoreMod(mod_id) => {
+                app.restore_mod(mod_id);
+            },
+            ModAction::UpdateMod(mod_id) => {
+                app.update_mods(std::slice::from_ref(mod_id));
+            },
+            ModAction::PreviewArchive(mod_id) => {
+                open_archive_preview(app, mod_id);
+                if app.archive_preview.is_none() {
+                    app.notify(Severity::Warning, "This mod has no archive to preview.".to_string());
+                }
+            },
         }
     }
 }
 
+// Switch back to the release the mod was updated from and deploy it in place of the update
+fn rollback_mod(
+    app: &mut ModManager,
+    mod_id: &str
+) {
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+        return;
+    };
+    let Some(version) = app.rollback_version(&mod_entry).map(str::to_string) else {
+        app.notify(Severity::Warning, format!("{} has no previous version to roll back to.", mod_entry.mod_name));
+        return;
+    };
+    
+    if let Err(e) = app.installer.undeploy_everywhere(&mod_entry) {
+        app.error_message = format!("Error removing {} {}: {}", mod_entry.mod_name, mod_entry.selected_version, e);
+        app.show_error_message = true;
+        return;
+    }
+    let profile = app.db.get_current_profile().to_string();
+    if let Err(e) = app.db.set_selected_version(mod_id, &version)
+        .and_then(|()| app.db.clear_previous_version(&profile, mod_id))
+    {
+        app.error_message = format!("Error selecting version {}: {}", version, e);
+        app.show_error_message = true;
+        return;
+    }
+    app.previous_versions.remove(&(profile, mod_id.to_string()));
+    
+    reload_mods(app);
+    refresh_mod_variants(app, mod_id);
+    deploy_if_enabled(app, mod_id);
+    app.notify(Severity::Success, format!("Rolled {} back to {}.", mod_entry.mod_name, version));
+}
+
 fn delete_mod_version(
     app: &mut ModManager,
     mod_id: &str
@@ -779,26 +2554,1236 @@ fn delete_mod_version(
             .join(&mod_entry.download_folder)
             .join(&mod_entry.selected_version);
         
-        if version_dir.exists() {
-            if let Err(e) = std::fs::remove_dir_all(&version_dir) {
-                println!("Failed to delete version directory: {}", e);
-            }
+        if version_dir.exists()
+            && let Err(e) = std::fs::remove_dir_all(&version_dir)
+        {
+            warn!("Failed to delete version directory: {}", e);
         }
     }
     app.mod_delete_confirmation_requested.remove(mod_id);
 }
 
-fn install_mod(
+// Follow-up steps once the background install of a mod has finished
+pub fn finish_install(
     app: &mut ModManager,
     mod_id: &str,
+    result: Result<(), String>
+) {
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id) else {
+        return;
+    };
+    let mod_name = mod_entry.mod_name.clone();
+    
+    // Updates report in one summary instead of a dialog per mod
+    let old_entry = app.take_update(mod_id);
+    if let Err(e) = result {
+        match old_entry {
+            Some(old_entry) => app.record_update_result(&old_entry, Err(e)),
+            None => {
+                app.error_message = format!("Error installing {}: {}", mod_name, e);
+                app.show_error_message = true;
+            }
+        }
+        return;
+    }
+    
+    if let Ok(()) = app.db.update_mod_installed(mod_id, true) {
+        // The previous version's paks are tracked in its own manifest
+        if let Some(old_entry) = &old_entry {
+            if let Err(e) = app.installer.undeploy_everywhere(old_entry) {
+                warn!("Failed to remove the previous version of {}: {}", mod_name, e);
+            }
+            app.record_previous_version(old_entry);
+        }
+        
+        reload_mods(app);
+        app.audio_previews.remove(mod_id);
+        refresh_mod_variants(app, mod_id);
+        deploy_if_enabled(app, mod_id);
+        
+        match &old_entry {
+            Some(old_entry) => app.record_update_result(old_entry, Ok(())),
+            // Offer a file selection for one archive at a time
+            None if app.archive_preview.is_none() => open_archive_preview(app, mod_id),
+            None => {},
+        }
+        check_mod_hub_dependency(app, mod_id);
+    }
+}
+
+// Keep the game folder in line with the enabled flag
+fn set_mod_enabled(
+    app: &mut ModManager,
+    mod_id: &str,
+    enabled: bool,
     needs_reload: &mut bool
+) -> bool {
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id) else {
+        return false;
+    };
+    
+    let result = if enabled {
+        app.installer.deploy_mod(mod_entry, &app.game_path)
+    } else {
+        app.installer.undeploy_mod(mod_entry, &app.game_path)
+    };
+    
+    // Leave the flag untouched when the files couldn't be changed
+    if let Err(e) = result {
+        app.error_message = format!(
+            "Error {} {}: {}",
+            if enabled { "enabling" } else { "disabling" },
+            mod_entry.mod_name,
+            e
+        );
+        app.show_error_message = true;
+        return false;
+    }
+    
+    // A manual toggle replaces any automatic decision
+    if let Ok(()) = app.db.update_mod_enabled(mod_id, enabled)
+        .and_then(|()| app.db.set_disabled_reason(mod_id, None))
+    {
+        *needs_reload = true;
+        return true;
+    }
+    false
+}
+
+// Enabled mods get their paks copied into the game right away
+fn deploy_if_enabled(
+    app: &mut ModManager,
+    mod_id: &str
 ) {
-    if let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id) {
-        if let Ok(()) = app.installer.install_mod(mod_entry) {
-            if let Ok(()) = app.db.update_mod_installed(mod_id, true) {
-                *needs_reload = true;
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id && m.enabled) else {
+        return;
+    };
+    
+    // Don't leave the mod marked enabled when the game won't load it
+    if let Err(e) = app.installer.deploy_mod(mod_entry, &app.game_path) {
+        app.auto_disable_mod(mod_id, DisableReason::DeployFailed(e));
+    }
+}
+
+fn is_mod_hub(mod_entry: &ModEntry) -> bool {
+    ParsedModRef::parse(&mod_entry.mod_link).is_some_and(|parsed| Some(parsed) == ParsedModRef::parse(MOD_HUB_URL))
+        || mod_entry.mod_name.to_lowercase().replace([' ', '-', '_'], "") == "modhub"
+}
+
+// Turn off enabled mods that rely on Mod Hub once it is disabled or uninstalled
+fn disable_mod_hub_dependents(
+    app: &mut ModManager,
+    mod_id: &str
+) {
+    if !app.mods.iter().any(|m| m.mod_id == mod_id && is_mod_hub(m)) {
+        return;
+    }
+    
+    let dependents: Vec<String> = app.mods
+        .iter()
+        .filter(|m| m.enabled && !is_mod_hub(m))
+        .filter(|m| {
+            let text = format!("{}\n{}", m.mod_name, app.installer.read_bundled_text(m));
+            ModIoClient::mentions_mod_hub(&text)
+        })
+        .map(|m| m.mod_id.clone())
+        .collect();
+    
+    for dependent in dependents {
+        app.auto_disable_mod(&dependent, DisableReason::MissingDependency("Mod Hub".to_string()));
+    }
+}
+
+fn check_mod_hub_dependency(
+    app: &mut ModManager,
+    mod_id: &str
+) {
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+        return;
+    };
+    if is_mod_hub(&mod_entry) {
+        return;
+    }
+    
+    // Mod.io entries carry a full description, fetched in the background
+    match mod_entry.modio_id() {
+        Some(modio_id) => app.worker.send(Task::FetchModDetails {
+            mod_id: mod_entry.mod_id,
+            modio_id,
+            purpose: DetailsPurpose::ModHubCheck,
+        }),
+        None => complete_mod_hub_check(app, mod_id, ""),
+    }
+}
+
+pub fn complete_mod_hub_check(
+    app: &mut ModManager,
+    mod_id: &str,
+    description: &str
+) {
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+        return;
+    };
+    
+    let text = format!(
+        "{}\n{}\n{}",
+        mod_entry.mod_name,
+        app.installer.read_bundled_text(&mod_entry),
+        description
+    );
+    if !ModIoClient::mentions_mod_hub(&text) {
+        return;
+    }
+    
+    let mod_hub_ready = app.mods.iter().any(|m| is_mod_hub(m) && m.installed && m.enabled);
+    if !mod_hub_ready {
+        app.mod_hub_prompt = Some(mod_entry.mod_name);
+    }
+}
+
+fn install_mod_hub(
+    app: &mut ModManager
+) {
+    // Reuse the library entry if Mod Hub was added before
+    let mod_id = match app.mods.iter().find(|m| is_mod_hub(m)) {
+        Some(mod_hub) => mod_hub.mod_id.clone(),
+        None => {
+            let mod_hub = ModEntry {
+                mod_id: "mod_hub".to_string(),
+                mod_name: "Mod Hub".to_string(),
+                mod_link: MOD_HUB_URL.to_string(),
+                download_folder: "downloads".to_string(),
+                selected_version: "1.0.0".to_string(),
+                versions: Vec::new(),
+                installed: false,
+                enabled: false,
+                variants: Vec::new(),
+                selected_variant: String::new(),
+                last_checked: None,
+                disabled_reason: None,
+                thumbnail_url: None,
+                update_available: None,
+                notes: String::new(),
+            };
+            if let Err(e) = app.db.add_mod(&mod_hub) {
+                app.error_message = format!("Error adding Mod Hub: {}", e);
+                app.show_error_message = true;
+                return;
+            }
+            reload_mods(app);
+            mod_hub.mod_id
+        }
+    };
+    
+    if !app.mods.iter().any(|m| m.mod_id == mod_id && m.installed) {
+        // Enabled up front so it gets deployed as soon as the download finishes
+        if let Err(e) = app.db.update_mod_enabled(&mod_id, true) {
+            app.error_message = format!("Error enabling Mod Hub: {}", e);
+            app.show_error_message = true;
+            return;
+        }
+        reload_mods(app);
+        app.start_install(&mod_id);
+        app.show_notification("Downloading Mod Hub...".to_string());
+        return;
+    }
+    
+    let mut needs_reload = false;
+    if set_mod_enabled(app, &mod_id, true, &mut needs_reload) {
+        reload_mods(app);
+        app.notify(Severity::Success, "Mod Hub enabled.".to_string());
+    }
+}
+
+fn render_ingame_import(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(found) = app.ingame_import.as_mut() else {
+        return;
+    };
+    
+    let mut import = false;
+    let mut skip = false;
+    
+    egui::Window::new("Import Mods From Game")
+        .collapsible(false)
+        .resizable(true)
+        .show(ctx, |ui| {
+            ui.label("The game's built-in mod.io integration already manages these mods.");
+            ui.label("Add them to the library so the manager's view matches the game?");
+            ui.separator();
+            
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (ingame, selected) in found.iter_mut() {
+                        ui.checkbox(selected, format!("{} ({})", ingame.name, ingame.modio_id))
+                            .on_hover_text(ingame.path.to_string_lossy());
+                    }
+                });
+            
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Import selected").clicked() {
+                    import = true;
+                }
+                if ui.button("Skip").clicked() {
+                    skip = true;
+                }
+            });
+        });
+    
+    if import {
+        let selected: Vec<_> = app.ingame_import
+            .take()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(ingame, _)| ingame)
+            .collect();
+        app.import_ingame_mods(&selected);
+    } else if skip {
+        app.ingame_import = None;
+    }
+}
+
+fn render_mod_hub_prompt(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(mod_name) = app.mod_hub_prompt.clone() else {
+        return;
+    };
+    
+    egui::Window::new("Mod Hub Required")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!(
+                "'{}' requires Mod Hub, which is not installed and enabled in profile '{}'.",
+                mod_name,
+                app.db.get_current_profile()
+            ));
+            ui.horizontal(|ui| {
+                let install = ui.button("Install Mod Hub");
+                focus_if_idle(ui, &install);
+                if install.clicked() {
+                    app.mod_hub_prompt = None;
+                    install_mod_hub(app);
+                }
+                if ui.button("Dismiss").clicked() {
+                    app.mod_hub_prompt = None;
+                }
+            });
+        });
+}
+
+// Headers sent with every download from one file host
+fn render_source_editor(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(editor) = app.source_editor.as_mut() else {
+        return;
+    };
+    
+    let mut save = false;
+    let mut cancel = false;
+    egui::Window::new(format!("Download headers for {}", editor.host))
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("Sent with every download from this host, e.g. Authorization or Cookie.");
+            ui.separator();
+            
+            let mut remove = None;
+            egui::Grid::new("source_headers").num_columns(3).show(ui, |ui| {
+                for (index, (name, value)) in editor.headers.iter_mut().enumerate() {
+                    ui.add(egui::TextEdit::singleline(name).hint_text("Header").desired_width(120.0));
+                    ui.add(egui::TextEdit::singleline(value).hint_text("Value").password(true).desired_width(200.0));
+                    if icon_button(ui, "🗑", "Remove header").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+            if let Some(index) = remove {
+                editor.headers.remove(index);
+            }
+            if ui.button("Add header").clicked() {
+                editor.headers.push((String::new(), String::new()));
+            }
+            
+            ui.separator();
+            ui.horizontal(|ui| {
+                if ui.button("Save").clicked() {
+                    save = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancel = true;
+                }
+            });
+        });
+    
+    if save {
+        app.save_source_editor();
+    } else if cancel {
+        app.source_editor = None;
+    }
+}
+
+fn render_dependency_prompt(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(prompt) = &app.dependency_prompt else {
+        return;
+    };
+    
+    let mut install = false;
+    let mut dismiss = false;
+    egui::Window::new("Missing Dependencies")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label(format!("{} need these mods to work:", prompt.mod_names.join(", ")));
+            for dependency in &prompt.missing {
+                ui.label(format!("• {}", dependency.name));
+            }
+            ui.horizontal(|ui| {
+                let install_button = ui.button("Install dependencies");
+                focus_if_idle(ui, &install_button);
+                if install_button.clicked() {
+                    install = true;
+                }
+                if ui.button("Skip").clicked() {
+                    dismiss = true;
+                }
+            });
+        });
+    
+    if install {
+        app.install_dependencies();
+    } else if dismiss {
+        app.dependency_prompt = None;
+    }
+}
+
+fn render_post_install_prompt(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(command) = &app.post_install_command_prompt else {
+        return;
+    };
+    
+    let mut run = false;
+    let mut dismiss = false;
+    egui::Window::new("Run Post-Install Command")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.label("Installs have finished. Run this command?");
+            ui.code(command);
+            ui.horizontal(|ui| {
+                if ui.button("Run").clicked() {
+                    run = true;
+                }
+                // Enter never runs the command by accident
+                let skip = ui.button("Skip");
+                focus_if_idle(ui, &skip);
+                if skip.clicked() {
+                    dismiss = true;
+                }
+            });
+        });
+    
+    if run {
+        app.confirm_post_install_command();
+    } else if dismiss {
+        app.post_install_command_prompt = None;
+    }
+}
+
+// Everything the startup check found, each with a way to fix it
+fn render_startup_issues(app: &mut ModManager, ctx: &egui::Context) {
+    if app.startup_issues.is_empty() {
+        return;
+    }
+    
+    let mut resolved = Vec::new();
+    let mut check_again = false;
+    let mut dismiss = false;
+    egui::Window::new("Issues Found")
+        .collapsible(false)
+        .resizable(false)
+        .show(ctx, |ui| {
+            for issue in app.startup_issues.clone() {
+                ui.label(issue.description());
+                ui.horizontal(|ui| match &issue {
+                    StartupIssue::UnvalidatedToken => {
+                        if ui.button("Validate now").clicked() {
+                            app.validate_mod_io_oauth_key();
+                            resolved.push(issue.clone());
+                        }
+                        if ui.button("Log out").clicked() {
+                            app.log_out_mod_io();
+                        }
+                    },
+                    StartupIssue::GamePathMissing(_) => {
+                        if ui.button("Detect again").clicked() {
+                            app.detect_game_path();
+                        }
+                        if ui.button("Open Settings").clicked() {
+                            app.current_tab = Tab::Settings;
+                            resolved.push(issue.clone());
+                        }
+                    },
+                    StartupIssue::StorageUnwritable(_) => {
+                        if ui.button("Open folder").clicked()
+                            && let Err(e) = launcher::open_folder(app.installer.app_data_dir())
+                        {
+                            warn!("Failed to open storage folder: {}", e);
+                        }
+                    },
+                    StartupIssue::SyncFolderMissing(_) => {
+                        if ui.button("Stop syncing").clicked() {
+                            app.set_sync_folder(String::new());
+                            resolved.push(issue.clone());
+                        }
+                        if ui.button("Open Settings").clicked() {
+                            app.current_tab = Tab::Settings;
+                            resolved.push(issue.clone());
+                        }
+                    },
+                });
+                ui.separator();
+            }
+            ui.horizontal(|ui| {
+                if ui.button("Check again").clicked() {
+                    check_again = true;
+                }
+                let close = ui.button("Dismiss");
+                focus_if_idle(ui, &close);
+                if close.clicked() {
+                    dismiss = true;
+                }
+            });
+        });
+    
+    if dismiss {
+        app.startup_issues.clear();
+    } else if check_again {
+        app.check_startup_issues();
+    } else {
+        app.startup_issues.retain(|issue| !resolved.contains(issue));
+    }
+}
+
+fn render_favorite_star(
+    app: &mut ModManager,
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry
+) {
+    let favorite = app.favorites.contains(&mod_item.mod_id);
+    let (star, label) = if favorite {
+        (RichText::new("★").color(Color32::from_rgb(255, 200, 60)), "Remove from favorites")
+    } else {
+        (RichText::new("☆").weak(), "Add to favorites")
+    };
+    let response = ui.add(egui::Label::new(star).sense(egui::Sense::click()));
+    if with_label(response, egui::WidgetType::Button, label)
+        .on_hover_text(label)
+        .clicked()
+    {
+        app.set_favorite(&mod_item.mod_id, !favorite);
+    }
+}
+
+// The user's tags of a mod; clicking one filters the list by it
+fn render_local_tags(
+    app: &mut ModManager,
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry
+) {
+    let Some(tags) = app.local_tags.get(&mod_item.mod_id).cloned() else {
+        return;
+    };
+    for tag in &tags {
+        let selected = app.local_tag_filter.contains(tag);
+        if tag_chip(ui, tag, app.tag_color(tag), selected)
+            .on_hover_text("Show only mods with this tag")
+            .clicked()
+        {
+            app.toggle_local_tag_filter(tag);
+        }
+    }
+}
+
+// A tag on its color, with dark text on light colors; a check mark shows it filters the list
+fn tag_chip(
+    ui: &mut egui::Ui,
+    tag: &str,
+    color: [u8; 3],
+    selected: bool
+) -> egui::Response {
+    let [r, g, b] = color;
+    let light = u32::from(r) * 299 + u32::from(g) * 587 + u32::from(b) * 114 > 150_000;
+    let text = if selected { format!(" ✔ {} ", tag) } else { format!(" {} ", tag) };
+    let label = RichText::new(text)
+        .small()
+        .color(if light { Color32::BLACK } else { Color32::WHITE })
+        .background_color(Color32::from_rgb(r, g, b));
+    with_label(ui.add(egui::Label::new(label).sense(egui::Sense::click())), egui::WidgetType::Button, tag)
+}
+
+// Warning on installed mods whose assets another enabled mod replaces too
+fn render_conflict_badge(
+    app: &mut ModManager,
+    ui: &mut egui::Ui,
+    mod_item: &ModEntry
+) {
+    if !matches!(app.current_tab, Tab::Installed) || !mod_item.enabled {
+        return;
+    }
+    let conflicting = app.conflicting_mods(&mod_item.mod_id);
+    if conflicting.is_empty() {
+        return;
+    }
+    
+    let details: Vec<String> = conflicting
+        .iter()
+        .map(|(mod_name, count)| format!("{} ({} files)", mod_name, count))
+        .collect();
+    let description = format!("Replaces the same assets as {}", details.join(", "));
+    let badge = ui.add(egui::Label::new(RichText::new("⚠").color(Color32::from_rgb(255, 165, 0))).sense(egui::Sense::click()));
+    if with_label(badge, egui::WidgetType::Button, &description)
+        .on_hover_text(description)
+        .clicked()
+    {
+        app.show_conflicts = true;
+    }
+}
+
+// Overlapping assets grouped by the mods that share them
+fn render_conflicts_dialog(app: &mut ModManager, ctx: &egui::Context) {
+    if !app.show_conflicts {
+        return;
+    }
+    
+    let mut groups: BTreeMap<Vec<String>, Vec<&str>> = BTreeMap::new();
+    for conflict in &app.asset_conflicts {
+        let mod_names = conflict.mods.iter().map(|m| m.mod_name.clone()).collect();
+        groups.entry(mod_names).or_default().push(&conflict.path);
+    }
+    
+    let mut open = true;
+    egui::Window::new("Asset conflicts")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(500.0)
+        .show(ctx, |ui| {
+            if groups.is_empty() {
+                ui.label("No enabled mods replace the same assets.");
+                return;
+            }
+            ui.label("These mods replace the same game files; only the one loaded last takes effect. Paks named *_P.pak load after the others.");
+            ui.separator();
+            
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (mod_names, paths) in &groups {
+                    egui::CollapsingHeader::new(format!("{} — {} files", mod_names.join(" ↔ "), paths.len()))
+                        .show(ui, |ui| {
+                            for path in paths {
+                                ui.label(RichText::new(*path).monospace());
+                            }
+                        });
+                }
+            });
+        });
+    app.show_conflicts = open;
+}
+
+fn render_mod_list_comparison(app: &mut ModManager, ctx: &egui::Context) {
+    let Some((comparison, file_name)) = &app.mod_list_comparison else {
+        return;
+    };
+    
+    let mut open = true;
+    egui::Window::new(format!("Compared with {}", file_name))
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(450.0)
+        .show(ctx, |ui| {
+            if comparison.is_match() {
+                ui.label(RichText::new("✓ Both lists have the same mods and versions.").color(Color32::from_rgb(100, 200, 100)));
+                return;
+            }
+            
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                if !comparison.missing.is_empty() {
+                    ui.label(RichText::new(format!("Missing here ({})", comparison.missing.len())).strong().color(Color32::from_rgb(200, 100, 100)));
+                    for entry in &comparison.missing {
+                        ui.label(format!("{} ({})", entry.display_name(), entry.id));
+                    }
+                    ui.separator();
+                }
+                if !comparison.version_differs.is_empty() {
+                    ui.label(RichText::new(format!("Different versions ({})", comparison.version_differs.len())).strong().color(Color32::from_rgb(255, 165, 0)));
+                    for difference in &comparison.version_differs {
+                        ui.label(format!("{}: mine v{}, theirs v{}", difference.name, difference.mine, difference.theirs));
+                    }
+                    ui.separator();
+                }
+                if !comparison.extra.is_empty() {
+                    ui.label(RichText::new(format!("Not in their list ({})", comparison.extra.len())).strong());
+                    for entry in &comparison.extra {
+                        ui.label(format!("{} ({})", entry.display_name(), entry.id));
+                    }
+                }
+            });
+        });
+    if !open {
+        app.mod_list_comparison = None;
+    }
+}
+
+// Quota the last mod.io response reported, once there has been one
+fn render_rate_limit(ui: &mut egui::Ui) {
+    let rate_limit = mod_io::rate_limit();
+    let now = chrono::Utc::now().timestamp();
+    if let Some(blocked_until) = rate_limit.blocked_until.filter(|until| *until > now) {
+        ui.label(RichText::new(format!(
+            "mod.io rate limit reached; requests resume in {}",
+            format::duration((blocked_until - now) as u64)
+        )).color(Color32::from_rgb(255, 165, 0)));
+        ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+    } else if let (Some(remaining), Some(limit)) = (rate_limit.remaining, rate_limit.limit) {
+        ui.weak(format!("API quota: {} of {} requests left", format::number(remaining.into()), format::number(limit.into())))
+            .on_hover_text("Reported by mod.io with its last response");
+    }
+}
+
+// Log in with a code mod.io sends by email instead of pasting a token
+fn render_email_login(app: &mut ModManager, ui: &mut egui::Ui) {
+    ui.collapsing("Log in with email", |ui| {
+        ui.horizontal(|ui| {
+            ui.label("API key:");
+            ui.add(egui::TextEdit::singleline(&mut app.mod_io_api_key).password(true).desired_width(220.0))
+                .on_hover_text("Your read-only API key from mod.io/me/access; only needed for the email login");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Email:");
+            ui.add(egui::TextEdit::singleline(&mut app.email_login.email).desired_width(220.0));
+            let can_send = !app.email_login.pending
+                && !app.email_login.email.trim().is_empty()
+                && !app.mod_io_api_key.trim().is_empty();
+            let label = if app.email_login.code_sent { "Send again" } else { "Send code" };
+            if ui.add_enabled(can_send, egui::Button::new(label)).clicked() {
+                app.request_email_code();
+            }
+        });
+        if app.email_login.code_sent {
+            ui.horizontal(|ui| {
+                ui.label("Code:");
+                let response = ui.add(egui::TextEdit::singleline(&mut app.email_login.security_code)
+                    .char_limit(5)
+                    .desired_width(60.0)
+                    .hint_text("12345"));
+                let submitted = response.lost_focus() && ui.input(|input| input.key_pressed(egui::Key::Enter));
+                let can_log_in = !app.email_login.pending && app.email_login.security_code.trim().len() == 5;
+                if (ui.add_enabled(can_log_in, egui::Button::new("Log in")).clicked() || submitted) && can_log_in {
+                    app.exchange_email_code();
+                }
+            });
+        }
+        if app.email_login.pending {
+            ui.spinner();
+        }
+    });
+}
+
+fn render_downloads_tab(app: &mut ModManager, ui: &mut egui::Ui) {
+    ui.heading("Downloads");
+    ui.separator();
+    
+    let mod_name = |mod_id: &str| app.mods.iter()
+        .find(|m| m.mod_id == mod_id)
+        .map_or(mod_id.to_string(), |m| m.mod_name.clone());
+    let mut active: Vec<(String, String)> = app.downloads.keys().map(|mod_id| (mod_id.clone(), mod_name(mod_id))).collect();
+    active.sort_by(|a, b| a.1.cmp(&b.1));
+    let queued: Vec<(String, String)> = app.download_queue.iter().map(|mod_id| (mod_id.clone(), mod_name(mod_id))).collect();
+    let now = ui.input(|input| input.time);
+    let retrying: Vec<(String, String, f64)> = app.pending_retries
+        .iter()
+        .map(|(mod_id, due)| (mod_id.clone(), mod_name(mod_id), (due - now).max(0.0)))
+        .collect();
+    
+    let mut pause = None;
+    let mut resume = None;
+    let mut cancel = None;
+    let mut retry = None;
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        ui.label(RichText::new(format!("Active ({})", active.len())).strong());
+        if active.is_empty() {
+            ui.weak("Nothing is downloading.");
+        }
+        for (mod_id, name) in &active {
+            let Some(download) = app.downloads.get(mod_id) else {
+                continue;
+            };
+            ui.horizontal(|ui| {
+                ui.label(format!("{} v{}", name, download.version));
+                if let Some(speed) = download.speed {
+                    ui.weak(format!("{}/s", format::size(speed as u64)));
+                }
+                if let Some(eta) = download.eta() {
+                    ui.weak(format!("{} left", format::duration(eta.ceil() as u64)));
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if icon_button(ui, "✖", &format!("Cancel the download of {}", name)).clicked() {
+                        cancel = Some(mod_id.clone());
+                    }
+                    if icon_button(ui, "⏸", &format!("Pause the download of {}", name)).clicked() {
+                        pause = Some(mod_id.clone());
+                    }
+                    render_download_progress(ui, download.downloaded, download.total);
+                });
+            });
+        }
+        
+        if !queued.is_empty() || !retrying.is_empty() {
+            ui.add_space(8.0);
+            ui.label(RichText::new(format!("Queued ({})", queued.len() + retrying.len())).strong());
+        }
+        for (mod_id, name) in &queued {
+            ui.horizontal(|ui| {
+                match app.queue_position(mod_id) {
+                    Some(position) => ui.label(format!("#{} {}", position, name)),
+                    None => ui.weak(format!("{} (paused)", name)),
+                };
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    if icon_button(ui, "✖", &format!("Remove {} from the queue", name)).clicked() {
+                        cancel = Some(mod_id.clone());
+                    }
+                    if app.paused_downloads.contains(mod_id) {
+                        if icon_button(ui, "▶", &format!("Resume the download of {}", name)).clicked() {
+                            resume = Some(mod_id.clone());
+                        }
+                    } else if icon_button(ui, "⏸", &format!("Hold {} back in the queue", name)).clicked() {
+                        pause = Some(mod_id.clone());
+                    }
+                });
+            });
+        }
+        for (_, name, wait) in &retrying {
+            ui.weak(format!("{}: retrying in {:.0}s", name, wait.ceil()));
+        }
+        
+        ui.add_space(8.0);
+        ui.label(RichText::new("Finished this session").strong());
+        if app.download_history.is_empty() {
+            ui.weak("No downloads have finished yet.");
+        }
+        for finished in &app.download_history {
+            ui.horizontal(|ui| {
+                match &finished.outcome {
+                    DownloadOutcome::Completed => {
+                        ui.label(RichText::new("✓").color(Color32::from_rgb(100, 200, 100)));
+                    },
+                    DownloadOutcome::Failed(_) => {
+                        ui.label(RichText::new("✖").color(Color32::from_rgb(200, 100, 100)));
+                    },
+                    DownloadOutcome::Cancelled => {
+                        ui.weak("–");
+                    },
+                }
+                ui.label(format!("{} v{}", finished.mod_name, finished.version));
+                if finished.size > 0 {
+                    ui.weak(format::size(finished.size));
+                }
+                ui.weak(format::relative_date(finished.finished_at));
+                
+                let can_retry = !matches!(finished.outcome, DownloadOutcome::Completed)
+                    && !app.downloads.contains_key(&finished.mod_id)
+                    && !app.download_queue.contains(&finished.mod_id);
+                if can_retry {
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.small_button("Retry").clicked() {
+                            retry = Some(finished.mod_id.clone());
+                        }
+                    });
+                }
+            });
+            if let DownloadOutcome::Failed(error) = &finished.outcome {
+                ui.label(RichText::new(error).small().color(Color32::from_rgb(200, 100, 100)));
+            }
+        }
+    });
+    
+    if let Some(mod_id) = pause {
+        app.pause_download(&mod_id);
+    }
+    if let Some(mod_id) = resume {
+        app.resume_download(&mod_id);
+    }
+    if let Some(mod_id) = cancel {
+        app.cancel_download(&mod_id);
+    }
+    if let Some(mod_id) = retry {
+        app.start_install(&mod_id);
+    }
+    
+    // Keep speeds and countdowns moving while something is in flight
+    if !app.downloads.is_empty() || !retrying.is_empty() {
+        ui.ctx().request_repaint_after(std::time::Duration::from_secs(1));
+    }
+}
+
+fn render_verification_report(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(report) = &app.verification_report else {
+        return;
+    };
+    
+    let mut open = true;
+    let mut repair = Vec::new();
+    egui::Window::new("Mod verification")
+        .open(&mut open)
+        .collapsible(false)
+        .default_width(450.0)
+        .show(ctx, |ui| {
+            if report.is_empty() {
+                ui.label(RichText::new("✓ Every stored and deployed file matches its recorded hash.").color(Color32::from_rgb(100, 200, 100)));
+                return;
+            }
+            
+            egui::ScrollArea::vertical().max_height(400.0).show(ui, |ui| {
+                for (mod_id, problems) in report {
+                    let mod_name = app.mods.iter()
+                        .find(|m| &m.mod_id == mod_id)
+                        .map_or(mod_id.as_str(), |m| m.mod_name.as_str());
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(mod_name).strong().color(Color32::from_rgb(200, 100, 100)));
+                        if ui.small_button("Repair")
+                            .on_hover_text("Download the release again and redeploy it")
+                            .clicked()
+                        {
+                            repair.push(mod_id.clone());
+                        }
+                    });
+                    for problem in problems {
+                        ui.label(format!("• {}", problem));
+                    }
+                    ui.separator();
+                }
+            });
+            
+            if ui.button("Repair all").clicked() {
+                repair.extend(report.iter().map(|(mod_id, _)| mod_id.clone()));
+            }
+        });
+    
+    for mod_id in repair {
+        app.repair_mod(&mod_id);
+    }
+    if !open {
+        app.verification_report = None;
+    }
+}
+
+fn render_rating_prompt(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(mod_name) = app.rating_prompt
+        .as_ref()
+        .and_then(|mod_id| app.mods.iter().find(|m| &m.mod_id == mod_id))
+        .map(|m| m.mod_name.clone())
+    else {
+        return;
+    };
+    
+    egui::Window::new("Rate on mod.io")
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+        .show(ctx, |ui| {
+            ui.label(format!("Enjoying '{}'? A rating on mod.io helps its author.", mod_name));
+            ui.horizontal(|ui| {
+                if ui.button("👍 Rate").clicked() {
+                    app.close_rating_prompt(true, false);
+                }
+                if ui.button("Not now").clicked() {
+                    app.close_rating_prompt(false, false);
+                }
+                if ui.button("Don't ask again").clicked() {
+                    app.close_rating_prompt(false, true);
+                }
+            });
+        });
+}
+
+fn refresh_mod_variants(
+    app: &mut ModManager,
+    mod_id: &str
+) {
+    let Some(mut mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+        return;
+    };
+    
+    // A single pak is not a variant choice
+    let mut variants = app.installer.detect_variants(&mod_entry);
+    if variants.len() < 2 {
+        variants.clear();
+    }
+    
+    if let Err(e) = app.db.set_mod_variants(mod_id, &variants) {
+        app.error_message = format!("Error saving mod variants: {}", e);
+        app.show_error_message = true;
+        return;
+    }
+    
+    if let Some(first) = variants.first() {
+        let selected = if variants.contains(&mod_entry.selected_variant) {
+            mod_entry.selected_variant.clone()
+        } else {
+            first.clone()
+        };
+        mod_entry.variants = variants;
+        select_mod_variant(app, &mod_entry, &selected);
+    }
+}
+
+fn select_mod_variant(
+    app: &mut ModManager,
+    mod_entry: &ModEntry,
+    variant: &str
+) {
+    let result = app.db.set_selected_variant(&mod_entry.mod_id, variant)
+        .map_err(|e| e.to_string())
+        .and_then(|()| app.installer.apply_variant(mod_entry, variant));
+    
+    if let Err(e) = result {
+        app.error_message = format!("Error switching variant: {}", e);
+        app.show_error_message = true;
+        return;
+    }
+    
+    // Swap the deployed pak for the newly chosen one
+    if mod_entry.installed {
+        deploy_if_enabled(app, &mod_entry.mod_id);
+    }
+}
+
+fn open_archive_preview(
+    app: &mut ModManager,
+    mod_id: &str
+) {
+    let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == mod_id).cloned() else {
+        return;
+    };
+    let Some(archive_path) = app.installer.find_archive(&mod_entry) else {
+        return;
+    };
+    
+    match ModInstaller::list_archive(&archive_path) {
+        Ok(entries) => {
+            // Start from the previously recorded selection, if any
+            let excluded = app.installer.load_manifest(&mod_entry)
+                .map(|manifest| manifest.excluded_files.into_iter().collect())
+                .unwrap_or_default();
+            
+            app.archive_preview = Some(ArchivePreview {
+                mod_id: mod_entry.mod_id,
+                mod_name: mod_entry.mod_name,
+                archive_path,
+                entries,
+                excluded,
+            });
+        },
+        Err(e) => {
+            app.error_message = format!("Error reading archive: {}", e);
+            app.show_error_message = true;
+        }
+    }
+}
+
+// Directory tree built from the flat archive listing
+#[derive(Default)]
+struct ArchiveTreeNode {
+    dirs: BTreeMap<String, ArchiveTreeNode>,
+    files: Vec<usize>,
+}
+
+impl ArchiveTreeNode {
+    fn build(entries: &[ArchiveEntry]) -> Self {
+        let mut root = Self::default();
+        for (index, entry) in entries.iter().enumerate() {
+            let mut node = &mut root;
+            let mut parts: Vec<&str> = entry.path.split('/').collect();
+            parts.pop();
+            for part in parts {
+                node = node.dirs.entry(part.to_string()).or_default();
+            }
+            node.files.push(index);
+        }
+        root
+    }
+    
+    fn file_indices(&self) -> Vec<usize> {
+        let mut indices = self.files.clone();
+        for child in self.dirs.values() {
+            indices.extend(child.file_indices());
+        }
+        indices
+    }
+}
+
+// Mount point, compression and estimated load impact of each pak, to spot heavy mods
+fn render_pak_stats(ui: &mut egui::Ui, paks: &[(String, Result<PakStats, String>)]) {
+    ui.strong("Paks");
+    let mut total_impact = 0.0;
+    for (name, stats) in paks {
+        ui.label(name);
+        match stats {
+            Ok(stats) => {
+                total_impact += stats.load_impact();
+                ui.label(format!("Mount point: {}", stats.mount_point));
+                ui.label(format!(
+                    "{} files, {} stored, {} uncompressed ({})",
+                    format::number(stats.file_count as u64),
+                    format::size(stats.stored_size),
+                    format::size(stats.uncompressed_size),
+                    format::percent(stats.compression_ratio())
+                ));
+                ui.label(format!("Load impact: {}", format::decimal(stats.load_impact(), 1)));
+            },
+            Err(e) => {
+                ui.label(RichText::new(format!("Can't read the pak: {}", e)).color(Color32::RED));
+            },
+        }
+        ui.add_space(4.0);
+    }
+    
+    let (rating, color) = if total_impact < 5.0 {
+        ("light", Color32::GREEN)
+    } else if total_impact < 50.0 {
+        ("moderate", Color32::YELLOW)
+    } else {
+        ("heavy", Color32::RED)
+    };
+    ui.label(RichText::new(format!("Estimated load impact: {} ({})", format::decimal(total_impact, 1), rating)).color(color))
+        .on_hover_text("About one point per MiB the game reads after decompressing, plus one per 50 files. \
+            Disabling heavy mods shortens load times the most.");
+}
+
+fn render_archive_tree(
+    ui: &mut egui::Ui,
+    node: &ArchiveTreeNode,
+    path: &str,
+    entries: &[ArchiveEntry],
+    excluded: &mut std::collections::HashSet<String>
+) {
+    for (name, child) in &node.dirs {
+        let child_path = format!("{}{}/", path, name);
+        let indices = child.file_indices();
+        let dir_size: u64 = indices.iter().map(|&i| entries[i].size).sum();
+        let mut all_included = indices.iter().all(|&i| !excluded.contains(&entries[i].path));
+        
+        ui.horizontal(|ui| {
+            // Toggle every file below this folder at once
+            if ui.checkbox(&mut all_included, "").changed() {
+                for &i in &indices {
+                    if all_included {
+                        excluded.remove(&entries[i].path);
+                    } else {
+                        excluded.insert(entries[i].path.clone());
+                    }
+                }
+            }
+            egui::CollapsingHeader::new(format!("📁 {} ({})", name, format::size(dir_size)))
+                .id_salt(&child_path)
+                .default_open(true)
+                .show(ui, |ui| {
+                    render_archive_tree(ui, child, &child_path, entries, excluded);
+                });
+        });
+    }
+    
+    for &i in &node.files {
+        let entry = &entries[i];
+        let file_name = entry.path.rsplit('/').next().unwrap_or(&entry.path);
+        let mut included = !excluded.contains(&entry.path);
+        
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut included, file_name).changed() {
+                if included {
+                    excluded.remove(&entry.path);
+                } else {
+                    excluded.insert(entry.path.clone());
+                }
+            }
+            ui.label(RichText::new(format::size(entry.size)).weak());
+        });
+    }
+}
+
+fn render_archive_preview(app: &mut ModManager, ctx: &egui::Context) {
+    let Some(preview) = app.archive_preview.as_mut() else {
+        return;
+    };
+    
+    let mut confirmed = false;
+    let mut cancelled = false;
+    
+    egui::Window::new(format!("Archive Preview - {}", preview.mod_name))
+        .collapsible(false)
+        .resizable(true)
+        .default_width(450.0)
+        .show(ctx, |ui| {
+            ui.label(preview.archive_path.to_string_lossy());
+            ui.label("Uncheck optional files or alternative variants you don't want deployed.");
+            ui.separator();
+            
+            let tree = ArchiveTreeNode::build(&preview.entries);
+            egui::ScrollArea::vertical()
+                .max_height(350.0)
+                .auto_shrink([false, true])
+                .show(ui, |ui| {
+                    render_archive_tree(ui, &tree, "", &preview.entries, &mut preview.excluded);
+                });
+            
+            ui.separator();
+            
+            let selected: Vec<&ArchiveEntry> = preview.entries
+                .iter()
+                .filter(|entry| !preview.excluded.contains(&entry.path))
+                .collect();
+            let selected_size: u64 = selected.iter().map(|entry| entry.size).sum();
+            let compressed_size: u64 = preview.entries.iter().map(|entry| entry.compressed_size).sum();
+            ui.label(format!(
+                "{} of {} files selected, {} (archive {})",
+                selected.len(),
+                preview.entries.len(),
+                format::size(selected_size),
+                format::size(compressed_size)
+            ));
+            
+            ui.horizontal(|ui| {
+                if ui.button("Confirm").clicked() {
+                    confirmed = true;
+                }
+                if ui.button("Cancel").clicked() {
+                    cancelled = true;
+                }
+            });
+        });
+    
+    if confirmed && let Some(preview) = app.archive_preview.take() {
+        if let Some(mod_entry) = app.mods.iter().find(|m| m.mod_id == preview.mod_id) {
+            let mut excluded_files: Vec<String> = preview.excluded.into_iter().collect();
+            excluded_files.sort();
+            
+            let mut manifest = app.installer.manifest_or_default(mod_entry);
+            manifest.excluded_files = excluded_files;
+            
+            match app.installer.save_manifest(mod_entry, &manifest) {
+                Ok(()) => {
+                    // Redeploy so the game only sees the chosen files
+                    deploy_if_enabled(app, &preview.mod_id);
+                    app.notify(Severity::Success, format!("Saved file selection for {}.", preview.mod_name));
+                },
+                Err(e) => {
+                    app.error_message = e;
+                    app.show_error_message = true;
+                }
             }
         }
+    } else if cancelled {
+        app.archive_preview = None;
     }
 }
 
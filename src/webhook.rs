@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+// Events that can be posted to a webhook, e.g. a Discord channel of a shared modpack
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    UpdatesAvailable,
+    InstallFailed,
+    ProfileApplied,
+}
+
+impl WebhookEvent {
+    pub const ALL: [WebhookEvent; 3] = [
+        WebhookEvent::UpdatesAvailable,
+        WebhookEvent::InstallFailed,
+        WebhookEvent::ProfileApplied,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            WebhookEvent::UpdatesAvailable => "Updates available",
+            WebhookEvent::InstallFailed => "Install failed",
+            WebhookEvent::ProfileApplied => "Profile applied",
+        }
+    }
+}
+
+// Discord's message format; Slack and most chat bridges accept the same "content" field
+#[derive(Serialize)]
+struct WebhookMessage<'a> {
+    username: &'a str,
+    content: &'a str,
+}
+
+pub fn post(url: &str, content: &str) -> Result<(), String> {
+    let message = WebhookMessage {
+        username: "DRG Mod Manager",
+        content,
+    };
+    reqwest::blocking::Client::new()
+        .post(url)
+        .timeout(std::time::Duration::from_secs(15))
+        .json(&message)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,452 @@
+use crate::conflicts::{Conflict, ConflictReport};
+use crate::db::ModEntry;
+use crate::installer::ModInstaller;
+use crate::webhook;
+use crate::mod_io::{CatalogSort, ModIoClient, ModIoDependency, ModIoFile, ModIoMod, ModIoTagOption, ModIoUser};
+use crate::mod_url::ParsedModRef;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+use tracing::warn;
+
+// What fetched mod details are used for
+#[derive(Clone, Copy, PartialEq)]
+pub enum DetailsPurpose {
+    ModHubCheck,
+    DetailPanel,
+}
+
+// Network work the UI hands off so the window keeps repainting
+pub enum Task {
+    SetOAuthToken(String),
+    ValidateOAuthToken(String),
+    // Email login: mail a security code, then trade it for a token
+    RequestEmailCode {
+        api_key: String,
+        email: String,
+    },
+    ExchangeEmailCode {
+        api_key: String,
+        security_code: String,
+    },
+    LoadCatalogPage {
+        offset: u32,
+        limit: u32,
+        sort: CatalogSort,
+        search: String,
+        tags: Vec<String>,
+    },
+    FetchTagOptions,
+    // (mod_id, mod.io ID) pairs to query
+    CheckUpdates(Vec<(String, u32)>),
+    FetchModDetails {
+        mod_id: String,
+        modio_id: u32,
+        purpose: DetailsPurpose,
+    },
+    // Look up the mod a pasted mod.io link points to
+    ResolveModLink {
+        link: String,
+        reference: ParsedModRef,
+    },
+    // Downloads run side by side on their own threads; setting `cancel` stops the download
+    InstallMod {
+        mod_entry: Box<ModEntry>,
+        cancel: Arc<AtomicBool>,
+    },
+    // Hash every stored and deployed file of these mods
+    VerifyMods {
+        mods: Vec<ModEntry>,
+        game_path: String,
+    },
+    // Read the pak indexes of these mods for shared assets; `key` names the enabled set
+    DetectAssetConflicts {
+        key: String,
+        mods: Vec<ModEntry>,
+    },
+    // Release history of a mod.io mod
+    FetchVersions {
+        mod_id: String,
+        modio_id: u32,
+    },
+    RateMod {
+        mod_id: String,
+        modio_id: u32,
+    },
+    FetchDependencies {
+        mod_id: String,
+        modio_id: u32,
+    },
+    ImportSubscriptions,
+    SetSubscribed {
+        mod_id: String,
+        modio_id: u32,
+        subscribed: bool,
+    },
+    // Fire-and-forget message to a webhook
+    PostWebhook {
+        url: String,
+        content: String,
+    },
+    // Download an image into the thumbnail cache
+    FetchThumbnail {
+        mod_id: String,
+        url: String,
+        path: PathBuf,
+    },
+}
+
+pub enum TaskResult {
+    OAuthTokenValidated(Result<ModIoUser, String>),
+    EmailCodeRequested(Result<(), String>),
+    // The new OAuth token
+    EmailCodeExchanged(Result<String, String>),
+    CatalogPage {
+        offset: u32,
+        sort: CatalogSort,
+        search: String,
+        tags: Vec<String>,
+        result: Result<Vec<ModIoMod>, String>,
+    },
+    TagOptions(Result<Vec<ModIoTagOption>, String>),
+    // mod_id of every entry with its current metadata, or the error from querying it
+    UpdatesChecked(Vec<(String, Result<Box<ModIoMod>, String>)>),
+    ModDetails {
+        mod_id: String,
+        purpose: DetailsPurpose,
+        result: Result<Box<ModIoMod>, String>,
+    },
+    ModLinkResolved {
+        link: String,
+        result: Result<Box<ModIoMod>, String>,
+    },
+    // Sent repeatedly while an install is running
+    DownloadProgress {
+        mod_id: String,
+        downloaded: u64,
+        total: Option<u64>,
+    },
+    Installed {
+        mod_id: String,
+        result: Result<(), String>,
+    },
+    // Name of each mod with problems, and what is wrong with it
+    VerificationFinished(Vec<(String, Vec<String>)>),
+    AssetConflictsDetected {
+        key: String,
+        conflicts: Vec<Conflict>,
+    },
+    // Version labels, newest first
+    VersionsFetched {
+        mod_id: String,
+        result: Result<Vec<String>, String>,
+    },
+    ModRated {
+        mod_id: String,
+        result: Result<(), String>,
+    },
+    DependenciesFetched {
+        mod_id: String,
+        result: Result<Vec<ModIoDependency>, String>,
+    },
+    Subscriptions(Result<Vec<ModIoMod>, String>),
+    SubscriptionChanged {
+        mod_id: String,
+        subscribed: bool,
+        result: Result<(), String>,
+    },
+    ThumbnailFetched {
+        mod_id: String,
+        // Whether the cached file was replaced
+        result: Result<bool, String>,
+    },
+}
+
+// Stored next to a cached thumbnail to revalidate it with If-None-Match / If-Modified-Since
+#[derive(Default, Serialize, Deserialize)]
+struct ThumbnailValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+pub struct Worker {
+    tasks: Sender<Task>,
+    results: Receiver<TaskResult>,
+    pending: usize,
+}
+
+impl Worker {
+    pub fn spawn(app_data_dir: PathBuf) -> Self {
+        let (task_sender, task_receiver) = channel::<Task>();
+        let (result_sender, result_receiver) = channel::<TaskResult>();
+
+        // Thumbnails queue on their own thread so a page of them doesn't hold up other requests
+        let (thumbnail_sender, thumbnail_receiver) = channel::<(String, String, PathBuf)>();
+        let thumbnail_results = result_sender.clone();
+        thread::spawn(move || {
+            let client = reqwest::blocking::Client::new();
+            for (mod_id, url, path) in thumbnail_receiver {
+                let result = Self::fetch_thumbnail(&client, &url, &path);
+                if thumbnail_results.send(TaskResult::ThumbnailFetched { mod_id, result }).is_err() {
+                    break;
+                }
+            }
+        });
+
+        thread::spawn(move || {
+            let mut client = ModIoClient::new();
+            client.set_cache_dir(app_data_dir.join("modio_cache"));
+            // Handed to install threads, which download through their own client
+            let mut oauth_token = String::new();
+            for task in task_receiver {
+                let result = match task {
+                    Task::SetOAuthToken(token) => {
+                        client.set_oauth_token(&token);
+                        oauth_token = token;
+                        continue;
+                    },
+                    Task::ValidateOAuthToken(token) => {
+                        let result = client.get_account(&token).map_err(|e| e.to_string());
+                        if result.is_ok() {
+                            client.set_oauth_token(&token);
+                            oauth_token = token;
+                        }
+                        TaskResult::OAuthTokenValidated(result)
+                    },
+                    Task::RequestEmailCode { api_key, email } => TaskResult::EmailCodeRequested(
+                        client.request_email_code(&api_key, &email).map_err(|e| e.to_string())
+                    ),
+                    Task::ExchangeEmailCode { api_key, security_code } => TaskResult::EmailCodeExchanged(
+                        client.exchange_email_code(&api_key, &security_code)
+                            .map(|token| token.access_token)
+                            .map_err(|e| e.to_string())
+                    ),
+                    Task::LoadCatalogPage { offset, limit, sort, search, tags } => TaskResult::CatalogPage {
+                        offset,
+                        sort,
+                        result: client.get_mods(offset, limit, sort, &search, &tags).map_err(|e| e.to_string()),
+                        search,
+                        tags,
+                    },
+                    Task::FetchTagOptions => TaskResult::TagOptions(
+                        client.get_tag_options().map_err(|e| e.to_string())
+                    ),
+                    Task::CheckUpdates(mods) => TaskResult::UpdatesChecked(
+                        mods.into_iter()
+                            .map(|(mod_id, modio_id)| {
+                                let result = client.get_mod_by_id(modio_id)
+                                    .map(Box::new)
+                                    .map_err(|e| e.to_string());
+                                (mod_id, result)
+                            })
+                            .collect()
+                    ),
+                    Task::FetchModDetails { mod_id, modio_id, purpose } => TaskResult::ModDetails {
+                        mod_id,
+                        purpose,
+                        result: client.get_mod_by_id(modio_id).map(Box::new).map_err(|e| e.to_string()),
+                    },
+                    Task::ResolveModLink { link, reference } => TaskResult::ModLinkResolved {
+                        result: match reference {
+                            ParsedModRef::Id(modio_id) => client.get_mod_by_id(modio_id),
+                            ParsedModRef::NameId(name_id) => client.get_mod_by_name_id(&name_id),
+                        }
+                        .map(Box::new)
+                        .map_err(|e| e.to_string()),
+                        link,
+                    },
+                    Task::FetchVersions { mod_id, modio_id } => TaskResult::VersionsFetched {
+                        mod_id,
+                        result: client.get_mod_files(modio_id)
+                            .map(|files| files.iter().map(ModIoFile::version_label).collect())
+                            .map_err(|e| e.to_string()),
+                    },
+                    Task::RateMod { mod_id, modio_id } => TaskResult::ModRated {
+                        mod_id,
+                        result: client.rate_mod(modio_id, true).map_err(|e| e.to_string()),
+                    },
+                    Task::FetchDependencies { mod_id, modio_id } => TaskResult::DependenciesFetched {
+                        mod_id,
+                        result: client.get_mod_dependencies(modio_id).map_err(|e| e.to_string()),
+                    },
+                    Task::ImportSubscriptions => TaskResult::Subscriptions(
+                        client.get_subscriptions().map_err(|e| e.to_string())
+                    ),
+                    Task::SetSubscribed { mod_id, modio_id, subscribed } => TaskResult::SubscriptionChanged {
+                        mod_id,
+                        subscribed,
+                        result: client.set_subscribed(modio_id, subscribed).map_err(|e| e.to_string()),
+                    },
+                    Task::InstallMod { mod_entry, cancel } => {
+                        Self::spawn_install(app_data_dir.clone(), &oauth_token, *mod_entry, cancel, result_sender.clone());
+                        continue;
+                    },
+                    Task::VerifyMods { mods, game_path } => {
+                        Self::spawn_verification(app_data_dir.clone(), mods, game_path, result_sender.clone());
+                        continue;
+                    },
+                    Task::DetectAssetConflicts { key, mods } => {
+                        Self::spawn_asset_conflicts(app_data_dir.clone(), key, mods, result_sender.clone());
+                        continue;
+                    },
+                    Task::PostWebhook { url, content } => {
+                        thread::spawn(move || {
+                            if let Err(e) = webhook::post(&url, &content) {
+                                warn!("Failed to post to webhook: {}", e);
+                            }
+                        });
+                        continue;
+                    },
+                    Task::FetchThumbnail { mod_id, url, path } => {
+                        let _ = thumbnail_sender.send((mod_id, url, path));
+                        continue;
+                    },
+                };
+
+                // The app is gone once the receiver is dropped
+                if result_sender.send(result).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            tasks: task_sender,
+            results: result_receiver,
+            pending: 0,
+        }
+    }
+
+    fn spawn_install(
+        app_data_dir: PathBuf,
+        oauth_token: &str,
+        mod_entry: ModEntry,
+        cancel: Arc<AtomicBool>,
+        results: Sender<TaskResult>
+    ) {
+        let oauth_token = oauth_token.to_string();
+        thread::spawn(move || {
+            let mut installer = ModInstaller::new(app_data_dir);
+            installer.set_mod_io_token(&oauth_token);
+            installer.set_cancel_flag(cancel);
+            let progress = |downloaded, total| {
+                let _ = results.send(TaskResult::DownloadProgress {
+                    mod_id: mod_entry.mod_id.clone(),
+                    downloaded,
+                    total,
+                });
+            };
+            let result = installer.install_mod(&mod_entry, &progress);
+            let _ = results.send(TaskResult::Installed {
+                mod_id: mod_entry.mod_id.clone(),
+                result,
+            });
+        });
+    }
+
+    // Download a thumbnail, or revalidate the cached one with the validators stored next to it.
+    // Returns whether the file was replaced
+    fn fetch_thumbnail(client: &reqwest::blocking::Client, url: &str, path: &Path) -> Result<bool, String> {
+        let validators_path = path.with_extension("validators");
+        let validators: ThumbnailValidators = std::fs::read_to_string(&validators_path)
+            .ok()
+            .filter(|_| path.exists())
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        
+        let mut request = client.get(url);
+        if let Some(etag) = &validators.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &validators.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+        }
+        let response = request.send()
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| e.to_string())?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(false);
+        }
+        
+        let header = |name| response.headers()
+            .get(name)
+            .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+            .map(str::to_string);
+        let validators = ThumbnailValidators {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        };
+        let bytes = response.bytes().map_err(|e| e.to_string())?;
+        
+        // Write next to the final path first so a partial file is never decoded
+        let partial = path.with_extension("part");
+        std::fs::create_dir_all(path.parent().unwrap_or(Path::new(".")))
+            .and_then(|()| std::fs::write(&partial, &bytes))
+            .and_then(|()| std::fs::rename(&partial, path))
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = serde_json::to_string(&validators)
+            .map_err(|e| e.to_string())
+            .and_then(|json| std::fs::write(&validators_path, json).map_err(|e| e.to_string()))
+        {
+            warn!("Failed to save the validators of {:?}: {}", path, e);
+        }
+        Ok(true)
+    }
+
+    // Hashing can take minutes, so it must not hold up other requests
+    fn spawn_verification(app_data_dir: PathBuf, mods: Vec<ModEntry>, game_path: String, results: Sender<TaskResult>) {
+        thread::spawn(move || {
+            let installer = ModInstaller::new(app_data_dir);
+            let problems = mods
+                .iter()
+                .filter_map(|mod_entry| {
+                    let problems = installer.verify_mod(mod_entry, &game_path).unwrap_or_else(|e| vec![e]);
+                    (!problems.is_empty()).then(|| (mod_entry.mod_id.clone(), problems))
+                })
+                .collect();
+            let _ = results.send(TaskResult::VerificationFinished(problems));
+        });
+    }
+
+    // Large paks take a while to index, so this runs beside the other requests too
+    fn spawn_asset_conflicts(app_data_dir: PathBuf, key: String, mods: Vec<ModEntry>, results: Sender<TaskResult>) {
+        thread::spawn(move || {
+            let installer = ModInstaller::new(app_data_dir);
+            let conflicts = ConflictReport::detect_assets(&installer, &mods);
+            let _ = results.send(TaskResult::AssetConflictsDetected { key, conflicts });
+        });
+    }
+
+    pub fn send(&mut self, task: Task) {
+        let expects_result = !matches!(task, Task::SetOAuthToken(_) | Task::PostWebhook { .. });
+        match self.tasks.send(task) {
+            Ok(()) if expects_result => self.pending += 1,
+            Ok(()) => {},
+            Err(_) => warn!("Background worker has stopped"),
+        }
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.pending > 0
+    }
+
+    pub fn try_recv(&mut self) -> Option<TaskResult> {
+        match self.results.try_recv() {
+            Ok(result) => {
+                // Progress updates come ahead of the task's final result
+                if !matches!(result, TaskResult::DownloadProgress { .. }) {
+                    self.pending -= 1;
+                }
+                Some(result)
+            },
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                self.pending = 0;
+                None
+            },
+        }
+    }
+}